@@ -0,0 +1,39 @@
+//! Timings for parsing huge block scalars. Run with `cargo bench`.
+
+use mini_yaml_rs::parse;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Run `f` repeatedly for about a second and print the mean time per run
+fn bench(name: &str, mut f: impl FnMut()) {
+    // warm up
+    for _ in 0..3 {
+        f();
+    }
+    let mut runs = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        f();
+        runs += 1;
+    }
+    println!("{name:<32} {:>10.1?} per run", start.elapsed() / runs);
+}
+
+fn main() {
+    let log = (0..100_000)
+        .map(|i| format!("  {i:06} INFO request handled in {}ms\n", i % 97))
+        .collect::<String>();
+    let certificate = (0..50_000)
+        .map(|_| "  MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA\n\n")
+        .collect::<String>();
+
+    for (name, header, body) in [
+        ("4MB literal log", "log: |\n", &log),
+        ("2MB folded, blank lines", "cert: >\n", &certificate),
+    ] {
+        let input = format!("{header}{body}");
+        bench(name, || {
+            black_box(parse(black_box(&input)).unwrap());
+        });
+    }
+}
@@ -0,0 +1,83 @@
+//! Timings for converting large documents to JSON, against converting
+//! every sequence item through its own call to `to_json_with_options` as
+//! `to_json` used to. Run with `cargo bench`.
+
+use mini_yaml_rs::{parse, JsonOptions, Yaml};
+use serde_json::Value;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Run `f` repeatedly for about a second and print the mean time per run
+fn bench(name: &str, mut f: impl FnMut()) {
+    // warm up
+    for _ in 0..10 {
+        f();
+    }
+    let mut runs = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        f();
+        runs += 1;
+    }
+    println!("{name:<32} {:>10.1?} per run", start.elapsed() / runs);
+}
+
+/// `to_json` as it was before sequence items were converted in place (the
+/// keys of the documents here are all plain strings)
+fn per_item_to_json(node: &Yaml<'_>, opts: &JsonOptions) -> Value {
+    match node {
+        Yaml::Sequence(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| per_item_to_json(item, opts))
+                .collect(),
+        ),
+        Yaml::Mapping(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|entry| (entry.key.to_string(), per_item_to_json(&entry.value, opts)))
+                .collect(),
+        ),
+        other => other.to_json_with_options(opts),
+    }
+}
+
+fn main() {
+    let ints = format!(
+        "[{}]",
+        (0..10_000)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let floats = format!(
+        "[{}]",
+        (0..10_000)
+            .map(|i| format!("{}.5", i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let points = (0..2_000)
+        .map(|i| format!("- [{i}, {}.25, {}]\n", i * 2, i * 3))
+        .collect::<String>();
+    let records = (0..2_000)
+        .map(|i| format!("- id: {i}\n  name: item{i}\n  tags: [a, b]\n"))
+        .collect::<String>();
+
+    for (name, input) in [
+        ("10k integers", &ints),
+        ("10k floats", &floats),
+        ("2k points", &points),
+        ("2k records", &records),
+    ] {
+        let yaml = parse(input).unwrap();
+        let opts = JsonOptions::default();
+        assert_eq!(yaml.to_json(), per_item_to_json(&yaml, &opts));
+        bench(&format!("to_json {name}"), || {
+            black_box(black_box(&yaml).to_json());
+        });
+        bench(&format!("per item {name}"), || {
+            black_box(per_item_to_json(black_box(&yaml), &opts));
+        });
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{Result, YamlOwned};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+struct CacheEntry {
+    /// the source, compared on lookup so that inputs with the same hash are
+    /// told apart
+    source: Arc<str>,
+    tree: Arc<YamlOwned>,
+    /// the estimated size of the tree, in bytes
+    size: usize,
+    last_used: u64,
+}
+
+/// A cache of parse results keyed by a hash of the source text, holding at
+/// most `capacity` trees, and optionally at most a number of bytes of them.
+/// When full, the least recently used trees are evicted. Each tree is kept
+/// with a copy of its source, which a lookup must match.
+///
+/// Parse errors are not cached.
+pub struct ParseCache {
+    capacity: usize,
+    memory_limit: Option<usize>,
+    /// the estimated size of every cached tree, in bytes
+    memory_usage: usize,
+    entries: HashMap<u64, CacheEntry>,
+    /// hash of each entry by the tick it was last used at, oldest first
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl ParseCache {
+    /// Create a cache holding at most `capacity` trees
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            memory_limit: None,
+            memory_usage: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Also limit the cache to trees whose [`Yaml::estimate_size`] adds up
+    /// to at most `bytes`. A tree larger than the whole budget is returned
+    /// but not cached.
+    ///
+    /// [`Yaml::estimate_size`]: crate::Yaml::estimate_size
+    #[must_use]
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    fn hash(input: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Look up the tree for `input` without parsing it on a miss
+    pub fn get(&mut self, input: &str) -> Option<Arc<YamlOwned>> {
+        let hash = Self::hash(input);
+        let entry = self
+            .entries
+            .get_mut(&hash)
+            .filter(|e| *e.source == *input)?;
+        self.recency.remove(&entry.last_used);
+        self.tick += 1;
+        entry.last_used = self.tick;
+        self.recency.insert(self.tick, hash);
+        Some(Arc::clone(&entry.tree))
+    }
+
+    /// Return the cached tree for `input`, parsing and caching it on a miss
+    /// # Errors
+    /// Returns `Err` if the input is invalid Yaml, with a message indicating
+    /// where the error occurred and possibly more information on the cause
+    pub fn parse(&mut self, input: &str) -> Result<Arc<YamlOwned>> {
+        if let Some(tree) = self.get(input) {
+            self.hits += 1;
+            return Ok(tree);
+        }
+        self.misses += 1;
+        let tree = Arc::new(crate::parse(input)?.into_owned());
+        self.insert(input, Arc::clone(&tree));
+        Ok(tree)
+    }
+
+    fn insert(&mut self, input: &str, tree: Arc<YamlOwned>) {
+        let size = tree.estimate_size();
+        let limit = self.memory_limit.unwrap_or(usize::MAX);
+        if self.capacity == 0 || size > limit {
+            return;
+        }
+        while self.entries.len() >= self.capacity || self.memory_usage + size > limit {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(old) = self.entries.remove(&oldest) {
+                self.memory_usage -= old.size;
+            }
+        }
+        let hash = Self::hash(input);
+        self.tick += 1;
+        let entry = CacheEntry {
+            source: Arc::from(input),
+            tree,
+            size,
+            last_used: self.tick,
+        };
+        self.memory_usage += size;
+        if let Some(old) = self.entries.insert(hash, entry) {
+            self.recency.remove(&old.last_used);
+            self.memory_usage -= old.size;
+        }
+        self.recency.insert(self.tick, hash);
+    }
+
+    /// The number of cached trees
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[must_use]
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.memory_limit
+    }
+
+    /// The estimated size of every cached tree, in bytes
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// The number of calls to [`ParseCache::parse`] answered from the cache
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of calls to [`ParseCache::parse`] which had to parse
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Remove every cached tree
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.memory_usage = 0;
+    }
+}
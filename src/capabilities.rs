@@ -0,0 +1,299 @@
+use crate::parse::STANDARD_TAGS;
+use serde_json::{json, Value};
+
+/// The parts of Yaml this crate understands, for applications which adapt
+/// their editors and error hints to what the parser supports
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Capabilities {
+    /// the version of this crate
+    pub version: &'static str,
+    /// anchors (`&name`) and aliases (`*name`), which are expanded into
+    /// copies of the anchored value
+    pub anchors: bool,
+    /// the merge key `<<`
+    pub merge_keys: bool,
+    /// directives (`%YAML`, `%TAG`) before a single document, which are
+    /// skipped; `parse_all` skips them between documents either way
+    pub directives: bool,
+    /// streams of several documents, read by `parse_all`
+    pub multiple_documents: bool,
+    /// explicit mapping keys (`? key`)
+    pub explicit_keys: bool,
+    /// plain scalars which continue onto following lines
+    pub multi_line_plain_scalars: bool,
+    /// local tags (`!name`), kept as [`Yaml::Tagged`](crate::Yaml::Tagged)
+    pub local_tags: bool,
+    /// the standard tags understood after the `!!` handle
+    pub secondary_tags: &'static [&'static str],
+    /// the names of the schemas plain scalars can be resolved with
+    pub schemas: &'static [&'static str],
+    /// how well the parser reads the output of other emitters, feature by
+    /// feature
+    pub interop: &'static [InteropFeature],
+}
+
+/// A program whose Yaml output the parser is checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Producer {
+    /// `PyYAML`'s `yaml.dump`
+    PyYaml,
+    /// Go's `gopkg.in/yaml.v3` (`yaml.Marshal`)
+    GoYaml,
+}
+
+impl Producer {
+    /// The name the emitter is known by
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Producer::PyYaml => "PyYAML",
+            Producer::GoYaml => "go-yaml",
+        }
+    }
+}
+
+/// How well the parser reads a feature of another emitter's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Support {
+    /// read as the emitter meant it
+    Full,
+    /// read as the emitter meant it with
+    /// [`ParseOptions::compat`](crate::ParseOptions::compat) set
+    Compat,
+    /// parsed, but the value keeps the tag it was written
+    /// with
+    TagOnly,
+}
+
+impl Support {
+    /// The name used for this level in JSON
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Support::Full => "full",
+            Support::Compat => "compat",
+            Support::TagOnly => "tag-only",
+        }
+    }
+}
+
+/// One feature of another emitter's output, with an example of it as the
+/// emitter writes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteropFeature {
+    /// the program which writes it
+    pub producer: Producer,
+    /// what the feature is
+    pub feature: &'static str,
+    /// a document showing the feature
+    pub example: &'static str,
+    /// how well it is read
+    pub support: Support,
+}
+
+const fn feature(
+    producer: Producer,
+    feature: &'static str,
+    example: &'static str,
+    support: Support,
+) -> InteropFeature {
+    InteropFeature {
+        producer,
+        feature,
+        example,
+        support,
+    }
+}
+
+/// The features of other emitters' output the parser is tested against
+const INTEROP: &[InteropFeature] = &[
+    feature(Producer::PyYaml, "null values", "a: null\n", Support::Full),
+    feature(
+        Producer::PyYaml,
+        "single-quoted keys and values",
+        "'001': 'yes'\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "anchors on sequence items",
+        "- &id001\n  x: 1\n- *id001\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "sequences level with their key",
+        "a:\n- 1\n- 2\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "document end marker after a scalar",
+        "hello\n...\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "version directive",
+        "%YAML 1.1\n---\na: 1\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "long strings wrapped onto several lines",
+        "a: a long string\n  wrapped here\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "flow collections",
+        "a: {b: [1, 2]}\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "complex keys",
+        "? [1, 2]\n: x\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "binary data",
+        "!!binary |\n  aGVsbG8=\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "Python objects",
+        "!!python/object:__main__.Point {x: 1}\n",
+        Support::TagOnly,
+    ),
+    feature(
+        Producer::PyYaml,
+        "Python built-in types",
+        "!!python/tuple [1, 2]\n",
+        Support::Compat,
+    ),
+    feature(
+        Producer::PyYaml,
+        "Python 2 strings",
+        "!!python/unicode 'text'\n",
+        Support::Compat,
+    ),
+    feature(Producer::PyYaml, "sets", "!!set {a: null}\n", Support::Full),
+    feature(
+        Producer::PyYaml,
+        "ordered mappings",
+        "!!omap\n- a: 1\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::PyYaml,
+        "tag handles declared by %TAG",
+        "%TAG !e! tag:example.com,2000:\n---\n!e!foo 1\n",
+        Support::TagOnly,
+    ),
+    feature(
+        Producer::GoYaml,
+        "four-space indentation",
+        "a:\n    - x\n    - y\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::GoYaml,
+        "double-quoted strings",
+        "a: \"yes\"\nb: \"1.0\"\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::GoYaml,
+        "literal block scalars",
+        "a: |-\n    one\n    two\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::GoYaml,
+        "merge keys",
+        "base: &b\n    x: 1\nc:\n    <<: *b\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::GoYaml,
+        "binary data",
+        "a: !!binary aGVsbG8=\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::GoYaml,
+        "empty collections",
+        "a: {}\nb: []\n",
+        Support::Full,
+    ),
+    feature(Producer::GoYaml, "null values", "a: null\n", Support::Full),
+    feature(
+        Producer::GoYaml,
+        "timestamps",
+        "a: 2024-06-01T12:00:00Z\n",
+        Support::Full,
+    ),
+    feature(
+        Producer::GoYaml,
+        "head and line comments",
+        "# head\na: 1 # line\n",
+        Support::Full,
+    ),
+];
+
+/// Describe what this version of the parser supports
+#[must_use]
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        anchors: true,
+        merge_keys: true,
+        directives: true,
+        multiple_documents: true,
+        explicit_keys: true,
+        multi_line_plain_scalars: true,
+        local_tags: true,
+        secondary_tags: STANDARD_TAGS,
+        schemas: &["yaml-1.1", "core", "json"],
+        interop: INTEROP,
+    }
+}
+
+impl Capabilities {
+    /// The capabilities as a JSON object, with the field names as keys
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version": self.version,
+            "anchors": self.anchors,
+            "merge_keys": self.merge_keys,
+            "directives": self.directives,
+            "multiple_documents": self.multiple_documents,
+            "explicit_keys": self.explicit_keys,
+            "multi_line_plain_scalars": self.multi_line_plain_scalars,
+            "local_tags": self.local_tags,
+            "secondary_tags": self.secondary_tags,
+            "schemas": self.schemas,
+            "interop": self.interop.iter().map(InteropFeature::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl InteropFeature {
+    /// The feature as a JSON object, with the field names as keys
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "producer": self.producer.name(),
+            "feature": self.feature,
+            "example": self.example,
+            "support": self.support.as_str(),
+        })
+    }
+}
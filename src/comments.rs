@@ -0,0 +1,170 @@
+use crate::cst::comment_spans;
+use crate::document::Document;
+use crate::path::{Path, PathSegment};
+use crate::span::Span;
+use std::collections::HashMap;
+
+/// The comments around a node of a [`Document`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comments<'a> {
+    /// the comment lines above the node (above its key, for a mapping
+    /// value, or its `-`, for a block sequence item), each from the `#` to
+    /// the end of the line
+    pub leading: Vec<&'a str>,
+    /// the comment at the end of the node's line: after a scalar or a flow
+    /// collection, or after the `:` or `-` which a block collection follows
+    pub trailing: Option<&'a str>,
+}
+
+/// The comments of a document, by the path of the node they belong to
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CommentTable<'a> {
+    pub(crate) nodes: HashMap<Path, Comments<'a>>,
+    /// the comment lines after the last node
+    pub(crate) end: Vec<&'a str>,
+}
+
+impl<'a> CommentTable<'a> {
+    pub(crate) fn get(&self, path: &Path) -> Option<&Comments<'a>> {
+        self.nodes.get(path)
+    }
+}
+
+/// Where a node is introduced in the source: its key, its `-`, or else the
+/// node itself
+struct Anchor<'p> {
+    path: &'p Path,
+    start: usize,
+    /// the end of the key or `-`, for block collections
+    indicator_end: Option<usize>,
+    /// the end of the node itself
+    end: usize,
+}
+
+/// Attach every comment of `document` to a node: a comment at the end of a
+/// line to the node ending there, and a comment line to the node which
+/// comes next. Comment lines with no node after them end the document.
+pub(crate) fn collect<'a>(document: &Document<'a>) -> CommentTable<'a> {
+    let source = document.source();
+    let comments = comment_spans(document);
+    // the first comment ending each stretch of source, by the length of the
+    // source before it without the blank space there
+    let mut ending = HashMap::new();
+    for comment in &comments {
+        ending
+            .entry(source[..comment.end].trim_end().len())
+            .or_insert(*comment);
+    }
+    // the source before `idx`, without the blank space and comments ending there
+    let before = |mut idx: usize| loop {
+        let text = source[..idx].trim_end();
+        match ending.get(&text.len()) {
+            Some(comment) if comment.end > comment.start => idx = comment.start,
+            _ => break text,
+        }
+    };
+    let anchors: Vec<Anchor> = document
+        .spans()
+        .iter()
+        .map(|(path, node)| {
+            let dash = matches!(path.segments().last(), Some(PathSegment::Index(_)))
+                .then(|| before(node.span.start))
+                .filter(|before| before.ends_with('-'))
+                .map(str::len);
+            let indicator_end = node.key.map(|key| key.end).or(dash);
+            let start = match (node.key, dash) {
+                (Some(key), _) => key.start,
+                (None, Some(dash)) => dash - 1,
+                (None, None) => node.span.start,
+            };
+            Anchor {
+                path,
+                start,
+                indicator_end,
+                end: node.span.end,
+            }
+        })
+        .collect();
+    // the anchors by each offset a comment is matched against, so that
+    // only those near the comment are looked at
+    let by_end = sorted_by(&anchors, |anchor| Some(anchor.end));
+    let by_indicator = sorted_by(&anchors, |anchor| anchor.indicator_end);
+    let by_start = sorted_by(&anchors, |anchor| Some(anchor.start));
+
+    let mut table = CommentTable::default();
+    for &Span { start, end } in &comments {
+        let text = source[start..end].trim_end_matches('\r');
+        let line_start = source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+        let own_line = source[line_start..start].trim().is_empty();
+        // the text between `from` and the comment, if both are on its line
+        let between =
+            |from: usize| (line_start <= from && from <= start).then(|| source[from..start].trim());
+        let trailing = if own_line {
+            None
+        } else {
+            // the deepest node ending just before the comment, or else the
+            // block collection whose key or `-` does
+            deepest(within(&by_end, line_start, start).filter(|a| between(a.end) == Some("")))
+                .or_else(|| {
+                    deepest(within(&by_indicator, line_start, start).filter(|a| {
+                        a.indicator_end
+                            .and_then(between)
+                            .is_some_and(|text| matches!(text, "" | ":"))
+                    }))
+                })
+        };
+        if let Some(anchor) = trailing {
+            table.nodes.entry(anchor.path.clone()).or_default().trailing = Some(text);
+            continue;
+        }
+        let next = &by_start[by_start.partition_point(|&(offset, _)| offset < end)..];
+        let first = next.first().map(|&(offset, _)| offset);
+        let next = next
+            .iter()
+            .take_while(|&&(offset, _)| Some(offset) == first)
+            .map(|&(_, anchor)| anchor);
+        match deepest(next) {
+            Some(anchor) => table
+                .nodes
+                .entry(anchor.path.clone())
+                .or_default()
+                .leading
+                .push(text),
+            None => table.end.push(text),
+        }
+    }
+    table
+}
+
+/// The anchors which have an offset `key` gives, sorted by it
+fn sorted_by<'x, 'p>(
+    anchors: &'x [Anchor<'p>],
+    key: impl Fn(&Anchor<'p>) -> Option<usize>,
+) -> Vec<(usize, &'x Anchor<'p>)> {
+    let mut sorted: Vec<_> = anchors
+        .iter()
+        .filter_map(|anchor| Some((key(anchor)?, anchor)))
+        .collect();
+    sorted.sort_by_key(|&(offset, _)| offset);
+    sorted
+}
+
+/// The anchors of `sorted` whose offset is from `from` to `to`, inclusive
+fn within<'x, 'p>(
+    sorted: &'x [(usize, &'x Anchor<'p>)],
+    from: usize,
+    to: usize,
+) -> impl Iterator<Item = &'x Anchor<'p>> {
+    let first = sorted.partition_point(|&(offset, _)| offset < from);
+    let last = sorted.partition_point(|&(offset, _)| offset <= to);
+    sorted[first..last.max(first)]
+        .iter()
+        .map(|&(_, anchor)| anchor)
+}
+
+fn deepest<'x, 'p>(anchors: impl Iterator<Item = &'x Anchor<'p>>) -> Option<&'x Anchor<'p>>
+where
+    'p: 'x,
+{
+    anchors.max_by_key(|anchor| anchor.path.segments().len())
+}
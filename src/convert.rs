@@ -0,0 +1,226 @@
+use crate::errors::ConversionError;
+use crate::{Entry, Timestamp, Yaml};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+macro_rules! from_int {
+    ($($ty: ty),*) => {$(
+        impl From<$ty> for Yaml<'_> {
+            fn from(value: $ty) -> Self {
+                Yaml::Int(i64::from(value))
+            }
+        }
+    )*};
+}
+
+from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+/// Integers beyond the range of `i64` become floats, as in `from_json`, and
+/// so lose precision: `u64::MAX` and `u64::MAX - 1` both become
+/// `1.8446744073709552e19`
+impl From<u64> for Yaml<'_> {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(Yaml::Float(value as f64), Yaml::Int)
+    }
+}
+
+/// As for `u64`, including the loss of precision beyond `i64::MAX`
+impl From<usize> for Yaml<'_> {
+    fn from(value: usize) -> Self {
+        // usize is at most 64 bits on every supported target
+        Yaml::from(value as u64)
+    }
+}
+
+impl From<bool> for Yaml<'_> {
+    fn from(value: bool) -> Self {
+        Yaml::Bool(value)
+    }
+}
+
+impl From<f32> for Yaml<'_> {
+    fn from(value: f32) -> Self {
+        Yaml::Float(f64::from(value))
+    }
+}
+
+impl From<f64> for Yaml<'_> {
+    fn from(value: f64) -> Self {
+        Yaml::Float(value)
+    }
+}
+
+/// Borrows the text, as a scalar read from a document does
+impl<'a> From<&'a str> for Yaml<'a> {
+    fn from(value: &'a str) -> Self {
+        Yaml::Scalar(value)
+    }
+}
+
+impl From<String> for Yaml<'_> {
+    fn from(value: String) -> Self {
+        Yaml::String(value)
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Yaml<'a> {
+    fn from(value: Cow<'a, str>) -> Self {
+        match value {
+            Cow::Borrowed(value) => Yaml::Scalar(value),
+            Cow::Owned(value) => Yaml::String(value),
+        }
+    }
+}
+
+impl From<Timestamp> for Yaml<'_> {
+    fn from(value: Timestamp) -> Self {
+        Yaml::Timestamp(value)
+    }
+}
+
+/// `None` is a null
+impl<'a, T: Into<Yaml<'a>>> From<Option<T>> for Yaml<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Yaml::Null, Into::into)
+    }
+}
+
+impl<'a, T: Into<Yaml<'a>>> From<Vec<T>> for Yaml<'a> {
+    fn from(items: Vec<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+/// A mapping with the keys in sorted order
+impl<'a, K: Into<Yaml<'a>>, V: Into<Yaml<'a>>> From<BTreeMap<K, V>> for Yaml<'a> {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// A mapping in the map's iteration order, which for the standard hasher
+/// differs from run to run; use a `BTreeMap` for stable output
+impl<'a, K: Into<Yaml<'a>>, V: Into<Yaml<'a>>, S: BuildHasher> From<HashMap<K, V, S>> for Yaml<'a> {
+    fn from(map: HashMap<K, V, S>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// A mapping in insertion order
+#[cfg(feature = "indexmap")]
+impl<'a, K: Into<Yaml<'a>>, V: Into<Yaml<'a>>, S> From<indexmap::IndexMap<K, V, S>> for Yaml<'a> {
+    fn from(map: indexmap::IndexMap<K, V, S>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// A mapping in the order of the JSON object, as for [`Yaml::from_json`]
+impl From<serde_json::Value> for Yaml<'_> {
+    fn from(value: serde_json::Value) -> Self {
+        Yaml::from_json(&value)
+    }
+}
+
+/// Collects items into a sequence
+impl<'a, T: Into<Yaml<'a>>> FromIterator<T> for Yaml<'a> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Yaml::Sequence(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Collects key and value pairs into a mapping, keeping every pair even if
+/// keys repeat
+impl<'a, K: Into<Yaml<'a>>, V: Into<Yaml<'a>>> FromIterator<(K, V)> for Yaml<'a> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Yaml::Mapping(
+            iter.into_iter()
+                .map(|(key, value)| Entry::new(key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// The name of what a node holds, for conversion errors
+pub(crate) fn describe(node: &Yaml<'_>) -> &'static str {
+    match node.untagged() {
+        Yaml::Scalar(_) | Yaml::String(_) => "a string",
+        Yaml::Int(_) => "an integer",
+        Yaml::Float(_) => "a float",
+        Yaml::Bool(_) => "a boolean",
+        Yaml::Null => "null",
+        Yaml::Timestamp(_) => "a timestamp",
+        Yaml::Bytes(_) => "binary data",
+        Yaml::Sequence(_) => "a sequence",
+        Yaml::Mapping(_) => "a mapping",
+        Yaml::Tagged(..) => "a tagged value",
+    }
+}
+
+fn mismatch(expected: &'static str, node: &Yaml<'_>) -> ConversionError {
+    ConversionError {
+        expected,
+        found: describe(node),
+    }
+}
+
+/// Reads an integer, looking through tags
+impl TryFrom<&Yaml<'_>> for i64 {
+    type Error = ConversionError;
+
+    fn try_from(node: &Yaml<'_>) -> Result<Self, ConversionError> {
+        match node.untagged() {
+            Yaml::Int(value) => Ok(*value),
+            other => Err(mismatch("an integer", other)),
+        }
+    }
+}
+
+/// Reads a float or an integer, looking through tags
+impl TryFrom<&Yaml<'_>> for f64 {
+    type Error = ConversionError;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn try_from(node: &Yaml<'_>) -> Result<Self, ConversionError> {
+        match node.untagged() {
+            Yaml::Float(value) => Ok(*value),
+            Yaml::Int(value) => Ok(*value as f64),
+            other => Err(mismatch("a number", other)),
+        }
+    }
+}
+
+/// Reads a boolean, looking through tags
+impl TryFrom<&Yaml<'_>> for bool {
+    type Error = ConversionError;
+
+    fn try_from(node: &Yaml<'_>) -> Result<Self, ConversionError> {
+        match node.untagged() {
+            Yaml::Bool(value) => Ok(*value),
+            other => Err(mismatch("a boolean", other)),
+        }
+    }
+}
+
+/// Borrows a string, looking through tags
+impl<'y> TryFrom<&'y Yaml<'_>> for &'y str {
+    type Error = ConversionError;
+
+    fn try_from(node: &'y Yaml<'_>) -> Result<Self, ConversionError> {
+        match node.untagged() {
+            Yaml::Scalar(value) => Ok(value),
+            Yaml::String(value) => Ok(value),
+            other => Err(mismatch("a string", other)),
+        }
+    }
+}
+
+/// Copies a string, looking through tags
+impl TryFrom<&Yaml<'_>> for String {
+    type Error = ConversionError;
+
+    fn try_from(node: &Yaml<'_>) -> Result<Self, ConversionError> {
+        <&str>::try_from(node).map(ToString::to_string)
+    }
+}
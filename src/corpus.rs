@@ -0,0 +1,212 @@
+use crate::{parse, parse_all, parse_bytes, parse_document, EmitOptions};
+use std::any::Any;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+macro_rules! builtin {
+    ($($name: literal),* $(,)?) => {
+        &[$(($name, include_bytes!(concat!("../corpus/", $name)))),*]
+    };
+}
+
+/// Inputs which have crashed or hung the parser, or come close to it, kept
+/// in the crate's `corpus` directory
+pub(crate) const BUILTIN: &[(&str, &[u8])] = builtin![
+    "alias_fanout.yaml",
+    "bom_directive.yaml",
+    "crlf_blank.yaml",
+    "dash_after_colon.yaml",
+    "dedented_block_scalar.yaml",
+    "empty.yaml",
+    "explicit_keys.yaml",
+    "flow_key_without_value.yaml",
+    "folded_blank_lines.yaml",
+    "lone_dash.yaml",
+    "misaligned_sequence.yaml",
+    "multibyte_colon.yaml",
+    "scalars.yaml",
+    "tabs.yaml",
+    "tags.yaml",
+    "trailing_after_quote.yaml",
+    "two_documents.yaml",
+    "unclosed_flow.yaml",
+    "undefined_alias.yaml",
+    "unterminated_quote.yaml",
+];
+
+/// What [`run_corpus`] checks besides the parser not panicking
+#[derive(Debug, Clone, Default)]
+pub struct CorpusOptions {
+    /// Check that every document which parses is emitted with these options
+    /// as text which parses back to the same value (compared as JSON). The
+    /// default [`Dialect::MiniYaml`](crate::Dialect::MiniYaml) prints
+    /// strings verbatim, so a dialect which quotes them is needed for most
+    /// documents to survive this.
+    pub round_trip: Option<EmitOptions>,
+}
+
+/// How an input in a corpus failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpusFailureKind {
+    /// Parsing or emitting the input panicked
+    Panic,
+    /// A document did not come back the same from emitting it and parsing
+    /// the result
+    RoundTrip,
+}
+
+/// An input in a corpus which failed a check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusFailure {
+    /// The input's file name, or the path of a file under the directory run
+    pub name: PathBuf,
+    pub kind: CorpusFailureKind,
+    pub message: String,
+}
+
+impl fmt::Display for CorpusFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            CorpusFailureKind::Panic => "panic",
+            CorpusFailureKind::RoundTrip => "round trip",
+        };
+        write!(f, "{}: {kind}: {}", self.name.display(), self.message)
+    }
+}
+
+/// The outcome of running a corpus
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusReport {
+    /// The number of inputs run
+    pub inputs: usize,
+    /// The number of inputs which parsed without error
+    pub parsed: usize,
+    pub failures: Vec<CorpusFailure>,
+}
+
+impl CorpusReport {
+    /// Check a single input, adding the outcome to the report
+    pub fn check(&mut self, name: impl Into<PathBuf>, input: &[u8], opts: &CorpusOptions) {
+        self.inputs += 1;
+        let name = name.into();
+        let text = String::from_utf8_lossy(input);
+        match panic::catch_unwind(AssertUnwindSafe(|| check_input(input, &text, opts))) {
+            Ok(Ok(parsed)) => self.parsed += usize::from(parsed),
+            Ok(Err(message)) => self.failures.push(CorpusFailure {
+                name,
+                kind: CorpusFailureKind::RoundTrip,
+                message,
+            }),
+            Err(payload) => self.failures.push(CorpusFailure {
+                name,
+                kind: CorpusFailureKind::Panic,
+                message: panic_message(payload.as_ref()),
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panic with every failure if there are any, for use in tests
+    /// # Panics
+    /// Panics if any input failed
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            let failures: Vec<String> = self.failures.iter().map(ToString::to_string).collect();
+            panic!(
+                "{} of {} corpus inputs failed:\n{}",
+                failures.len(),
+                self.inputs,
+                failures.join("\n")
+            );
+        }
+    }
+}
+
+/// Parse every file under `dir`, in every subdirectory and in order of
+/// path, checking that the parser does not panic on any of them, along
+/// with the checks in `opts`. A panic is still printed by the panic hook,
+/// and a stack overflow aborts the process rather than being reported.
+/// # Errors
+/// Returns `Err` if the directory or a file in it cannot be read
+pub fn run_corpus(dir: impl AsRef<Path>, opts: &CorpusOptions) -> io::Result<CorpusReport> {
+    let mut paths = Vec::new();
+    collect_files(dir.as_ref(), &mut paths)?;
+    paths.sort();
+    let mut report = CorpusReport::default();
+    for path in paths {
+        let input = fs::read(&path)?;
+        report.check(path, &input, opts);
+    }
+    Ok(report)
+}
+
+/// Run the crate's own corpus of inputs which have troubled the parser, as
+/// by [`run_corpus`]
+#[must_use]
+pub fn run_builtin_corpus(opts: &CorpusOptions) -> CorpusReport {
+    let mut report = CorpusReport::default();
+    for (name, input) in BUILTIN {
+        report.check(name, input, opts);
+    }
+    report
+}
+
+pub(crate) fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Run the input through every entry point, returning whether it parsed,
+/// or what did not survive a round trip
+fn check_input(input: &[u8], text: &str, opts: &CorpusOptions) -> Result<bool, String> {
+    let _ = parse_bytes(input);
+    let _ = parse_document(text);
+    let parsed = parse(text).is_ok();
+    let Ok(docs) = parse_all(text) else {
+        return Ok(parsed);
+    };
+    if let Some(emit_opts) = &opts.round_trip {
+        for (idx, doc) in docs.iter().enumerate() {
+            let emitted = doc.emit(emit_opts);
+            match parse(&emitted) {
+                Ok(reparsed) if reparsed.to_json() == doc.to_json() => {}
+                Ok(reparsed) => {
+                    return Err(format!(
+                        "document {idx} was emitted as {emitted:?}, which parses as {}",
+                        reparsed.to_json()
+                    ))
+                }
+                Err(err) => {
+                    return Err(format!(
+                        "document {idx} was emitted as {emitted:?}, which fails to parse: {err}"
+                    ))
+                }
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,537 @@
+use crate::document::{parse_document, Document};
+use crate::lexer::Lexer;
+use crate::path::{self, PathSegment};
+use crate::span::{self, Span};
+use crate::{Result, Yaml};
+use core::fmt;
+use std::cmp::Reverse;
+
+/// The kind of a lossless syntax tree node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// The whole input, including leading and trailing trivia
+    Document,
+    /// A block or flow mapping
+    Mapping,
+    /// A key, the `:` and the value of a mapping entry
+    Entry,
+    /// A block or flow sequence
+    Sequence,
+    /// A sequence item, including its `-` in block sequences
+    Item,
+    /// A (possibly tagged) scalar, used for both keys and values
+    Scalar,
+}
+
+/// The kind of a lossless syntax tree token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A run of spaces and tabs
+    Whitespace,
+    /// A line break (`\n` or `\r\n`)
+    Newline,
+    /// A comment, from `#` up to the end of the line
+    Comment,
+    /// One of `-`, `?`, `:`, `,`, `[`, `]`, `{` and `}`
+    Indicator,
+    /// `---`
+    DocumentStart,
+    /// `...`
+    DocumentEnd,
+    /// A `%` directive line
+    Directive,
+    /// A `!tag`
+    Tag,
+    /// An `&anchor`
+    Anchor,
+    /// An `*alias`
+    Alias,
+    /// A plain scalar
+    Plain,
+    /// A `'single quoted'` scalar
+    SingleQuoted,
+    /// A `"double quoted"` scalar
+    DoubleQuoted,
+    /// A `|` or `>` block scalar, including its header and content lines
+    BlockScalar,
+    /// Text which does not belong to any other token
+    Text,
+    /// Text which cannot be read, such as an unclosed quote, or where a
+    /// parse error occurred; only produced by [`tokenize`]
+    Error,
+}
+
+impl TokenKind {
+    /// Whether the token carries no meaning (whitespace, newlines, comments)
+    #[must_use]
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::Newline | TokenKind::Comment
+        )
+    }
+}
+
+/// Which indicator a [`TokenKind::Indicator`] token is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Indicator {
+    /// `-`, before a block sequence item
+    Dash,
+    /// `?`, before an explicit key
+    Question,
+    /// `:`, between a key and its value
+    Colon,
+    /// `,`, between flow collection entries
+    Comma,
+    /// `[`
+    OpenBracket,
+    /// `]`
+    CloseBracket,
+    /// `{`
+    OpenBrace,
+    /// `}`
+    CloseBrace,
+}
+
+/// A token of the lossless syntax tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxToken {
+    /// What sort of token this is
+    pub kind: TokenKind,
+    /// Where the token is in the source
+    pub span: Span,
+}
+
+impl SyntaxToken {
+    /// Which indicator the token is, given the source it was read from, or
+    /// `None` if it is not an indicator
+    #[must_use]
+    pub fn indicator(&self, source: &str) -> Option<Indicator> {
+        if self.kind != TokenKind::Indicator {
+            return None;
+        }
+        match source.as_bytes().get(self.span.start)? {
+            b'-' => Some(Indicator::Dash),
+            b'?' => Some(Indicator::Question),
+            b':' => Some(Indicator::Colon),
+            b',' => Some(Indicator::Comma),
+            b'[' => Some(Indicator::OpenBracket),
+            b']' => Some(Indicator::CloseBracket),
+            b'{' => Some(Indicator::OpenBrace),
+            b'}' => Some(Indicator::CloseBrace),
+            _ => None,
+        }
+    }
+}
+
+/// A child of a lossless syntax tree node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    /// A nested node
+    Node(SyntaxNode),
+    /// A token directly under the node
+    Token(SyntaxToken),
+}
+
+/// A node of the lossless syntax tree. The spans of its children are
+/// contiguous and cover exactly the span of the node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    kind: NodeKind,
+    span: Span,
+    children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    /// What sort of node this is
+    #[must_use]
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Where the node is in the source, from its first child to its last
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The child nodes and tokens, in source order
+    #[must_use]
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    /// The child nodes, skipping tokens
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Every token below this node, in source order
+    #[must_use]
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut out = Vec::new();
+        self.collect_tokens(&mut out);
+        out
+    }
+
+    fn collect_tokens(&self, out: &mut Vec<SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(out),
+                SyntaxElement::Token(token) => out.push(*token),
+            }
+        }
+    }
+}
+
+/// A lossless concrete syntax tree: every byte of the input, including
+/// whitespace, blank lines and comments, belongs to exactly one token, so
+/// printing the tokens in order reproduces the input exactly
+#[derive(Debug, Clone)]
+pub struct SyntaxTree<'a> {
+    document: Document<'a>,
+    root: SyntaxNode,
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// The `Document` node of the tree
+    #[must_use]
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    /// The text the tree was parsed from
+    #[must_use]
+    pub fn source(&self) -> &'a str {
+        self.document.source()
+    }
+
+    /// The source text covered by a node or token
+    #[must_use]
+    pub fn text(&self, span: Span) -> &'a str {
+        &self.document.source()[span.start..span.end]
+    }
+
+    /// The abstract syntax tree of the input
+    #[must_use]
+    pub fn to_yaml(&self) -> &Yaml<'a> {
+        self.document.root()
+    }
+
+    #[must_use]
+    pub fn into_document(self) -> Document<'a> {
+        self.document
+    }
+}
+
+impl fmt::Display for SyntaxTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.root.tokens() {
+            f.write_str(self.text(token.span))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse Yaml input into a lossless syntax tree
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml, with a message indicating
+/// where the error occurred and possibly more information on the cause
+pub fn parse_cst(input: &str) -> Result<SyntaxTree<'_>> {
+    let document = parse_document(input)?;
+    let root = Builder::new(&document).build();
+    Ok(SyntaxTree { document, root })
+}
+
+/// Split Yaml input into tokens, whether or not it parses, e.g. for syntax
+/// highlighting while it is being edited. The tokens cover every byte of
+/// the input in order. For valid input they are those of [`parse_cst`];
+/// otherwise the input is lexed from its text alone, with an unclosed quote
+/// or unmatched bracket, and the token at which parsing failed, marked as
+/// [`TokenKind::Error`], and lexing carries on after them.
+#[must_use]
+pub fn tokenize(input: &str) -> Vec<SyntaxToken> {
+    let err = match parse_cst(input) {
+        Ok(tree) => return tree.root().tokens(),
+        Err(err) => err,
+    };
+    let mut tokens: Vec<SyntaxToken> = Lexer::new(input).collect();
+    // the token the error is in, or the last one before it if the error is
+    // in trivia or at the end of the input, unless the lexer already found
+    // an error there, such as a quote left open up to the end
+    let offset = span::offset_of(input, err.line(), err.col());
+    let failed = tokens
+        .iter()
+        .rposition(|token| token.span.start <= offset && !token.kind.is_trivia());
+    if let Some(idx) = failed {
+        if tokens[..=idx]
+            .iter()
+            .all(|token| token.kind != TokenKind::Error)
+        {
+            tokens[idx].kind = TokenKind::Error;
+        }
+    }
+    tokens
+}
+
+/// The spans of the comments in a document, in source order
+pub(crate) fn comment_spans(document: &Document<'_>) -> Vec<Span> {
+    Builder::new(document)
+        .build()
+        .tokens()
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Comment)
+        .map(|token| token.span)
+        .collect()
+}
+
+/// A node's range and kind, before its tokens are filled in
+struct Range {
+    kind: NodeKind,
+    span: Span,
+    depth: usize,
+    children: Vec<Range>,
+}
+
+struct Builder<'d, 'a> {
+    source: &'a str,
+    document: &'d Document<'a>,
+}
+
+impl<'d, 'a> Builder<'d, 'a> {
+    fn new(document: &'d Document<'a>) -> Self {
+        Self {
+            source: document.source(),
+            document,
+        }
+    }
+
+    fn build(&self) -> SyntaxNode {
+        let mut ranges = self.collect_ranges();
+        ranges.sort_by_key(|range| (range.span.start, Reverse(range.span.end), range.depth));
+
+        // Nest the ranges by containment
+        let mut stack = vec![Range {
+            kind: NodeKind::Document,
+            span: Span::new(0, self.source.len()),
+            depth: 0,
+            children: Vec::new(),
+        }];
+        for range in ranges {
+            while stack.len() > 1 && !contains(stack[stack.len() - 1].span, range.span) {
+                close_last(&mut stack);
+            }
+            stack.push(range);
+        }
+        while stack.len() > 1 {
+            close_last(&mut stack);
+        }
+        self.fill(stack.remove(0))
+    }
+
+    /// The ranges of every node the parser recorded a span for
+    fn collect_ranges(&self) -> Vec<Range> {
+        let root = self.document.root();
+        let mut ranges = Vec::new();
+        for (node_path, node) in self.document.spans() {
+            // Aliases are leaves in the source, whatever they stand for
+            let is_alias = self.source[node.span.start..].starts_with('*');
+            let kind = match path::resolve(root, node_path) {
+                Some(_) if is_alias => NodeKind::Scalar,
+                Some(value) => value_kind(value),
+                None => self.kind_from_source(node.span),
+            };
+            let depth = node_path.segments().len() * 2;
+            ranges.push(Range {
+                kind,
+                span: node.span,
+                depth: depth + 1,
+                children: Vec::new(),
+            });
+            if let Some(key) = node.key {
+                ranges.push(Range {
+                    kind: NodeKind::Entry,
+                    span: Span::new(key.start, node.span.end.max(key.end)),
+                    depth,
+                    children: Vec::new(),
+                });
+                ranges.push(Range {
+                    kind: NodeKind::Scalar,
+                    span: key,
+                    depth: depth + 1,
+                    children: Vec::new(),
+                });
+            } else if let Some(PathSegment::Index(_)) = node_path.segments().last() {
+                ranges.push(Range {
+                    kind: NodeKind::Item,
+                    span: Span::new(self.item_start(node.span.start), node.span.end),
+                    depth,
+                    children: Vec::new(),
+                });
+            }
+        }
+        ranges
+    }
+
+    fn kind_from_source(&self, span: Span) -> NodeKind {
+        let text = &self.source[span.start..span.end];
+        if text.starts_with('{') {
+            NodeKind::Mapping
+        } else if text.starts_with('[') || text.starts_with("- ") || text.starts_with("-\n") {
+            NodeKind::Sequence
+        } else {
+            NodeKind::Scalar
+        }
+    }
+
+    /// Where a sequence item starts: at its `-` for block sequences
+    fn item_start(&self, value_start: usize) -> usize {
+        let before = self.source[..value_start].trim_end_matches([' ', '\t']);
+        if before.ends_with('-') {
+            before.len() - 1
+        } else {
+            value_start
+        }
+    }
+
+    /// Fill in the tokens between a range's children
+    fn fill(&self, range: Range) -> SyntaxNode {
+        let mut children = Vec::new();
+        let mut cursor = range.span.start;
+        for child in range.children {
+            if child.span.start < cursor {
+                // overlapping ranges (e.g. duplicate keys) are lexed as part
+                // of the node which contains them
+                continue;
+            }
+            self.lex(cursor, child.span.start, &mut children);
+            cursor = child.span.end;
+            children.push(SyntaxElement::Node(self.fill(child)));
+        }
+        if range.kind == NodeKind::Scalar {
+            self.lex_scalar(cursor, range.span.end, &mut children);
+        } else {
+            self.lex(cursor, range.span.end, &mut children);
+        }
+        SyntaxNode {
+            kind: range.kind,
+            span: range.span,
+            children,
+        }
+    }
+
+    /// Lex a scalar: any properties (tags and anchors) followed by the
+    /// scalar text as a single token
+    fn lex_scalar(&self, mut pos: usize, end: usize, out: &mut Vec<SyntaxElement>) {
+        while pos < end {
+            let rest = &self.source[pos..end];
+            let (kind, len) = match rest.as_bytes()[0] {
+                b'!' => (TokenKind::Tag, tag_len(rest)),
+                b'&' => (TokenKind::Anchor, word_len(rest)),
+                b' ' | b'\t' => (
+                    TokenKind::Whitespace,
+                    run_len(rest, |c| c == ' ' || c == '\t'),
+                ),
+                b'\'' => (TokenKind::SingleQuoted, rest.len()),
+                b'"' => (TokenKind::DoubleQuoted, rest.len()),
+                b'|' | b'>' => (TokenKind::BlockScalar, rest.len()),
+                b'*' => (TokenKind::Alias, rest.len()),
+                _ => (TokenKind::Plain, rest.len()),
+            };
+            push_token(out, kind, pos, pos + len);
+            pos += len;
+        }
+    }
+
+    /// Lex trivia and indicators between nodes
+    fn lex(&self, mut pos: usize, end: usize, out: &mut Vec<SyntaxElement>) {
+        while pos < end {
+            let rest = &self.source[pos..end];
+            let at_line_start = pos == 0 || self.source.as_bytes()[pos - 1] == b'\n';
+            let marker = |m: &str| {
+                at_line_start
+                    && rest.strip_prefix(m).is_some_and(|after| {
+                        after.is_empty() || after.starts_with([' ', '\t', '\r', '\n'])
+                    })
+            };
+            let (kind, len) = match rest.as_bytes()[0] {
+                b' ' | b'\t' => (
+                    TokenKind::Whitespace,
+                    run_len(rest, |c| c == ' ' || c == '\t'),
+                ),
+                b'\n' => (TokenKind::Newline, 1),
+                b'\r' if rest.starts_with("\r\n") => (TokenKind::Newline, 2),
+                b'#' => (TokenKind::Comment, line_len(rest)),
+                b'%' if at_line_start => (TokenKind::Directive, line_len(rest)),
+                b'-' if marker("---") => (TokenKind::DocumentStart, 3),
+                b'.' if marker("...") => (TokenKind::DocumentEnd, 3),
+                b'-' | b'?' | b':' | b',' | b'[' | b']' | b'{' | b'}' => (TokenKind::Indicator, 1),
+                b'!' => (TokenKind::Tag, tag_len(rest)),
+                b'&' => (TokenKind::Anchor, word_len(rest)),
+                b'*' => (TokenKind::Alias, word_len(rest)),
+                _ => (TokenKind::Text, word_len(rest)),
+            };
+            push_token(out, kind, pos, pos + len);
+            pos += len;
+        }
+    }
+}
+
+/// The node kind of a value, looking through tags
+fn value_kind(value: &Yaml<'_>) -> NodeKind {
+    match value.untagged() {
+        Yaml::Mapping(_) => NodeKind::Mapping,
+        Yaml::Sequence(_) => NodeKind::Sequence,
+        _ => NodeKind::Scalar,
+    }
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+/// Pop the innermost open range and add it to its parent
+fn close_last(stack: &mut Vec<Range>) {
+    if let Some(done) = stack.pop() {
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(done);
+        }
+    }
+}
+
+fn push_token(out: &mut Vec<SyntaxElement>, kind: TokenKind, start: usize, end: usize) {
+    out.push(SyntaxElement::Token(SyntaxToken {
+        kind,
+        span: Span::new(start, end),
+    }));
+}
+
+pub(crate) fn run_len(s: &str, pred: impl Fn(char) -> bool) -> usize {
+    s.find(|c| !pred(c)).unwrap_or(s.len())
+}
+
+/// Length up to (not including) the end of the line
+pub(crate) fn line_len(s: &str) -> usize {
+    s.find(['\r', '\n']).unwrap_or(s.len())
+}
+
+/// Length up to whitespace or a flow indicator, at least one character
+/// The length of the tag at the start of `s`, which for a verbatim tag
+/// (`!<uri>`) runs to the closing `>`
+fn tag_len(s: &str) -> usize {
+    let uri = s.strip_prefix("!<").unwrap_or_default();
+    match uri.find(|c: char| c == '>' || c.is_whitespace()) {
+        Some(end) if uri[end..].starts_with('>') => end + 3,
+        _ => word_len(s),
+    }
+}
+
+pub(crate) fn word_len(s: &str) -> usize {
+    let len = s
+        .find(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}'))
+        .unwrap_or(s.len());
+    len.max(s.chars().next().map_or(1, char::len_utf8))
+}
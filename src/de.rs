@@ -0,0 +1,603 @@
+use crate::path::{Path, PathSegment};
+use crate::{parse, Entry, SerdeError, Timestamp, Yaml};
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+/// Deserialize a value of type `T` from Yaml text. Strings which need no
+/// unescaping are borrowed from the input, so `T` may hold `&str` fields.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Server<'a> {
+///     host: &'a str,
+///     port: u16,
+/// }
+///
+/// let server: Server = mini_yaml_rs::from_str("host: localhost\nport: 8080\n").unwrap();
+/// assert_eq!((server.host, server.port), ("localhost", 8080));
+/// ```
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml or does not fit `T`
+pub fn from_str<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T, SerdeError> {
+    from_yaml(parse(input).map_err(SerdeError::Parse)?)
+}
+
+/// Deserialize a value of type `T` from a parsed Yaml tree
+/// # Errors
+/// Returns `Err` if the tree does not fit `T`
+pub fn from_yaml<'de, T: Deserialize<'de>>(value: Yaml<'de>) -> Result<T, SerdeError> {
+    T::deserialize(value)
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Invalid {
+            path: String::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl SerdeError {
+    /// Add the step from a parent node to the path of the node the error
+    /// concerns
+    fn within(self, segment: PathSegment) -> Self {
+        match self {
+            SerdeError::Invalid { path, message } => {
+                let mut full = Path::default();
+                full.push(segment);
+                for segment in Path::parse(&path).unwrap_or_default().segments() {
+                    full.push(segment.clone());
+                }
+                SerdeError::Invalid {
+                    path: full.to_string(),
+                    message,
+                }
+            }
+            err @ SerdeError::Parse(_) => err,
+        }
+    }
+}
+
+/// The error for a value which is not of the type expected
+fn invalid_type<E: de::Error>(value: &Yaml<'_>, exp: &dyn de::Expected) -> E {
+    let unexpected = match value {
+        Yaml::Scalar(s) => de::Unexpected::Str(s),
+        Yaml::String(s) => de::Unexpected::Str(s),
+        Yaml::Int(i) => de::Unexpected::Signed(*i),
+        Yaml::Float(f) => de::Unexpected::Float(*f),
+        Yaml::Bool(b) => de::Unexpected::Bool(*b),
+        Yaml::Null => de::Unexpected::Unit,
+        Yaml::Timestamp(_) => de::Unexpected::Other("timestamp"),
+        Yaml::Bytes(bytes) => de::Unexpected::Bytes(bytes),
+        Yaml::Sequence(_) => de::Unexpected::Seq,
+        Yaml::Mapping(_) => de::Unexpected::Map,
+        Yaml::Tagged(..) => de::Unexpected::Other("tagged value"),
+    };
+    E::invalid_type(unexpected, exp)
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for Yaml<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// An integer too large for `i64`, which parsing leaves a string, but which
+/// still fits a `u64`
+fn beyond_i64(s: &str) -> Option<u64> {
+    s.parse::<u64>()
+        .ok()
+        .filter(|&value| i64::try_from(value).is_err())
+}
+
+macro_rules! deserialize_number {
+    ($($method: ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                match self {
+                    Yaml::Int(_) | Yaml::Float(_) => self.deserialize_any(visitor),
+                    Yaml::Scalar(s) => match beyond_i64(s) {
+                        Some(value) => visitor.visit_u64(value),
+                        None => Err(invalid_type(&self, &visitor)),
+                    },
+                    Yaml::Tagged(_, value) => value.$method(visitor),
+                    other => Err(invalid_type(&other, &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+/// Values are deserialized as what they were resolved to when parsing, so
+/// `"8080"` does not deserialize into a number, while `8080` deserializes
+/// into a string as `"8080"`. Integers too large for `i64`, which parse as
+/// strings, still deserialize into `u64`. Tags are passed over, except that
+/// a tagged value is read as a variant of an enum named by the tag.
+impl<'de> de::Deserializer<'de> for Yaml<'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Scalar(s) => visitor.visit_borrowed_str(s),
+            Yaml::String(s) => visitor.visit_string(s),
+            Yaml::Int(i) => visitor.visit_i64(i),
+            Yaml::Float(f) => visitor.visit_f64(f),
+            Yaml::Bool(b) => visitor.visit_bool(b),
+            Yaml::Null => visitor.visit_unit(),
+            Yaml::Timestamp(ts) => visitor.visit_string(ts.to_string()),
+            Yaml::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            Yaml::Sequence(items) => visit_sequence(items, visitor),
+            Yaml::Mapping(entries) => visit_mapping(entries, visitor),
+            Yaml::Tagged(_, value) => value.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Bool(b) => visitor.visit_bool(b),
+            Yaml::Tagged(_, value) => value.deserialize_bool(visitor),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Scalar(s) => visitor.visit_borrowed_str(s),
+            Yaml::String(s) => visitor.visit_string(s),
+            Yaml::Int(_) | Yaml::Float(_) | Yaml::Bool(_) | Yaml::Timestamp(_) => {
+                visitor.visit_string(self.key_string())
+            }
+            Yaml::Tagged(_, value) => value.deserialize_str(visitor),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            Yaml::Tagged(_, value) => value.deserialize_bytes(visitor),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Null => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Null => visitor.visit_unit(),
+            Yaml::Tagged(_, value) => value.deserialize_unit(visitor),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Sequence(items) => visit_sequence(items, visitor),
+            Yaml::Tagged(_, value) => value.deserialize_seq(visitor),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Mapping(entries) => visit_mapping(entries, visitor),
+            Yaml::Tagged(_, value) => value.deserialize_map(visitor),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Mapping(entries) => visit_mapping(entries, visitor),
+            Yaml::Sequence(items) => visit_sequence(items, visitor),
+            Yaml::Tagged(_, value) => value.deserialize_struct(name, fields, visitor),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    /// An enum variant is written as a tagged value (`!Circle {r: 1}`), as
+    /// a mapping with a single key (`Circle: {r: 1}`), or for a unit
+    /// variant as a plain string (`Empty`)
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self {
+            Yaml::Tagged(tag, value) => visitor.visit_enum(Variant {
+                name: match tag {
+                    Cow::Borrowed(tag) => Yaml::Scalar(tag),
+                    Cow::Owned(tag) => Yaml::String(tag),
+                },
+                value: Some(*value),
+                key: None,
+            }),
+            Yaml::Mapping(mut entries) if entries.len() == 1 => {
+                let Entry { key, value } = entries.remove(0);
+                visitor.visit_enum(Variant {
+                    key: Some(key.key_string()),
+                    name: key,
+                    value: Some(value),
+                })
+            }
+            name @ (Yaml::Scalar(_) | Yaml::String(_)) => visitor.visit_enum(Variant {
+                name,
+                value: None,
+                key: None,
+            }),
+            other => Err(invalid_type(&other, &visitor)),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+}
+
+fn visit_sequence<'de, V: Visitor<'de>>(
+    items: Vec<Yaml<'de>>,
+    visitor: V,
+) -> Result<V::Value, SerdeError> {
+    let len = items.len();
+    let mut access = Items {
+        items: items.into_iter(),
+        idx: 0,
+    };
+    let value = visitor.visit_seq(&mut access)?;
+    if access.items.len() == 0 {
+        Ok(value)
+    } else {
+        Err(de::Error::invalid_length(len, &"fewer elements"))
+    }
+}
+
+fn visit_mapping<'de, V: Visitor<'de>>(
+    entries: Vec<Entry<'de>>,
+    visitor: V,
+) -> Result<V::Value, SerdeError> {
+    visitor.visit_map(Entries {
+        iter: entries.into_iter(),
+        key: None,
+        value: None,
+    })
+}
+
+struct Items<'de> {
+    items: std::vec::IntoIter<Yaml<'de>>,
+    idx: usize,
+}
+
+impl<'de> SeqAccess<'de> for Items<'de> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        let Some(item) = self.items.next() else {
+            return Ok(None);
+        };
+        let idx = self.idx;
+        self.idx += 1;
+        seed.deserialize(item)
+            .map(Some)
+            .map_err(|err| err.within(PathSegment::Index(idx)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct Entries<'de> {
+    iter: std::vec::IntoIter<Entry<'de>>,
+    /// the key of the entry whose value is next, for error paths
+    key: Option<String>,
+    value: Option<Yaml<'de>>,
+}
+
+impl<'de> MapAccess<'de> for Entries<'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        let Some(Entry { key, value }) = self.iter.next() else {
+            return Ok(None);
+        };
+        let name = key.key_string();
+        self.value = Some(value);
+        let key = seed
+            .deserialize(key)
+            .map_err(|err| err.within(PathSegment::Key(name.clone())))?;
+        self.key = Some(name);
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is missing"))?;
+        let key = self.key.take().unwrap_or_default();
+        seed.deserialize(value)
+            .map_err(|err| err.within(PathSegment::Key(key)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// An enum variant's name and content
+struct Variant<'de> {
+    name: Yaml<'de>,
+    value: Option<Yaml<'de>>,
+    /// the mapping key holding the content, for error paths
+    key: Option<String>,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'de> {
+    type Error = SerdeError;
+    type Variant = VariantContent<'de>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, VariantContent<'de>), SerdeError> {
+        let variant = seed.deserialize(self.name)?;
+        Ok((
+            variant,
+            VariantContent {
+                value: self.value,
+                key: self.key,
+            },
+        ))
+    }
+}
+
+struct VariantContent<'de> {
+    value: Option<Yaml<'de>>,
+    key: Option<String>,
+}
+
+impl<'de> VariantContent<'de> {
+    /// Deserialize the variant's content with `deserialize`
+    fn deserialize<T>(
+        self,
+        deserialize: impl FnOnce(Yaml<'de>) -> Result<T, SerdeError>,
+    ) -> Result<T, SerdeError> {
+        let value = self.value.ok_or_else(|| {
+            de::Error::invalid_type(de::Unexpected::UnitVariant, &"variant with content")
+        })?;
+        deserialize(value).map_err(|err| match self.key {
+            Some(key) => err.within(PathSegment::Key(key)),
+            None => err,
+        })
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantContent<'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            None | Some(Yaml::Null) => Ok(()),
+            Some(other) => Err(invalid_type(&other, &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        self.deserialize(|value| seed.deserialize(value))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize(|value| de::Deserializer::deserialize_seq(value, visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize(|value| de::Deserializer::deserialize_map(value, visitor))
+    }
+}
+
+/// A Yaml value is deserialized from any self-describing format, as the
+/// inverse of its `Serialize` implementation: a map whose first key is
+/// `__type` becomes a tagged value. Strings borrowed from the input become
+/// [`Yaml::Scalar`]s and others [`Yaml::String`]s; strings are not resolved
+/// into other types, so a timestamp comes back as its string. Deserializing
+/// a `Yaml` with [`from_str`] passes over tags, which [`parse`] keeps.
+impl<'de: 'a, 'a> Deserialize<'de> for Yaml<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(YamlVisitor)
+    }
+}
+
+struct YamlVisitor;
+
+impl<'de> Visitor<'de> for YamlVisitor {
+    type Value = Yaml<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any Yaml value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Int(value))
+    }
+
+    /// Integers beyond the range of `i64` become floats, as in `from_json`
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Yaml<'de>, E> {
+        Ok(i64::try_from(value).map_or(Yaml::Float(value as f64), Yaml::Int))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Float(value))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Scalar(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::String(value))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Bytes(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Bytes(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Yaml<'de>, E> {
+        Ok(Yaml::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Yaml<'de>, D::Error> {
+        Yaml::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Yaml<'de>, D::Error> {
+        Yaml::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Yaml<'de>, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Yaml::Sequence(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Yaml<'de>, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some((key, value)) = map.next_entry()? {
+            entries.push(Entry::new(key, value));
+        }
+        Ok(Yaml::untag_mapping(entries))
+    }
+}
+
+/// An entry is deserialized from a `(key, value)` tuple
+impl<'de: 'a, 'a> Deserialize<'de> for Entry<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (key, value) = <(Yaml<'a>, Yaml<'a>)>::deserialize(deserializer)?;
+        Ok(Entry::new(key, value))
+    }
+}
+
+/// A timestamp is deserialized from a string in any of the forms
+/// [`Timestamp::parse`] reads
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Timestamp::parse(&text)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&text), &"a timestamp"))
+    }
+}
@@ -0,0 +1,202 @@
+use crate::document::parse_document;
+use crate::path::{Path, PathSegment};
+use crate::snippet::snippet;
+use crate::span::line_col;
+use crate::{Yaml, YamlParseError};
+use core::fmt;
+use std::collections::HashSet;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// the input could not be used as written
+    Error,
+    /// the input was used, but probably does not mean what was intended
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// A single issue found in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// the line the issue was found on
+    pub line: usize,
+    /// the column the issue was found at
+    pub col: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}: {}",
+            self.severity, self.line, self.col, self.message
+        )
+    }
+}
+
+impl From<&YamlParseError> for Diagnostic {
+    fn from(err: &YamlParseError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            line: err.line,
+            col: err.col,
+            message: err
+                .msg
+                .clone()
+                .unwrap_or_else(|| "invalid input".to_string()),
+        }
+    }
+}
+
+/// Every issue found in the input by a lenient API, which reports all of
+/// them at once rather than stopping at the first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a diagnostic to the errors or warnings, according to its severity
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        match diagnostic.severity {
+            Severity::Error => self.errors.push(diagnostic),
+            Severity::Warning => self.warnings.push(diagnostic),
+        }
+    }
+
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    /// The total number of errors and warnings
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.errors.len() + self.warnings.len()
+    }
+
+    /// Every diagnostic, errors first
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.errors.iter().chain(&self.warnings)
+    }
+}
+
+/// One line per diagnostic, errors first
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, diagnostic) in self.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter =
+        std::iter::Chain<std::vec::IntoIter<Diagnostic>, std::vec::IntoIter<Diagnostic>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter().chain(self.warnings)
+    }
+}
+
+impl<'d> IntoIterator for &'d Diagnostics {
+    type Item = &'d Diagnostic;
+    type IntoIter =
+        std::iter::Chain<std::slice::Iter<'d, Diagnostic>, std::slice::Iter<'d, Diagnostic>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter().chain(&self.warnings)
+    }
+}
+
+/// Check Yaml input, reporting every issue found rather than just the
+/// first. Currently reports parse errors, and duplicate mapping keys as
+/// warnings.
+#[must_use]
+pub fn validate(input: &str) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    let doc = match parse_document(input) {
+        Ok(doc) => doc,
+        Err(err) => {
+            diagnostics.push(Diagnostic::from(&err));
+            return diagnostics;
+        }
+    };
+    let mut path = Path::default();
+    find_duplicate_keys(doc.root(), &mut path, &mut |path, key| {
+        // the recorded key span is that of the last occurrence, which wins
+        let (line, col) = doc
+            .key_span(&path.to_string())
+            .map_or((0, 0), |span| line_col(input, span.start));
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            line,
+            col,
+            message: format!(
+                "duplicate key '{}', only the last value is used",
+                snippet(key, Some(80), false)
+            ),
+        });
+    });
+    diagnostics
+}
+
+fn find_duplicate_keys<F>(node: &Yaml<'_>, path: &mut Path, report: &mut F)
+where
+    F: FnMut(&Path, &str),
+{
+    match node {
+        Yaml::Sequence(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(idx));
+                find_duplicate_keys(item, path, report);
+                path.pop();
+            }
+        }
+        Yaml::Mapping(entries) => {
+            let mut seen = HashSet::new();
+            let mut reported = HashSet::new();
+            for entry in entries {
+                let key = entry.key.key_string();
+                if !seen.insert(key.clone()) && reported.insert(key.clone()) {
+                    path.push(PathSegment::Key(key.clone()));
+                    report(path, &key);
+                    path.pop();
+                }
+            }
+            for entry in entries {
+                path.push(PathSegment::Key(entry.key.key_string()));
+                find_duplicate_keys(&entry.value, path, report);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,141 @@
+use crate::path::{Path, PathSegment};
+use crate::Yaml;
+use std::fmt;
+
+/// One difference between two trees, as found by [`diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffOp<'y> {
+    /// A mapping key or sequence item only in the new tree
+    Added { path: String, value: &'y Yaml<'y> },
+    /// A mapping key or sequence item only in the old tree
+    Removed { path: String, value: &'y Yaml<'y> },
+    /// A node whose value differs between the trees
+    Changed {
+        path: String,
+        old: &'y Yaml<'y>,
+        new: &'y Yaml<'y>,
+    },
+}
+
+impl DiffOp<'_> {
+    /// The path of the node, such as `server.hosts[1]`, or `""` for the root
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            DiffOp::Added { path, .. }
+            | DiffOp::Removed { path, .. }
+            | DiffOp::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// Prints a line like `+ server.tls: {enabled: true}`, `- debug: false` or
+/// `~ server.port: 80 -> 8080`, with values as flow Yaml; the root has no
+/// path
+impl fmt::Display for DiffOp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = match self {
+            DiffOp::Added { .. } => '+',
+            DiffOp::Removed { .. } => '-',
+            DiffOp::Changed { .. } => '~',
+        };
+        write!(f, "{sign} ")?;
+        if !self.path().is_empty() {
+            write!(f, "{}: ", self.path())?;
+        }
+        match self {
+            DiffOp::Added { value, .. } | DiffOp::Removed { value, .. } => write!(f, "{value:#}"),
+            DiffOp::Changed { old, new, .. } => write!(f, "{old:#} -> {new:#}"),
+        }
+    }
+}
+
+/// The differences between an old and a new version of a tree, in document
+/// order. Mappings are compared key by key, keys only in the new tree
+/// coming after the others, and with duplicate keys the last one counts,
+/// as in `to_json`. Sequences are compared item by item, so an item
+/// inserted in the middle shows as changes to the items after it. Scalars
+/// are equal when their JSON values are, and a node whose tag changed is
+/// reported as changed as a whole.
+#[must_use]
+pub fn diff<'y>(old: &'y Yaml<'_>, new: &'y Yaml<'_>) -> Vec<DiffOp<'y>> {
+    let mut ops = Vec::new();
+    diff_at(old, new, &mut Path::default(), &mut ops);
+    ops
+}
+
+fn diff_at<'y>(old: &'y Yaml<'y>, new: &'y Yaml<'y>, path: &mut Path, ops: &mut Vec<DiffOp<'y>>) {
+    match (old, new) {
+        (Yaml::Mapping(_), Yaml::Mapping(_)) => {
+            let old_entries = unique_entries(old);
+            let new_entries = unique_entries(new);
+            for (key, old_value) in &old_entries {
+                path.push(PathSegment::Key(key.clone()));
+                match new_entries.iter().find(|(new_key, _)| new_key == key) {
+                    Some((_, new_value)) => diff_at(old_value, new_value, path, ops),
+                    None => ops.push(DiffOp::Removed {
+                        path: path.to_string(),
+                        value: old_value,
+                    }),
+                }
+                path.pop();
+            }
+            for (key, new_value) in &new_entries {
+                if !old_entries.iter().any(|(old_key, _)| old_key == key) {
+                    path.push(PathSegment::Key(key.clone()));
+                    ops.push(DiffOp::Added {
+                        path: path.to_string(),
+                        value: new_value,
+                    });
+                    path.pop();
+                }
+            }
+        }
+        (Yaml::Sequence(old_items), Yaml::Sequence(new_items)) => {
+            for idx in 0..old_items.len().max(new_items.len()) {
+                path.push(PathSegment::Index(idx));
+                match (old_items.get(idx), new_items.get(idx)) {
+                    (Some(old), Some(new)) => diff_at(old, new, path, ops),
+                    (Some(old), None) => ops.push(DiffOp::Removed {
+                        path: path.to_string(),
+                        value: old,
+                    }),
+                    (None, Some(new)) => ops.push(DiffOp::Added {
+                        path: path.to_string(),
+                        value: new,
+                    }),
+                    (None, None) => {}
+                }
+                path.pop();
+            }
+        }
+        (Yaml::Tagged(old_tag, old_value), Yaml::Tagged(new_tag, new_value))
+            if old_tag == new_tag =>
+        {
+            diff_at(old_value, new_value, path, ops);
+        }
+        (old, new)
+            if old.tag().is_none() && new.tag().is_none() && old.to_json() == new.to_json() => {}
+        (old, new) => ops.push(DiffOp::Changed {
+            path: path.to_string(),
+            old,
+            new,
+        }),
+    }
+}
+
+/// The entries of a mapping by JSON key, keeping the last of duplicates in
+/// the place of the first
+fn unique_entries<'y>(node: &'y Yaml<'y>) -> Vec<(String, &'y Yaml<'y>)> {
+    let mut unique: Vec<(String, &Yaml)> = Vec::new();
+    if let Yaml::Mapping(entries) = node {
+        for entry in entries {
+            let key = entry.key.key_string();
+            match unique.iter_mut().find(|(seen, _)| *seen == key) {
+                Some(slot) => slot.1 = &entry.value,
+                None => unique.push((key, &entry.value)),
+            }
+        }
+    }
+    unique
+}
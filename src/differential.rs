@@ -0,0 +1,278 @@
+use crate::corpus::{collect_files, BUILTIN};
+use crate::path::{Path, PathSegment};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path as FsPath, PathBuf};
+
+/// Another Yaml parser which inputs are compared against, such as
+/// `serde_yaml` or `yaml-rust`, reduced to the JSON value of the first
+/// document of an input. No implementations for other crates are provided,
+/// as this crate does not depend on them; wrap one in an [`FnReference`].
+pub trait ReferenceParser {
+    /// The name the parser is reported under
+    fn name(&self) -> &str;
+
+    /// Parse `input` to JSON, in the form [`Yaml::to_json`](crate::Yaml::to_json)
+    /// gives: tags dropped or kept as `__type` keys, and timestamps as
+    /// strings
+    /// # Errors
+    /// Returns `Err` with the parser's message if it rejects the input
+    fn parse(&self, input: &str) -> Result<Value, String>;
+}
+
+/// A closure used as a [`ReferenceParser`]
+pub struct FnReference<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> FnReference<F>
+where
+    F: Fn(&str) -> Result<Value, String>,
+{
+    /// A reference parser named `name` which parses with `f`, e.g.
+    /// `|input| serde_yaml::from_str(input).map_err(|err| err.to_string())`
+    pub fn new(name: &str, f: F) -> Self {
+        Self {
+            name: name.to_string(),
+            f,
+        }
+    }
+}
+
+impl<F> ReferenceParser for FnReference<F>
+where
+    F: Fn(&str) -> Result<Value, String>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parse(&self, input: &str) -> Result<Value, String> {
+        (self.f)(input)
+    }
+}
+
+/// What a parser made of an input
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The JSON value of the input
+    Value(Value),
+    /// The parser's error message
+    Error(String),
+}
+
+impl Outcome {
+    fn from_result(result: Result<Value, String>) -> Self {
+        match result {
+            Ok(value) => Outcome::Value(value),
+            Err(message) => Outcome::Error(message),
+        }
+    }
+}
+
+/// An input the two parsers disagree on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// The input's file name, or the path of a file under the directory run
+    pub name: PathBuf,
+    /// The path of the first value which differs, such as `spec.ports[0]`,
+    /// or `""` for the root or when only one parser failed
+    pub path: String,
+    /// What this crate made of the input
+    pub ours: Outcome,
+    /// What the reference parser made of it
+    pub theirs: Outcome,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name.display())?;
+        if !self.path.is_empty() {
+            write!(f, " at '{}'", self.path)?;
+        }
+        // the values where they differ rather than the whole documents
+        let path = Path::parse(&self.path).unwrap_or_default();
+        let describe = |outcome: &Outcome| match outcome {
+            Outcome::Value(value) => value_at(value, &path).to_string(),
+            Outcome::Error(message) => format!("error ({message})"),
+        };
+        write!(
+            f,
+            ": ours {}, reference {}",
+            describe(&self.ours),
+            describe(&self.theirs)
+        )
+    }
+}
+
+/// The outcome of comparing inputs against a reference parser
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DifferentialReport {
+    /// The name of the reference parser
+    pub reference: String,
+    /// The number of inputs compared
+    pub inputs: usize,
+    /// The number of inputs both parsers read to the same value, or both
+    /// rejected
+    pub agreed: usize,
+    /// The inputs the parsers disagree on, in the order they were checked
+    pub divergences: Vec<Divergence>,
+}
+
+impl DifferentialReport {
+    /// An empty report for comparisons against `reference`
+    #[must_use]
+    pub fn new(reference: &dyn ReferenceParser) -> Self {
+        Self {
+            reference: reference.name().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Parse a single input with both parsers, adding the outcome to the
+    /// report
+    pub fn check(
+        &mut self,
+        name: impl Into<PathBuf>,
+        input: &str,
+        reference: &dyn ReferenceParser,
+    ) {
+        self.inputs += 1;
+        let ours = Outcome::from_result(
+            crate::parse(input)
+                .map(|doc| doc.to_json())
+                .map_err(|err| err.to_string()),
+        );
+        let theirs = Outcome::from_result(reference.parse(input));
+        let path = match (&ours, &theirs) {
+            (Outcome::Value(a), Outcome::Value(b)) => first_difference(a, b),
+            (Outcome::Error(_), Outcome::Error(_)) => None,
+            _ => Some(Path::default()),
+        };
+        match path {
+            Some(path) => self.divergences.push(Divergence {
+                name: name.into(),
+                path: path.to_string(),
+                ours,
+                theirs,
+            }),
+            None => self.agreed += 1,
+        }
+    }
+
+    /// Whether the parsers agreed on every input
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for DifferentialReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} inputs agree with {}",
+            self.agreed, self.inputs, self.reference
+        )?;
+        for divergence in &self.divergences {
+            write!(f, "\n{divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare this crate with `reference` on every file under `dir`, in every
+/// subdirectory and in order of path. Files which are not UTF-8 are
+/// skipped.
+/// # Errors
+/// Returns `Err` if the directory or a file in it cannot be read
+pub fn run_differential(
+    dir: impl AsRef<FsPath>,
+    reference: &dyn ReferenceParser,
+) -> io::Result<DifferentialReport> {
+    let mut paths = Vec::new();
+    collect_files(dir.as_ref(), &mut paths)?;
+    paths.sort();
+    let mut report = DifferentialReport::new(reference);
+    for path in paths {
+        if let Ok(input) = String::from_utf8(fs::read(&path)?) {
+            report.check(path, &input, reference);
+        }
+    }
+    Ok(report)
+}
+
+/// Compare this crate with `reference` on the crate's own corpus, as by
+/// [`run_differential`]
+#[must_use]
+pub fn run_builtin_differential(reference: &dyn ReferenceParser) -> DifferentialReport {
+    let mut report = DifferentialReport::new(reference);
+    for (name, input) in BUILTIN {
+        if let Ok(input) = std::str::from_utf8(input) {
+            report.check(name, input, reference);
+        }
+    }
+    report
+}
+
+/// The value at `path` in `value`, or `value` itself if the path does not
+/// lead anywhere
+fn value_at<'v>(value: &'v Value, path: &Path) -> &'v Value {
+    let mut node = value;
+    for segment in path.segments() {
+        let next = match (segment, node) {
+            (PathSegment::Key(key), Value::Object(map)) => map.get(key),
+            (PathSegment::Index(idx), Value::Array(items)) => items.get(*idx),
+            _ => None,
+        };
+        match next {
+            Some(next) => node = next,
+            None => return value,
+        }
+    }
+    node
+}
+
+/// The path of the first value in `a` which differs from `b`, or `None` if
+/// they are the same. Objects are compared regardless of key order.
+fn first_difference(a: &Value, b: &Value) -> Option<Path> {
+    let mut path = Path::default();
+    if diff_at(a, b, &mut path) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Whether `a` and `b` differ, leaving `path` at the first difference
+fn diff_at(a: &Value, b: &Value, path: &mut Path) -> bool {
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (idx, (a, b)) in a.iter().zip(b).enumerate() {
+                path.push(PathSegment::Index(idx));
+                if diff_at(a, b, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+        (Value::Object(a), Value::Object(b)) if a.len() == b.len() => {
+            for (key, a) in a {
+                let Some(b) = b.get(key) else {
+                    return true;
+                };
+                path.push(PathSegment::Key(key.clone()));
+                if diff_at(a, b, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+        (a, b) => a != b,
+    }
+}
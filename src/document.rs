@@ -0,0 +1,804 @@
+use crate::comments::{self, CommentTable, Comments};
+use crate::emit::{finish_output, is_scalar, Emitter};
+use crate::errors::EditError;
+use crate::metadata::MetaTable;
+use crate::parse::Parser;
+use crate::path::{self, Path, PathSegment};
+use crate::span::{NodeSpan, Span, SpanTable, Spanned};
+use crate::{Dialect, EmitOptions, Entry, Newline, Result, TimestampFormat, Yaml};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A parsed document which remembers where each of its nodes came from in
+/// the source text
+#[derive(Debug, Clone)]
+pub struct Document<'a> {
+    source: &'a str,
+    root: Yaml<'a>,
+    spans: SpanTable,
+    /// found the first time they are asked for
+    comments: OnceLock<CommentTable<'a>>,
+    /// values attached to nodes by the user
+    meta: MetaTable,
+}
+
+/// A replacement of a range of the original source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// the byte range of the original source to replace
+    pub range: Span,
+    /// the text to put in place of `range`
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Apply non-overlapping edits to `source`, returning the edited text
+    #[must_use]
+    pub fn apply(source: &str, edits: &[TextEdit]) -> String {
+        let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+        sorted.sort_by_key(|edit| edit.range.start);
+        let mut out = String::with_capacity(source.len());
+        let mut pos = 0;
+        for edit in sorted {
+            out.push_str(&source[pos..edit.range.start]);
+            out.push_str(&edit.replacement);
+            pos = edit.range.end;
+        }
+        out.push_str(&source[pos..]);
+        out
+    }
+}
+
+/// Parse Yaml input, recording the source span of every node
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml, with a message indicating
+/// where the error occurred and possibly more information on the cause
+pub fn parse_document(input: &str) -> Result<Document<'_>> {
+    let mut parser = Parser::new(input)?;
+    parser.record_spans();
+    let root = parser.parse()?;
+    let spans = parser.take_spans();
+    Ok(Document {
+        source: input,
+        root,
+        spans,
+        comments: OnceLock::new(),
+        meta: MetaTable::default(),
+    })
+}
+
+/// The options edits are written with: strings which would read back as
+/// something else are quoted, as `serde_yaml` quotes them
+fn edit_options(indent: usize) -> EmitOptions {
+    EmitOptions {
+        dialect: Dialect::SerdeYaml,
+        indent,
+        ..EmitOptions::default()
+    }
+}
+
+/// Render `value` as it is printed after the `:` of a mapping entry (or the
+/// `-` of a sequence item, when `in_sequence` is set) whose line is indented
+/// by `indent`
+fn render_after_indicator(value: &Yaml<'_>, indent: usize, in_sequence: bool) -> String {
+    let (wrapper, skip) = if in_sequence {
+        (Yaml::Sequence(vec![value.clone()]), indent + 1)
+    } else {
+        let entry = Entry::new(Yaml::Scalar("k"), value.clone());
+        (Yaml::Mapping(vec![entry]), indent + 2)
+    };
+    let out = wrapper.emit(&edit_options(indent));
+    let out = out.strip_suffix('\n').unwrap_or(&out);
+    out[skip..].to_string()
+}
+
+/// Render `value` on its own, for values which fit on a single line. Inside
+/// flow collections (`in_flow`) strings holding flow indicators are quoted
+/// as well.
+fn render_inline(value: &Yaml<'_>, in_flow: bool) -> Option<String> {
+    match value {
+        Yaml::Sequence(seq) if seq.is_empty() => Some("[]".to_string()),
+        Yaml::Mapping(map) if map.is_empty() => Some("{}".to_string()),
+        node if is_scalar(node) => {
+            let mut out = String::new();
+            let opts = edit_options(0);
+            let mut emitter = Emitter::new(&mut out, &opts);
+            // Writing into a String cannot fail
+            let _ = if in_flow {
+                emitter.emit_flow(node)
+            } else {
+                emitter.emit(node)
+            };
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+/// Render a mapping key, quoting it if it would otherwise be read as
+/// something else
+fn render_key(key: &str, in_flow: bool) -> String {
+    render_inline(&Yaml::String(key.to_string()), in_flow).unwrap_or_default()
+}
+
+/// Render a block collection so that it can replace a node starting at
+/// column `col`: continuation lines are indented, the first line is not
+fn render_block(value: &Yaml<'_>, col: usize) -> String {
+    let out = value.emit(&edit_options(col));
+    let out = out.strip_suffix('\n').unwrap_or(&out);
+    out[col.min(out.len())..].to_string()
+}
+
+impl<'a> Document<'a> {
+    /// The line break style of the source, for emitting the document in
+    /// the style it was written in
+    #[must_use]
+    pub fn newline(&self) -> Newline {
+        Newline::detect(self.source)
+    }
+
+    /// The text the document was parsed from
+    #[must_use]
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The top level Yaml element
+    #[must_use]
+    pub fn root(&self) -> &Yaml<'a> {
+        &self.root
+    }
+
+    #[must_use]
+    pub fn into_root(self) -> Yaml<'a> {
+        self.root
+    }
+
+    /// Whether the source ends with a line break
+    #[must_use]
+    pub fn ends_with_newline(&self) -> bool {
+        self.source.ends_with('\n')
+    }
+
+    /// Print the document, keeping the final line break of the source (or
+    /// its absence) unless `opts` asks otherwise, and its comments if
+    /// `opts.comments` is set
+    #[must_use]
+    pub fn emit(&self, opts: &EmitOptions) -> String {
+        let restored;
+        let root = if opts.timestamp == TimestampFormat::Original {
+            let mut root = self.root.clone();
+            self.restore_timestamps(&mut root, &mut Path::default());
+            restored = root;
+            &restored
+        } else {
+            &self.root
+        };
+        let mut out = String::new();
+        let mut emitter = Emitter::new(&mut out, opts);
+        if opts.comments {
+            emitter = emitter.with_comments(self.comment_table());
+        }
+        // Writing into a String cannot fail
+        let _ = emitter.emit(root);
+        finish_output(&mut out, opts, Some(self.ends_with_newline()));
+        out
+    }
+
+    /// Replace every timestamp under `node`, which is at `path`, with the
+    /// text it was written as
+    fn restore_timestamps(&self, node: &mut Yaml<'a>, path: &mut Path) {
+        match node {
+            Yaml::Timestamp(_) => {
+                let Some(node_span) = self.spans.get(path) else {
+                    return;
+                };
+                // the span starts at the node's tag or anchor, if it has
+                // one, and a copy made by an alias keeps its value
+                let mut text = &self.source[node_span.span.start..node_span.span.end];
+                while text.starts_with(['!', '&']) {
+                    let end = text.find([' ', '\t']).unwrap_or(text.len());
+                    text = text[end..].trim_start();
+                }
+                if !text.starts_with('*') {
+                    *node = Yaml::Scalar(text);
+                }
+            }
+            // tagged values are recorded at the tag's path
+            Yaml::Tagged(_, value) => self.restore_timestamps(value, path),
+            Yaml::Sequence(items) => {
+                for (idx, item) in items.iter_mut().enumerate() {
+                    path.push(PathSegment::Index(idx));
+                    self.restore_timestamps(item, path);
+                    path.pop();
+                }
+            }
+            Yaml::Mapping(entries) => {
+                for entry in entries {
+                    path.push(PathSegment::Key(entry.key.key_string()));
+                    self.restore_timestamps(&mut entry.value, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The source span of the node at `path` (e.g. `server.hosts[1]`)
+    #[must_use]
+    pub fn span(&self, path: &str) -> Option<Span> {
+        let path = Path::parse(path)?;
+        self.spans.get(&path).map(|node| node.span)
+    }
+
+    /// The node at `path` together with its source span
+    #[must_use]
+    pub fn spanned(&self, path: &str) -> Option<Spanned<&Yaml<'a>>> {
+        let path = Path::parse(path)?;
+        let span = self.spans.get(&path)?.span;
+        // spans are recorded through tags, so the path is followed the same way
+        let mut node = &self.root;
+        for segment in path.segments() {
+            node = match segment {
+                PathSegment::Key(key) => node.get(key.as_str())?,
+                PathSegment::Index(idx) => node.get(*idx)?,
+            };
+        }
+        Some(Spanned::new(node, span))
+    }
+
+    /// Every node of the document with its path and source span, from the
+    /// root down and in the order of the source, e.g. for mapping values
+    /// back to the text they came from in diagnostics. The copies an alias
+    /// stands for are listed as the alias alone, as the nodes below it were
+    /// not written there. Of the entries of a key written more than once,
+    /// only the last is listed, as [`Document::spanned`] gives it.
+    #[must_use]
+    pub fn spanned_nodes(&self) -> Vec<(String, Spanned<&Yaml<'a>>)> {
+        let mut nodes = Vec::new();
+        self.collect_spanned(&self.root, &mut Path::default(), &mut nodes);
+        nodes
+    }
+
+    fn collect_spanned<'d>(
+        &self,
+        node: &'d Yaml<'a>,
+        path: &mut Path,
+        out: &mut Vec<(String, Spanned<&'d Yaml<'a>>)>,
+    ) {
+        let Some(span) = self.spans.get(path) else {
+            return;
+        };
+        out.push((path.to_string(), Spanned::new(node, span.span)));
+        match node.untagged() {
+            Yaml::Mapping(entries) => {
+                // a key written more than once has the span of its last
+                // entry, so the entries it shadows are left out
+                let keys: Vec<String> =
+                    entries.iter().map(|entry| entry.key.key_string()).collect();
+                let last: HashMap<&str, usize> = keys
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, key)| (key.as_str(), idx))
+                    .collect();
+                for (idx, (entry, key)) in entries.iter().zip(&keys).enumerate() {
+                    if last[key.as_str()] != idx {
+                        continue;
+                    }
+                    path.push(PathSegment::Key(key.clone()));
+                    self.collect_spanned(&entry.value, path, out);
+                    path.pop();
+                }
+            }
+            Yaml::Sequence(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(idx));
+                    self.collect_spanned(item, path, out);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The source span of the key which the node at `path` is stored under.
+    /// Returns `None` if `path` does not name a mapping entry.
+    #[must_use]
+    pub fn key_span(&self, path: &str) -> Option<Span> {
+        let path = Path::parse(path)?;
+        self.spans.get(&path)?.key
+    }
+
+    /// The source span of the value of the mapping entry at `path`.
+    /// Unlike [`Document::span`], returns `None` for sequence items and the
+    /// root node.
+    #[must_use]
+    pub fn value_span(&self, path: &str) -> Option<Span> {
+        let path = Path::parse(path)?;
+        let node = self.spans.get(&path)?;
+        node.key.map(|_| node.span)
+    }
+
+    /// The source text of the node at `path`, exactly as written (quotes,
+    /// tags and all), for interpreting it some other way
+    #[must_use]
+    pub fn source_slice(&self, path: &str) -> Option<&'a str> {
+        let span = self.span(path)?;
+        Some(&self.source[span.start..span.end])
+    }
+
+    /// The source text of the key which the node at `path` is stored under.
+    /// Returns `None` if `path` does not name a mapping entry.
+    #[must_use]
+    pub fn key_source_slice(&self, path: &str) -> Option<&'a str> {
+        let span = self.key_span(path)?;
+        Some(&self.source[span.start..span.end])
+    }
+
+    pub(crate) fn spans(&self) -> &SpanTable {
+        &self.spans
+    }
+
+    fn comment_table(&self) -> &CommentTable<'a> {
+        self.comments.get_or_init(|| comments::collect(self))
+    }
+
+    /// The comments attached to the node at `path`: the comment lines
+    /// above it and the comment at the end of its line. Returns `None` if
+    /// the node has neither.
+    ///
+    /// Comments are found the first time any are asked for.
+    #[must_use]
+    pub fn comments(&self, path: &str) -> Option<&Comments<'a>> {
+        self.comment_table().get(&Path::parse(path)?)
+    }
+
+    /// The comment lines after the last node of the document
+    #[must_use]
+    pub fn end_comments(&self) -> &[&'a str] {
+        &self.comment_table().end
+    }
+
+    /// Attach a value of type `T` to the node at `path`, so that tools
+    /// which make several passes over a document (a validator, then a
+    /// renderer) can leave notes on its nodes for later passes. A node
+    /// holds one value of each type, so the value of type `T` it held
+    /// before, if any, is returned.
+    /// # Errors
+    /// Returns `Err` if the path is malformed or there is no node at it
+    pub fn set_meta<T: Any + Clone + Send + Sync>(
+        &mut self,
+        path: &str,
+        value: T,
+    ) -> std::result::Result<Option<T>, EditError> {
+        let parsed = Path::parse(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        if path::resolve(&self.root, &parsed).is_none() {
+            return Err(EditError::NotFound(path.to_string()));
+        }
+        Ok(self.meta.insert(parsed, value))
+    }
+
+    /// The value of type `T` attached to the node at `path`
+    #[must_use]
+    pub fn meta<T: Any>(&self, path: &str) -> Option<&T> {
+        self.meta.get(&Path::parse(path)?)
+    }
+
+    #[must_use]
+    pub fn meta_mut<T: Any>(&mut self, path: &str) -> Option<&mut T> {
+        self.meta.get_mut(&Path::parse(path)?)
+    }
+
+    /// Detach the value of type `T` from the node at `path`
+    pub fn remove_meta<T: Any>(&mut self, path: &str) -> Option<T> {
+        self.meta.remove(&Path::parse(path)?)
+    }
+
+    /// The path of every node with a value of type `T` attached, and the
+    /// value, in the order the nodes appear in the source
+    #[must_use]
+    pub fn all_meta<T: Any>(&self) -> Vec<(String, &T)> {
+        let mut all: Vec<_> = self.meta.iter::<T>().collect();
+        all.sort_by_key(|(path, _)| self.spans.get(path).map(|node| node.span.start));
+        all.into_iter()
+            .map(|(path, value)| (path.to_string(), value))
+            .collect()
+    }
+
+    /// The source span of the sequence item at `path`, from its `-` (in
+    /// block sequences) to the end of its value. Returns `None` if `path`
+    /// does not name a sequence item.
+    #[must_use]
+    pub fn item_span(&self, path: &str) -> Option<Span> {
+        let path = Path::parse(path)?;
+        self.item(&path).map(|(span, _)| span)
+    }
+
+    /// The comment attached to the block sequence item at `path`: the
+    /// comment lines directly above it, with no blank line in between, from
+    /// the first `#` to the end of the last comment line.
+    #[must_use]
+    pub fn item_comment(&self, path: &str) -> Option<&'a str> {
+        let path = Path::parse(path)?;
+        let (span, true) = self.item(&path)? else {
+            return None;
+        };
+        let start = self.leading_comment(span.start)?;
+        let end = self.line_start(span.start) - 1;
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
+
+    /// The text which belongs to the sequence item at `path`, for moving it
+    /// as a unit: for block sequences, its attached comment (see
+    /// [`Document::item_comment`]) through to the end of the item's last
+    /// line, including any comment there; for flow sequences, just the item.
+    #[must_use]
+    pub fn item_extent(&self, path: &str) -> Option<Span> {
+        let path = Path::parse(path)?;
+        self.extent(&path)
+    }
+
+    /// The span of the sequence item at `path`, and whether it is in a block
+    /// sequence
+    fn item(&self, path: &Path) -> Option<(Span, bool)> {
+        let Some((parent, PathSegment::Index(_))) = path.split_last() else {
+            return None;
+        };
+        let span = self.spans.get(path)?.span;
+        if self.is_flow(self.spans.get(&parent)?.span) {
+            return Some((span, false));
+        }
+        let dash = self.after_indicator(span.start, '-')? - 1;
+        Some((Span::new(dash, span.end), true))
+    }
+
+    fn extent(&self, path: &Path) -> Option<Span> {
+        let (span, block) = self.item(path)?;
+        if !block {
+            return Some(span);
+        }
+        let start = self.leading_comment(span.start).unwrap_or(span.start);
+        let rest = &self.source[span.end..];
+        let line_end = span.end + rest.find('\n').unwrap_or(rest.len());
+        let end = span
+            .end
+            .max(line_end - usize::from(self.source[..line_end].ends_with('\r')));
+        Some(Span::new(start, end))
+    }
+
+    /// The offset of the first `#` of the comment lines directly above the
+    /// line holding `offset`, which must start that line
+    fn leading_comment(&self, offset: usize) -> Option<usize> {
+        let line_start = self.line_start(offset);
+        if !self.source[line_start..offset].trim().is_empty() {
+            return None;
+        }
+        let col = offset - line_start;
+        let mut first = None;
+        let mut start = line_start;
+        while start > 0 {
+            let prev = self.line_start(start - 1);
+            let line = &self.source[prev..start - 1];
+            let comment = line.trim_start();
+            if !comment.starts_with('#') || line.len() - comment.len() < col {
+                break;
+            }
+            first = Some(start - 1 - line.len() + (line.len() - comment.len()));
+            start = prev;
+        }
+        first
+    }
+
+    /// The offset of the start of the line containing `offset`
+    fn line_start(&self, offset: usize) -> usize {
+        self.source[..offset].rfind('\n').map_or(0, |idx| idx + 1)
+    }
+
+    /// If the text before `offset` (ignoring whitespace) ends with the
+    /// indicator `ind`, returns the offset just after the indicator
+    fn after_indicator(&self, offset: usize, ind: char) -> Option<usize> {
+        let before = self.source[..offset].trim_end();
+        before.ends_with(ind).then_some(before.len())
+    }
+
+    fn is_flow(&self, span: Span) -> bool {
+        matches!(self.source.as_bytes().get(span.start), Some(b'{' | b'['))
+    }
+
+    /// Compute the text edits which replace the node at `path` with
+    /// `new_value`, touching as little of the original text as possible.
+    /// A missing final mapping key is inserted after the mapping's last
+    /// entry, as is a sequence index one past the end.
+    ///
+    /// The edits are verified by re-parsing the edited text.
+    /// # Errors
+    /// Returns `Err` if the path is malformed or its parent does not exist,
+    /// if a block value would have to be written inside a flow collection,
+    /// or if the edited text would not parse to the requested document
+    pub fn edit(
+        &self,
+        path: &str,
+        new_value: &Yaml<'_>,
+    ) -> std::result::Result<Vec<TextEdit>, EditError> {
+        let parsed = Path::parse(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        let mut edit = match self.spans.get(&parsed) {
+            Some(node) => self.replace(&parsed, *node, new_value)?,
+            None => self.insert(&parsed, new_value)?,
+        };
+        // write the new lines with the source's line breaks
+        if self.newline() == Newline::CrLf {
+            edit.replacement = edit.replacement.replace('\n', "\r\n");
+        }
+        let mut expected = self.root.clone();
+        if !path::set(&mut expected, &parsed, new_value.clone()) {
+            return Err(EditError::NotFound(path.to_string()));
+        }
+        self.verify(vec![edit], &expected, &parsed)
+    }
+
+    /// Compute the text edit which renames the mapping key at `path` to
+    /// `new_key`. Only the key is rewritten; the value's text, including
+    /// its formatting and any comments, is left untouched.
+    ///
+    /// The edit is verified by re-parsing the edited text.
+    /// # Errors
+    /// Returns `Err` if the path is malformed or does not name a mapping
+    /// entry, if the mapping already contains `new_key`, or if the edited
+    /// text would not parse to the requested document
+    pub fn rename_key(
+        &self,
+        path: &str,
+        new_key: &str,
+    ) -> std::result::Result<Vec<TextEdit>, EditError> {
+        let parsed = Path::parse(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        let not_found = || EditError::NotFound(path.to_string());
+        let key_span = self
+            .spans
+            .get(&parsed)
+            .and_then(|node| node.key)
+            .ok_or_else(not_found)?;
+        let Some((parent, PathSegment::Key(old_key))) = parsed.split_last() else {
+            return Err(not_found());
+        };
+
+        let mut expected = self.root.clone();
+        let Some(Yaml::Mapping(entries)) = path::resolve_mut(&mut expected, &parent) else {
+            return Err(not_found());
+        };
+        if old_key != new_key && entries.iter().any(|e| path::key_matches(&e.key, new_key)) {
+            return Err(EditError::KeyExists(new_key.to_string()));
+        }
+        if let Some(entry) = entries
+            .iter_mut()
+            .rev()
+            .find(|e| path::key_matches(&e.key, old_key))
+        {
+            entry.key = Yaml::String(new_key.to_string());
+        }
+
+        let in_flow = self
+            .spans
+            .get(&parent)
+            .is_some_and(|node| self.is_flow(node.span));
+        let edit = TextEdit {
+            range: key_span,
+            replacement: render_key(new_key, in_flow),
+        };
+        self.verify(vec![edit], &expected, &parsed)
+    }
+
+    /// Compute the text edits which move the sequence item at `path` to
+    /// index `to` of the same sequence, shifting the items in between. Each
+    /// item is moved together with its extent (see
+    /// [`Document::item_extent`]), so attached comments travel with it and
+    /// the other items' text is left as it was.
+    ///
+    /// The edits are verified by re-parsing the edited text.
+    /// # Errors
+    /// Returns `Err` if the path is malformed or does not name a sequence
+    /// item, if `to` is past the end of the sequence, or if the edited text
+    /// would not parse to the requested document
+    pub fn move_item(
+        &self,
+        path: &str,
+        to: usize,
+    ) -> std::result::Result<Vec<TextEdit>, EditError> {
+        let parsed = Path::parse(path).ok_or_else(|| EditError::InvalidPath(path.to_string()))?;
+        let not_found = || EditError::NotFound(path.to_string());
+        let Some((parent, &PathSegment::Index(from))) = parsed.split_last() else {
+            return Err(not_found());
+        };
+        let Some(Yaml::Sequence(items)) = path::resolve(&self.root, &parent) else {
+            return Err(not_found());
+        };
+        if from >= items.len() {
+            return Err(not_found());
+        }
+        if to >= items.len() {
+            let mut target = parent.clone();
+            target.push(PathSegment::Index(to));
+            return Err(EditError::NotFound(target.to_string()));
+        }
+        let extents = (0..items.len())
+            .map(|idx| {
+                let mut item = parent.clone();
+                item.push(PathSegment::Index(idx));
+                self.extent(&item)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(not_found)?;
+
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        let moved = order.remove(from);
+        order.insert(to, moved);
+        let edits = order
+            .iter()
+            .enumerate()
+            .filter(|(slot, src)| slot != *src)
+            .map(|(slot, &src)| TextEdit {
+                range: extents[slot],
+                replacement: self.source[extents[src].start..extents[src].end].to_string(),
+            })
+            .collect();
+
+        let mut expected = self.root.clone();
+        if let Some(Yaml::Sequence(items)) = path::resolve_mut(&mut expected, &parent) {
+            let item = items.remove(from);
+            items.insert(to, item);
+        }
+        self.verify(edits, &expected, &parsed)
+    }
+
+    /// Make sure the edited text means what was asked for
+    fn verify(
+        &self,
+        edits: Vec<TextEdit>,
+        expected: &Yaml<'_>,
+        path: &Path,
+    ) -> std::result::Result<Vec<TextEdit>, EditError> {
+        let edited = TextEdit::apply(self.source, &edits);
+        match crate::parse(&edited) {
+            Ok(reparsed) if reparsed.to_json() == expected.to_json() => Ok(edits),
+            _ => Err(EditError::NotRoundTrip(path.to_string())),
+        }
+    }
+
+    fn replace(
+        &self,
+        path: &Path,
+        node: NodeSpan,
+        new_value: &Yaml<'_>,
+    ) -> std::result::Result<TextEdit, EditError> {
+        let span = node.span;
+        let Some((parent, last)) = path.split_last() else {
+            // Replacing the whole document
+            let replacement =
+                render_inline(new_value, false).unwrap_or_else(|| render_block(new_value, 0));
+            return Ok(TextEdit {
+                range: span,
+                replacement,
+            });
+        };
+        let in_flow = self
+            .spans
+            .get(&parent)
+            .is_some_and(|node| self.is_flow(node.span));
+        let inline = render_inline(new_value, in_flow);
+        if in_flow {
+            return match inline {
+                // An omitted value (`{a, b: }`) is written after its key
+                Some(replacement) if span.is_empty() => {
+                    let colon = self.source[..span.start].ends_with(':');
+                    Ok(TextEdit {
+                        range: span,
+                        replacement: format!("{}{replacement}", if colon { " " } else { ": " }),
+                    })
+                }
+                Some(replacement) => Ok(TextEdit {
+                    range: span,
+                    replacement,
+                }),
+                None => Err(EditError::Unsupported(
+                    "block values cannot be written inside flow collections".to_string(),
+                )),
+            };
+        }
+
+        let line_start = self.line_start(span.start);
+        let starts_line = self.source[line_start..span.start].trim().is_empty();
+        let col = span.start - line_start;
+        let (indicator, in_sequence) = match last {
+            PathSegment::Key(_) => (':', false),
+            PathSegment::Index(_) => ('-', true),
+        };
+        // The column of the key or "-" which owns the value; nested block
+        // content must be indented past it
+        let owner_col = |indicator_end: usize| {
+            let owner = match node.key {
+                Some(key) => key.start,
+                None => indicator_end - 1,
+            };
+            owner - self.line_start(owner)
+        };
+        let tagged = new_value.tag().is_some();
+        match inline {
+            // Scalar replacing a scalar on the same line
+            Some(replacement) if !starts_line && span.start < span.end => Ok(TextEdit {
+                range: span,
+                replacement,
+            }),
+            // Block collection replacing one which starts its own line, or a
+            // mapping written directly after a sequence item's "-"
+            None if !tagged
+                && (starts_line || (in_sequence && matches!(new_value, Yaml::Mapping(..)))) =>
+            {
+                Ok(TextEdit {
+                    range: span,
+                    replacement: render_block(new_value, col),
+                })
+            }
+            // Otherwise re-render everything after the ":" or "-"
+            _ => match self.after_indicator(span.start, indicator) {
+                Some(start) => Ok(TextEdit {
+                    range: Span::new(start, span.end),
+                    replacement: render_after_indicator(new_value, owner_col(start), in_sequence),
+                }),
+                None => Ok(TextEdit {
+                    range: span,
+                    replacement: render_block(new_value, col),
+                }),
+            },
+        }
+    }
+
+    fn insert(
+        &self,
+        path: &Path,
+        new_value: &Yaml<'_>,
+    ) -> std::result::Result<TextEdit, EditError> {
+        let not_found = || EditError::NotFound(path.to_string());
+        let (parent, last) = path.split_last().ok_or_else(not_found)?;
+        let parent_span = self.spans.get(&parent).ok_or_else(not_found)?.span;
+        let parent_node = path::resolve(&self.root, &parent).ok_or_else(not_found)?;
+        let (entry, is_empty) = match (parent_node, last) {
+            (Yaml::Mapping(map), PathSegment::Key(key)) => (
+                Yaml::Mapping(vec![Entry::new(
+                    Yaml::String(key.clone()),
+                    new_value.clone(),
+                )]),
+                map.is_empty(),
+            ),
+            (Yaml::Sequence(items), PathSegment::Index(idx)) if *idx == items.len() => {
+                (Yaml::Sequence(vec![new_value.clone()]), items.is_empty())
+            }
+            _ => return Err(not_found()),
+        };
+
+        if self.is_flow(parent_span) {
+            let Some(value) = render_inline(new_value, true) else {
+                return Err(EditError::Unsupported(
+                    "block values cannot be written inside flow collections".to_string(),
+                ));
+            };
+            let body = match last {
+                PathSegment::Key(key) => format!("{}: {value}", render_key(key, true)),
+                PathSegment::Index(_) => value,
+            };
+            let close = parent_span.end - 1;
+            let separator = if is_empty { "" } else { ", " };
+            return Ok(TextEdit {
+                range: Span::new(close, close),
+                replacement: format!("{separator}{body}"),
+            });
+        }
+
+        let col = parent_span.start - self.line_start(parent_span.start);
+        let rendered = entry.emit(&edit_options(col));
+        let rendered = rendered.strip_suffix('\n').unwrap_or(&rendered);
+        Ok(TextEdit {
+            range: Span::new(parent_span.end, parent_span.end),
+            replacement: format!("\n{rendered}"),
+        })
+    }
+}
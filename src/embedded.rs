@@ -0,0 +1,167 @@
+use crate::document::Document;
+use crate::path::{Path, PathSegment};
+use crate::span::{self, Span};
+use crate::Yaml;
+
+/// Which block scalars [`Document::embedded_blocks`] extracts, and how it
+/// finds their language
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedOptions {
+    /// Patterns for the keys whose block scalar values are extracted, where
+    /// `*` matches any run of characters (`command`, `*_script`)
+    pub keys: Vec<String>,
+    /// A key beside the block scalar whose value names its language, used
+    /// when the block scalar has no tag (`!js |`) to say so
+    pub language_key: Option<String>,
+}
+
+impl Default for EmbeddedOptions {
+    fn default() -> Self {
+        Self {
+            keys: vec!["command".to_string()],
+            language_key: Some("language".to_string()),
+        }
+    }
+}
+
+/// A block scalar embedded in a document, such as a script under a
+/// `command: |` key, with where its text is in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedBlock {
+    /// The path of the block scalar, e.g. `steps[2].command`
+    pub path: String,
+    /// The text of the block scalar, with its indentation removed
+    pub text: String,
+    /// The language given by the block scalar's tag or the language key
+    pub language: Option<String>,
+    /// The source span of the block scalar, including any tag and its
+    /// `|` or `>` header
+    pub span: Span,
+    /// The 1-based source line of the first line of `text`
+    pub line: usize,
+    /// The number of columns the content is indented by in the source. For
+    /// literal (`|`) blocks, line `n` of `text` (counting from 0) is line
+    /// `line + n` of the source, and each of its columns `indent` further
+    /// right.
+    pub indent: usize,
+}
+
+impl Document<'_> {
+    /// Every block scalar (`|` or `>`) stored under a key matching one of
+    /// `opts.keys`, in source order, e.g. the scripts in `command: |` blocks,
+    /// for linting or running them with errors mapped back to the source.
+    #[must_use]
+    pub fn embedded_blocks(&self, opts: &EmbeddedOptions) -> Vec<EmbeddedBlock> {
+        let mut blocks = Vec::new();
+        self.find_blocks(self.root(), &mut Path::default(), opts, &mut blocks);
+        blocks.sort_by_key(|block| block.span.start);
+        blocks
+    }
+
+    fn find_blocks(
+        &self,
+        node: &Yaml<'_>,
+        path: &mut Path,
+        opts: &EmbeddedOptions,
+        blocks: &mut Vec<EmbeddedBlock>,
+    ) {
+        match node {
+            Yaml::Sequence(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(idx));
+                    self.find_blocks(item, path, opts, blocks);
+                    path.pop();
+                }
+            }
+            Yaml::Mapping(entries) => {
+                for entry in entries {
+                    let key = entry.key.key_string();
+                    let wanted = opts.keys.iter().any(|pattern| glob_match(pattern, &key));
+                    path.push(PathSegment::Key(key));
+                    let block = wanted.then(|| self.block_at(&entry.value, path)).flatten();
+                    match block {
+                        Some(mut block) => {
+                            if block.language.is_none() {
+                                block.language = opts.language_key.as_ref().and_then(|name| {
+                                    entries
+                                        .iter()
+                                        .find(|entry| entry.key.key_string() == *name)
+                                        .and_then(|entry| text(&entry.value))
+                                        .map(str::to_string)
+                                });
+                            }
+                            blocks.push(block);
+                        }
+                        None => self.find_blocks(&entry.value, path, opts, blocks),
+                    }
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The block scalar `value` at `path`, if it is one
+    fn block_at(&self, value: &Yaml<'_>, path: &Path) -> Option<EmbeddedBlock> {
+        let (language, content) = match value {
+            Yaml::Tagged(tag, content) => {
+                (Some(tag.trim_start_matches('!').to_string()), &**content)
+            }
+            value => (None, value),
+        };
+        let content = text(content)?;
+        let span = self.spans().get(path)?.span;
+        let source = &self.source()[span.start..span.end];
+        // skip any tag and anchor before the header
+        let mut header = source;
+        while header.starts_with(['!', '&']) {
+            let end = header.find(char::is_whitespace).unwrap_or(header.len());
+            header = header[end..].trim_start_matches([' ', '\t']);
+        }
+        if !header.starts_with(['|', '>']) {
+            return None;
+        }
+        let header_start = span.end - header.len();
+        let content_start = header
+            .find('\n')
+            .map_or(span.end, |idx| header_start + idx + 1);
+        let body = &self.source()[content_start..span.end];
+        let indent = body
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .map_or(0, |line| line.len() - line.trim_start_matches(' ').len());
+        Some(EmbeddedBlock {
+            path: path.to_string(),
+            text: content.to_string(),
+            language,
+            span,
+            line: span::line_col(self.source(), content_start).0,
+            indent,
+        })
+    }
+}
+
+fn text<'y>(node: &'y Yaml<'_>) -> Option<&'y str> {
+    match node {
+        Yaml::Scalar(s) => Some(s),
+        Yaml::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+/// Whether `text` matches `pattern`, in which `*` matches any run of
+/// characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            text.char_indices()
+                .map(|(idx, _)| idx)
+                .chain([text.len()])
+                .any(|idx| glob_match(rest, &text[idx..]))
+        }
+    }
+}
@@ -0,0 +1,795 @@
+use crate::base64;
+use crate::comments::{CommentTable, Comments};
+use crate::parse::is_tag_char;
+use crate::path::{key_matches, Path, PathSegment};
+use crate::stream::is_marker;
+use crate::{Entry, Yaml};
+use core::fmt;
+use core::fmt::Write as _;
+
+pub(crate) const INDENT_AMT: usize = 2;
+
+/// Options controlling how a Yaml tree is printed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmitOptions {
+    /// Number of spaces every emitted line is indented by. Used when the
+    /// output is spliced into an existing document at a nested position.
+    pub indent: usize,
+    /// Whose conventions to follow for quoting and spelling scalars
+    pub dialect: Dialect,
+    /// Start the output with a `---` document start marker, so that
+    /// documents can be concatenated into a stream
+    pub explicit_document_start: bool,
+    /// Start the output with a `%YAML` directive for this version (e.g.
+    /// `"1.2"`), followed by the `---` marker a directive requires
+    pub version_directive: Option<String>,
+    /// The line break ending every line, including those inside multi-line
+    /// strings
+    pub newline: Newline,
+    /// Whether the output ends with a line break
+    pub trailing_newline: TrailingNewline,
+    /// How timestamps are written
+    pub timestamp: TimestampFormat,
+    /// When printing a [`Document`](crate::Document), write its comments
+    /// back: comment lines above the entry or item they were attached to,
+    /// and end-of-line comments after their node (see
+    /// [`Document::comments`](crate::Document::comments))
+    pub comments: bool,
+    /// Keys to print first in the mappings below given paths, whatever
+    /// order the mappings hold them in
+    pub key_order: Vec<KeyOrder>,
+}
+
+/// A preferred order for the keys of the mappings at and below a path, e.g.
+/// `apiVersion`, `kind` and `metadata` first in Kubernetes manifests. Keys
+/// in the list are printed first, in its order; the others follow in the
+/// order the mapping holds them. A mapping uses the rule with the longest
+/// path which contains it, so a rule for `spec` takes over from one for
+/// the root (one with no keys restores the mapping's own order). Indices
+/// match any item: a rule for `spec.containers[0]` applies to every
+/// container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyOrder {
+    path: Path,
+    keys: Vec<String>,
+}
+
+impl KeyOrder {
+    /// Print `keys` first in the mappings at and below `path` (written as
+    /// for [`Document::span`](crate::Document::span); an empty path is the
+    /// root)
+    /// # Panics
+    /// Panics if `path` is not a valid path
+    #[must_use]
+    pub fn new(path: &str, keys: &[&str]) -> Self {
+        Self {
+            path: Path::parse(path).unwrap_or_else(|| panic!("invalid key order path {path:?}")),
+            keys: keys.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Whether the rule applies to the mapping at `path`
+    fn contains(&self, path: &Path) -> bool {
+        let prefix = self.path.segments();
+        path.segments().len() >= prefix.len()
+            && prefix.iter().zip(path.segments()).all(|pair| match pair {
+                (PathSegment::Index(_), PathSegment::Index(_)) => true,
+                (a, b) => a == b,
+            })
+    }
+}
+
+/// How timestamps are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// ISO 8601, with a zone only if one was given
+    /// (`2001-12-14T21:59:43.1-05:00`)
+    #[default]
+    Iso8601,
+    /// RFC 3339, where a time always has a zone, `Z` if none was given
+    Rfc3339,
+    /// The date alone (`2001-12-14`)
+    DateOnly,
+    /// The text the timestamp was written as, when printing a
+    /// [`Document`](crate::Document); otherwise the same as
+    /// [`TimestampFormat::Iso8601`]
+    Original,
+}
+
+/// Whether printed output ends with a line break
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingNewline {
+    /// End with a line break if the source did, when printing a
+    /// [`Document`](crate::Document); otherwise leave the output as printed,
+    /// where collections end with a line break and scalars do not
+    #[default]
+    Preserve,
+    /// Always end with a line break
+    Always,
+    /// Never end with a line break
+    Never,
+}
+
+/// A line break style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Newline {
+    /// `\n`, as on Unix
+    #[default]
+    Lf,
+    /// `\r\n`, as on Windows
+    CrLf,
+}
+
+impl Newline {
+    /// The line break style `text` is written in, going by its first line
+    /// break; [`Newline::Lf`] if it has none
+    #[must_use]
+    pub fn detect(text: &str) -> Newline {
+        match text.find('\n') {
+            Some(idx) if text[..idx].ends_with('\r') => Newline::CrLf,
+            _ => Newline::Lf,
+        }
+    }
+
+    /// The characters of the line break
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Add or remove the line break at the end of `out` as `opts` asks.
+/// `source_newline` is whether the source the output was printed from ended
+/// with a line break, if there was one.
+pub(crate) fn finish_output(out: &mut String, opts: &EmitOptions, source_newline: Option<bool>) {
+    let wanted = match (opts.trailing_newline, source_newline) {
+        (TrailingNewline::Always, _) => true,
+        (TrailingNewline::Never, _) => false,
+        (TrailingNewline::Preserve, Some(wanted)) => wanted,
+        (TrailingNewline::Preserve, None) => return,
+    };
+    let newline = opts.newline.as_str();
+    match (wanted, out.ends_with(newline)) {
+        (true, false) => out.push_str(newline),
+        (false, true) => out.truncate(out.len() - newline.len()),
+        _ => {}
+    }
+}
+
+/// Passes output through to `inner`, writing each `\n` (or `\r\n`) as
+/// `newline`
+struct NewlineWriter<W> {
+    inner: W,
+    newline: Newline,
+}
+
+impl<W: fmt::Write> fmt::Write for NewlineWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.newline == Newline::Lf {
+            return self.inner.write_str(s);
+        }
+        let mut lines = s.split('\n');
+        let mut line = lines.next().unwrap_or_default();
+        for next in lines {
+            self.inner
+                .write_str(line.strip_suffix('\r').unwrap_or(line))?;
+            self.inner.write_str(self.newline.as_str())?;
+            line = next;
+        }
+        self.inner.write_str(line)
+    }
+}
+
+/// The scalar conventions of a Yaml emitter, so that generated files match
+/// the output of other tools working on the same files.
+///
+/// The dialect decides when strings are quoted and how floats, nulls and
+/// booleans are spelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// mini-yaml's own output: strings are printed verbatim and nulls as `~`
+    #[default]
+    MiniYaml,
+    /// The output of `serde_yaml::to_string`: strings which would read back
+    /// as another type (including the YAML 1.1 booleans `y` and `n`) are
+    /// single quoted, and nulls are printed as `null`
+    SerdeYaml,
+    /// The output of `PyYAML`'s `yaml.dump` with default settings: like
+    /// `SerdeYaml`, but following `PyYAML`'s resolver, which also quotes dates
+    /// and sexagesimal numbers but not `y` and `n`
+    PyYamlDefault,
+}
+
+impl Dialect {
+    /// How a null value is spelled
+    #[must_use]
+    pub fn null_str(self) -> &'static str {
+        match self {
+            Dialect::MiniYaml => "~",
+            Dialect::SerdeYaml | Dialect::PyYamlDefault => "null",
+        }
+    }
+
+    /// How a boolean is spelled. All supported dialects use the YAML 1.2
+    /// spelling.
+    #[must_use]
+    pub fn bool_str(self, value: bool) -> &'static str {
+        if value {
+            "true"
+        } else {
+            "false"
+        }
+    }
+
+    /// Check whether `s` would be read back as something other than the
+    /// string `s` if printed as a plain scalar
+    fn needs_quotes(self, s: &str) -> bool {
+        if s.is_empty() || s.trim() != s {
+            return true;
+        }
+        if is_ambiguous_word(s) || looks_numeric(s) {
+            return true;
+        }
+        // document markers, which end the document at the start of a line,
+        // and the merge key
+        if is_marker(s, "---") || is_marker(s, "...") || s == "<<" {
+            return true;
+        }
+        match self {
+            Dialect::MiniYaml => {}
+            Dialect::SerdeYaml => {
+                if matches!(s, "y" | "Y" | "n" | "N") {
+                    return true;
+                }
+            }
+            Dialect::PyYamlDefault => {
+                if looks_like_date(s) || looks_sexagesimal(s) {
+                    return true;
+                }
+            }
+        }
+        let first = s.chars().next().unwrap_or_default();
+        let second = s.chars().nth(1);
+        let indicator = match first {
+            '-' | '?' | ':' => second.is_none_or(|c| c == ' '),
+            ',' | '[' | ']' | '{' | '}' | '#' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%'
+            | '@' | '`' => true,
+            _ => false,
+        };
+        indicator
+            || s.ends_with(':')
+            || s.contains(": ")
+            || s.contains(" #")
+            || s.chars().any(char::is_control)
+    }
+}
+
+/// Words which YAML 1.1 or 1.2 resolve to null or a boolean, with the
+/// booleans in any case as [`Yaml11Schema`](crate::Yaml11Schema) reads them
+fn is_ambiguous_word(s: &str) -> bool {
+    matches!(s, "~" | "null" | "Null" | "NULL")
+        || matches!(
+            s.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off"
+        )
+}
+
+/// Check whether `s` reads as an integer or float in YAML 1.1 or 1.2
+fn looks_numeric(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if matches!(
+        unsigned,
+        ".inf" | ".Inf" | ".INF" | ".nan" | ".NaN" | ".NAN"
+    ) {
+        return true;
+    }
+    if !unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return false;
+    }
+    let digits = unsigned.replace('_', "");
+    let radix = [("0x", 16), ("0o", 8), ("0b", 2)]
+        .iter()
+        .find_map(|(prefix, radix)| Some((digits.strip_prefix(prefix)?, *radix)));
+    match radix {
+        Some((rest, radix)) => !rest.is_empty() && u64::from_str_radix(rest, radix).is_ok(),
+        None => digits.parse::<f64>().is_ok(),
+    }
+}
+
+/// Check whether `s` is a YAML 1.1 timestamp, or starts like one
+/// (`2001-12-14`, `2001-12-14t21:59:43.10-05:00`)
+fn looks_like_date(s: &str) -> bool {
+    let mut parts = s.splitn(3, '-');
+    let (Some(year), Some(month), Some(rest)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    let day_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let all_digits = |part: &str| part.bytes().all(|c| c.is_ascii_digit());
+    year.len() == 4
+        && all_digits(year)
+        && (1..=2).contains(&month.len())
+        && all_digits(month)
+        && (1..=2).contains(&day_len)
+        && (day_len == rest.len() || rest[day_len..].starts_with(['T', 't', ' ']))
+}
+
+/// Check whether `s` is a YAML 1.1 base 60 number (`1:30:00`)
+fn looks_sexagesimal(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let mut parts = unsigned.split(':');
+    let head = parts.next().unwrap_or_default();
+    let mut rest = parts.peekable();
+    rest.peek().is_some()
+        && !head.is_empty()
+        && head.bytes().all(|c| c.is_ascii_digit() || c == b'_')
+        && rest
+            .all(|part| (1..=2).contains(&part.len()) && part.bytes().all(|c| c.is_ascii_digit()))
+}
+
+/// Print `s` as a single quoted scalar, or double quoted if it contains
+/// characters which can only be written as escapes
+fn write_quoted<W: fmt::Write>(out: &mut W, s: &str) -> fmt::Result {
+    if !s.chars().any(char::is_control) {
+        return write!(out, "'{}'", s.replace('\'', "''"));
+    }
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\t' => out.write_str("\\t")?,
+            '\r' => out.write_str("\\r")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c))?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PrintStyle {
+    Block,
+    Flow,
+}
+
+/// Whether `tag` can be written after a `!` as it is, or else needs the
+/// verbatim form (`!<tag>`), e.g. for a URI
+fn is_shorthand_tag(tag: &str) -> bool {
+    let name = tag.strip_prefix('!').unwrap_or(tag);
+    let mut angle_depth = 0usize;
+    !name.is_empty()
+        && !name.starts_with('<')
+        && name.bytes().all(|b| match b {
+            b'<' => {
+                angle_depth += 1;
+                true
+            }
+            b'>' => angle_depth
+                .checked_sub(1)
+                .map(|depth| angle_depth = depth)
+                .is_some(),
+            _ => is_tag_char(b, angle_depth > 0),
+        })
+        && angle_depth == 0
+}
+
+/// Check if a Yaml value is a simple scalar type
+pub(crate) fn is_scalar(node: &Yaml<'_>) -> bool {
+    matches!(
+        node,
+        Yaml::Scalar(..)
+            | Yaml::String(..)
+            | Yaml::Int(..)
+            | Yaml::Float(..)
+            | Yaml::Bool(..)
+            | Yaml::Null
+            | Yaml::Timestamp(..)
+            | Yaml::Bytes(..)
+    )
+}
+
+/// Whether a node is printed on the line of whatever owns it: scalars, and
+/// empty collections, which are written as `[]` and `{}`
+fn is_inline(node: &Yaml<'_>) -> bool {
+    is_scalar(node)
+        || matches!(node, Yaml::Sequence(items) if items.is_empty())
+        || matches!(node, Yaml::Mapping(entries) if entries.is_empty())
+}
+
+/// Prints Yaml nodes to any `fmt::Write` sink according to a set of `EmitOptions`
+pub(crate) struct Emitter<'o, W> {
+    out: NewlineWriter<W>,
+    opts: &'o EmitOptions,
+    /// comments to write back, by path
+    comments: Option<&'o CommentTable<'o>>,
+    /// the path of the node being printed, kept only with comments
+    path: Path,
+}
+
+impl<'o, W: fmt::Write> Emitter<'o, W> {
+    pub(crate) fn new(out: W, opts: &'o EmitOptions) -> Self {
+        let out = NewlineWriter {
+            inner: out,
+            newline: opts.newline,
+        };
+        Self {
+            out,
+            opts,
+            comments: None,
+            path: Path::default(),
+        }
+    }
+
+    /// Write `comments` back around the nodes they belong to
+    pub(crate) fn with_comments(mut self, comments: &'o CommentTable<'o>) -> Self {
+        self.comments = Some(comments);
+        self
+    }
+
+    /// Whether the path of the node being printed is needed
+    fn tracks_path(&self) -> bool {
+        self.comments.is_some() || !self.opts.key_order.is_empty()
+    }
+
+    fn enter(&mut self, segment: PathSegment) {
+        if self.tracks_path() {
+            self.path.push(segment);
+        }
+    }
+
+    fn leave(&mut self) {
+        if self.tracks_path() {
+            self.path.pop();
+        }
+    }
+
+    /// The entries of the mapping at the current path, in the order
+    /// `opts.key_order` asks for
+    fn ordered<'e, 'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'e Entry<'a>>,
+    ) -> Vec<&'e Entry<'a>>
+    where
+        'a: 'e,
+    {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        let rule = self
+            .opts
+            .key_order
+            .iter()
+            .filter(|rule| rule.contains(&self.path))
+            .max_by_key(|rule| rule.path.segments().len());
+        if let Some(rule) = rule {
+            let rank = |entry: &Entry<'_>| {
+                rule.keys
+                    .iter()
+                    .position(|key| key_matches(&entry.key, key))
+                    .unwrap_or(rule.keys.len())
+            };
+            entries.sort_by_key(|entry| rank(entry));
+        }
+        entries
+    }
+
+    fn node_comments(&self) -> Option<&'o Comments<'o>> {
+        self.comments?.get(&self.path)
+    }
+
+    /// Print the comment lines above the current node at `indent`
+    fn print_leading_comments(&mut self, indent: usize) -> fmt::Result {
+        for comment in self.node_comments().map_or(&[][..], |c| &c.leading) {
+            self.print_indent(indent)?;
+            writeln!(self.out, "{comment}")?;
+        }
+        Ok(())
+    }
+
+    /// Whether the block mapping `item` of a sequence has comments which
+    /// can't follow its `-` on the same line
+    fn comments_inside_item(&mut self, item: &Yaml<'_>) -> bool {
+        let Yaml::Mapping(entries) = item else {
+            return false;
+        };
+        if self.node_comments().is_some_and(|c| c.trailing.is_some()) {
+            return true;
+        }
+        let Some(first) = self.ordered(entries).first().copied() else {
+            return false;
+        };
+        self.enter(PathSegment::Key(first.key.key_string()));
+        let leading = self.node_comments().is_some_and(|c| !c.leading.is_empty());
+        self.leave();
+        leading
+    }
+
+    /// End the current node's line, with its comment if it has one
+    fn end_line(&mut self) -> fmt::Result {
+        match self.node_comments().and_then(|c| c.trailing) {
+            Some(comment) => writeln!(self.out, " {comment}"),
+            None => writeln!(self.out),
+        }
+    }
+
+    /// Print a complete node on a single line, with flow collections
+    pub(crate) fn emit_flow(&mut self, node: &Yaml<'_>) -> fmt::Result {
+        self.print_yaml(node, 0, PrintStyle::Flow)
+    }
+
+    /// Print a complete node, starting at the configured base indentation
+    pub(crate) fn emit(&mut self, node: &Yaml<'_>) -> fmt::Result {
+        if let Some(version) = &self.opts.version_directive {
+            writeln!(self.out, "%YAML {version}")?;
+        }
+        if self.opts.explicit_document_start || self.opts.version_directive.is_some() {
+            writeln!(self.out, "---")?;
+        }
+        if is_inline(node) {
+            self.print_leading_comments(self.opts.indent)?;
+        }
+        self.print_yaml(node, self.opts.indent, PrintStyle::Block)?;
+        if is_inline(node) && self.comments.is_some() {
+            self.end_line()?;
+        }
+        for comment in self.comments.map_or(&[][..], |c| &c.end) {
+            self.print_indent(self.opts.indent)?;
+            writeln!(self.out, "{comment}")?;
+        }
+        Ok(())
+    }
+
+    fn print_indent(&mut self, indent: usize) -> fmt::Result {
+        write!(self.out, "{:indent$}", "", indent = indent)
+    }
+
+    /// Print a value after ":" has been written. Handles tagged values inline.
+    fn print_value_after_colon(&mut self, value: &Yaml<'_>, indent: usize) -> fmt::Result {
+        // the tag of a tagged value goes on the key's line
+        if let Yaml::Tagged(tag, value) = value {
+            write!(self.out, " ")?;
+            return self.print_tagged(tag, value, indent + INDENT_AMT);
+        }
+        // Regular value handling
+        if is_inline(value) {
+            write!(self.out, " ")?;
+            self.print_yaml(value, indent, PrintStyle::Block)?;
+            self.end_line()?;
+        } else {
+            self.end_line()?;
+            self.print_yaml(value, indent + INDENT_AMT, PrintStyle::Block)?;
+        }
+        Ok(())
+    }
+
+    /// Print a tagged node once whatever owns it (a `:`, a `-`, or nothing
+    /// at the root) has been written. The tag goes on the current line,
+    /// followed by the value if it is a scalar; block content goes on the
+    /// lines below, at `indent`.
+    fn print_tagged(&mut self, tag: &str, value: &Yaml<'_>, indent: usize) -> fmt::Result {
+        self.print_tag(tag)?;
+        match value {
+            Yaml::Sequence(items) if items.is_empty() => writeln!(self.out, " []"),
+            Yaml::Mapping(entries) if entries.is_empty() => writeln!(self.out, " {{}}"),
+            value if is_scalar(value) => {
+                write!(self.out, " ")?;
+                self.print_yaml(value, indent, PrintStyle::Block)?;
+                writeln!(self.out)
+            }
+            value => {
+                writeln!(self.out)?;
+                self.print_yaml(value, indent, PrintStyle::Block)
+            }
+        }
+    }
+
+    /// Print mapping entries (key: value pairs) at the given indent level
+    fn print_mapping_entries<'a, I>(&mut self, entries: I, indent: usize) -> fmt::Result
+    where
+        I: Iterator<Item = &'a Entry<'a>>,
+    {
+        for entry in self.ordered(entries) {
+            self.enter(PathSegment::Key(entry.key.key_string()));
+            self.print_leading_comments(indent)?;
+            // Print key
+            if is_scalar(&entry.key) {
+                self.print_indent(indent)?;
+                self.print_yaml(&entry.key, indent, PrintStyle::Block)?;
+            } else if self.explicit_key(&entry.key, indent) {
+                self.print_indent(indent)?;
+                write!(self.out, "?")?;
+                self.print_value_after_colon(&entry.key, indent)?;
+                self.print_indent(indent)?;
+            } else {
+                self.print_yaml(&entry.key, indent + INDENT_AMT, PrintStyle::Block)?;
+                self.print_indent(indent)?;
+            }
+            write!(self.out, ":")?;
+            self.print_value_after_colon(&entry.value, indent)?;
+            self.leave();
+        }
+        Ok(())
+    }
+
+    /// Whether a collection used as a key at `indent` is written after `?`.
+    /// mini-yaml also reads a sequence bare on the lines before the `:`, but
+    /// not at the root, where it would be read as the document itself.
+    fn explicit_key(&self, key: &Yaml<'_>, indent: usize) -> bool {
+        self.opts.dialect != Dialect::MiniYaml || indent == 0 || !matches!(key, Yaml::Sequence(_))
+    }
+
+    fn print_string(&mut self, s: &str) -> fmt::Result {
+        let dialect = self.opts.dialect;
+        if dialect != Dialect::MiniYaml && dialect.needs_quotes(s) {
+            write_quoted(&mut self.out, s)
+        } else {
+            write!(self.out, "{s}")
+        }
+    }
+
+    fn print_float(&mut self, fl: f64) -> fmt::Result {
+        if fl.is_nan() {
+            write!(self.out, ".nan")
+        } else if fl.is_infinite() {
+            write!(self.out, "{}.inf", if fl < 0.0 { "-" } else { "" })
+        } else if self.opts.dialect == Dialect::MiniYaml {
+            write!(self.out, "{fl}")
+        } else {
+            // Other emitters always print floats so that they read back as floats
+            let text = fl.to_string();
+            if text.contains(['.', 'e']) {
+                write!(self.out, "{text}")
+            } else {
+                write!(self.out, "{text}.0")
+            }
+        }
+    }
+
+    /// Print a string inside a flow collection, where it must stay on one
+    /// line and may not contain the flow indicators unquoted
+    fn print_flow_string(&mut self, s: &str) -> fmt::Result {
+        let flow_indicator = s.contains([',', '[', ']', '{', '}']) || s.contains(": ");
+        if flow_indicator || s.contains(" #") || self.opts.dialect.needs_quotes(s) {
+            write_quoted(&mut self.out, s)
+        } else {
+            write!(self.out, "{s}")
+        }
+    }
+
+    /// Write a tag, in its shorthand form (`!name`) if it has one
+    fn print_tag(&mut self, tag: &str) -> fmt::Result {
+        if is_shorthand_tag(tag) {
+            write!(self.out, "!{tag}")
+        } else {
+            write!(self.out, "!<{tag}>")
+        }
+    }
+
+    /// Print a mapping on one line, as `{key: value, ...}`
+    fn print_flow_mapping(&mut self, entries: &[Entry<'_>]) -> fmt::Result {
+        write!(self.out, "{{")?;
+        for (idx, entry) in self.ordered(entries).into_iter().enumerate() {
+            if idx > 0 {
+                write!(self.out, ", ")?;
+            }
+            self.print_yaml(&entry.key, 0, PrintStyle::Flow)?;
+            write!(self.out, ": ")?;
+            self.enter(PathSegment::Key(entry.key.key_string()));
+            self.print_yaml(&entry.value, 0, PrintStyle::Flow)?;
+            self.leave();
+        }
+        write!(self.out, "}}")
+    }
+
+    /// Print `el` as an item of a block sequence, starting with its `-`
+    fn print_block_item(&mut self, el: &Yaml<'_>, indent: usize) -> fmt::Result {
+        self.print_leading_comments(indent)?;
+        self.print_indent(indent)?;
+        write!(self.out, "-")?;
+        if is_inline(el) {
+            write!(self.out, " ")?;
+            self.print_yaml(el, indent, PrintStyle::Block)?;
+            self.end_line()?;
+        } else if let Yaml::Sequence(..) = el {
+            self.end_line()?;
+            self.print_yaml(el, indent + INDENT_AMT, PrintStyle::Block)?;
+        } else if let Yaml::Tagged(tag, value) = el {
+            write!(self.out, " ")?;
+            self.print_tagged(tag, value, indent + INDENT_AMT)?;
+        } else if let (Yaml::Mapping(map), true) = (el, self.comments_inside_item(el)) {
+            // the comments need lines of their own
+            self.end_line()?;
+            self.print_mapping_entries(map.iter(), indent + INDENT_AMT)?;
+        } else if let Yaml::Mapping(map) = el {
+            // Print first entry on same line as "-" if key is simple
+            if let Some((first, rest)) = self.ordered(map).split_first() {
+                let entry_indent = indent + INDENT_AMT;
+                self.enter(PathSegment::Key(first.key.key_string()));
+                if is_scalar(&first.key) {
+                    write!(self.out, " ")?;
+                    self.print_yaml(&first.key, indent, PrintStyle::Block)?;
+                } else if self.explicit_key(&first.key, entry_indent) {
+                    write!(self.out, " ?")?;
+                    self.print_value_after_colon(&first.key, entry_indent)?;
+                    self.print_indent(entry_indent)?;
+                } else {
+                    writeln!(self.out)?;
+                    self.print_yaml(&first.key, entry_indent + INDENT_AMT, PrintStyle::Block)?;
+                    self.print_indent(entry_indent)?;
+                }
+                write!(self.out, ":")?;
+                self.print_value_after_colon(&first.value, entry_indent)?;
+                self.leave();
+                self.print_mapping_entries(rest.iter().copied(), entry_indent)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_yaml(&mut self, node: &Yaml<'_>, indent: usize, style: PrintStyle) -> fmt::Result {
+        match node {
+            Yaml::Scalar(s) if style == PrintStyle::Flow => self.print_flow_string(s),
+            Yaml::String(s) if style == PrintStyle::Flow => self.print_flow_string(s),
+            Yaml::Scalar(s) => self.print_string(s),
+            Yaml::String(s) => self.print_string(s),
+            Yaml::Int(i) => write!(self.out, "{}", i),
+            Yaml::Float(fl) => self.print_float(*fl),
+            Yaml::Bool(b) => write!(self.out, "{}", self.opts.dialect.bool_str(*b)),
+            Yaml::Null => write!(self.out, "{}", self.opts.dialect.null_str()),
+            Yaml::Timestamp(ts) => match self.opts.timestamp {
+                TimestampFormat::Iso8601 | TimestampFormat::Original => write!(self.out, "{ts}"),
+                TimestampFormat::Rfc3339 => write!(self.out, "{}", ts.to_rfc3339()),
+                TimestampFormat::DateOnly => write!(self.out, "{}", ts.date()),
+            },
+            Yaml::Bytes(bytes) => write!(self.out, "!!binary {}", base64::encode(bytes)),
+            Yaml::Sequence(seq) if seq.is_empty() => write!(self.out, "[]"),
+            Yaml::Mapping(map) if map.is_empty() => write!(self.out, "{{}}"),
+            Yaml::Sequence(seq) => {
+                match style {
+                    PrintStyle::Block => {
+                        for (idx, el) in seq.iter().enumerate() {
+                            self.enter(PathSegment::Index(idx));
+                            self.print_block_item(el, indent)?;
+                            self.leave();
+                        }
+                    }
+                    PrintStyle::Flow => {
+                        write!(self.out, "[")?;
+                        for (idx, el) in seq.iter().enumerate() {
+                            if idx > 0 {
+                                write!(self.out, ", ")?;
+                            }
+                            self.enter(PathSegment::Index(idx));
+                            self.print_yaml(el, indent, style)?;
+                            self.leave();
+                        }
+                        write!(self.out, "]")?;
+                    }
+                }
+                Ok(())
+            }
+            Yaml::Mapping(map) => {
+                match style {
+                    PrintStyle::Block => self.print_mapping_entries(map.iter(), indent)?,
+                    PrintStyle::Flow => self.print_flow_mapping(map)?,
+                }
+                Ok(())
+            }
+            Yaml::Tagged(tag, value) => match style {
+                PrintStyle::Block => {
+                    self.print_indent(indent)?;
+                    self.print_tagged(tag, value, indent)
+                }
+                PrintStyle::Flow => {
+                    self.print_tag(tag)?;
+                    write!(self.out, " ")?;
+                    self.print_yaml(value, 0, PrintStyle::Flow)
+                }
+            },
+        }
+    }
+}
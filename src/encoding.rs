@@ -0,0 +1,58 @@
+/// The encodings Yaml input may be given in, from its byte order mark or,
+/// without one, from where the zero bytes of its first character fall
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Find the encoding of `input`, returning it with the length of the byte
+/// order mark to skip
+pub(crate) fn detect(input: &[u8]) -> (Encoding, usize) {
+    match input {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        // a Yaml stream starts with an ASCII character
+        [0, first, ..] if *first != 0 => (Encoding::Utf16Be, 0),
+        [first, 0, ..] if *first != 0 => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Transcode UTF-16 `input` (without its byte order mark) to UTF-8. Unpaired
+/// surrogates become `U+FFFD` if `lossy` is set; otherwise the byte offset
+/// of the first one is returned as the error, as is the offset of a trailing
+/// odd byte.
+pub(crate) fn decode_utf16(input: &[u8], encoding: Encoding, lossy: bool) -> Result<String, usize> {
+    let to_unit = match encoding {
+        Encoding::Utf16Be => u16::from_be_bytes,
+        _ => u16::from_le_bytes,
+    };
+    let units = input
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]));
+    let mut out = String::with_capacity(input.len() / 2);
+    let mut offset = 0;
+    for decoded in char::decode_utf16(units) {
+        match decoded {
+            Ok(c) => {
+                offset += c.len_utf16() * 2;
+                out.push(c);
+            }
+            Err(_) if lossy => {
+                offset += 2;
+                out.push(char::REPLACEMENT_CHARACTER);
+            }
+            Err(_) => return Err(offset),
+        }
+    }
+    if input.len() % 2 == 1 {
+        if !lossy {
+            return Err(input.len() - 1);
+        }
+        out.push(char::REPLACEMENT_CHARACTER);
+    }
+    Ok(out)
+}
@@ -0,0 +1,136 @@
+use crate::index::{entries_mut, kind};
+use crate::path::key_matches;
+use crate::{Entry, Yaml};
+
+/// A key of a mapping, which may or may not be in it, as given by
+/// [`Yaml::entry`]
+#[derive(Debug)]
+pub enum MapEntry<'y, 'a> {
+    Occupied(OccupiedEntry<'y, 'a>),
+    Vacant(VacantEntry<'y, 'a>),
+}
+
+/// A key which is in the mapping; with duplicate keys, the last one
+#[derive(Debug)]
+pub struct OccupiedEntry<'y, 'a> {
+    entries: &'y mut Vec<Entry<'a>>,
+    idx: usize,
+}
+
+/// A key which is not in the mapping
+#[derive(Debug)]
+pub struct VacantEntry<'y, 'a> {
+    entries: &'y mut Vec<Entry<'a>>,
+    key: String,
+}
+
+impl<'a> Yaml<'a> {
+    /// The entry for a key of a mapping, for updating or adding it in one
+    /// lookup, e.g. `yaml.entry("hosts").or_insert_with(|| Yaml::Sequence(vec![]))`.
+    /// A null becomes an empty mapping first, and tags are looked through.
+    /// # Panics
+    /// Panics if the node is neither a mapping nor null
+    pub fn entry(&mut self, key: &str) -> MapEntry<'_, 'a> {
+        if let Yaml::Null = self {
+            *self = Yaml::Mapping(Vec::new());
+        }
+        let kind = kind(self);
+        let Some(entries) = entries_mut(self) else {
+            panic!("cannot take an entry of {kind}");
+        };
+        match entries
+            .iter()
+            .rposition(|entry| key_matches(&entry.key, key))
+        {
+            Some(idx) => MapEntry::Occupied(OccupiedEntry { entries, idx }),
+            None => MapEntry::Vacant(VacantEntry {
+                entries,
+                key: key.to_string(),
+            }),
+        }
+    }
+}
+
+impl<'y, 'a> MapEntry<'y, 'a> {
+    /// The value of the key, after adding it with `default` if it is missing
+    #[allow(clippy::must_use_candidate)]
+    pub fn or_insert(self, default: Yaml<'a>) -> &'y mut Yaml<'a> {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// The value of the key, after adding it with the value `default` gives
+    /// if it is missing
+    pub fn or_insert_with(self, default: impl FnOnce() -> Yaml<'a>) -> &'y mut Yaml<'a> {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// The value of the key, after adding it with a null if it is missing
+    #[allow(clippy::must_use_candidate)]
+    pub fn or_default(self) -> &'y mut Yaml<'a> {
+        self.or_insert(Yaml::Null)
+    }
+
+    /// Change the value of the key if it is in the mapping
+    #[must_use]
+    pub fn and_modify(mut self, f: impl FnOnce(&mut Yaml<'a>)) -> Self {
+        if let MapEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'y, 'a> OccupiedEntry<'y, 'a> {
+    #[must_use]
+    pub fn key(&self) -> &Yaml<'a> {
+        &self.entries[self.idx].key
+    }
+
+    #[must_use]
+    pub fn get(&self) -> &Yaml<'a> {
+        &self.entries[self.idx].value
+    }
+
+    pub fn get_mut(&mut self) -> &mut Yaml<'a> {
+        &mut self.entries[self.idx].value
+    }
+
+    /// The value, borrowed for as long as the mapping was
+    #[must_use]
+    pub fn into_mut(self) -> &'y mut Yaml<'a> {
+        &mut self.entries[self.idx].value
+    }
+
+    /// Replace the value, keeping the key in its place, and return the old
+    /// value
+    pub fn insert(&mut self, value: Yaml<'a>) -> Yaml<'a> {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Take the entry out of the mapping, returning its value
+    #[allow(clippy::must_use_candidate)]
+    pub fn remove(self) -> Yaml<'a> {
+        self.entries.remove(self.idx).value
+    }
+}
+
+impl<'y, 'a> VacantEntry<'y, 'a> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Append the key to the mapping with `value`, returning the value
+    #[allow(clippy::must_use_candidate)]
+    pub fn insert(self, value: Yaml<'a>) -> &'y mut Yaml<'a> {
+        self.entries.push(Entry::new(Yaml::String(self.key), value));
+        let last = self.entries.len() - 1;
+        &mut self.entries[last].value
+    }
+}
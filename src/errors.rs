@@ -1,9 +1,9 @@
+use crate::parse::ParseContext;
 use core::fmt;
 use std::error::Error;
 
 #[derive(Debug, PartialEq, Clone)]
 #[non_exhaustive]
-#[allow(dead_code)]
 #[doc(hidden)]
 #[allow(clippy::enum_variant_names)]
 pub(crate) enum MiniYamlError {
@@ -11,6 +11,389 @@ pub(crate) enum MiniYamlError {
     AliasesDisallowed,
     /// error produced when an anchor is encountered in the parser input
     AnchorsDisallowed,
+    /// error produced when a directive is encountered in the parser input
+    DirectivesDisallowed,
+}
+
+/// What went wrong when parsing input. Each kind has a stable key and an
+/// English message template, listed in [`MESSAGE_CATALOG`], whose `{name}`
+/// placeholders are filled in from the kind's fields; products which show
+/// errors in another language can translate the templates and fill them in
+/// with [`ErrorKind::render`], or pass [`ParseOptions::messages`](crate::ParseOptions::messages)
+/// to have errors carry their own messages.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// the input is empty
+    ExpectedInput,
+    /// the input ends in the middle of a node
+    UnexpectedEnd,
+    /// the input ends inside a flow mapping
+    UnexpectedEndInFlowMapping,
+    /// the input ends inside a flow sequence
+    UnexpectedEndInFlowSequence,
+    /// the input ends inside a quoted scalar
+    UnclosedQuote {
+        /// the missing closing quote
+        quote: char,
+    },
+    /// a closing bracket or brace where no collection is open
+    UnexpectedSymbol {
+        /// the bracket or brace
+        symbol: char,
+    },
+    /// a byte which cannot appear where it is
+    UnexpectedByte {
+        /// the byte's value
+        byte: u8,
+    },
+    /// the input could not be read as any kind of node
+    InvalidNode,
+    /// a block collection inside a flow collection
+    BlockInFlow,
+    /// a block mapping inside a flow collection
+    BlockMappingInFlow,
+    /// a block sequence inside a flow collection
+    BlockSequenceInFlow,
+    /// directives which are not followed by `---`
+    ExpectedDocumentStart,
+    /// a `>` in a tag with no `<` before it
+    UnmatchedTagAngle,
+    /// a `<` in a tag with no `>` after it
+    UnclosedTagAngle,
+    /// a `!` with no tag name after it
+    ExpectedTagName,
+    /// a verbatim tag (`!<...>`) with no closing `>`
+    UnclosedVerbatimTag,
+    /// an empty verbatim tag
+    ExpectedVerbatimTag,
+    /// a `!!binary` value which is not valid base64
+    InvalidBase64,
+    /// a value which does not fit its standard tag, such as `!!int x`
+    InvalidTaggedValue {
+        /// the tag, without its `!!`
+        tag: String,
+    },
+    /// a `&` or `*` with no name after it
+    ExpectedAnchorName,
+    /// an alias with no anchor of its name before it
+    UnknownAlias {
+        /// the alias's name
+        name: String,
+    },
+    /// aliases which copy more nodes than the parser allows
+    AliasLimit {
+        /// the number of nodes allowed
+        limit: usize,
+    },
+    /// a block scalar indicator followed by more than a comment
+    ExpectedBlockScalarNewline,
+    /// a flow mapping which does not start with `{`
+    ExpectedLeftBrace,
+    /// a flow mapping entry which is not `key: value`
+    InvalidFlowMapping,
+    /// a block mapping key with no `:` after it
+    ExpectedColon,
+    /// a block mapping entry which is not `key: value`
+    InvalidBlockMapping,
+    /// a key written twice in a mapping, with
+    /// [`DuplicateKeys::Error`](crate::DuplicateKeys::Error)
+    DuplicateKey {
+        /// the key, shortened if it is long
+        key: String,
+    },
+    /// a merge key (`<<`) whose value is not a mapping or sequence of them
+    InvalidMerge,
+    /// a tab in indentation, with
+    /// [`ParseOptions::reject_tab_indentation`](crate::ParseOptions::reject_tab_indentation)
+    TabIndentation,
+    /// a flow sequence item which is not followed by `,` or `]`
+    InvalidFlowSequence,
+    /// a `-` which does not start a sequence item
+    UnexpectedDash,
+    /// a block sequence line which is not an item
+    ExpectedSequenceItem,
+    /// a block sequence which could not be read
+    InvalidBlockSequence,
+    /// a token which cannot appear where it is
+    UnexpectedToken,
+    /// a `\` at the end of a double-quoted scalar
+    UnterminatedEscape,
+    /// an unknown escape sequence in a double-quoted scalar
+    InvalidEscape {
+        /// the text after the `\`
+        escape: String,
+    },
+    /// a `\x`, `\u` or `\U` escape without enough hex digits of a valid
+    /// character
+    InvalidHexEscape {
+        /// the escape's letter
+        escape: char,
+        /// the number of digits it takes
+        digits: usize,
+    },
+    /// input which is not validly encoded
+    InvalidEncoding {
+        /// the encoding, such as `UTF-8`
+        encoding: String,
+        /// the byte offset of the bad sequence
+        offset: usize,
+    },
+    /// an anchor, with [`ParseOptions::reject_unsupported`](crate::ParseOptions::reject_unsupported)
+    AnchorsDisallowed,
+    /// an alias, with [`ParseOptions::reject_unsupported`](crate::ParseOptions::reject_unsupported)
+    AliasesDisallowed,
+    /// a directive, with [`ParseOptions::reject_unsupported`](crate::ParseOptions::reject_unsupported)
+    DirectivesDisallowed,
+    /// content after the end of the document, with [`parse_complete`](crate::parse_complete)
+    TrailingContent,
+    /// an inconsistency in the parser's own state
+    Internal {
+        /// what went wrong
+        detail: String,
+    },
+}
+
+/// The key and English message template of every [`ErrorKind`]
+pub const MESSAGE_CATALOG: &[(&str, &str)] = &[
+    ("expected-input", "expected input"),
+    ("unexpected-end", "unexpected end of input"),
+    (
+        "unexpected-end-in-flow-mapping",
+        "unexpected end of input in flow mapping",
+    ),
+    (
+        "unexpected-end-in-flow-sequence",
+        "unexpected end of input in flow sequence",
+    ),
+    (
+        "unclosed-quote",
+        "unexpected end of input; expected '{quote}'",
+    ),
+    ("unexpected-symbol", "unexpected symbol '{symbol}'"),
+    (
+        "unexpected-byte",
+        "unexpectedly found \"{byte}\" while parsing",
+    ),
+    ("invalid-node", "failed to parse at top level"),
+    (
+        "block-in-flow",
+        "block collections cannot be values in flow collections",
+    ),
+    (
+        "block-mapping-in-flow",
+        "block mappings may not appear in flow collections",
+    ),
+    (
+        "block-sequence-in-flow",
+        "block sequences may not appear in flow collections",
+    ),
+    ("expected-document-start", "expected '---' after directives"),
+    ("unmatched-tag-angle", "unmatched '>' in tag name"),
+    ("unclosed-tag-angle", "unclosed '<' in tag name"),
+    ("expected-tag-name", "expected tag name after '!'"),
+    ("unclosed-verbatim-tag", "unclosed '<' in verbatim tag"),
+    ("expected-verbatim-tag", "expected tag after '!<'"),
+    ("invalid-base64", "invalid base64 in '!!binary' value"),
+    ("invalid-tagged-value", "invalid '!!{tag}' value"),
+    ("expected-anchor-name", "expected a name after '&' or '*'"),
+    ("unknown-alias", "unknown alias '*{name}'"),
+    ("alias-limit", "aliases expand to more than {limit} nodes"),
+    (
+        "expected-block-scalar-newline",
+        "expected newline after block scalar indicator",
+    ),
+    ("expected-left-brace", "expected left brace"),
+    ("invalid-flow-mapping", "failed to parse flow mapping"),
+    (
+        "expected-colon",
+        "failed to parse block mapping, expected ':'",
+    ),
+    ("invalid-block-mapping", "failed to parse block mapping"),
+    ("duplicate-key", "duplicate key '{key}'"),
+    (
+        "invalid-merge",
+        "the value of '<<' must be a mapping or a sequence of mappings",
+    ),
+    ("tab-indentation", "tab used for indentation"),
+    ("invalid-flow-sequence", "failed to parse flow sequence"),
+    ("unexpected-dash", "unexpected '-'"),
+    ("expected-sequence-item", "expected sequence item"),
+    ("invalid-block-sequence", "failed to parse block sequence"),
+    ("unexpected-token", "token was not expected"),
+    ("unterminated-escape", "unterminated escape sequence"),
+    ("invalid-escape", "invalid escape sequence '\\{escape}'"),
+    (
+        "invalid-hex-escape",
+        "invalid escape sequence '\\{escape}': expected {digits} hex digits of a valid character",
+    ),
+    (
+        "invalid-encoding",
+        "invalid {encoding} at byte offset {offset}",
+    ),
+    ("anchors-disallowed", "anchors are not supported"),
+    ("aliases-disallowed", "aliases are not supported"),
+    ("directives-disallowed", "directives are not supported"),
+    (
+        "trailing-content",
+        "unexpected content after the end of the document",
+    ),
+    ("internal", "{detail}"),
+];
+
+impl ErrorKind {
+    /// The stable key identifying the kind of error, such as
+    /// `unknown-alias`
+    #[must_use]
+    pub fn key(&self) -> &'static str {
+        match self {
+            ErrorKind::ExpectedInput => "expected-input",
+            ErrorKind::UnexpectedEnd => "unexpected-end",
+            ErrorKind::UnexpectedEndInFlowMapping => "unexpected-end-in-flow-mapping",
+            ErrorKind::UnexpectedEndInFlowSequence => "unexpected-end-in-flow-sequence",
+            ErrorKind::UnclosedQuote { .. } => "unclosed-quote",
+            ErrorKind::UnexpectedSymbol { .. } => "unexpected-symbol",
+            ErrorKind::UnexpectedByte { .. } => "unexpected-byte",
+            ErrorKind::InvalidNode => "invalid-node",
+            ErrorKind::BlockInFlow => "block-in-flow",
+            ErrorKind::BlockMappingInFlow => "block-mapping-in-flow",
+            ErrorKind::BlockSequenceInFlow => "block-sequence-in-flow",
+            ErrorKind::ExpectedDocumentStart => "expected-document-start",
+            ErrorKind::UnmatchedTagAngle => "unmatched-tag-angle",
+            ErrorKind::UnclosedTagAngle => "unclosed-tag-angle",
+            ErrorKind::ExpectedTagName => "expected-tag-name",
+            ErrorKind::UnclosedVerbatimTag => "unclosed-verbatim-tag",
+            ErrorKind::ExpectedVerbatimTag => "expected-verbatim-tag",
+            ErrorKind::InvalidBase64 => "invalid-base64",
+            ErrorKind::InvalidTaggedValue { .. } => "invalid-tagged-value",
+            ErrorKind::ExpectedAnchorName => "expected-anchor-name",
+            ErrorKind::UnknownAlias { .. } => "unknown-alias",
+            ErrorKind::AliasLimit { .. } => "alias-limit",
+            ErrorKind::ExpectedBlockScalarNewline => "expected-block-scalar-newline",
+            ErrorKind::ExpectedLeftBrace => "expected-left-brace",
+            ErrorKind::InvalidFlowMapping => "invalid-flow-mapping",
+            ErrorKind::ExpectedColon => "expected-colon",
+            ErrorKind::InvalidBlockMapping => "invalid-block-mapping",
+            ErrorKind::DuplicateKey { .. } => "duplicate-key",
+            ErrorKind::InvalidMerge => "invalid-merge",
+            ErrorKind::TabIndentation => "tab-indentation",
+            ErrorKind::InvalidFlowSequence => "invalid-flow-sequence",
+            ErrorKind::UnexpectedDash => "unexpected-dash",
+            ErrorKind::ExpectedSequenceItem => "expected-sequence-item",
+            ErrorKind::InvalidBlockSequence => "invalid-block-sequence",
+            ErrorKind::UnexpectedToken => "unexpected-token",
+            ErrorKind::UnterminatedEscape => "unterminated-escape",
+            ErrorKind::InvalidEscape { .. } => "invalid-escape",
+            ErrorKind::InvalidHexEscape { .. } => "invalid-hex-escape",
+            ErrorKind::InvalidEncoding { .. } => "invalid-encoding",
+            ErrorKind::AnchorsDisallowed => "anchors-disallowed",
+            ErrorKind::AliasesDisallowed => "aliases-disallowed",
+            ErrorKind::DirectivesDisallowed => "directives-disallowed",
+            ErrorKind::TrailingContent => "trailing-content",
+            ErrorKind::Internal { .. } => "internal",
+        }
+    }
+
+    /// The values of the placeholders of the kind's template, by name
+    #[must_use]
+    pub fn args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ErrorKind::UnclosedQuote { quote } => vec![("quote", quote.to_string())],
+            ErrorKind::UnexpectedSymbol { symbol } => vec![("symbol", symbol.to_string())],
+            ErrorKind::UnexpectedByte { byte } => vec![("byte", byte.to_string())],
+            ErrorKind::InvalidTaggedValue { tag } => vec![("tag", tag.clone())],
+            ErrorKind::UnknownAlias { name } => vec![("name", name.clone())],
+            ErrorKind::AliasLimit { limit } => vec![("limit", limit.to_string())],
+            ErrorKind::DuplicateKey { key } => vec![("key", key.clone())],
+            ErrorKind::InvalidEscape { escape } => vec![("escape", escape.clone())],
+            ErrorKind::InvalidHexEscape { escape, digits } => {
+                vec![
+                    ("escape", escape.to_string()),
+                    ("digits", digits.to_string()),
+                ]
+            }
+            ErrorKind::InvalidEncoding { encoding, offset } => {
+                vec![
+                    ("encoding", encoding.clone()),
+                    ("offset", offset.to_string()),
+                ]
+            }
+            ErrorKind::Internal { detail } => vec![("detail", detail.clone())],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The English message template of the kind, from [`MESSAGE_CATALOG`]
+    #[must_use]
+    pub fn template(&self) -> &'static str {
+        let key = self.key();
+        MESSAGE_CATALOG
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map_or("", |(_, template)| template)
+    }
+
+    /// Fill in the placeholders of `template`, such as a translation of
+    /// [`ErrorKind::template`], with the kind's values. Placeholders the
+    /// kind has no value for are left as they are.
+    #[must_use]
+    pub fn render(&self, template: &str) -> String {
+        let mut out = template.to_string();
+        for (name, value) in self.args() {
+            out = out.replace(&format!("{{{name}}}"), &value);
+        }
+        out
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(self.template()))
+    }
+}
+
+/// A kind of node which the parser is inside of while parsing its content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Construct {
+    FlowMapping,
+    FlowSequence,
+    BlockSequence,
+    BlockMapping,
+}
+
+impl fmt::Display for Construct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Construct::FlowMapping => "flow mapping",
+            Construct::FlowSequence => "flow sequence",
+            Construct::BlockSequence => "block sequence",
+            Construct::BlockMapping => "block mapping",
+        })
+    }
+}
+
+/// One of the nested constructs the parser was inside of when an error
+/// occurred, and where it started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextFrame {
+    /// the Yaml context the construct's content is parsed in
+    pub context: ParseContext,
+    pub construct: Construct,
+    /// the line on which the construct started
+    pub line: usize,
+    /// the column (in characters) at which the construct started
+    pub col: usize,
+}
+
+impl fmt::Display for ContextFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "while parsing {} started at line {}, column {}",
+            self.construct, self.line, self.col
+        )
+    }
 }
 
 /// An error generated while parsing input
@@ -20,9 +403,60 @@ pub struct YamlParseError {
     pub(crate) line: usize,
     /// the column in the input on which the error occurred
     pub(crate) col: usize,
+    /// what went wrong
+    pub(crate) kind: ErrorKind,
     /// more information about the error, if there is any
     pub(crate) msg: Option<String>,
     pub(crate) source: Option<MiniYamlError>,
+    /// the constructs the error occurred inside of, outermost first
+    pub(crate) contexts: Vec<ContextFrame>,
+}
+
+impl YamlParseError {
+    /// An error of the given kind at `line` and `col`, with the kind's
+    /// English message
+    pub(crate) fn new(line: usize, col: usize, kind: ErrorKind) -> Self {
+        Self {
+            line,
+            col,
+            msg: Some(kind.to_string()),
+            kind,
+            source: None,
+            contexts: Vec::new(),
+        }
+    }
+
+    /// What went wrong
+    #[must_use]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The line on which the error occurred
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column at which the error occurred
+    #[must_use]
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    /// More information about the error, if there is any
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.msg.as_deref()
+    }
+
+    /// The flow and block collections the error occurred inside of,
+    /// outermost first, for showing where the broken construct began. The
+    /// last one is also named in the error's `Display`.
+    #[must_use]
+    pub fn contexts(&self) -> &[ContextFrame] {
+        &self.contexts
+    }
 }
 
 impl Error for YamlParseError {}
@@ -34,12 +468,394 @@ impl fmt::Display for YamlParseError {
                 f,
                 "error occurred parsing the input at line {}, column {} : {}",
                 self.line, self.col, msg
-            ),
+            )?,
             None => write!(
                 f,
                 "error occurred parsing the input at line {}, column {}",
                 self.line, self.col
+            )?,
+        }
+        match self.contexts.last() {
+            Some(frame) => write!(f, " ({frame})"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An error generated while computing text edits for a document
+#[derive(Debug, PartialEq, Clone)]
+pub enum EditError {
+    /// the path expression could not be parsed
+    InvalidPath(String),
+    /// neither the node at the path nor its parent exist
+    NotFound(String),
+    /// the new value cannot be written at the requested location
+    Unsupported(String),
+    /// applying the edit would not produce the requested document
+    NotRoundTrip(String),
+    /// a key cannot be renamed to one its mapping already contains
+    KeyExists(String),
+}
+
+impl Error for EditError {}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EditError::InvalidPath(path) => write!(f, "invalid path '{path}'"),
+            EditError::NotFound(path) => write!(f, "no node found at path '{path}'"),
+            EditError::Unsupported(msg) => write!(f, "unsupported edit: {msg}"),
+            EditError::NotRoundTrip(path) => write!(
+                f,
+                "the value for path '{path}' cannot be written without changing its meaning"
             ),
+            EditError::KeyExists(key) => write!(f, "the mapping already contains key '{key}'"),
+        }
+    }
+}
+
+/// An error generated when a batch of updates cannot be applied to a tree
+#[derive(Debug, PartialEq, Clone)]
+pub struct UpdateError {
+    /// the position of the failing update in the batch
+    pub index: usize,
+    /// its path
+    pub path: String,
+    /// why it failed: an `InvalidPath`, or a path with no parent node
+    /// (`NotFound`)
+    pub reason: EditError,
+}
+
+impl Error for UpdateError {}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "update {} failed: {}", self.index, self.reason)
+    }
+}
+
+/// An error generated when a JSON Patch cannot be applied to a tree
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatchError {
+    /// the position of the failing operation in the patch
+    pub index: usize,
+    /// why it failed
+    pub message: String,
+}
+
+impl Error for PatchError {}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "patch operation {} failed: {}", self.index, self.message)
+    }
+}
+
+/// An error generated when a query expression is malformed
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryError {
+    /// the byte offset in the expression where it went wrong
+    pub position: usize,
+    /// what was expected there
+    pub message: String,
+}
+
+impl Error for QueryError {}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid query at offset {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+/// An error generated when a node is converted to a Rust value of another
+/// type, such as a string to an `i64`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ConversionError {
+    /// what the conversion needs, such as "an integer"
+    pub expected: &'static str,
+    /// what the node holds, such as "a string"
+    pub found: &'static str,
+}
+
+impl Error for ConversionError {}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+/// An error generated when a mapping is built with a key it already contains
+#[derive(Debug, PartialEq, Clone)]
+pub struct DuplicateKeyError {
+    /// the key, as it appears in JSON output
+    pub key: String,
+}
+
+impl Error for DuplicateKeyError {}
+
+impl fmt::Display for DuplicateKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "duplicate mapping key '{}'", self.key)
+    }
+}
+
+/// An error generated by [`Yaml::insert_unique`](crate::Yaml::insert_unique)
+#[derive(Debug, PartialEq, Clone)]
+pub enum InsertError {
+    /// the mapping already contains the key
+    DuplicateKey(DuplicateKeyError),
+    /// the node is not a mapping
+    NotAMapping {
+        /// what the node holds, such as "a sequence"
+        found: &'static str,
+    },
+}
+
+impl Error for InsertError {}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InsertError::DuplicateKey(err) => err.fmt(f),
+            InsertError::NotAMapping { found } => {
+                write!(f, "expected a mapping, found {found}")
+            }
+        }
+    }
+}
+
+/// An error generated by the methods which sort, deduplicate or reorder the
+/// items of a sequence in place, such as
+/// [`Yaml::sort_by_field`](crate::Yaml::sort_by_field)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SequenceError {
+    /// the node is not a sequence
+    NotASequence {
+        /// what the node holds, such as "a mapping"
+        found: &'static str,
+    },
+    /// the field path could not be parsed
+    InvalidPath(String),
+    /// an index given to `reorder` is out of bounds or given twice
+    BadIndex(usize),
+}
+
+impl Error for SequenceError {}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SequenceError::NotASequence { found } => {
+                write!(f, "expected a sequence, found {found}")
+            }
+            SequenceError::InvalidPath(path) => write!(f, "invalid path '{path}'"),
+            SequenceError::BadIndex(idx) => {
+                write!(f, "index {idx} is out of bounds or given twice")
+            }
+        }
+    }
+}
+
+/// An error reported by a `Pass` of a `Pipeline`
+#[derive(Debug, PartialEq, Clone)]
+pub struct PassError {
+    /// the name of the pass which failed
+    pub pass: String,
+    /// the path of the node being processed, if the failure concerns one
+    pub path: Option<String>,
+    /// what went wrong
+    pub message: String,
+}
+
+impl PassError {
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            pass: String::new(),
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    /// Attach the path of the node the error concerns
+    #[must_use]
+    pub fn at(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl Error for PassError {}
+
+impl fmt::Display for PassError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pass '{}' failed", self.pass)?;
+        if let Some(path) = &self.path {
+            write!(f, " at '{path}'")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// An error generated while running a `Pipeline`
+#[derive(Debug, PartialEq, Clone)]
+pub enum PipelineError {
+    /// the input could not be parsed
+    Parse(YamlParseError),
+    /// one of the passes failed
+    Pass(PassError),
+}
+
+impl Error for PipelineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PipelineError::Parse(err) => Some(err),
+            PipelineError::Pass(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineError::Parse(err) => write!(f, "{err}"),
+            PipelineError::Pass(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<YamlParseError> for PipelineError {
+    fn from(err: YamlParseError) -> Self {
+        PipelineError::Parse(err)
+    }
+}
+
+impl From<PassError> for PipelineError {
+    fn from(err: PassError) -> Self {
+        PipelineError::Pass(err)
+    }
+}
+
+/// An error generated by [`parse_reader`](crate::parse_reader)
+#[derive(Debug)]
+pub enum ReadError {
+    /// the input could not be read
+    Io(std::io::Error),
+    /// the input could not be parsed
+    Parse(YamlParseError),
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(err) => Some(err),
+            ReadError::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(err) => write!(f, "error reading the input: {err}"),
+            ReadError::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReadError {
+    fn from(err: std::io::Error) -> Self {
+        ReadError::Io(err)
+    }
+}
+
+impl From<YamlParseError> for ReadError {
+    fn from(err: YamlParseError) -> Self {
+        ReadError::Parse(err)
+    }
+}
+
+/// An error generated by [`sanitize`](crate::sanitize)
+#[derive(Debug, PartialEq, Clone)]
+pub enum SanitizeError {
+    /// the input could not be parsed
+    Parse(YamlParseError),
+    /// a node is not allowed by the policy
+    Rejected {
+        /// the path of the node, such as `servers[1].name`, or `""` for
+        /// the root
+        path: String,
+        /// what the policy does not allow
+        reason: String,
+    },
+}
+
+impl Error for SanitizeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SanitizeError::Parse(err) => Some(err),
+            SanitizeError::Rejected { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for SanitizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanitizeError::Parse(err) => write!(f, "{err}"),
+            SanitizeError::Rejected { path, reason } if path.is_empty() => write!(f, "{reason}"),
+            SanitizeError::Rejected { path, reason } => write!(f, "at '{path}': {reason}"),
+        }
+    }
+}
+
+impl From<YamlParseError> for SanitizeError {
+    fn from(err: YamlParseError) -> Self {
+        SanitizeError::Parse(err)
+    }
+}
+
+/// An error generated while deserializing a Rust value from Yaml, or
+/// serializing one to Yaml
+#[cfg(feature = "serde")]
+#[derive(Debug, PartialEq, Clone)]
+pub enum SerdeError {
+    /// the input could not be parsed
+    Parse(YamlParseError),
+    /// a value does not fit the type it is deserialized into, or cannot be
+    /// serialized
+    Invalid {
+        /// the path of the value, such as `servers[1].port`, or `""` for
+        /// the root
+        path: String,
+        /// what went wrong
+        message: String,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl Error for SerdeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SerdeError::Parse(err) => Some(err),
+            SerdeError::Invalid { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerdeError::Parse(err) => write!(f, "{err}"),
+            SerdeError::Invalid { path, message } if path.is_empty() => write!(f, "{message}"),
+            SerdeError::Invalid { path, message } => write!(f, "at '{path}': {message}"),
         }
     }
 }
@@ -52,8 +868,9 @@ impl fmt::Display for MiniYamlError {
             f,
             "{}",
             match self {
-                MiniYamlError::AliasesDisallowed => "aliases are disallowed in minimal-yaml",
-                MiniYamlError::AnchorsDisallowed => "anchors are disallowed in minimal-yaml",
+                MiniYamlError::AliasesDisallowed => "aliases are not supported",
+                MiniYamlError::AnchorsDisallowed => "anchors are not supported",
+                MiniYamlError::DirectivesDisallowed => "directives are not supported",
             }
         )
     }
@@ -0,0 +1,305 @@
+use crate::path::key_matches;
+use crate::{Entry, Yaml};
+use std::ops;
+
+/// A key or position which can look up a node in a [`Yaml`] value: a `&str`
+/// or `String` for a mapping key, or a `usize` for a sequence item. Used by
+/// [`Yaml::get`], [`Yaml::insert`], [`Yaml::remove`] and the `[]` operator.
+pub trait YamlIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'y, 'a>(&self, node: &'y Yaml<'a>) -> Option<&'y Yaml<'a>>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> Option<&'y mut Yaml<'a>>;
+
+    /// The node for `yaml[index] = ...`, which may add it
+    #[doc(hidden)]
+    fn index_or_insert<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a>;
+
+    #[doc(hidden)]
+    fn insert_into<'a>(&self, node: &mut Yaml<'a>, value: Yaml<'a>) -> Option<Yaml<'a>>;
+
+    #[doc(hidden)]
+    fn remove_from<'a>(&self, node: &mut Yaml<'a>) -> Option<Yaml<'a>>;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+/// The entries of the mapping below any tags of `node`, if it is one
+pub(crate) fn entries_mut<'y, 'a>(node: &'y mut Yaml<'a>) -> Option<&'y mut Vec<Entry<'a>>> {
+    match node {
+        Yaml::Mapping(entries) => Some(entries),
+        Yaml::Tagged(_, value) => entries_mut(value),
+        _ => None,
+    }
+}
+
+fn items_mut<'y, 'a>(node: &'y mut Yaml<'a>) -> Option<&'y mut Vec<Yaml<'a>>> {
+    match node {
+        Yaml::Sequence(items) => Some(items),
+        Yaml::Tagged(_, value) => items_mut(value),
+        _ => None,
+    }
+}
+
+/// The name of a node's kind, for panic messages
+pub(crate) fn kind(node: &Yaml<'_>) -> &'static str {
+    match node.untagged() {
+        Yaml::Sequence(_) => "a sequence",
+        Yaml::Mapping(_) => "a mapping",
+        _ => "a scalar",
+    }
+}
+
+impl YamlIndex for usize {
+    fn index_into<'y, 'a>(&self, node: &'y Yaml<'a>) -> Option<&'y Yaml<'a>> {
+        match node.untagged() {
+            Yaml::Sequence(items) => items.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> Option<&'y mut Yaml<'a>> {
+        items_mut(node)?.get_mut(*self)
+    }
+
+    fn index_or_insert<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a> {
+        let kind = kind(node);
+        match items_mut(node) {
+            Some(items) => {
+                let len = items.len();
+                items.get_mut(*self).unwrap_or_else(|| {
+                    panic!("cannot index a sequence of length {len} with {self}")
+                })
+            }
+            None => panic!("cannot index {kind} with {self}"),
+        }
+    }
+
+    fn insert_into<'a>(&self, node: &mut Yaml<'a>, value: Yaml<'a>) -> Option<Yaml<'a>> {
+        if let Yaml::Null = node {
+            *node = Yaml::Sequence(Vec::new());
+        }
+        let kind = kind(node);
+        let Some(items) = items_mut(node) else {
+            panic!("cannot insert into {kind} at {self}");
+        };
+        assert!(
+            *self <= items.len(),
+            "cannot insert into a sequence of length {} at {self}",
+            items.len()
+        );
+        items.insert(*self, value);
+        None
+    }
+
+    fn remove_from<'a>(&self, node: &mut Yaml<'a>) -> Option<Yaml<'a>> {
+        let items = items_mut(node)?;
+        (*self < items.len()).then(|| items.remove(*self))
+    }
+}
+
+impl YamlIndex for str {
+    fn index_into<'y, 'a>(&self, node: &'y Yaml<'a>) -> Option<&'y Yaml<'a>> {
+        match node.untagged() {
+            // the last of duplicate keys wins, as in `to_json`
+            Yaml::Mapping(entries) => entries
+                .iter()
+                .rev()
+                .find(|entry| key_matches(&entry.key, self))
+                .map(|entry| &entry.value),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> Option<&'y mut Yaml<'a>> {
+        entries_mut(node)?
+            .iter_mut()
+            .rev()
+            .find(|entry| key_matches(&entry.key, self))
+            .map(|entry| &mut entry.value)
+    }
+
+    fn index_or_insert<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a> {
+        if let Yaml::Null = node {
+            *node = Yaml::Mapping(Vec::new());
+        }
+        let kind = kind(node);
+        let Some(entries) = entries_mut(node) else {
+            panic!("cannot index {kind} with {self:?}");
+        };
+        let idx = entries
+            .iter()
+            .rposition(|entry| key_matches(&entry.key, self))
+            .unwrap_or_else(|| {
+                entries.push(Entry::new(Yaml::String(self.to_string()), Yaml::Null));
+                entries.len() - 1
+            });
+        &mut entries[idx].value
+    }
+
+    fn insert_into<'a>(&self, node: &mut Yaml<'a>, value: Yaml<'a>) -> Option<Yaml<'a>> {
+        if let Yaml::Null = node {
+            *node = Yaml::Mapping(Vec::new());
+        }
+        let kind = kind(node);
+        let Some(entries) = entries_mut(node) else {
+            panic!("cannot insert into {kind} with {self:?}");
+        };
+        if let Some(entry) = entries
+            .iter_mut()
+            .rev()
+            .find(|entry| key_matches(&entry.key, self))
+        {
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+        entries.push(Entry::new(Yaml::String(self.to_string()), value));
+        None
+    }
+
+    fn remove_from<'a>(&self, node: &mut Yaml<'a>) -> Option<Yaml<'a>> {
+        let entries = entries_mut(node)?;
+        let mut removed = None;
+        entries.retain_mut(|entry| {
+            if key_matches(&entry.key, self) {
+                removed = Some(std::mem::replace(&mut entry.value, Yaml::Null));
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+}
+
+impl YamlIndex for String {
+    fn index_into<'y, 'a>(&self, node: &'y Yaml<'a>) -> Option<&'y Yaml<'a>> {
+        self.as_str().index_into(node)
+    }
+
+    fn index_into_mut<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> Option<&'y mut Yaml<'a>> {
+        self.as_str().index_into_mut(node)
+    }
+
+    fn index_or_insert<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a> {
+        self.as_str().index_or_insert(node)
+    }
+
+    fn insert_into<'a>(&self, node: &mut Yaml<'a>, value: Yaml<'a>) -> Option<Yaml<'a>> {
+        self.as_str().insert_into(node, value)
+    }
+
+    fn remove_from<'a>(&self, node: &mut Yaml<'a>) -> Option<Yaml<'a>> {
+        self.as_str().remove_from(node)
+    }
+}
+
+impl<T: YamlIndex + ?Sized> YamlIndex for &T {
+    fn index_into<'y, 'a>(&self, node: &'y Yaml<'a>) -> Option<&'y Yaml<'a>> {
+        (**self).index_into(node)
+    }
+
+    fn index_into_mut<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> Option<&'y mut Yaml<'a>> {
+        (**self).index_into_mut(node)
+    }
+
+    fn index_or_insert<'y, 'a>(&self, node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a> {
+        (**self).index_or_insert(node)
+    }
+
+    fn insert_into<'a>(&self, node: &mut Yaml<'a>, value: Yaml<'a>) -> Option<Yaml<'a>> {
+        (**self).insert_into(node, value)
+    }
+
+    fn remove_from<'a>(&self, node: &mut Yaml<'a>) -> Option<Yaml<'a>> {
+        (**self).remove_from(node)
+    }
+}
+
+/// What indexing gives for a missing key or item
+static NULL: Yaml<'static> = Yaml::Null;
+
+impl<'a> Yaml<'a> {
+    /// The value of a mapping key (`get("port")`) or a sequence item
+    /// (`get(0)`), looking through tags, or `None` if there is none. With
+    /// duplicate keys the last one wins, as in `to_json`.
+    pub fn get(&self, index: impl YamlIndex) -> Option<&Yaml<'a>> {
+        index.index_into(self)
+    }
+
+    /// Mutable counterpart of [`get`](Self::get)
+    pub fn get_mut(&mut self, index: impl YamlIndex) -> Option<&mut Yaml<'a>> {
+        index.index_into_mut(self)
+    }
+
+    /// Whether a mapping, below any tags, has the key
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        key.index_into(self).is_some()
+    }
+
+    /// Store `value` under a mapping key (`insert("port", value)`),
+    /// replacing the value of an existing key in its place and returning
+    /// the old value, or appending the key; or insert an item into a
+    /// sequence before a position (`insert(0, value)`), shifting the later
+    /// items. A null becomes an empty mapping or sequence first, and tags
+    /// are looked through.
+    /// # Panics
+    /// Panics if the node is not a mapping (or null) and `index` is a key,
+    /// not a sequence (or null) and `index` is a position, or the position
+    /// is past the end of the sequence
+    pub fn insert(&mut self, index: impl YamlIndex, value: Yaml<'a>) -> Option<Yaml<'a>> {
+        index.insert_into(self, value)
+    }
+
+    /// Take the value of a mapping key (every entry with the key, returning
+    /// the last value), or an item of a sequence, out of the node. Returns
+    /// `None` if there is none.
+    pub fn remove(&mut self, index: impl YamlIndex) -> Option<Yaml<'a>> {
+        index.remove_from(self)
+    }
+
+    /// Append an item to a sequence, or to an empty one in place of a null
+    /// # Panics
+    /// Panics if the node is neither a sequence (below any tags) nor null
+    pub fn push(&mut self, value: Yaml<'a>) {
+        if let Yaml::Null = self {
+            *self = Yaml::Sequence(Vec::new());
+        }
+        let kind = kind(self);
+        let Some(items) = items_mut(self) else {
+            panic!("cannot push onto {kind}");
+        };
+        items.push(value);
+    }
+}
+
+/// `yaml["server"]["port"]` or `yaml[0]` gives the node at a key or
+/// position, or a `Yaml::Null` if there is none, so that lookups can be
+/// chained without checking each step; use [`Yaml::get`] to tell a missing
+/// node from a null one.
+impl<'a, I: YamlIndex> ops::Index<I> for Yaml<'a> {
+    type Output = Yaml<'a>;
+
+    fn index(&self, index: I) -> &Yaml<'a> {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// `yaml["key"] = value` replaces the value of a key, adding the key if the
+/// mapping lacks it and turning a null into a mapping; `yaml[0] = value`
+/// replaces an existing item.
+/// # Panics
+/// Panics when indexing a node which is not a mapping (or null) by key, or
+/// not a sequence by position, and when a position is out of range
+impl<'a, I: YamlIndex> ops::IndexMut<I> for Yaml<'a> {
+    fn index_mut(&mut self, index: I) -> &mut Yaml<'a> {
+        index.index_or_insert(self)
+    }
+}
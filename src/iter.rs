@@ -0,0 +1,110 @@
+use crate::path::{Path, PathSegment};
+use crate::{Entry, Yaml};
+
+impl<'a> Yaml<'a> {
+    /// The items of a sequence or the values of a mapping, in order, looking
+    /// through tags; nothing for a scalar
+    pub fn iter(&self) -> impl Iterator<Item = &Yaml<'a>> {
+        let (items, entries): (&[Yaml<'a>], &[Entry<'a>]) = match self.untagged() {
+            Yaml::Sequence(items) => (items, &[]),
+            Yaml::Mapping(entries) => (&[], entries),
+            _ => (&[], &[]),
+        };
+        items.iter().chain(entries.iter().map(|entry| &entry.value))
+    }
+
+    /// The items of a sequence or the values of a mapping, as by
+    /// [`iter`](Self::iter), for changing them in place
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Yaml<'a>> {
+        let (items, entries): (&mut [Yaml<'a>], &mut [Entry<'a>]) = match untagged_mut(self) {
+            Yaml::Sequence(items) => (items, &mut []),
+            Yaml::Mapping(entries) => (&mut [], entries),
+            _ => (&mut [], &mut []),
+        };
+        items
+            .iter_mut()
+            .chain(entries.iter_mut().map(|entry| &mut entry.value))
+    }
+
+    /// The keys of a mapping, in order, looking through tags; nothing for
+    /// any other node
+    pub fn keys(&self) -> impl Iterator<Item = &Yaml<'a>> {
+        self.entries().map(|entry| &entry.key)
+    }
+
+    /// The values of a mapping, in the order of [`keys`](Self::keys)
+    pub fn values(&self) -> impl Iterator<Item = &Yaml<'a>> {
+        self.entries().map(|entry| &entry.value)
+    }
+
+    /// Every node below this one, depth first and in document order, with
+    /// its path written as for [`Document::span`](crate::Document::span),
+    /// e.g. `spec.ports[0]`. The node itself is not included. For example,
+    /// `descendants().filter(|(_, node)| **node == Yaml::Null).count()`
+    /// counts the nulls in a tree.
+    #[must_use]
+    pub fn descendants(&self) -> Descendants<'_, 'a> {
+        let mut descendants = Descendants { stack: Vec::new() };
+        descendants.push_children(&Path::default(), self);
+        descendants
+    }
+
+    fn entries(&self) -> std::slice::Iter<'_, Entry<'a>> {
+        match self.untagged() {
+            Yaml::Mapping(entries) => entries.iter(),
+            _ => [].iter(),
+        }
+    }
+}
+
+fn untagged_mut<'y, 'a>(node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a> {
+    match node {
+        Yaml::Tagged(_, value) => untagged_mut(value),
+        node => node,
+    }
+}
+
+/// The iterator [`Yaml::descendants`] returns, yielding each node with its
+/// path
+#[derive(Debug, Clone)]
+pub struct Descendants<'y, 'a> {
+    /// The nodes still to visit, the next one last
+    stack: Vec<(Path, &'y Yaml<'a>)>,
+}
+
+impl<'y, 'a> Descendants<'y, 'a> {
+    fn push_children(&mut self, path: &Path, node: &'y Yaml<'a>) {
+        let child = |segment| {
+            let mut path = path.clone();
+            path.push(segment);
+            path
+        };
+        let start = self.stack.len();
+        match node.untagged() {
+            Yaml::Sequence(items) => self.stack.extend(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| (child(PathSegment::Index(idx)), item)),
+            ),
+            Yaml::Mapping(entries) => self.stack.extend(entries.iter().map(|entry| {
+                (
+                    child(PathSegment::Key(entry.key.key_string())),
+                    &entry.value,
+                )
+            })),
+            _ => {}
+        }
+        self.stack[start..].reverse();
+    }
+}
+
+impl<'y, 'a> Iterator for Descendants<'y, 'a> {
+    type Item = (String, &'y Yaml<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, node) = self.stack.pop()?;
+        self.push_children(&path, node);
+        Some((path.to_string(), node))
+    }
+}
@@ -0,0 +1,287 @@
+use crate::cst::{line_len, run_len, word_len, Indicator, SyntaxToken, TokenKind};
+use crate::span::Span;
+
+/// Splits a source into tokens from its text alone, without parsing it, so
+/// that it also covers documents which do not parse, e.g. for editors or
+/// parsers of one's own built on this crate. It is an iterator over the
+/// tokens, and every byte belongs to exactly one token. Text which cannot be
+/// lexed, such as an unclosed quote or a closing bracket with no opening
+/// one, becomes an `Error` token, and lexing carries on after it.
+///
+/// Tokens are as in [`SyntaxTree`](crate::SyntaxTree)s:
+/// [`SyntaxToken::indicator`] tells a `-` from a `:` or a `[`, and
+/// [`Lexer::is_key`] whether the scalar just returned is a key. Unlike
+/// [`tokenize`](crate::tokenize), the lexer never tries to parse the input,
+/// so it is cheaper and does not mark where parsing fails.
+///
+/// ```
+/// use mini_yaml_rs::{Lexer, TokenKind};
+///
+/// let input = "port: 80 # http";
+/// let tokens: Vec<_> = Lexer::new(input)
+///     .filter(|token| !token.kind.is_trivia() || token.kind == TokenKind::Comment)
+///     .map(|token| (token.kind, &input[token.span.start..token.span.end]))
+///     .collect();
+/// assert_eq!(
+///     tokens,
+///     [
+///         (TokenKind::Plain, "port"),
+///         (TokenKind::Indicator, ":"),
+///         (TokenKind::Plain, "80"),
+///         (TokenKind::Comment, "# http"),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    /// the open flow collections, by their opening bracket
+    flow: Vec<u8>,
+    /// the indentation of the current line
+    indent: usize,
+    /// the indentation of the line the outermost open flow collection is
+    /// on, if it follows a key or `-` there: its content must be indented
+    /// further, so a line which is not was left unclosed
+    flow_indent: Option<usize>,
+    /// the token before the next one
+    last: Option<SyntaxToken>,
+}
+
+impl<'a> Lexer<'a> {
+    /// A lexer over the whole of `source`
+    #[must_use]
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            pos: 0,
+            flow: Vec::new(),
+            indent: 0,
+            flow_indent: None,
+            last: None,
+        }
+    }
+
+    /// Whether the token last returned is a key: a scalar or alias followed
+    /// on its line by a `:` indicator. Keys of explicit `?` entries whose
+    /// `:` is on a later line are not found.
+    #[must_use]
+    pub fn is_key(&self) -> bool {
+        let Some(last) = self.last else {
+            return false;
+        };
+        if !matches!(
+            last.kind,
+            TokenKind::Plain | TokenKind::SingleQuoted | TokenKind::DoubleQuoted | TokenKind::Alias
+        ) {
+            return false;
+        }
+        let mut ahead = self.clone();
+        ahead
+            .find(|token| token.kind != TokenKind::Whitespace)
+            .is_some_and(|token| token.indicator(self.source) == Some(Indicator::Colon))
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.pos == 0 || self.source.as_bytes()[self.pos - 1] == b'\n'
+    }
+
+    /// Whether the last token on this line was whitespace, or there is none
+    fn after_space(&self) -> bool {
+        self.at_line_start() || self.source.as_bytes()[self.pos - 1].is_ascii_whitespace()
+    }
+
+    fn next_token(&mut self) -> (TokenKind, usize) {
+        let rest = &self.source[self.pos..];
+        let line_start = self.at_line_start();
+        if line_start {
+            self.indent = run_len(rest, |c| c == ' ');
+            let content = rest[self.indent..].trim_start();
+            if self.flow_indent.is_some_and(|indent| self.indent <= indent)
+                && !content.is_empty()
+                && !content.starts_with([']', '}', '#'])
+            {
+                self.flow.clear();
+            }
+        }
+        let marker = |m: &str| {
+            line_start
+                && rest.strip_prefix(m).is_some_and(|after| {
+                    after.is_empty() || after.starts_with([' ', '\t', '\r', '\n'])
+                })
+        };
+        // `-`, `?` and `:` are indicators before whitespace, and `:` also
+        // before a flow indicator or after a quoted key inside flow
+        // collections
+        let next = rest.as_bytes().get(1).copied();
+        let spaced = next.is_none_or(|b| b.is_ascii_whitespace());
+        let in_flow = !self.flow.is_empty();
+        match rest.as_bytes()[0] {
+            b' ' | b'\t' => (
+                TokenKind::Whitespace,
+                run_len(rest, |c| c == ' ' || c == '\t'),
+            ),
+            b'\n' => (TokenKind::Newline, 1),
+            b'\r' if rest.starts_with("\r\n") => (TokenKind::Newline, 2),
+            b'#' if self.after_space() => (TokenKind::Comment, line_len(rest)),
+            b'%' if line_start && !in_flow => (TokenKind::Directive, line_len(rest)),
+            b'-' if marker("---") => (TokenKind::DocumentStart, 3),
+            b'.' if marker("...") => (TokenKind::DocumentEnd, 3),
+            b'-' | b'?' if spaced => (TokenKind::Indicator, 1),
+            b':' if spaced
+                || (in_flow && next.is_some_and(|b| b",[]{}".contains(&b)))
+                || self.after_quote() =>
+            {
+                (TokenKind::Indicator, 1)
+            }
+            open @ (b'[' | b'{') => {
+                if self.flow.is_empty() {
+                    let line = self.source[..self.pos].rfind('\n').map_or(0, |idx| idx + 1);
+                    self.flow_indent =
+                        (!self.source[line..self.pos].trim().is_empty()).then_some(self.indent);
+                }
+                self.flow.push(open);
+                (TokenKind::Indicator, 1)
+            }
+            close @ (b']' | b'}') => {
+                let open = if close == b']' { b'[' } else { b'{' };
+                if self.flow.last() == Some(&open) {
+                    self.flow.pop();
+                    (TokenKind::Indicator, 1)
+                } else {
+                    (TokenKind::Error, 1)
+                }
+            }
+            b',' if in_flow => (TokenKind::Indicator, 1),
+            b'!' => (TokenKind::Tag, word_len(rest)),
+            b'&' => (TokenKind::Anchor, word_len(rest)),
+            b'*' => (TokenKind::Alias, word_len(rest)),
+            b'\'' => quoted(rest, TokenKind::SingleQuoted, |s| {
+                // `''` is an escaped quote
+                let mut idx = 1;
+                while let Some(found) = s[idx..].find('\'') {
+                    idx += found + 1;
+                    if !s[idx..].starts_with('\'') {
+                        return Some(idx);
+                    }
+                    idx += 1;
+                }
+                None
+            }),
+            b'"' => quoted(rest, TokenKind::DoubleQuoted, |s| {
+                let mut escaped = false;
+                for (idx, c) in s.char_indices().skip(1) {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => return Some(idx + 1),
+                        _ => {}
+                    }
+                }
+                None
+            }),
+            b'|' | b'>' if !in_flow => (TokenKind::BlockScalar, self.block_scalar_len(rest)),
+            _ => (TokenKind::Plain, self.plain_len(rest)),
+        }
+    }
+
+    /// Whether the previous token is a quoted scalar, after which `:` is a
+    /// value indicator even without a space (`{"a":1}`)
+    fn after_quote(&self) -> bool {
+        self.last.is_some_and(|token| {
+            token.span.end == self.pos
+                && matches!(
+                    token.kind,
+                    TokenKind::SingleQuoted | TokenKind::DoubleQuoted
+                )
+        })
+    }
+
+    /// The length of a block scalar's header and its content: the lines
+    /// after it which are indented further than the line it is on, and the
+    /// blank lines between them
+    fn block_scalar_len(&self, rest: &str) -> usize {
+        let mut end = line_len(rest);
+        let mut pos = end;
+        while pos < rest.len() {
+            let start = pos
+                + if rest[pos..].starts_with("\r\n") {
+                    2
+                } else {
+                    1
+                };
+            let line = &rest[start..start + line_len(&rest[start..])];
+            pos = start + line.len();
+            let content = line.trim_start_matches(' ');
+            if content.trim().is_empty() {
+                continue;
+            }
+            if line.len() - content.len() <= self.indent {
+                break;
+            }
+            end = pos;
+        }
+        end
+    }
+
+    /// The length of a plain scalar, up to the end of the line, a `: ` or
+    /// ` #`, or inside flow collections a flow indicator
+    fn plain_len(&self, rest: &str) -> usize {
+        let bytes = rest.as_bytes();
+        let in_flow = !self.flow.is_empty();
+        let mut end = 0;
+        for (idx, &b) in bytes.iter().enumerate() {
+            let next = bytes.get(idx + 1).copied();
+            let stop = match b {
+                b'\r' | b'\n' => true,
+                b':' => {
+                    next.is_none_or(|b| b.is_ascii_whitespace())
+                        || (in_flow && next.is_some_and(|b| b",[]{}".contains(&b)))
+                }
+                b'#' => idx > 0 && bytes[idx - 1].is_ascii_whitespace(),
+                b',' | b'[' | b']' | b'{' | b'}' => in_flow,
+                _ => false,
+            };
+            if stop && idx > 0 {
+                break;
+            }
+            if !b.is_ascii_whitespace() {
+                end = idx + 1;
+            }
+        }
+        // trailing whitespace is its own token; a multibyte character is
+        // never split, as the bytes checked above are all ASCII
+        end.max(rest.chars().next().map_or(1, char::len_utf8))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = SyntaxToken;
+
+    fn next(&mut self) -> Option<SyntaxToken> {
+        if self.pos >= self.source.len() {
+            return None;
+        }
+        let (kind, len) = self.next_token();
+        let token = SyntaxToken {
+            kind,
+            span: Span::new(self.pos, self.pos + len),
+        };
+        self.pos += len;
+        self.last = Some(token);
+        Some(token)
+    }
+}
+
+/// A quoted scalar whose closing quote `close` finds, or if it is never
+/// closed an `Error` token up to the end of the line
+fn quoted(
+    rest: &str,
+    kind: TokenKind,
+    close: impl Fn(&str) -> Option<usize>,
+) -> (TokenKind, usize) {
+    match close(rest) {
+        Some(len) => (kind, len),
+        None => (TokenKind::Error, line_len(rest)),
+    }
+}
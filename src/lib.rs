@@ -1,18 +1,108 @@
 #![warn(clippy::all, clippy::pedantic)]
+mod base64;
 mod bytes;
+mod cache;
+mod capabilities;
+mod comments;
+mod convert;
+#[cfg(feature = "corpus")]
+mod corpus;
+mod cst;
+#[cfg(feature = "serde")]
+mod de;
+mod diagnostics;
+mod diff;
+#[cfg(feature = "differential")]
+mod differential;
+mod document;
+mod embedded;
+mod emit;
+mod encoding;
+mod entry;
 mod errors;
+mod index;
+mod iter;
+mod lexer;
+mod merge;
+mod metadata;
 mod parse;
+mod patch;
+mod path;
+mod pipeline;
+mod query;
+mod resolve;
+mod sanitize;
+#[cfg(feature = "serde")]
+mod ser;
+mod sink;
+mod sniff;
+mod snippet;
+mod source_map;
+mod span;
+mod stream;
 mod tests;
+mod timestamp;
+mod walk;
 
-pub use crate::errors::YamlParseError;
+pub use crate::cache::ParseCache;
+pub use crate::capabilities::{capabilities, Capabilities, InteropFeature, Producer, Support};
+pub use crate::comments::Comments;
+#[cfg(feature = "corpus")]
+pub use crate::corpus::{
+    run_builtin_corpus, run_corpus, CorpusFailure, CorpusFailureKind, CorpusOptions, CorpusReport,
+};
+pub use crate::cst::{
+    parse_cst, tokenize, Indicator, NodeKind, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTree,
+    TokenKind,
+};
+#[cfg(feature = "serde")]
+pub use crate::de::{from_str, from_yaml};
+pub use crate::diagnostics::{validate, Diagnostic, Diagnostics, Severity};
+pub use crate::diff::{diff, DiffOp};
+#[cfg(feature = "differential")]
+pub use crate::differential::{
+    run_builtin_differential, run_differential, DifferentialReport, Divergence, FnReference,
+    Outcome, ReferenceParser,
+};
+pub use crate::document::{parse_document, Document, TextEdit};
+pub use crate::embedded::{EmbeddedBlock, EmbeddedOptions};
+pub use crate::emit::{Dialect, EmitOptions, KeyOrder, Newline, TimestampFormat, TrailingNewline};
+pub use crate::entry::{MapEntry, OccupiedEntry, VacantEntry};
+#[cfg(feature = "serde")]
+pub use crate::errors::SerdeError;
+pub use crate::errors::{
+    Construct, ContextFrame, ConversionError, DuplicateKeyError, EditError, ErrorKind, InsertError,
+    PassError, PatchError, PipelineError, QueryError, ReadError, SanitizeError, SequenceError,
+    UpdateError, YamlParseError, MESSAGE_CATALOG,
+};
+pub use crate::index::YamlIndex;
+pub use crate::iter::Descendants;
+pub use crate::lexer::Lexer;
+pub use crate::merge::MergeStrategy;
+pub use crate::parse::{DuplicateKeys, MessageHook, ParseContext, ParseOptions};
+pub use crate::pipeline::{EnvInterpolation, Include, MxTransform, Pass, Pipeline, ResolveTags};
+pub use crate::query::Query;
+pub use crate::resolve::{CoreSchema, JsonSchema, ScalarResolver, Schema, Yaml11Schema};
+pub use crate::sanitize::{sanitize, OnViolation, SanitizePolicy};
+#[cfg(feature = "serde")]
+pub use crate::ser::{to_string, to_string_with_options, to_yaml};
+pub use crate::sink::JsonSink;
+pub use crate::sniff::{sniff, InputKind};
+pub use crate::source_map::{SourceMap, SourceRange};
+pub use crate::span::{Span, Spanned, Utf16Index};
+pub use crate::stream::{parse_all, parse_documents, Documents, StreamParser};
+pub use crate::timestamp::{Time, Timestamp};
+pub use crate::walk::Visitor;
 
 pub(crate) type Result<T> = std::result::Result<T, YamlParseError>;
 
+use emit::Emitter;
 use parse::Parser;
+use path::PathSegment;
+use snippet::snippet;
 
 use serde_json::{Map, Value};
-use std::{fmt, fmt::Display};
-#[cfg_attr(test, derive(serde::Deserialize, serde::Serialize))]
+use std::{borrow::Cow, cmp::Ordering, fmt, fmt::Display};
 #[derive(Clone, Debug, PartialEq)]
 /// A Yaml Element
 pub enum Yaml<'a> {
@@ -31,6 +121,16 @@ pub enum Yaml<'a> {
     /// A boolean value, parsed from `!bool` tag
     Bool(bool),
 
+    /// A null value, written as `~`, `null`, `Null` or `NULL`
+    Null,
+
+    /// A date, or a date and time, written as an unquoted timestamp
+    /// (`2024-06-01`, `2024-06-01T12:00:00Z`)
+    Timestamp(Timestamp),
+
+    /// Binary data, written as a base64 scalar tagged `!!binary`
+    Bytes(Vec<u8>),
+
     /// A sequence of values in flow style
     /// `[x, y, z]`
     /// or in block style
@@ -50,217 +150,22 @@ pub enum Yaml<'a> {
     ///     z: Z
     /// ```
     Mapping(Vec<Entry<'a>>),
-}
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum PrintStyle {
-    Block,
-    #[allow(unused)]
-    Flow,
-}
-
-fn print_indent(indent: usize, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{:indent$}", "", indent = indent)
-}
-
-/// Check if a Yaml node is a tagged mapping (has __type as first key).
-/// Returns the tag name if so.
-fn get_tag_name<'a>(node: &'a Yaml<'a>) -> Option<&'a str> {
-    match node {
-        Yaml::Mapping(map) => match map.first() {
-            Some(Entry {
-                key: Yaml::Scalar("__type"),
-                value: Yaml::Scalar(tag),
-            }) => Some(tag),
-            _ => None,
-        },
-        _ => None,
-    }
-}
-
-/// Check if a Yaml value is a simple scalar type
-fn is_scalar(node: &Yaml<'_>) -> bool {
-    matches!(
-        node,
-        Yaml::Scalar(..) | Yaml::String(..) | Yaml::Int(..) | Yaml::Float(..) | Yaml::Bool(..)
-    )
-}
-
-const INDENT_AMT: usize = 2;
-
-/// Print a value after ":" has been written. Handles tagged mappings inline.
-/// Returns true if it handled the value (used for continue in loops).
-fn print_value_after_colon(value: &Yaml<'_>, indent: usize, f: &mut fmt::Formatter) -> fmt::Result {
-    // Check if value is a tagged mapping - print tag inline
-    if let Some(tag) = get_tag_name(value) {
-        if let Yaml::Mapping(value_map) = value {
-            write!(f, " !{}", tag)?;
-            // Check if it's __type only (empty tagged mapping)
-            if value_map.len() == 1 {
-                writeln!(f, " {{}}")?;
-                return Ok(());
-            }
-            // Check if it's __type + __value only
-            if value_map.len() == 2 {
-                if let Some(second) = value_map.get(1) {
-                    if let Yaml::Scalar("__value") = &second.key {
-                        write!(f, " ")?;
-                        print_yaml(&second.value, indent, f, PrintStyle::Block)?;
-                        writeln!(f)?;
-                        return Ok(());
-                    }
-                }
-            }
-            // Print remaining fields on new lines
-            writeln!(f)?;
-            print_mapping_entries(value_map.iter().skip(1), indent + INDENT_AMT, f)?;
-            return Ok(());
-        }
-    }
-    // Regular value handling
-    if is_scalar(value) {
-        write!(f, " ")?;
-        print_yaml(value, indent, f, PrintStyle::Block)?;
-        writeln!(f)?;
-    } else {
-        writeln!(f)?;
-        print_yaml(value, indent + INDENT_AMT, f, PrintStyle::Block)?;
-    }
-    Ok(())
-}
-
-/// Print mapping entries (key: value pairs) at the given indent level
-fn print_mapping_entries<'a, I>(entries: I, indent: usize, f: &mut fmt::Formatter) -> fmt::Result
-where
-    I: Iterator<Item = &'a Entry<'a>>,
-{
-    for entry in entries {
-        // Print key
-        if is_scalar(&entry.key) {
-            print_indent(indent, f)?;
-            print_yaml(&entry.key, indent, f, PrintStyle::Block)?;
-        } else {
-            print_yaml(&entry.key, indent + INDENT_AMT, f, PrintStyle::Block)?;
-            print_indent(indent, f)?;
-        }
-        write!(f, ":")?;
-        print_value_after_colon(&entry.value, indent, f)?;
-    }
-    Ok(())
-}
 
-fn print_yaml(
-    node: &Yaml<'_>,
-    indent: usize,
-    f: &mut fmt::Formatter,
-    style: PrintStyle,
-) -> fmt::Result {
-    match node {
-        Yaml::Scalar(slice) => write!(f, "{}", slice),
-        Yaml::String(s) => write!(f, "{}", s),
-        Yaml::Int(i) => write!(f, "{}", i),
-        Yaml::Float(fl) => write!(f, "{}", fl),
-        Yaml::Bool(b) => write!(f, "{}", b),
-        Yaml::Sequence(seq) => {
-            match style {
-                PrintStyle::Block => {
-                    for el in seq.iter() {
-                        print_indent(indent, f)?;
-                        write!(f, "-")?;
-                        if is_scalar(el) {
-                            write!(f, " ")?;
-                            print_yaml(el, indent, f, PrintStyle::Block)?;
-                            writeln!(f)?;
-                        } else if let Yaml::Sequence(..) = el {
-                            writeln!(f)?;
-                            print_yaml(el, indent + INDENT_AMT, f, style)?;
-                        } else if let Yaml::Mapping(map) = el {
-                            // Print first entry on same line as "-" if key is simple
-                            if let Some((first, rest)) = map.split_first() {
-                                let entry_indent = indent + INDENT_AMT;
-                                if is_scalar(&first.key) {
-                                    write!(f, " ")?;
-                                    print_yaml(&first.key, indent, f, PrintStyle::Block)?;
-                                } else {
-                                    writeln!(f)?;
-                                    print_yaml(
-                                        &first.key,
-                                        entry_indent + INDENT_AMT,
-                                        f,
-                                        PrintStyle::Block,
-                                    )?;
-                                    print_indent(entry_indent, f)?;
-                                }
-                                write!(f, ":")?;
-                                print_value_after_colon(&first.value, entry_indent, f)?;
-                                print_mapping_entries(rest.iter(), entry_indent, f)?;
-                            } else {
-                                writeln!(f, " {{}}")?;
-                            }
-                        }
-                    }
-                }
-                PrintStyle::Flow => {
-                    write!(f, "[ ")?;
-                    let last_idx = seq.len() - 1;
-                    for (idx, elem) in seq.iter().enumerate() {
-                        if idx == last_idx {
-                            write!(f, "{}", elem)?;
-                        } else {
-                            write!(f, "{}, ", elem)?;
-                        }
-                    }
-                    write!(f, " ]")?;
-                }
-            }
-            Ok(())
-        }
-        Yaml::Mapping(map) => {
-            match style {
-                PrintStyle::Block => {
-                    // Check if this is a tagged mapping (__type field)
-                    if let Some(tag) = get_tag_name(node) {
-                        print_indent(indent, f)?;
-                        write!(f, "!{}", tag)?;
-                        // Check if it's __type + __value only
-                        if map.len() == 2 {
-                            if let Some(second) = map.get(1) {
-                                if let Yaml::Scalar("__value") = &second.key {
-                                    write!(f, " ")?;
-                                    print_yaml(&second.value, indent, f, PrintStyle::Block)?;
-                                    writeln!(f)?;
-                                    return Ok(());
-                                }
-                            }
-                        }
-                        // Print remaining fields (skip __type)
-                        writeln!(f)?;
-                        print_mapping_entries(map.iter().skip(1), indent, f)?;
-                        return Ok(());
-                    }
-                    // Regular mapping
-                    print_mapping_entries(map.iter(), indent, f)?;
-                }
-                PrintStyle::Flow => {
-                    write!(f, "{{")?;
-                    let last_idx = map.len() - 1;
-                    for (idx, entry) in map.iter().enumerate() {
-                        if idx == last_idx {
-                            write!(f, "{}", entry)?;
-                        } else {
-                            write!(f, "{}, ", entry)?;
-                        }
-                    }
-                    write!(f, "}}")?;
-                }
-            }
-            Ok(())
-        }
-    }
+    /// A value with a tag the parser does not resolve itself, such as
+    /// `!ref name` or `!!python/object {...}`. The tag is kept without its
+    /// leading `!` (`ref`, `!python/object`), and verbatim tags as their URI.
+    Tagged(Cow<'a, str>, Box<Yaml<'a>>),
 }
-
+/// Prints block Yaml, or with the alternate flag (`{:#}`) a single line of
+/// flow Yaml such as `{name: web, ports: [80, 443]}`, e.g. for logging
 impl Display for Yaml<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        print_yaml(&self, 0, f, PrintStyle::Block)
+        let opts = EmitOptions::default();
+        if f.alternate() {
+            Emitter::new(f, &opts).emit_flow(self)
+        } else {
+            Emitter::new(f, &opts).emit(self)
+        }
     }
 }
 
@@ -270,32 +175,80 @@ impl Yaml<'_> {
     /// This format is compatible with SQLite JSON extension.
     #[must_use]
     pub fn to_json(&self) -> Value {
+        self.to_json_with_options(&JsonOptions::default())
+    }
+
+    /// Convert the Yaml value to a `serde_json::Value`, representing the
+    /// values JSON has no type for as `opts` says
+    #[must_use]
+    pub fn to_json_with_options(&self, opts: &JsonOptions) -> Value {
         match self {
             Yaml::Scalar(s) => Value::String((*s).to_string()),
             Yaml::String(s) => Value::String(s.clone()),
             Yaml::Int(i) => Value::Number((*i).into()),
-            Yaml::Float(f) => {
-                Value::Number(serde_json::Number::from_f64(*f).unwrap_or_else(|| 0.into()))
-            }
+            Yaml::Float(f) => float_to_json(*f),
             Yaml::Bool(b) => Value::Bool(*b),
-            Yaml::Sequence(seq) => Value::Array(seq.iter().map(|item| item.to_json()).collect()),
+            Yaml::Null => Value::Null,
+            Yaml::Timestamp(ts) => match opts.timestamps {
+                TimestampJson::Iso8601 => Value::String(ts.to_string()),
+                TimestampJson::Rfc3339 => Value::String(ts.to_rfc3339()),
+                TimestampJson::EpochMillis => Value::Number(ts.unix_millis().into()),
+            },
+            Yaml::Bytes(bytes) => match opts.bytes {
+                BytesFormat::Base64 => Value::String(base64::encode(bytes)),
+                BytesFormat::Array => Value::Array(bytes.iter().map(|&b| b.into()).collect()),
+            },
+            Yaml::Sequence(seq) => Value::Array(sequence_to_json(seq, opts)),
             Yaml::Mapping(entries) => {
                 let mut map = Map::new();
                 for entry in entries {
-                    let key = match &entry.key {
-                        Yaml::Scalar(s) => (*s).to_string(),
-                        Yaml::Int(i) => i.to_string(),
-                        Yaml::Float(f) => f.to_string(),
-                        Yaml::Bool(b) => b.to_string(),
-                        other => other.to_json().to_string(),
-                    };
-                    map.insert(key, entry.value.to_json());
+                    map.insert(
+                        entry.key.key_string(),
+                        entry.value.to_json_with_options(opts),
+                    );
+                }
+                Value::Object(map)
+            }
+            // the tag goes first, followed by the entries of a mapping or
+            // else the value under `__value`
+            Yaml::Tagged(tag, value) => {
+                let mut map = Map::new();
+                map.insert("__type".to_string(), Value::String(tag.to_string()));
+                match value.to_json_with_options(opts) {
+                    Value::Object(entries) if matches!(**value, Yaml::Mapping(_)) => {
+                        map.extend(entries);
+                    }
+                    value => {
+                        map.insert("__value".to_string(), value);
+                    }
                 }
                 Value::Object(map)
             }
         }
     }
 
+    /// Pass the value to `sink` as the JSON [`Yaml::to_json`] converts it
+    /// to, without building a `serde_json::Value`: keys and strings are
+    /// borrowed from the tree instead of copied. Unlike [`Yaml::to_json`],
+    /// a key repeated within a mapping is passed each time it occurs.
+    pub fn to_json_visit(&self, sink: &mut dyn JsonSink) {
+        sink::visit(self, sink, &JsonOptions::default());
+    }
+
+    /// [`Yaml::to_json_visit`], representing the values JSON has no type for
+    /// as `opts` says
+    pub fn to_json_visit_with_options(&self, sink: &mut dyn JsonSink, opts: &JsonOptions) {
+        sink::visit(self, sink, opts);
+    }
+
+    /// Pass the value to `sink` as the JSON [`Yaml::to_mx_with_options`]
+    /// converts it to, borrowing keys and strings below the top level keys
+    /// from the tree. As with [`Yaml::to_json_visit`], repeated keys are
+    /// passed each time they occur.
+    pub fn to_mx_visit(&self, sink: &mut dyn JsonSink, opts: &MxOptions) {
+        sink::visit_mx(self, sink, opts);
+    }
+
     /// Convert the Yaml value to a serde_json::Value with mx transformation.
     ///
     /// The top-level value must be an object with keys matching the format
@@ -303,9 +256,19 @@ impl Yaml<'_> {
     /// The key becomes `+name`, with `__name` set to the `[...]` content
     /// and `__value` set to the `(...)` content if present.
     ///
-    /// If the format is invalid, returns `{"+error": {"__name": "error message", "__value": "yaml content"}}`
+    /// If the format is invalid, returns `{"+error": {"__name": "error message", "__value": "yaml content"}}`.
+    /// For an invalid key, the error also holds the key in `__key` and in
+    /// `__index` the index of its entry among the entries of the top level
+    /// mapping, counting from 0 (not a position in the source text).
     #[must_use]
     pub fn to_mx(&self) -> Value {
+        self.to_mx_with_options(&MxOptions::default())
+    }
+
+    /// Convert the Yaml value to a `serde_json::Value` with mx transformation,
+    /// using the given options. See [`Yaml::to_mx`].
+    #[must_use]
+    pub fn to_mx_with_options(&self, opts: &MxOptions) -> Value {
         // Handle top-level scalar that matches mx key pattern (e.g., "+shop[Name]()")
         if let Yaml::Scalar(s) = self {
             if let Some((name_part, bracket_content, paren_content)) = Self::parse_mx_key(s) {
@@ -325,20 +288,14 @@ impl Yaml<'_> {
         let entries = match self {
             Yaml::Mapping(entries) => entries,
             _ => {
-                return Self::make_mx_error("Top level value must be an object", &self.to_string());
+                return self.make_mx_error("Top level value must be an object", None, opts);
             }
         };
 
         let mut result_map = Map::new();
 
-        for entry in entries {
-            let key = match &entry.key {
-                Yaml::Scalar(s) => (*s).to_string(),
-                Yaml::Int(i) => i.to_string(),
-                Yaml::Float(f) => f.to_string(),
-                Yaml::Bool(b) => b.to_string(),
-                other => other.to_json().to_string(),
-            };
+        for (idx, entry) in entries.iter().enumerate() {
+            let key = entry.key.key_string();
 
             if let Some((name_part, bracket_content, paren_content)) = Self::parse_mx_key(&key) {
                 // Build the new key: +name
@@ -363,12 +320,14 @@ impl Yaml<'_> {
                 result_map.insert(new_key, Value::Object(value_obj));
             } else {
                 // Key doesn't match the expected format
-                return Self::make_mx_error(
+                let key = snippet(&key, opts.snippet_limit, false);
+                return self.make_mx_error(
                     &format!(
                         "Key '{}' does not match expected format +name[label](value)",
                         key
                     ),
-                    &self.to_string(),
+                    Some((&key, idx)),
+                    opts,
                 );
             }
         }
@@ -425,24 +384,234 @@ impl Yaml<'_> {
         ))
     }
 
-    fn make_mx_error(message: &str, yaml_content: &str) -> Value {
+    /// `key` is the offending key (already sanitized) and the index of its
+    /// entry, if the error is about a key
+    fn make_mx_error(&self, message: &str, key: Option<(&str, usize)>, opts: &MxOptions) -> Value {
         let mut error_inner = Map::new();
         error_inner.insert("__name".to_string(), Value::String(message.to_string()));
-        error_inner.insert(
-            "__value".to_string(),
-            Value::String(yaml_content.to_string()),
-        );
+        if opts.include_body {
+            error_inner.insert(
+                "__value".to_string(),
+                Value::String(snippet(&self.to_string(), opts.snippet_limit, true)),
+            );
+        }
+        if let Some((key, idx)) = key {
+            error_inner.insert("__key".to_string(), Value::String(key.to_string()));
+            error_inner.insert("__index".to_string(), Value::Number(idx.into()));
+        }
         let mut error_obj = Map::new();
         error_obj.insert("+error".to_string(), Value::Object(error_inner));
         Value::Object(error_obj)
     }
 
+    /// The string form of a value used as a mapping key when converting to JSON
+    pub(crate) fn key_string(&self) -> String {
+        match self {
+            Yaml::Scalar(s) => (*s).to_string(),
+            Yaml::String(s) => s.clone(),
+            Yaml::Int(i) => i.to_string(),
+            Yaml::Float(f) => f.to_string(),
+            Yaml::Bool(b) => b.to_string(),
+            Yaml::Null => "null".to_string(),
+            Yaml::Timestamp(ts) => ts.to_string(),
+            Yaml::Bytes(bytes) => base64::encode(bytes),
+            other => other.to_json().to_string(),
+        }
+    }
+
+    /// Print the Yaml value using the given options
+    #[must_use]
+    pub fn emit(&self, opts: &EmitOptions) -> String {
+        let mut out = String::new();
+        // Writing into a String cannot fail
+        let _ = Emitter::new(&mut out, opts).emit(self);
+        emit::finish_output(&mut out, opts, None);
+        out
+    }
+
+    /// The node found at `path`, such as `server.tls.port` or
+    /// `servers[2].name`; keys holding `.` or `[` are written in quoted
+    /// brackets (`["a.b"].c`). With duplicate keys the last one wins, as in
+    /// `to_json`. Returns `None` if the path is malformed or does not exist.
+    #[must_use]
+    pub fn get_path(&self, path: &str) -> Option<&Self> {
+        path::resolve(self, &path::Path::parse(path)?)
+    }
+
+    /// Mutable counterpart of [`get_path`](Self::get_path)
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Self> {
+        path::resolve_mut(self, &path::Path::parse(path)?)
+    }
+
+    /// The node at a JSON pointer (RFC 6901), such as
+    /// `/spec/containers/0/image`, or `""` for the root, as
+    /// `serde_json::Value::pointer` finds it in [`to_json`](Self::to_json):
+    /// the entries of a tagged mapping are under the mapping's pointer, and
+    /// other tagged values under `/__value`. With duplicate keys the last
+    /// one wins. Returns `None` if the pointer is malformed or leads nowhere.
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Self> {
+        let mut node = self;
+        for token in source_map::tokens(pointer)? {
+            node = node.pointer_step(&token)?;
+        }
+        Some(node)
+    }
+
+    /// Mutable counterpart of [`pointer`](Self::pointer)
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Self> {
+        let mut node = self;
+        for token in source_map::tokens(pointer)? {
+            node = node.pointer_step_mut(&token)?;
+        }
+        Some(node)
+    }
+
+    /// The child of a mapping or sequence a pointer's reference token names
+    fn pointer_step(&self, token: &str) -> Option<&Self> {
+        match self {
+            // a tagged mapping's entries follow its `__type` in JSON, while
+            // other tagged values are under `__value`
+            Yaml::Tagged(_, value) => match **value {
+                Yaml::Mapping(_) => value.pointer_step(token),
+                _ => (token == "__value").then_some(&**value),
+            },
+            Yaml::Mapping(entries) => entries
+                .iter()
+                .rev()
+                .find(|entry| path::key_matches(&entry.key, token))
+                .map(|entry| &entry.value),
+            Yaml::Sequence(items) => items.get(source_map::token_index(token)?),
+            _ => None,
+        }
+    }
+
+    fn pointer_step_mut(&mut self, token: &str) -> Option<&mut Self> {
+        match self {
+            Yaml::Tagged(_, value) => match **value {
+                Yaml::Mapping(_) => value.pointer_step_mut(token),
+                _ => (token == "__value").then_some(&mut **value),
+            },
+            Yaml::Mapping(entries) => entries
+                .iter_mut()
+                .rev()
+                .find(|entry| path::key_matches(&entry.key, token))
+                .map(|entry| &mut entry.value),
+            Yaml::Sequence(items) => items.get_mut(source_map::token_index(token)?),
+            _ => None,
+        }
+    }
+
+    /// Print only the node found at `path` (e.g. `server.hosts[1]`), for
+    /// splicing back into the document it was parsed from.
+    ///
+    /// Every line after the first is indented by `opts.indent`, which should
+    /// be the column the node's block content starts at. The first line is
+    /// left unindented and the final newline is dropped, so the output can
+    /// replace the node's text in place. Returns `None` if the path is
+    /// malformed or does not exist.
+    #[must_use]
+    pub fn emit_subtree(&self, path: &str, opts: &EmitOptions) -> Option<String> {
+        let path = path::Path::parse(path)?;
+        let node = path::resolve(self, &path)?;
+        // the subtree is spliced into a document, so it gets no header
+        let out = node.emit(&EmitOptions {
+            explicit_document_start: false,
+            version_directive: None,
+            ..opts.clone()
+        });
+        let out = out.strip_suffix('\n').unwrap_or(&out);
+        let leading = out.len() - out.trim_start_matches(' ').len();
+        Some(out[leading.min(opts.indent)..].to_string())
+    }
+
+    /// Re-create the tree borrowing from `new_source` instead of `source`,
+    /// the text it was parsed from, without parsing `new_source`. Every
+    /// borrowed scalar is re-borrowed from the same offset in `new_source`,
+    /// e.g. after an editor reloads a file into a fresh buffer.
+    ///
+    /// Returns `None` if the text differs, in which case `new_source` has to
+    /// be parsed again.
+    #[must_use]
+    pub fn deep_clone_into<'b>(&self, source: &str, new_source: &'b str) -> Option<Yaml<'b>> {
+        if source != new_source {
+            return None;
+        }
+        Some(self.reborrow(source, new_source))
+    }
+
+    fn reborrow<'b>(&self, source: &str, new_source: &'b str) -> Yaml<'b> {
+        match self {
+            Yaml::Scalar(s) => match offset_in(source, s) {
+                Some(start) => Yaml::Scalar(&new_source[start..start + s.len()]),
+                // text the parser made up rather than borrowed
+                None => Yaml::String((*s).to_string()),
+            },
+            Yaml::String(s) => Yaml::String(s.clone()),
+            Yaml::Int(i) => Yaml::Int(*i),
+            Yaml::Float(f) => Yaml::Float(*f),
+            Yaml::Bool(b) => Yaml::Bool(*b),
+            Yaml::Null => Yaml::Null,
+            Yaml::Timestamp(ts) => Yaml::Timestamp(*ts),
+            Yaml::Bytes(bytes) => Yaml::Bytes(bytes.clone()),
+            Yaml::Sequence(seq) => Yaml::Sequence(
+                seq.iter()
+                    .map(|item| item.reborrow(source, new_source))
+                    .collect(),
+            ),
+            Yaml::Mapping(map) => Yaml::Mapping(
+                map.iter()
+                    .map(|entry| {
+                        Entry::new(
+                            entry.key.reborrow(source, new_source),
+                            entry.value.reborrow(source, new_source),
+                        )
+                    })
+                    .collect(),
+            ),
+            Yaml::Tagged(tag, value) => {
+                let tag = match tag {
+                    Cow::Borrowed(tag) => match offset_in(source, tag) {
+                        Some(start) => Cow::Borrowed(&new_source[start..start + tag.len()]),
+                        None => Cow::Owned((*tag).to_string()),
+                    },
+                    Cow::Owned(tag) => Cow::Owned(tag.clone()),
+                };
+                Yaml::Tagged(tag, Box::new(value.reborrow(source, new_source)))
+            }
+        }
+    }
+
+    /// Copy any text borrowed from the input, so the value can outlive it
+    #[must_use]
+    pub fn into_owned(self) -> YamlOwned {
+        match self {
+            Yaml::Scalar(s) => Yaml::String(s.to_string()),
+            Yaml::String(s) => Yaml::String(s),
+            Yaml::Int(i) => Yaml::Int(i),
+            Yaml::Float(f) => Yaml::Float(f),
+            Yaml::Bool(b) => Yaml::Bool(b),
+            Yaml::Null => Yaml::Null,
+            Yaml::Timestamp(ts) => Yaml::Timestamp(ts),
+            Yaml::Bytes(bytes) => Yaml::Bytes(bytes),
+            Yaml::Sequence(seq) => Yaml::Sequence(seq.into_iter().map(Yaml::into_owned).collect()),
+            Yaml::Mapping(map) => Yaml::Mapping(
+                map.into_iter()
+                    .map(|entry| Entry::new(entry.key.into_owned(), entry.value.into_owned()))
+                    .collect(),
+            ),
+            Yaml::Tagged(tag, value) => {
+                Yaml::Tagged(Cow::Owned(tag.into_owned()), Box::new(value.into_owned()))
+            }
+        }
+    }
+
     /// Convert a serde_json::Value to a Yaml value.
     /// This creates an owned Yaml structure (uses String variant for strings).
     #[must_use]
     pub fn from_json(value: &Value) -> Yaml<'static> {
         match value {
-            Value::Null => Yaml::String("null".to_string()),
+            Value::Null => Yaml::Null,
             Value::Bool(b) => Yaml::Bool(*b),
             Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
@@ -455,28 +624,520 @@ impl Yaml<'_> {
             }
             Value::String(s) => Yaml::String(s.clone()),
             Value::Array(arr) => Yaml::Sequence(arr.iter().map(Yaml::from_json).collect()),
-            Value::Object(obj) => {
-                let entries = obj
-                    .iter()
-                    .map(|(k, v)| Entry {
-                        key: Yaml::String(k.clone()),
-                        value: Yaml::from_json(v),
-                    })
-                    .collect();
-                Yaml::Mapping(entries)
+            Value::Object(obj) => Yaml::untag_mapping(Self::entries_from_json(obj.iter())),
+        }
+    }
+
+    fn entries_from_json<'j>(
+        fields: impl Iterator<Item = (&'j String, &'j Value)>,
+    ) -> Vec<Entry<'static>> {
+        fields
+            .map(|(k, v)| Entry {
+                key: Yaml::String(k.clone()),
+                value: Yaml::from_json(v),
+            })
+            .collect()
+    }
+}
+
+impl<'a> Yaml<'a> {
+    /// A mapping, or if its first key is `__type` the tagged value it
+    /// stands for, read from the `__type` and `__value` entries `to_json`
+    /// writes for tagged values
+    pub(crate) fn untag_mapping(mut entries: Vec<Entry<'a>>) -> Yaml<'a> {
+        let tag = match entries.first() {
+            Some(Entry { key, value }) if path::key_matches(key, "__type") => match value {
+                Yaml::Scalar(tag) => Cow::Borrowed(*tag),
+                Yaml::String(tag) => Cow::Owned(tag.clone()),
+                _ => return Yaml::Mapping(entries),
+            },
+            _ => return Yaml::Mapping(entries),
+        };
+        entries.remove(0);
+        let value = match entries.as_slice() {
+            [Entry { key, .. }] if path::key_matches(key, "__value") => {
+                entries.pop().map_or(Yaml::Null, |entry| entry.value)
+            }
+            _ => Yaml::Mapping(entries),
+        };
+        Yaml::Tagged(tag, Box::new(value))
+    }
+
+    /// A value with a tag, given without its leading `!`
+    #[must_use]
+    pub fn tagged(tag: impl Into<Cow<'a, str>>, value: Yaml<'a>) -> Self {
+        Yaml::Tagged(tag.into(), Box::new(value))
+    }
+
+    /// The tag of a tagged value, without its leading `!`
+    #[must_use]
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            Yaml::Tagged(tag, _) => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// The value itself, with any tag it has taken off
+    #[must_use]
+    pub fn untagged(&self) -> &Yaml<'a> {
+        match self {
+            Yaml::Tagged(_, value) => value.untagged(),
+            value => value,
+        }
+    }
+
+    /// The text of a plain scalar, borrowed from the input it was parsed
+    /// from, for interpreting it some other way. Returns `None` for every
+    /// other kind of node, including numbers and booleans, whose text is not
+    /// kept; use [`Document::source_slice`] to get at that.
+    #[must_use]
+    pub fn as_source_slice(&self) -> Option<&'a str> {
+        match self {
+            Yaml::Scalar(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// An estimate of the memory the tree takes up, in bytes: the node
+    /// itself plus everything it owns on the heap, counting the capacity of
+    /// each `Vec` and `String` rather than its length. Text borrowed from the
+    /// input is not counted, as it belongs to the input; see
+    /// [`Yaml::estimate_owned_size`] for the size after
+    /// [`Yaml::into_owned`].
+    ///
+    /// Allocator overhead is not included, so the real footprint is
+    /// somewhat larger.
+    #[must_use]
+    pub fn estimate_size(&self) -> usize {
+        core::mem::size_of::<Self>() + self.heap_size(false)
+    }
+
+    /// An estimate of the memory the tree would take up once every borrowed
+    /// scalar is copied by [`Yaml::into_owned`], in bytes, e.g. to decide
+    /// whether a tree is worth caching before converting it.
+    #[must_use]
+    pub fn estimate_owned_size(&self) -> usize {
+        core::mem::size_of::<Self>() + self.heap_size(true)
+    }
+
+    /// The bytes owned by the node on the heap, counting borrowed scalars as
+    /// copies if `copy_borrowed` is set
+    fn heap_size(&self, copy_borrowed: bool) -> usize {
+        match self {
+            Yaml::Scalar(s) if copy_borrowed => s.len(),
+            Yaml::String(s) => s.capacity(),
+            Yaml::Bytes(bytes) => bytes.capacity(),
+            Yaml::Sequence(seq) => {
+                seq.capacity() * core::mem::size_of::<Yaml>()
+                    + seq
+                        .iter()
+                        .map(|item| item.heap_size(copy_borrowed))
+                        .sum::<usize>()
+            }
+            Yaml::Mapping(map) => {
+                map.capacity() * core::mem::size_of::<Entry>()
+                    + map
+                        .iter()
+                        .map(|entry| {
+                            entry.key.heap_size(copy_borrowed)
+                                + entry.value.heap_size(copy_borrowed)
+                        })
+                        .sum::<usize>()
+            }
+            Yaml::Tagged(tag, value) => {
+                let tag = match tag {
+                    Cow::Borrowed(tag) if copy_borrowed => tag.len(),
+                    Cow::Borrowed(_) => 0,
+                    Cow::Owned(tag) => tag.capacity(),
+                };
+                tag + core::mem::size_of::<Yaml>() + value.heap_size(copy_borrowed)
+            }
+            Yaml::Scalar(_)
+            | Yaml::Int(_)
+            | Yaml::Float(_)
+            | Yaml::Bool(_)
+            | Yaml::Null
+            | Yaml::Timestamp(_) => 0,
+        }
+    }
+
+    /// Remove every node below this one for which `keep` returns `false`,
+    /// e.g. to strip metadata keys or disabled sections before emitting or
+    /// converting a tree. Mapping values and sequence items are visited from
+    /// the top down: `keep` is given each node's path (written as for
+    /// [`Document::span`], e.g. `a.b[2]`) and the node itself. The children
+    /// of a removed node are not visited; those of a kept node are filtered
+    /// in turn. Paths are those of the tree before anything was removed.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &Yaml<'a>) -> bool) {
+        self.retain_at(&mut path::Path::default(), &mut keep);
+    }
+
+    fn retain_at(&mut self, path: &mut path::Path, keep: &mut impl FnMut(&str, &Yaml<'a>) -> bool) {
+        let mut visit = |segment, node: &mut Yaml<'a>| {
+            path.push(segment);
+            let kept = keep(&path.to_string(), node);
+            if kept {
+                node.retain_at(path, keep);
+            }
+            path.pop();
+            kept
+        };
+        match self {
+            Yaml::Mapping(entries) => entries.retain_mut(|entry| {
+                visit(PathSegment::Key(entry.key.key_string()), &mut entry.value)
+            }),
+            Yaml::Sequence(items) => {
+                let mut idx = 0;
+                items.retain_mut(|item| {
+                    idx += 1;
+                    visit(PathSegment::Index(idx - 1), item)
+                });
+            }
+            // a tag is not a level of the path
+            Yaml::Tagged(_, value) => value.retain_at(path, keep),
+            _ => {}
+        }
+    }
+
+    /// Rename mapping keys throughout the tree, e.g. to move a config from
+    /// camelCase to `snake_case` keys. `rename` is called with every string
+    /// key and returns the new name, or `None` to leave the key as it is;
+    /// only renamed keys become owned strings. Keys which are not strings
+    /// are left alone.
+    pub fn rename_keys(&mut self, mut rename: impl FnMut(&str) -> Option<String>) {
+        self.rename_keys_with(&mut rename);
+    }
+
+    fn rename_keys_with(&mut self, rename: &mut impl FnMut(&str) -> Option<String>) {
+        match self {
+            Yaml::Mapping(entries) => {
+                for entry in entries {
+                    let name = match &entry.key {
+                        Yaml::Scalar(name) => Some(*name),
+                        Yaml::String(name) => Some(name.as_str()),
+                        _ => None,
+                    };
+                    if let Some(renamed) = name.and_then(&mut *rename) {
+                        entry.key = Yaml::String(renamed);
+                    }
+                    entry.value.rename_keys_with(rename);
+                }
+            }
+            Yaml::Sequence(items) => {
+                for item in items {
+                    item.rename_keys_with(rename);
+                }
+            }
+            Yaml::Tagged(_, value) => value.rename_keys_with(rename),
+            _ => {}
+        }
+    }
+
+    /// Build a mapping from `entries`, failing if two of them have the same
+    /// key. Keys are compared by the JSON key they convert to, so `1` and
+    /// `"1"` are the same key.
+    /// # Errors
+    /// Returns `Err` naming the first key which appears more than once
+    pub fn mapping_from_unique(
+        entries: impl IntoIterator<Item = Entry<'a>>,
+    ) -> std::result::Result<Yaml<'a>, DuplicateKeyError> {
+        let mut seen = std::collections::HashSet::new();
+        let mut mapping = Vec::new();
+        for entry in entries {
+            let key_string = entry.key.key_string();
+            if seen.contains(&key_string) {
+                return Err(DuplicateKeyError { key: key_string });
+            }
+            seen.insert(key_string);
+            mapping.push(entry);
+        }
+        Ok(Yaml::Mapping(mapping))
+    }
+
+    /// Append an entry to a mapping, unless the mapping already has the key
+    /// (compared as in [`Yaml::mapping_from_unique`]), in which case it is
+    /// left unchanged.
+    /// # Errors
+    /// Returns `Err` if the mapping already contains the key, or if `self` is
+    /// not a mapping
+    pub fn insert_unique(
+        &mut self,
+        key: Yaml<'a>,
+        value: Yaml<'a>,
+    ) -> std::result::Result<(), InsertError> {
+        let Yaml::Mapping(entries) = self else {
+            return Err(InsertError::NotAMapping {
+                found: convert::describe(self),
+            });
+        };
+        let key_string = key.key_string();
+        if entries
+            .iter()
+            .any(|entry| entry.key.key_string() == key_string)
+        {
+            return Err(InsertError::DuplicateKey(DuplicateKeyError {
+                key: key_string,
+            }));
+        }
+        entries.push(Entry::new(key, value));
+        Ok(())
+    }
+
+    /// Store each value of `updates` at its path (written as for
+    /// [`Document::span`], e.g. `a.b[2]`), in order, replacing the node
+    /// there. A missing final mapping key is appended, as is a sequence
+    /// index one past the end, so an update may add to a node created by an
+    /// earlier one. The batch is applied as a whole or not at all: the tree
+    /// is only changed if every update succeeds.
+    /// # Errors
+    /// Returns `Err` with the first update whose path is invalid or has no
+    /// parent node, in which case `self` is left unchanged
+    pub fn apply(&mut self, updates: &[(&str, Yaml<'a>)]) -> std::result::Result<(), UpdateError> {
+        let fail = |index: usize, reason| {
+            Err(UpdateError {
+                index,
+                path: updates[index].0.to_string(),
+                reason,
+            })
+        };
+        let mut targets = Vec::with_capacity(updates.len());
+        for (index, (path, _)) in updates.iter().enumerate() {
+            match path::Path::parse(path) {
+                Some(target) => targets.push(target),
+                None => return fail(index, EditError::InvalidPath((*path).to_string())),
+            }
+        }
+        let mut tree = self.clone();
+        for (index, (target, (path, value))) in targets.iter().zip(updates).enumerate() {
+            if !path::set(&mut tree, target, value.clone()) {
+                return fail(index, EditError::NotFound((*path).to_string()));
+            }
+        }
+        *self = tree;
+        Ok(())
+    }
+
+    /// Sort the items of a sequence with `compare`. The sort is stable:
+    /// items which compare equal keep their order.
+    /// # Errors
+    /// Returns `Err` if `self` is not a sequence
+    pub fn sort_by(
+        &mut self,
+        compare: impl FnMut(&Yaml<'a>, &Yaml<'a>) -> Ordering,
+    ) -> std::result::Result<(), SequenceError> {
+        self.items_mut()?.sort_by(compare);
+        Ok(())
+    }
+
+    /// Sort a sequence by a field of its items, e.g. a list of mappings by
+    /// `name`. `field` is a path below each item (written as for
+    /// [`Document::span`], e.g. `meta.name`; an empty path sorts by the items
+    /// themselves). Numbers compare by value, strings by their text and
+    /// timestamps by time; values of different types are ordered null,
+    /// booleans, numbers, timestamps, strings, then the rest. Items without
+    /// the field go last, and the sort is stable.
+    /// # Errors
+    /// Returns `Err` if `self` is not a sequence, or `field` is not a valid
+    /// path
+    pub fn sort_by_field(&mut self, field: &str) -> std::result::Result<(), SequenceError> {
+        let field = field_path(field)?;
+        self.sort_by(
+            |a, b| match (path::resolve(a, &field), path::resolve(b, &field)) {
+                (Some(a), Some(b)) => compare_fields(a, b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        )
+    }
+
+    /// Remove the items of a sequence which `same` finds to duplicate an
+    /// earlier item, wherever they are in the sequence (unlike
+    /// [`Vec::dedup_by`], which only looks at neighbours). `same` is given a
+    /// later item, then the earlier one. The first of each group of
+    /// duplicates is kept.
+    /// # Errors
+    /// Returns `Err` if `self` is not a sequence
+    pub fn dedup_by(
+        &mut self,
+        mut same: impl FnMut(&Yaml<'a>, &Yaml<'a>) -> bool,
+    ) -> std::result::Result<(), SequenceError> {
+        let items = self.items_mut()?;
+        let mut kept: Vec<Yaml<'a>> = Vec::with_capacity(items.len());
+        for item in items.drain(..) {
+            if !kept.iter().any(|earlier| same(&item, earlier)) {
+                kept.push(item);
+            }
+        }
+        *items = kept;
+        Ok(())
+    }
+
+    /// Remove the items of a sequence whose `field` (a path as for
+    /// [`Yaml::sort_by_field`]) equals that of an earlier item, keeping the
+    /// first. Strings are compared by their text, whether or not they were
+    /// quoted. Items without the field are all kept.
+    /// # Errors
+    /// Returns `Err` if `self` is not a sequence, or `field` is not a valid
+    /// path
+    pub fn dedup_by_field(&mut self, field: &str) -> std::result::Result<(), SequenceError> {
+        let field = field_path(field)?;
+        self.dedup_by(
+            |a, b| match (path::resolve(a, &field), path::resolve(b, &field)) {
+                (Some(a), Some(b)) => same_field(a, b),
+                _ => false,
+            },
+        )
+    }
+
+    /// Move the items of a sequence at the indices in `order` to its front,
+    /// in that order. The other items follow in the order they had, so
+    /// `reorder(&[2])` moves the third item first and leaves the rest as
+    /// they were.
+    /// # Errors
+    /// Returns `Err` if `self` is not a sequence, or if `order` has an index
+    /// which is out of bounds or appears twice; the sequence is then left
+    /// unchanged
+    pub fn reorder(&mut self, order: &[usize]) -> std::result::Result<(), SequenceError> {
+        let items = self.items_mut()?;
+        let mut taken = vec![false; items.len()];
+        for &idx in order {
+            match taken.get_mut(idx) {
+                Some(taken) if !*taken => *taken = true,
+                _ => return Err(SequenceError::BadIndex(idx)),
             }
         }
+        let mut slots: Vec<Option<Yaml<'a>>> = items.drain(..).map(Some).collect();
+        let mut reordered: Vec<Yaml<'a>> =
+            order.iter().filter_map(|&idx| slots[idx].take()).collect();
+        reordered.extend(slots.into_iter().flatten());
+        *items = reordered;
+        Ok(())
+    }
+
+    fn items_mut(&mut self) -> std::result::Result<&mut Vec<Yaml<'a>>, SequenceError> {
+        match self {
+            Yaml::Sequence(items) => Ok(items),
+            other => Err(SequenceError::NotASequence {
+                found: convert::describe(other),
+            }),
+        }
+    }
+}
+
+fn field_path(field: &str) -> std::result::Result<path::Path, SequenceError> {
+    path::Path::parse(field).ok_or_else(|| SequenceError::InvalidPath(field.to_string()))
+}
+
+/// The order of [`Yaml::sort_by_field`]
+fn compare_fields(a: &Yaml<'_>, b: &Yaml<'_>) -> Ordering {
+    fn rank(node: &Yaml<'_>) -> u8 {
+        match node {
+            Yaml::Null => 0,
+            Yaml::Bool(_) => 1,
+            Yaml::Int(_) | Yaml::Float(_) => 2,
+            Yaml::Timestamp(_) => 3,
+            Yaml::Scalar(_) | Yaml::String(_) => 4,
+            Yaml::Bytes(_) | Yaml::Sequence(_) | Yaml::Mapping(_) | Yaml::Tagged(..) => 5,
+        }
+    }
+    #[allow(clippy::cast_precision_loss)]
+    match (a, b) {
+        (Yaml::Bool(a), Yaml::Bool(b)) => a.cmp(b),
+        (Yaml::Int(a), Yaml::Int(b)) => a.cmp(b),
+        (Yaml::Int(a), Yaml::Float(b)) => (*a as f64).total_cmp(b),
+        (Yaml::Float(a), Yaml::Int(b)) => a.total_cmp(&(*b as f64)),
+        (Yaml::Float(a), Yaml::Float(b)) => a.total_cmp(b),
+        (Yaml::Timestamp(a), Yaml::Timestamp(b)) => a.unix_millis().cmp(&b.unix_millis()),
+        _ => match (text(a), text(b)) {
+            (Some(a), Some(b)) => a.cmp(b),
+            _ => rank(a).cmp(&rank(b)),
+        },
+    }
+}
+
+/// The equality of [`Yaml::dedup_by_field`]
+fn same_field(a: &Yaml<'_>, b: &Yaml<'_>) -> bool {
+    match (text(a), text(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
     }
 }
-#[cfg_attr(test, derive(serde::Deserialize, serde::Serialize))]
+
+fn text<'y>(node: &'y Yaml<'_>) -> Option<&'y str> {
+    match node {
+        Yaml::Scalar(s) => Some(s),
+        Yaml::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+/// Options controlling how values JSON has no type for are converted by
+/// [`Yaml::to_json_with_options`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// How binary data is represented
+    pub bytes: BytesFormat,
+    /// How timestamps are represented
+    pub timestamps: TimestampJson,
+}
+
+/// A JSON representation of timestamps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampJson {
+    /// An ISO 8601 string, with a zone only if one was given
+    #[default]
+    Iso8601,
+    /// An RFC 3339 string, where a time always has a zone
+    Rfc3339,
+    /// The number of milliseconds since the Unix epoch, reading times
+    /// without a zone as UTC
+    EpochMillis,
+}
+
+/// A JSON representation of binary data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BytesFormat {
+    /// A base64 string, as written in Yaml
+    #[default]
+    Base64,
+    /// An array of numbers, one per byte
+    Array,
+}
+
+/// Options controlling the mx transformation
+#[derive(Debug, Clone, PartialEq)]
+pub struct MxOptions {
+    /// The maximum length, in characters as displayed (approximately
+    /// grapheme clusters), of user content embedded in `+error` values.
+    /// Longer content is truncated and ends with `…`; `Some(0)` leaves it
+    /// out. `None`, the default, embeds content in full.
+    pub snippet_limit: Option<usize>,
+    /// Whether `+error` values include the document in `__value`. Turn this
+    /// off for large documents, or ones which may hold secrets; the
+    /// offending key and the index of its entry are still reported.
+    pub include_body: bool,
+}
+
+impl Default for MxOptions {
+    fn default() -> Self {
+        Self {
+            snippet_limit: None,
+            include_body: true,
+        }
+    }
+}
+
+/// A Yaml tree which owns all of its text
+pub type YamlOwned = Yaml<'static>;
+
 #[derive(Clone, Debug, PartialEq)]
 /// A Yaml map entry
 pub struct Entry<'a> {
     /// The key associated with the entry
-    #[cfg_attr(test, serde(borrow))]
     pub key: Yaml<'a>,
     /// The value which the key maps to
-    #[cfg_attr(test, serde(borrow))]
     pub value: Yaml<'a>,
 }
 
@@ -489,10 +1150,40 @@ impl<'a> Entry<'a> {
 
 impl<'a> Display for Entry<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}: {}", self.key, self.value)
+        if f.alternate() {
+            write!(f, "{:#}: {:#}", self.key, self.value)
+        } else {
+            write!(f, "{}: {}", self.key, self.value)
+        }
     }
 }
 
+/// JSON has no infinities or NaN, which become 0
+fn float_to_json(f: f64) -> Value {
+    Value::Number(serde_json::Number::from_f64(f).unwrap_or_else(|| 0.into()))
+}
+
+/// Convert the items of a sequence. Numbers and plain strings, which make
+/// up large data arrays, are converted in place rather than through a call
+/// to [`Yaml::to_json_with_options`] each; `benches/to_json.rs` times the
+/// two.
+fn sequence_to_json(seq: &[Yaml<'_>], opts: &JsonOptions) -> Vec<Value> {
+    let mut items = Vec::with_capacity(seq.len());
+    items.extend(seq.iter().map(|item| match item {
+        Yaml::Int(i) => Value::Number((*i).into()),
+        Yaml::Float(f) => float_to_json(*f),
+        Yaml::Scalar(s) => Value::String((*s).to_string()),
+        item => item.to_json_with_options(opts),
+    }));
+    items
+}
+
+/// The offset of `slice` within `source`, if it was borrowed from it
+fn offset_in(source: &str, slice: &str) -> Option<usize> {
+    let start = (slice.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+    (start + slice.len() <= source.len()).then_some(start)
+}
+
 /// Parse Yaml input. Returns the top level Yaml element on success
 /// # Errors
 /// Returns `Err` if the input is invalid Yaml, with a message indicating
@@ -502,6 +1193,121 @@ pub fn parse(input: &str) -> Result<Yaml<'_>> {
     parser.parse()
 }
 
+/// Parse Yaml input with the given options. Returns the top level Yaml
+/// element on success
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml, with a message indicating
+/// where the error occurred and possibly more information on the cause
+pub fn parse_with_options<'a>(input: &'a str, options: &ParseOptions) -> Result<Yaml<'a>> {
+    let mut parser = Parser::with_options(input, options)?;
+    parser.parse()
+}
+
+/// Parse Yaml input into a tree which owns all of its text, so that it can
+/// outlive the input, e.g. to return it from a function which reads the
+/// input into a local `String`. See [`Yaml::into_owned`].
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml, with a message indicating
+/// where the error occurred and possibly more information on the cause
+pub fn parse_owned(input: &str) -> Result<YamlOwned> {
+    parse(input).map(Yaml::into_owned)
+}
+
+/// Parse Yaml input like [`parse`], but rejecting anything other than
+/// whitespace and comments after the document, such as a stray closing
+/// bracket or a second document, which [`parse`] ignores
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml, or with
+/// [`ErrorKind::TrailingContent`] at the first character of any content
+/// after the document
+pub fn parse_complete(input: &str) -> Result<Yaml<'_>> {
+    let mut parser = Parser::new(input)?;
+    let node = parser.parse()?;
+    parser.check_complete()?;
+    Ok(node)
+}
+
+/// Parse JSON, or Yaml, resolving plain scalars by the JSON rules
+/// ([`Schema::Json`]): every JSON document parses to the value a JSON parser
+/// would give, with integers too large for `i64` read as floats, while Yaml
+/// input is still accepted.
+/// # Errors
+/// Returns `Err` if the input is neither JSON nor valid Yaml, with a message
+/// indicating where the error occurred
+pub fn parse_json(input: &str) -> Result<Yaml<'_>> {
+    parse_with_options(input, &ParseOptions::default().schema(Schema::Json))
+}
+
+/// Parse Yaml input which has not yet been checked to be UTF-8, such as the
+/// contents of a file. A byte order mark is skipped, and UTF-16 input (with
+/// a byte order mark, or recognized by the zero bytes of its first
+/// character) is transcoded, in which case the tree owns all of its text.
+/// # Errors
+/// Returns `Err` if the input is not valid UTF-8 or UTF-16, with a message
+/// giving the byte offset of the first invalid sequence, or if it is
+/// invalid Yaml
+pub fn parse_bytes(input: &[u8]) -> Result<Yaml<'_>> {
+    let (encoding, bom_len) = encoding::detect(input);
+    let body = &input[bom_len..];
+    if encoding != encoding::Encoding::Utf8 {
+        let text = encoding::decode_utf16(body, encoding, false).map_err(|offset| {
+            let valid = encoding::decode_utf16(&body[..offset], encoding, true).unwrap_or_default();
+            invalid_input_error(&valid, "UTF-16", bom_len + offset)
+        })?;
+        return parse(&text).map(Yaml::into_owned);
+    }
+    let text = std::str::from_utf8(body).map_err(|err| {
+        let valid = std::str::from_utf8(&body[..err.valid_up_to()]).unwrap_or_default();
+        invalid_input_error(valid, "UTF-8", bom_len + valid.len())
+    })?;
+    parse(text)
+}
+
+/// Parse Yaml input read from `reader`, such as a file or the standard
+/// input, into a tree which owns all of its text. The input is buffered
+/// internally, so `reader` need not be buffered, and is decoded as by
+/// [`parse_bytes`].
+/// # Errors
+/// Returns `Err` if reading fails, or if the input is not validly encoded
+/// or is invalid Yaml
+pub fn parse_reader(mut reader: impl std::io::Read) -> std::result::Result<YamlOwned, ReadError> {
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    Ok(parse_bytes(&input)?.into_owned())
+}
+
+/// The error for input which is not validly encoded, where `valid` is the
+/// text before the bad sequence at byte `offset`
+pub(crate) fn invalid_input_error(valid: &str, encoding: &str, offset: usize) -> YamlParseError {
+    let (line, col) = span::line_col(valid, valid.len());
+    YamlParseError::new(
+        line,
+        col,
+        ErrorKind::InvalidEncoding {
+            encoding: encoding.to_string(),
+            offset,
+        },
+    )
+}
+
+/// Parse Yaml input which may not be UTF-8 (for instance Windows-1252
+/// text), replacing each invalid sequence with `U+FFFD`. Byte order marks
+/// and UTF-16 are handled as by [`parse_bytes`].
+/// # Errors
+/// Returns `Err` if the converted input is invalid Yaml
+pub fn parse_bytes_lossy(input: &[u8]) -> Result<YamlOwned> {
+    let (encoding, bom_len) = encoding::detect(input);
+    let body = &input[bom_len..];
+    let text = if encoding == encoding::Encoding::Utf8 {
+        String::from_utf8_lossy(body)
+    } else {
+        encoding::decode_utf16(body, encoding, true)
+            .unwrap_or_default()
+            .into()
+    };
+    parse(&text).map(Yaml::into_owned)
+}
+
 // WASM bindings
 #[cfg(feature = "wasm")]
 pub(crate) mod wasm {
@@ -517,20 +1323,129 @@ pub(crate) mod wasm {
             .map_err(|e| JsError::new(&e.to_string()))
     }
 
+    /// The `Error` thrown for a parse error, with its `line` and `col` and
+    /// its `offset` in UTF-16 code units, as JS counts
+    fn parse_error(input: &str, err: &YamlParseError) -> JsValue {
+        let error = js_sys::Error::new(&err.to_string());
+        let offset = Utf16Index::new(input).position(err.line, err.col);
+        for (key, value) in [("line", err.line), ("col", err.col), ("offset", offset)] {
+            // setting a property of a new plain object cannot fail
+            let _ = js_sys::Reflect::set(&error, &key.into(), &value.into());
+        }
+        error.into()
+    }
+
     /// Parse YAML string and return JSON object directly.
-    /// Returns a JavaScript object/array on success, or throws an error on parse failure.
+    /// Returns a JavaScript object/array on success, or throws an error on
+    /// parse failure, with the `line`, `col` and UTF-16 `offset` it is at.
+    /// If `with_warnings` is set, returns `{ value, warnings }` instead, with the
+    /// warnings `validate` finds (such as duplicate keys).
+    /// `messages` maps the keys of `errorCatalog()` to message templates,
+    /// e.g. translations, which errors use instead of the English ones.
     #[wasm_bindgen(js_name = parseYaml)]
-    pub fn parse_yaml_to_json(input: &str) -> std::result::Result<JsValue, JsError> {
-        let yaml = parse(input).map_err(|e| JsError::new(&e.to_string()))?;
-        to_js_object(&yaml.to_json())
+    pub fn parse_yaml_to_json(
+        input: &str,
+        with_warnings: Option<bool>,
+        messages: Option<JsValue>,
+    ) -> std::result::Result<JsValue, JsValue> {
+        let mut opts = ParseOptions::default();
+        if let Some(messages) = messages {
+            let templates: std::collections::HashMap<String, String> =
+                serde_wasm_bindgen::from_value(messages)
+                    .map_err(|e| JsError::new(&e.to_string()))?;
+            opts = opts.messages(move |kind| {
+                templates
+                    .get(kind.key())
+                    .map(|template| kind.render(template))
+            });
+        }
+        let yaml = parse_with_options(input, &opts).map_err(|e| parse_error(input, &e))?;
+        let value = if with_warnings.unwrap_or(false) {
+            value_with_warnings(input, &yaml.to_json())
+        } else {
+            yaml.to_json()
+        };
+        Ok(to_js_object(&value)?)
+    }
+
+    /// The `{ value, warnings }` object returned by `parseYaml` when asked for warnings
+    /// `offset` is where the warning is in UTF-16 code units, as JS counts.
+    pub(crate) fn value_with_warnings(input: &str, value: &Value) -> Value {
+        let index = Utf16Index::new(input);
+        let warnings: Vec<Value> = validate(input)
+            .warnings
+            .iter()
+            .map(|warning| {
+                serde_json::json!({
+                    "line": warning.line,
+                    "col": warning.col,
+                    "offset": index.position(warning.line, warning.col),
+                    "message": warning.message,
+                })
+            })
+            .collect();
+        serde_json::json!({ "value": value, "warnings": warnings })
     }
 
     /// Parse YAML string and return mx-formatted JSON object directly.
-    /// Returns a JavaScript object with mx transformation on success, or throws an error on parse failure.
+    /// Returns a JavaScript object with mx transformation on success, or throws an error on
+    /// parse failure, as `parseYaml` does.
     #[wasm_bindgen(js_name = parseYamlToMx)]
-    pub fn parse_yaml_to_mx(input: &str) -> std::result::Result<JsValue, JsError> {
-        let yaml = parse(input).map_err(|e| JsError::new(&e.to_string()))?;
-        to_js_object(&yaml.to_mx())
+    pub fn parse_yaml_to_mx(input: &str) -> std::result::Result<JsValue, JsValue> {
+        let yaml = parse(input).map_err(|e| parse_error(input, &e))?;
+        Ok(to_js_object(&yaml.to_mx())?)
+    }
+
+    /// The UTF-16 offset of a 1-based line and column in `input`, such as
+    /// those of a parse error, for placing markers in a JS editor. This
+    /// scans `input` on each call; use `Utf16Positions` for many lookups.
+    #[wasm_bindgen(js_name = utf16Offset)]
+    #[must_use]
+    pub fn utf16_offset(input: &str, line: usize, col: usize) -> usize {
+        Utf16Index::new(input).position(line, col)
+    }
+
+    /// A [`Utf16Index`] kept on the JS side, so that the input is scanned
+    /// once however many positions are looked up in it
+    #[wasm_bindgen(js_name = Utf16Positions)]
+    pub struct Utf16Positions(Utf16Index<'static>);
+
+    #[wasm_bindgen(js_class = Utf16Positions)]
+    impl Utf16Positions {
+        #[wasm_bindgen(constructor)]
+        #[must_use]
+        pub fn new(input: &str) -> Self {
+            Self(Utf16Index::new(input).into_owned())
+        }
+
+        /// The UTF-16 offset of a 1-based line and column
+        #[must_use]
+        pub fn position(&self, line: usize, col: usize) -> usize {
+            self.0.position(line, col)
+        }
+
+        /// The UTF-16 offset of a byte offset
+        #[must_use]
+        pub fn offset(&self, offset: usize) -> usize {
+            self.0.offset(offset)
+        }
+    }
+
+    /// The key and English template of every parse error message, as an
+    /// object, for translating them into the `messages` of `parseYaml`
+    #[wasm_bindgen(js_name = errorCatalog)]
+    pub fn error_catalog() -> std::result::Result<JsValue, JsError> {
+        let catalog: Map<String, Value> = MESSAGE_CATALOG
+            .iter()
+            .map(|(key, template)| ((*key).to_string(), Value::from(*template)))
+            .collect();
+        to_js_object(&catalog)
+    }
+
+    /// Describe what the parser supports, as returned by `capabilities()`.
+    #[wasm_bindgen(js_name = capabilities)]
+    pub fn capabilities_to_json() -> std::result::Result<JsValue, JsError> {
+        to_js_object(&capabilities().to_json())
     }
 
     /// Convert JSON to YAML string.
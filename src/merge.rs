@@ -0,0 +1,66 @@
+use crate::parse::same_key;
+use crate::Yaml;
+
+/// How [`Yaml::merge`] combines two sequences at the same place
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The overriding sequence replaces the base one
+    #[default]
+    Replace,
+    /// The overriding items follow the base ones
+    Append,
+    /// The overriding items follow the base ones, leaving out any item
+    /// equal (as JSON) to one already there
+    Unique,
+}
+
+impl<'a> Yaml<'a> {
+    /// Merge `other` into this node, as an override layered over defaults.
+    /// Mappings are merged key by key, recursively: keys only in `other`
+    /// are added at the end, and keys in both are merged. Sequences are
+    /// combined as `strategy` says, and anything else, including a null, is
+    /// replaced by the value from `other`. Keys are compared by the JSON key
+    /// they convert to, so `1` and `"1"` are the same key. A tag on `other`
+    /// replaces the node's tag, while an untagged `other` keeps it.
+    pub fn merge(&mut self, other: Yaml<'a>, strategy: MergeStrategy) {
+        match (self, other) {
+            (Yaml::Mapping(entries), Yaml::Mapping(others)) => {
+                for other in others {
+                    match entries
+                        .iter_mut()
+                        .rev()
+                        .find(|e| same_key(&e.key, &other.key))
+                    {
+                        Some(entry) => entry.value.merge(other.value, strategy),
+                        None => entries.push(other),
+                    }
+                }
+            }
+            (Yaml::Sequence(items), Yaml::Sequence(others)) => match strategy {
+                MergeStrategy::Replace => *items = others,
+                MergeStrategy::Append => items.extend(others),
+                MergeStrategy::Unique => {
+                    let mut seen: Vec<_> = items.iter().map(Yaml::to_json).collect();
+                    for other in others {
+                        let json = other.to_json();
+                        if !seen.contains(&json) {
+                            seen.push(json);
+                            items.push(other);
+                        }
+                    }
+                }
+            },
+            (Yaml::Tagged(tag, value), Yaml::Tagged(other_tag, other)) => {
+                *tag = other_tag;
+                value.merge(*other, strategy);
+            }
+            (Yaml::Tagged(_, value), other) => value.merge(other, strategy),
+            (node, Yaml::Tagged(tag, other)) => {
+                let mut value = std::mem::replace(node, Yaml::Null);
+                value.merge(*other, strategy);
+                *node = Yaml::Tagged(tag, Box::new(value));
+            }
+            (node, other) => *node = other,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::path::Path;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A value which can be attached to a node
+trait MetaValue: Any + Send + Sync {
+    fn clone_box(&self) -> Box<dyn MetaValue>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Any + Clone + Send + Sync> MetaValue for T {
+    fn clone_box(&self) -> Box<dyn MetaValue> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// The values attached to the nodes of a document, at most one of each
+/// type per node
+#[derive(Default)]
+pub(crate) struct MetaTable {
+    values: HashMap<(Path, TypeId), Box<dyn MetaValue>>,
+}
+
+impl MetaTable {
+    pub(crate) fn insert<T: Any + Clone + Send + Sync>(
+        &mut self,
+        path: Path,
+        value: T,
+    ) -> Option<T> {
+        let old = self
+            .values
+            .insert((path, TypeId::of::<T>()), Box::new(value))?;
+        old.into_any().downcast().ok().map(|old| *old)
+    }
+
+    pub(crate) fn get<T: Any>(&self, path: &Path) -> Option<&T> {
+        // the key is looked up by value, as `(Path, TypeId)` cannot be
+        // borrowed from a `&Path`
+        let value = self.values.get(&(path.clone(), TypeId::of::<T>()))?;
+        (**value).as_any().downcast_ref()
+    }
+
+    pub(crate) fn get_mut<T: Any>(&mut self, path: &Path) -> Option<&mut T> {
+        let value = self.values.get_mut(&(path.clone(), TypeId::of::<T>()))?;
+        (**value).as_any_mut().downcast_mut()
+    }
+
+    pub(crate) fn remove<T: Any>(&mut self, path: &Path) -> Option<T> {
+        let old = self.values.remove(&(path.clone(), TypeId::of::<T>()))?;
+        old.into_any().downcast().ok().map(|old| *old)
+    }
+
+    /// Every node with a value of type `T`, and the value
+    pub(crate) fn iter<T: Any>(&self) -> impl Iterator<Item = (&Path, &T)> {
+        self.values
+            .iter()
+            .filter(|((_, type_id), _)| *type_id == TypeId::of::<T>())
+            .filter_map(|((path, _), value)| Some((path, (**value).as_any().downcast_ref()?)))
+    }
+}
+
+impl Clone for MetaTable {
+    fn clone(&self) -> Self {
+        Self {
+            values: self
+                .values
+                .iter()
+                .map(|(key, value)| (key.clone(), (**value).clone_box()))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Debug for MetaTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set()
+            .entries(self.values.keys().map(|(path, _)| path.to_string()))
+            .finish()
+    }
+}
@@ -1,17 +1,32 @@
+use crate::base64;
 use crate::bytes::ByteExt;
+use crate::errors::{Construct, ContextFrame, ErrorKind, MiniYamlError};
+use crate::path::{Path, PathSegment};
+use crate::resolve::{parse_int, CoreSchema, ScalarResolver, Schema, Yaml11Schema};
+use crate::snippet::snippet;
+use crate::span::{line_col, NodeSpan, Span, SpanTable};
+use crate::stream;
 use crate::{Entry, Yaml, YamlParseError};
-use core::iter::{Iterator, Peekable};
-use std::str::Bytes;
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::Result;
-#[allow(dead_code)]
+/// The context a node is parsed in, as the Yaml spec names them, which
+/// decides e.g. whether `,` and `]` may appear in plain scalars
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum ParseContext {
+pub enum ParseContext {
+    /// inside a flow collection
     FlowIn,
+    /// outside a flow collection, where flow content may appear
     FlowOut,
+    /// a key in a flow mapping
     FlowKey,
+    /// inside a block collection
     BlockIn,
+    /// at the top level of block content
     BlockOut,
+    /// a key in a block mapping
     BlockKey,
 }
 
@@ -23,48 +38,380 @@ enum ParseContextKind {
     Block,
 }
 
+/// The most nodes aliases may copy into a document, which stops small inputs
+/// with nested aliases (a "billion laughs" attack) from exhausting memory
+const MAX_ALIASED_NODES: usize = 1_000_000;
+
+/// The prefix the `!!` handle stands for
+const YAML_TAG_PREFIX: &str = "tag:yaml.org,2002:";
+
+/// The tags after the `!!` handle which resolve values to a type
+pub(crate) const STANDARD_TAGS: &[&str] = &[
+    "str", "int", "float", "bool", "null", "map", "seq", "set", "omap", "pairs", "binary",
+];
+
+/// The tags `PyYAML` writes for Python's built-in types after `!!python/`,
+/// and the standard tags they stand for
+const PYTHON_TAGS: &[(&str, &str)] = &[
+    ("none", "null"),
+    ("bool", "bool"),
+    ("int", "int"),
+    ("long", "int"),
+    ("float", "float"),
+    ("str", "str"),
+    ("unicode", "str"),
+    ("bytes", "binary"),
+    ("list", "seq"),
+    ("tuple", "seq"),
+    ("dict", "map"),
+];
+
+/// Whether `byte` can be part of a tag name, `inside_angles` meaning within
+/// the parameters of a generic type such as `!map<string,int>`. Besides
+/// letters and digits, names may hold the `/`, `.` and `:` of tags such as
+/// `PyYAML`'s `!!python/object:module.Class`.
+pub(crate) fn is_tag_char(byte: u8, inside_angles: bool) -> bool {
+    match byte {
+        b'a'..=b'z'
+        | b'A'..=b'Z'
+        | b'0'..=b'9'
+        | b'-'
+        | b'_'
+        | b'/'
+        | b'.'
+        | b':'
+        | b'%'
+        | b'~'
+        | b'+' => true,
+        // Comma and pipe only allowed inside angle brackets
+        b',' | b'|' => inside_angles,
+        _ => false,
+    }
+}
+
+/// The entry of a mapping with exactly one entry, as the items of `!!omap`
+/// and `!!pairs` are
+fn single_entry(item: Yaml<'_>) -> Option<Entry<'_>> {
+    match item {
+        Yaml::Mapping(mut entries) if entries.len() == 1 => entries.pop(),
+        _ => None,
+    }
+}
+
+/// Resolve the text of a scalar with a standard tag as the core schema
+/// would, returning `None` if the text is not a value of the tag's type
+fn resolve_standard_tag(tag: &str, text: &str) -> Option<Yaml<'static>> {
+    match (tag, CoreSchema.resolve(text)) {
+        ("int", _) => parse_int(text).map(Yaml::Int),
+        ("float", Yaml::Float(f)) => Some(Yaml::Float(f)),
+        ("float", Yaml::Int(_)) => text.parse().ok().map(Yaml::Float),
+        ("bool", Yaml::Bool(b)) => Some(Yaml::Bool(b)),
+        ("null", Yaml::Null) => Some(Yaml::Null),
+        ("null", _) if text.is_empty() => Some(Yaml::Null),
+        ("binary", _) => base64::decode(text).map(Yaml::Bytes),
+        _ => None,
+    }
+}
+
+/// Where a plain scalar's text is relative to the template placeholders
+/// (`{{ x }}`, `${X}`) in it
+#[derive(Default)]
+struct Placeholder {
+    /// `$` has been seen before a `{`
+    dollar: bool,
+    /// the number of braces open inside the current placeholder
+    depth: usize,
+}
+
+impl Placeholder {
+    /// Take the next byte of the scalar, returning whether it is part of a
+    /// placeholder
+    fn step(&mut self, tok: u8, nxt: Option<u8>) -> bool {
+        match tok {
+            b'$' if self.depth == 0 && nxt == Some(b'{') => {
+                self.dollar = true;
+                true
+            }
+            b'{' if self.depth > 0 || self.dollar || nxt == Some(b'{') => {
+                self.dollar = false;
+                self.depth += 1;
+                true
+            }
+            b'}' if self.depth > 0 => {
+                self.depth -= 1;
+                true
+            }
+            _ => self.depth > 0,
+        }
+    }
+}
+
+/// Options controlling how input is parsed
+#[derive(Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ParseOptions {
+    /// Reject anchors, aliases and directives with an error naming the
+    /// construct, for inputs which are required to stick to the minimal
+    /// subset of Yaml
+    pub reject_unsupported: bool,
+    /// Read plain scalars such as `1_000_000` as numbers, ignoring the
+    /// underscores as YAML 1.1 does
+    pub numeric_underscores: bool,
+    /// How plain scalars are resolved to nulls, booleans and numbers;
+    /// [`Yaml11Schema`] if unset
+    pub resolver: Option<Arc<dyn ScalarResolver + Send + Sync>>,
+    /// Read the tags other emitters write for built-in types as the
+    /// standard tags they stand for, so that `PyYAML`'s
+    /// `!!python/tuple [1, 2]` is a plain sequence and `!!python/unicode`
+    /// a string; other tags are kept as usual
+    pub compat: bool,
+    /// Reject tabs in the indentation of block collections, which YAML
+    /// forbids, with a "tab used for indentation" error; otherwise a tab
+    /// counts as one space
+    pub reject_tab_indentation: bool,
+    /// What to do with a mapping key which appears more than once
+    pub duplicate_keys: DuplicateKeys,
+    /// Read template placeholders (`{{ .Values.image }}`, `${NAME}`) in
+    /// plain scalars as opaque text, so that templated files can be parsed,
+    /// edited and printed back: a value starting with `{{` is a plain
+    /// scalar rather than a flow mapping, and nothing inside a placeholder,
+    /// such as `: ` or `,`, ends the scalar. Lines holding nothing but a
+    /// placeholder (`{{ if .Values.x }}`) are not supported.
+    pub templates: bool,
+    /// Gives the message of each error, e.g. in another language; errors
+    /// for which it returns `None`, or all errors if it is unset, have the
+    /// English message of their [`ErrorKind`]
+    pub messages: Option<MessageHook>,
+}
+
+/// A function giving the message of a parse error, for
+/// [`ParseOptions::messages`]
+pub type MessageHook = Arc<dyn Fn(&ErrorKind) -> Option<String> + Send + Sync>;
+
+/// How [`ParseOptions`] handle a key written twice in one mapping. Keys are
+/// compared by the JSON key they convert to, so `1` and `"1"` are the same
+/// key; merge keys (`<<`) are never duplicates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep every entry in the mapping; `to_json` and path lookups use the
+    /// last one
+    #[default]
+    Keep,
+    /// Fail with a "duplicate key" error at the second occurrence
+    Error,
+    /// Keep the first entry and drop the later ones
+    FirstWins,
+    /// Keep the value of the last entry, in the place of the first one
+    LastWins,
+}
+
+impl ParseOptions {
+    /// Resolve plain scalars with `resolver`
+    #[must_use]
+    pub fn resolver(mut self, resolver: impl ScalarResolver + Send + Sync + 'static) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Resolve plain scalars with one of the built-in schemas;
+    /// `schema(Schema::Core)` gives strict YAML 1.2 resolution, where only
+    /// `true`, `false`, `null` and numbers are anything but strings
+    #[must_use]
+    pub fn schema(self, schema: Schema) -> Self {
+        self.resolver(schema)
+    }
+
+    /// Give errors the messages `messages` returns, falling back to the
+    /// English ones where it returns `None`
+    #[must_use]
+    pub fn messages(
+        mut self,
+        messages: impl Fn(&ErrorKind) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.messages = Some(Arc::new(messages));
+        self
+    }
+
+    /// The message of an error of kind `kind`
+    pub(crate) fn message(&self, kind: &ErrorKind) -> String {
+        self.messages
+            .as_ref()
+            .and_then(|messages| messages(kind))
+            .unwrap_or_else(|| kind.to_string())
+    }
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("reject_unsupported", &self.reject_unsupported)
+            .field("numeric_underscores", &self.numeric_underscores)
+            .field("resolver", &self.resolver.as_ref().map(|_| ".."))
+            .field("compat", &self.compat)
+            .field("reject_tab_indentation", &self.reject_tab_indentation)
+            .field("duplicate_keys", &self.duplicate_keys)
+            .field("templates", &self.templates)
+            .field("messages", &self.messages.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// Resolvers and message hooks compare equal only to themselves
+impl PartialEq for ParseOptions {
+    fn eq(&self, other: &Self) -> bool {
+        let same_resolver = match (&self.resolver, &other.resolver) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        };
+        let same_messages = match (&self.messages, &other.messages) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        };
+        self.reject_unsupported == other.reject_unsupported
+            && self.numeric_underscores == other.numeric_underscores
+            && self.compat == other.compat
+            && self.reject_tab_indentation == other.reject_tab_indentation
+            && self.duplicate_keys == other.duplicate_keys
+            && self.templates == other.templates
+            && same_resolver
+            && same_messages
+    }
+}
+
 pub(crate) struct Parser<'a> {
+    /// the byte at `idx`, or 0 once the input has ended
     current: u8,
-    stream: Peekable<Bytes<'a>>,
     bytes: &'a [u8],
     source: &'a str,
+    /// the offset of the current byte, the only record of the position;
+    /// the length of the source once the input has ended
     idx: usize,
     indent: usize,
     expected: Vec<u8>,
-    contexts: Vec<ParseContext>,
+    /// the contexts of the constructs being parsed, with what they are and
+    /// where they start. A scalar which may turn out to be a block mapping
+    /// key has a context but is no construct of its own, so it is left out
+    /// of errors.
+    contexts: Vec<(ParseContext, Option<Construct>, usize)>,
+    /// end offset of the most recently consumed piece of content
+    content_end: usize,
+    /// span of every node by path, only recorded when requested
+    spans: Option<SpanTable>,
+    path: Path,
+    options: ParseOptions,
+    /// anchored nodes by name; a later anchor with the same name replaces
+    /// an earlier one
+    anchors: HashMap<&'a str, Yaml<'a>>,
+    /// number of nodes copied into the document by aliases so far
+    aliased_nodes: usize,
+    /// leave plain scalars unresolved, for a standard tag to resolve
+    raw_scalars: bool,
+    /// the least indentation of the lines of a multi-line scalar: one more
+    /// than that of the block collection it is in
+    scalar_min_indent: usize,
 }
 
 impl<'a, 'b> Parser<'a> {
     pub(crate) fn new(source: &'a str) -> Result<Self> {
-        let mut stream = source.bytes().peekable();
-        let first = stream.next().ok_or_else(|| YamlParseError {
-            line: 0,
-            col: 0,
-            msg: Some("expected input".into()),
-            source: None,
-        })?;
-        Ok(Self {
+        // a document end marker ends the input
+        let source = &source[..stream::single_document_len(source)];
+        let first = *source
+            .as_bytes()
+            .first()
+            .ok_or_else(|| YamlParseError::new(1, 1, ErrorKind::ExpectedInput))?;
+        let mut parser = Self {
             current: first,
             bytes: source.as_bytes(),
-            stream,
             source,
             idx: 0,
             indent: 0,
             expected: Vec::new(),
             contexts: Vec::new(),
-        })
+            content_end: 0,
+            spans: None,
+            path: Path::default(),
+            options: ParseOptions::default(),
+            anchors: HashMap::new(),
+            aliased_nodes: 0,
+            raw_scalars: false,
+            scalar_min_indent: 0,
+        };
+        // a byte order mark may start the stream
+        if source.starts_with('\u{feff}') && !parser.bump_to(3) {
+            return parser.error(ErrorKind::ExpectedInput);
+        }
+        Ok(parser)
+    }
+
+    pub(crate) fn with_options(source: &'a str, options: &ParseOptions) -> Result<Self> {
+        let mut parser = Self::new(source).map_err(|mut err| {
+            err.msg = Some(options.message(&err.kind));
+            err
+        })?;
+        parser.options = options.clone();
+        Ok(parser)
+    }
+
+    /// Record the source span of every node while parsing
+    pub(crate) fn record_spans(&mut self) {
+        self.spans = Some(SpanTable::default());
+    }
+
+    pub(crate) fn take_spans(&mut self) -> SpanTable {
+        self.spans.take().unwrap_or_default()
+    }
+
+    /// Descend into the value of the mapping entry with the given key
+    fn enter_key(&mut self, key: &Yaml<'a>, key_span: Span) {
+        if let Some(spans) = &mut self.spans {
+            self.path.push(PathSegment::Key(key.key_string()));
+            spans.insert(
+                self.path.clone(),
+                NodeSpan {
+                    span: Span::default(),
+                    key: Some(key_span),
+                },
+            );
+        }
     }
 
-    fn start_context(&mut self, context_kind: ParseContextKind) -> Result<()> {
+    /// Descend into the sequence item at the given index
+    fn enter_index(&mut self, idx: usize) {
+        if self.spans.is_some() {
+            self.path.push(PathSegment::Index(idx));
+        }
+    }
+
+    fn leave(&mut self) {
+        if self.spans.is_some() {
+            self.path.pop();
+        }
+    }
+
+    /// Record the span of the node at the current path, from `start` up to the
+    /// end of the last consumed content
+    fn record_span(&mut self, start: usize) {
+        if let Some(spans) = &mut self.spans {
+            let span = Span::new(start, self.content_end.max(start));
+            spans.entry(self.path.clone()).or_default().span = span;
+        }
+    }
+
+    fn start_context(
+        &mut self,
+        context_kind: ParseContextKind,
+        construct: Option<Construct>,
+        start: usize,
+    ) -> Result<()> {
         let context = match self.context() {
             Some(ctx) => match context_kind {
                 ParseContextKind::Flow => ParseContext::FlowIn,
                 ParseContextKind::FlowMapping => ParseContext::FlowKey,
                 ParseContextKind::Block => match ctx {
                     ParseContext::FlowIn | ParseContext::FlowOut | ParseContext::FlowKey => {
-                        return self.parse_error_with_msg(
-                            "block collections cannot be values in flow collections",
-                        )
+                        return self.error(ErrorKind::BlockInFlow)
                     }
                     ParseContext::BlockIn | ParseContext::BlockOut | ParseContext::BlockKey => {
                         ParseContext::BlockIn
@@ -79,12 +426,12 @@ impl<'a, 'b> Parser<'a> {
                 ParseContextKind::BlockMapping => ParseContext::BlockKey,
             },
         };
-        self.contexts.push(context);
+        self.contexts.push((context, construct, start));
         Ok(())
     }
 
     fn end_context(&mut self, expect: ParseContextKind) -> Result<()> {
-        if let Some(actual) = self.contexts.pop() {
+        if let Some((actual, ..)) = self.contexts.pop() {
             let ctx_matches = match expect {
                 ParseContextKind::Flow => {
                     matches!(actual, ParseContext::FlowIn | ParseContext::FlowOut)
@@ -98,71 +445,96 @@ impl<'a, 'b> Parser<'a> {
             if ctx_matches {
                 Ok(())
             } else {
-                self.parse_error_with_msg(format!(
-                    "expected but failed to end context {:?}, instead found {:?}",
-                    expect, actual
-                ))
+                self.error(ErrorKind::Internal {
+                    detail: format!(
+                        "expected but failed to end context {expect:?}, instead found {actual:?}"
+                    ),
+                })
             }
         } else {
-            self.parse_error_with_msg(format!(
-                "expected context {:?} but no contexts remained",
-                expect
-            ))
+            self.error(ErrorKind::Internal {
+                detail: format!("expected context {expect:?} but no contexts remained"),
+            })
         }
     }
 
     fn context(&self) -> Option<ParseContext> {
-        self.contexts.last().copied()
+        self.contexts.last().map(|&(context, ..)| context)
     }
 
+    /// Move forward to the next byte. Returns `false`, moving to the end of
+    /// the input, if the input ends.
     fn bump(&mut self) -> bool {
-        match self.stream.next() {
-            Some(byte) => {
-                self.idx += 1;
-                self.current = byte;
-                true
-            }
-            None => false,
+        self.bump_to(self.idx + 1)
+    }
+
+    /// Move forward to the byte at `target`, which must not be behind the
+    /// current one. Returns `false`, moving to the end of the input, where
+    /// the current byte is 0, if the input ends first.
+    fn bump_to(&mut self, target: usize) -> bool {
+        debug_assert!(
+            target >= self.idx,
+            "moving back from {} to {target}",
+            self.idx
+        );
+        let Some(&byte) = self.bytes.get(target) else {
+            self.idx = self.bytes.len();
+            self.current = 0;
+            return false;
+        };
+        if target > self.idx {
+            self.idx = target;
+            self.current = byte;
         }
+        debug_assert_eq!(self.current, self.bytes[self.idx]);
+        true
     }
 
+    /// Move past the line break at the current position, which may be
+    /// `\r\n`. Returns `false` if the input ends.
+    fn bump_line_break(&mut self) -> bool {
+        if self.current == b'\r' && self.peek() == Some(b'\n') {
+            self.bump();
+        }
+        self.bump()
+    }
+
+    /// Move past the line break at the current position, and a line break
+    /// byte right after it. Returns `false` if the input ends.
     fn bump_newline(&mut self) -> bool {
-        match self.stream.next() {
-            Some(b'\n') | Some(b'\r') => {
-                self.idx += 1; // Account for the newline char consumed from stream
-                self.bump()
-            }
-            Some(byte) => {
-                self.idx += 1;
-                self.current = byte;
-                true
-            }
-            None => false,
+        if !self.bump() {
+            return false;
         }
+        if self.current.is_linebreak() {
+            return self.bump();
+        }
+        true
     }
 
     fn advance(&mut self) -> Result<()> {
         if self.bump() {
             Ok(())
         } else {
-            self.parse_error_with_msg("unexpected end of input")
+            self.error(ErrorKind::UnexpectedEnd)
         }
     }
 
-    fn peek(&mut self) -> Option<u8> {
-        self.stream.peek().copied()
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.idx + 1).copied()
     }
 
+    /// Whether the input has ended
     fn at_end(&self) -> bool {
-        self.idx == self.bytes.len() - 1
+        self.idx >= self.bytes.len()
     }
 
-    fn parse_mapping_maybe(&mut self, node: Yaml<'a>) -> Result<Yaml<'a>> {
+    fn parse_mapping_maybe(&mut self, node: Yaml<'a>, node_start: usize) -> Result<Yaml<'a>> {
+        let node_span = Span::new(node_start, self.content_end);
         self.chomp_whitespace();
         self.chomp_comment();
         match self.current {
             b':' if !matches!(self.expected.last(), Some(b'}') | Some(b':')) => {
-                self.parse_mapping_block(node)
+                self.parse_mapping_block(node, node_span)
             }
             _ => Ok(node),
         }
@@ -171,79 +543,182 @@ impl<'a, 'b> Parser<'a> {
     pub(crate) fn parse(&mut self) -> Result<Yaml<'a>> {
         let context = self.context();
         let peeked = self.peek();
+        let start = self.idx;
+        if self.at_end() {
+            // nothing but comments or a `---` up to the end of the input
+            self.record_span(start);
+            return Ok(Yaml::Null);
+        }
         let res = match self.current {
             b'#' => {
                 self.chomp_comment();
-                self.parse()?
+                return self.parse();
             }
             b'-' if self.check_ahead_1(|val| val == b'-')
-                && self.check_ahead_n(2, |val| val == b'-') =>
+                && self.check_ahead_n(2, |val| val == b'-')
+                && (self.idx + 3 == self.bytes.len()
+                    || self.check_ahead_n(3, |val| val.is_ws() || val.is_linebreak())) =>
             {
                 self.bump();
                 self.bump();
                 self.bump();
-                self.parse()?
+                return self.parse();
             }
             b'\n' | b'\r' => {
                 self.chomp_newlines()?;
                 self.indent = 0;
-                self.parse()?
+                return self.parse();
             }
+            b'?' if self.at_explicit_key() => self.parse_explicit_mapping()?,
             byt if byt.is_scalar_start(peeked, context) => self.parse_maybe_scalar()?,
+            b'{' if self.options.templates && peeked == Some(b'{') => self.parse_maybe_scalar()?,
             b'{' => {
                 self.expected.push(b'}');
                 let res = self.parse_mapping_flow()?;
                 if let Some(b'}') = self.expected.last() {
                     self.pop_if_match(b'}')?;
                 }
-                self.parse_mapping_maybe(res)?
+                self.parse_mapping_maybe(res, start)?
             }
             b'[' => {
                 let node = self.parse_sequence_flow()?;
-                self.parse_mapping_maybe(node)?
+                self.parse_mapping_maybe(node, start)?
             }
             b'-' => match self.peek() {
                 Some(byt) if byt.is_linebreak() || byt.is_ws() => self.parse_sequence_block()?,
-                byt => unreachable!("unexpected {:?}", byt.map(char::from)),
+                None => return self.error(ErrorKind::UnexpectedEnd),
+                // a `-` before a flow indicator, which is no plain scalar
+                Some(_) => return self.error(ErrorKind::InvalidNode),
             },
 
             b'}' | b']' => {
-                return self.parse_error_with_msg(format!(
-                    r#"unexpected symbol '{}'"#,
-                    char::from(self.current)
-                ))
+                return self.error(ErrorKind::UnexpectedSymbol {
+                    symbol: char::from(self.current),
+                })
             }
             b if b.is_ws() => {
-                self.chomp_indent();
+                self.chomp_indent()?;
                 if self.at_end() {
-                    return self.parse_error_with_msg("unexpected end of input");
+                    return self.error(ErrorKind::UnexpectedEnd);
                 }
-                self.parse()?
+                return self.parse();
             }
             b'!' => self.parse_tagged_value()?,
+            b'&' if self.options.reject_unsupported => {
+                return self.unsupported(MiniYamlError::AnchorsDisallowed)
+            }
+            b'*' if self.options.reject_unsupported => {
+                return self.unsupported(MiniYamlError::AliasesDisallowed)
+            }
+            b'%' if self.options.reject_unsupported => {
+                return self.unsupported(MiniYamlError::DirectivesDisallowed)
+            }
+            b'%' if self.at_directive() => {
+                self.skip_directives()?;
+                return self.parse();
+            }
+            b'&' => self.parse_anchored_value()?,
+            b'*' => self.parse_alias()?,
             b'|' => self.parse_literal_block_scalar()?,
             b'>' => self.parse_folded_block_scalar()?,
-            _ => return self.parse_error_with_msg("failed to parse at top level"),
+            _ => return self.error(ErrorKind::InvalidNode),
         };
+        self.record_span(start);
         Ok(res)
     }
+    /// Whether a `%` starts a directive: at the start of a line before the
+    /// first document, with nothing but comments before it
+    fn at_directive(&self) -> bool {
+        let before = self.source[..self.idx].trim_start_matches('\u{feff}');
+        self.contexts.is_empty()
+            && (before.is_empty() || before.ends_with('\n'))
+            && before.lines().all(stream::is_trivia)
+    }
+
+    /// Skip the directives (`%YAML 1.2`, `%TAG ...`) before the document,
+    /// which must then start with `---`. The handles declared by `%TAG` are
+    /// not expanded.
+    fn skip_directives(&mut self) -> Result<()> {
+        let mut end = self.idx;
+        for line in self.source[self.idx..].split_inclusive('\n') {
+            if !line.starts_with('%') && !stream::is_trivia(line) {
+                break;
+            }
+            end += line.len();
+        }
+        self.bump_to(end);
+        if !stream::is_marker(&self.source[end..], "---") {
+            return self.error(ErrorKind::ExpectedDocumentStart);
+        }
+        Ok(())
+    }
+
+    /// Parse a node inside a block collection indented by `parent_indent`,
+    /// which the lines of a multi-line scalar must be indented past
+    fn parse_block_value(&mut self, parent_indent: usize) -> Result<Yaml<'a>> {
+        let outer = std::mem::replace(&mut self.scalar_min_indent, parent_indent + 1);
+        let node = self.parse();
+        self.scalar_min_indent = outer;
+        node
+    }
+
+    /// Parse the value of a block mapping entry whose key is at `indent`,
+    /// from its `:`. An empty value is null.
+    fn parse_entry_value(&mut self, indent: usize) -> Result<Yaml<'a>> {
+        // an empty value's span is empty, right after the `:`
+        let after_colon = self.idx + 1;
+        if !self.bump() || {
+            self.chomp_whitespace();
+            self.at_empty_value(indent)
+        } {
+            self.record_span(after_colon);
+            return Ok(Yaml::Null);
+        }
+        self.parse_block_value(indent)
+    }
+
+    /// Whether nothing follows the `:` of a block mapping entry whose key is
+    /// at `indent`: the rest of the line is blank, and the next line with
+    /// content is indented no further than the key, unless it is an item
+    /// of a block sequence, which may be indented like the key.
+    fn at_empty_value(&self, indent: usize) -> bool {
+        let rest = &self.source[self.idx..];
+        let line_end = rest.find('\n').map_or(rest.len(), |idx| idx + 1);
+        let first = rest[..line_end].trim();
+        if !first.is_empty() && !first.starts_with('#') {
+            return false;
+        }
+        for line in rest[line_end..].split_inclusive('\n') {
+            let content = line.trim_start_matches([' ', '\t']);
+            let trimmed = content.trim_end();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let line_indent = line.len() - content.len();
+            let item = trimmed == "-" || trimmed.starts_with("- ") || trimmed.starts_with("-\t");
+            return line_indent < indent || (line_indent == indent && !item);
+        }
+        true
+    }
+
     pub(crate) fn parse_maybe_scalar(&mut self) -> Result<Yaml<'a>> {
+        let start = self.idx;
         match self.context() {
             None => {
-                self.start_context(ParseContextKind::BlockMapping)?;
+                self.start_context(ParseContextKind::BlockMapping, None, start)?;
                 let node = self.parse_scalar()?;
                 self.end_context(ParseContextKind::BlockMapping)?;
-                self.parse_mapping_maybe(node)
+                self.parse_mapping_maybe(node, start)
             }
             Some(ctx) => match ctx {
                 ParseContext::FlowIn | ParseContext::FlowOut | ParseContext::FlowKey => {
                     self.parse_scalar()
                 }
                 _ => {
-                    self.start_context(ParseContextKind::BlockMapping)?;
+                    self.start_context(ParseContextKind::BlockMapping, None, start)?;
                     let node = self.parse_scalar()?;
                     self.end_context(ParseContextKind::BlockMapping)?;
-                    self.parse_mapping_maybe(node)
+                    self.parse_mapping_maybe(node, start)
                 }
             },
         }
@@ -252,79 +727,193 @@ impl<'a, 'b> Parser<'a> {
     pub(crate) fn parse_scalar(&mut self) -> Result<Yaml<'a>> {
         let context = self.context();
         match self.current {
-            // Double-quoted string: strip the quotes
+            // Double-quoted string: strip the quotes and decode escapes
             b'\"' => {
                 self.advance()?; // consume opening quote
                 let scal_start = self.idx; // start after the quote
-                let mut accept_dq = |tok: u8, _: Option<u8>| !matches!(tok, b'\"');
-                let _ = self.take_while(&mut accept_dq).map_err(|_| {
-                    self.make_parse_error_with_msg("unexpected end of input; expected '\"'")
-                })?;
+                let mut escaped = false;
+                let mut accept_dq = |tok: u8, _: Option<u8>| {
+                    let accept = escaped || tok != b'\"';
+                    escaped = !escaped && tok == b'\\';
+                    accept
+                };
+                let _ = self
+                    .take_while(&mut accept_dq)
+                    .map_err(|_| self.make_error(ErrorKind::UnclosedQuote { quote: '"' }))?;
                 let scal_end = self.idx; // end before the closing quote
+                self.content_end = scal_end + 1;
                 self.bump(); // consume closing quote
                 let content = self.slice_range((scal_start, scal_end));
-                Ok(Yaml::Scalar(content))
+                if !content.contains(['\\', '\r', '\n']) {
+                    return Ok(Yaml::Scalar(content));
+                }
+                match unescape(content) {
+                    Ok(unescaped) => Ok(Yaml::String(unescaped)),
+                    Err((offset, kind)) => self.error_at(scal_start + offset, kind),
+                }
             }
-            // Single-quoted string: strip the quotes
+            // Single-quoted string: strip the quotes and undouble quotes
             b'\'' => {
                 self.advance()?; // consume opening quote
                 let scal_start = self.idx; // start after the quote
-                let mut accept_sq = |tok: u8, _: Option<u8>| !matches!(tok, b'\'');
-                self.take_while(&mut accept_sq).map_err(|_| {
-                    self.make_parse_error_with_msg("unexpected end of input; expected '\''")
-                })?;
+                let mut doubled = false;
+                let mut accept_sq = |tok: u8, nxt: Option<u8>| {
+                    if doubled {
+                        doubled = false;
+                        return true;
+                    }
+                    doubled = tok == b'\'' && nxt == Some(b'\'');
+                    doubled || tok != b'\''
+                };
+                self.take_while(&mut accept_sq)
+                    .map_err(|_| self.make_error(ErrorKind::UnclosedQuote { quote: '\'' }))?;
                 let scal_end = self.idx; // end before the closing quote
+                self.content_end = scal_end + 1;
                 self.bump(); // consume closing quote
                 let content = self.slice_range((scal_start, scal_end));
-                Ok(Yaml::Scalar(content))
-            }
-            _ => {
-                // Track bracket/paren depth to allow colons inside [] and ()
-                let mut bracket_depth: i32 = 0;
-                let mut paren_depth: i32 = 0;
-
-                let mut accept = |tok: u8, nxt: Option<u8>| {
-                    // Update bracket/paren depth
-                    match tok {
-                        b'[' => bracket_depth += 1,
-                        b']' => bracket_depth = (bracket_depth - 1).max(0),
-                        b'(' => paren_depth += 1,
-                        b')' => paren_depth = (paren_depth - 1).max(0),
-                        _ => {}
-                    }
+                if content.contains(['\'', '\r', '\n']) {
+                    Ok(Yaml::String(unquote_single(content)))
+                } else {
+                    Ok(Yaml::Scalar(content))
+                }
+            }
+            _ => Ok(self.parse_plain_scalar(context)),
+        }
+    }
 
-                    // When inside brackets or parens, allow colons even if followed by whitespace
-                    if bracket_depth > 0 || paren_depth > 0 {
-                        // Inside brackets/parens: allow everything except linebreak
-                        // But still stop at # for comments
-                        !tok.is_linebreak() && tok != b'#'
-                    } else {
-                        // Normal is_ns_plain behavior
-                        tok.is_ns_plain(nxt, context)
-                    }
-                };
+    /// Parse a plain scalar, folding the lines it continues onto
+    fn parse_plain_scalar(&mut self, context: Option<ParseContext>) -> Yaml<'a> {
+        // Track bracket/paren depth to allow colons inside [] and ()
+        let mut bracket_depth: i32 = 0;
+        let mut paren_depth: i32 = 0;
+        // and the depth of braces inside a template placeholder
+        let templates = self.options.templates;
+        let mut placeholder = Placeholder::default();
+
+        let mut accept = |tok: u8, nxt: Option<u8>| {
+            if templates && placeholder.step(tok, nxt) {
+                return !tok.is_linebreak();
+            }
+            // Update bracket/paren depth
+            match tok {
+                b'[' => bracket_depth += 1,
+                b']' => bracket_depth = (bracket_depth - 1).max(0),
+                b'(' => paren_depth += 1,
+                b')' => paren_depth = (paren_depth - 1).max(0),
+                _ => {}
+            }
 
-                let (start, mut end) = self.take_while(&mut accept).unwrap_or_else(|val| val);
-                loop {
-                    self.chomp_whitespace();
-                    self.chomp_comment();
-                    let (s, e) = self.take_while(&mut accept).unwrap_or_else(|val| val);
-                    if s == e {
-                        break;
-                    } else {
-                        end = e;
-                    }
-                    if self.at_end() {
-                        break;
-                    }
-                }
-                let entire_literal = self.slice_range((start, end));
-                // Automatically infer type for unquoted scalars
-                Ok(Self::infer_scalar_type(entire_literal))
+            // When inside brackets or parens, allow colons even if followed by whitespace
+            if bracket_depth > 0 || paren_depth > 0 {
+                // Inside brackets/parens: allow everything except linebreak
+                // But still stop at # for comments
+                !tok.is_linebreak() && tok != b'#'
+            } else {
+                // Normal is_ns_plain behavior
+                tok.is_ns_plain(nxt, context)
+            }
+        };
+
+        let (start, end, comment) = self.take_plain_line(&mut accept);
+        let entire_literal = self.slice_range((start, end));
+        self.content_end = start + entire_literal.len();
+        let in_flow = matches!(
+            context,
+            Some(ParseContext::FlowIn | ParseContext::FlowOut | ParseContext::FlowKey)
+        );
+        if comment || in_flow || !self.current.is_linebreak() {
+            // Automatically infer type for unquoted scalars
+            return self.infer_scalar_type(entire_literal);
+        }
+        // Fold the lines continuing the scalar into one string
+        let mut folded: Option<String> = None;
+        while let Some((line_start, blank_lines)) = self.plain_continuation() {
+            let text = folded.get_or_insert_with(|| entire_literal.to_string());
+            if blank_lines == 0 {
+                text.push(' ');
+            } else {
+                text.push_str(&"\n".repeat(blank_lines));
             }
+            self.bump_to(line_start);
+            let (line_start, line_end, comment) = self.take_plain_line(&mut accept);
+            text.push_str(self.slice_range((line_start, line_end)));
+            self.content_end = line_end;
+            if comment || !self.current.is_linebreak() {
+                break;
+            }
+        }
+        match folded {
+            Some(text) => Yaml::String(text),
+            None => self.infer_scalar_type(entire_literal),
         }
     }
 
+    /// Take the rest of a line of a plain scalar, up to a comment or a
+    /// character `accept` rejects. Returns the range of the text, without
+    /// trailing whitespace, and whether a comment ended it.
+    fn take_plain_line<F>(&mut self, accept: &mut F) -> (usize, usize, bool)
+    where
+        F: FnMut(u8, Option<u8>) -> bool,
+    {
+        let (start, mut end) = self.take_while(accept).unwrap_or_else(|val| val);
+        let mut comment = false;
+        loop {
+            self.chomp_whitespace();
+            comment |= self.current == b'#';
+            self.chomp_comment();
+            let (s, e) = self.take_while(accept).unwrap_or_else(|val| val);
+            if s == e {
+                break;
+            }
+            end = e;
+            if self.at_end() {
+                break;
+            }
+        }
+        (start, end, comment)
+    }
+
+    /// If the plain scalar ending at the current line break continues on a
+    /// later line, the offset that line's text starts at and the number of
+    /// blank lines before it. A continuation line is indented by at least
+    /// `scalar_min_indent` and is not a comment, a document marker, a mapping
+    /// entry or a block sequence item (which the emitter writes below a
+    /// scalar for a sequence used as a key).
+    fn plain_continuation(&self) -> Option<(usize, usize)> {
+        let rest = &self.source[self.idx..];
+        let mut offset = self.idx + rest.find('\n')? + 1;
+        let mut blank_lines = 0;
+        for line in self.source[offset..].split_inclusive('\n') {
+            let content = line.trim_start_matches(' ');
+            let text = content.trim();
+            if text.is_empty() {
+                blank_lines += 1;
+                offset += line.len();
+                continue;
+            }
+            let indent = line.len() - content.len();
+            let body = text.split(" #").next().unwrap_or_default();
+            let marker = indent == 0
+                && (text.starts_with("---") || text.starts_with("..."))
+                && text[3..].chars().next().is_none_or(char::is_whitespace);
+            let entry = body.contains(": ") || body.contains(":\t") || body.ends_with(':');
+            let indicator = matches!(text.as_bytes(), [b'-' | b'?', next, ..] if next.is_ws())
+                || text == "-"
+                || text == "?";
+            if indent < self.scalar_min_indent
+                || text.starts_with('#')
+                || marker
+                || entry
+                || indicator
+            {
+                return None;
+            }
+            let text_start = line.len() - line.trim_start().len();
+            return Some((offset + text_start, blank_lines));
+        }
+        None
+    }
+
     /// Parse a tag name after the `!` character.
     /// Returns the tag name as a string slice.
     /// Supports generic type syntax like `!seq<string>` or `!map<string,int>`.
@@ -332,14 +921,21 @@ impl<'a, 'b> Parser<'a> {
         // Consume the '!'
         self.advance()?;
 
-        // Capture tag name start
+        if self.current == b'<' {
+            return self.parse_verbatim_tag();
+        }
+
+        // Capture tag name start; secondary tags (`!!name`) keep their
+        // second '!'
         let tag_start = self.idx;
+        if self.current == b'!' {
+            self.bump();
+        }
+        let name_start = self.idx;
         let mut angle_depth: i32 = 0;
 
         loop {
             match self.current {
-                // Standard tag characters always allowed
-                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => {}
                 // Opening angle bracket - start generic type params
                 b'<' => {
                     angle_depth += 1;
@@ -347,12 +943,11 @@ impl<'a, 'b> Parser<'a> {
                 // Closing angle bracket - must have matching open
                 b'>' => {
                     if angle_depth == 0 {
-                        return self.parse_error_with_msg("unmatched '>' in tag name");
+                        return self.error(ErrorKind::UnmatchedTagAngle);
                     }
                     angle_depth -= 1;
                 }
-                // Comma and pipe only allowed inside angle brackets
-                b',' | b'|' if angle_depth > 0 => {}
+                byte if is_tag_char(byte, angle_depth > 0) => {}
                 // Any other character ends the tag name
                 _ => break,
             }
@@ -363,14 +958,14 @@ impl<'a, 'b> Parser<'a> {
 
         // Check for unclosed angle brackets
         if angle_depth > 0 {
-            return self.parse_error_with_msg("unclosed '<' in tag name");
+            return self.error(ErrorKind::UnclosedTagAngle);
         }
 
         let tag_end = self.idx;
         let tag_name = self.slice_range((tag_start, tag_end));
 
-        if tag_name.is_empty() {
-            return self.parse_error_with_msg("expected tag name after '!'");
+        if tag_end == name_start {
+            return self.error(ErrorKind::ExpectedTagName);
         }
 
         // Consume whitespace after tag
@@ -379,69 +974,222 @@ impl<'a, 'b> Parser<'a> {
         Ok(tag_name)
     }
 
-    /// Parse a tagged value (!tagname value).
-    /// All tags are wrapped in a mapping with __type field.
+    /// Parse a verbatim tag (`!<tag:example.com,2000:app/foo>`) after its
+    /// `!`, returning the URI between the brackets. A local tag written
+    /// verbatim (`!<!foo>`) is the same as `!foo`.
+    fn parse_verbatim_tag(&mut self) -> Result<&'a str> {
+        let start = self.idx;
+        let Some(len) = self.bytes[start + 1..]
+            .iter()
+            .position(|&byte| byte == b'>' || byte.is_ws() || byte.is_linebreak())
+        else {
+            self.bump_to(self.bytes.len());
+            return self.error(ErrorKind::UnclosedVerbatimTag);
+        };
+        let end = start + 1 + len;
+        self.bump_to(end);
+        if self.current != b'>' {
+            return self.error(ErrorKind::UnclosedVerbatimTag);
+        }
+        let uri = self.slice_range((start + 1, end));
+        let uri = uri.strip_prefix('!').unwrap_or(uri);
+        if uri.is_empty() {
+            return self.error(ErrorKind::ExpectedVerbatimTag);
+        }
+        self.bump();
+        self.chomp_whitespace();
+        Ok(uri)
+    }
+
+    /// Parse a tagged value (!tagname value), which keeps its tag unless it
+    /// is a standard one
     fn parse_tagged_value(&mut self) -> Result<Yaml<'a>> {
+        let start = self.idx;
         let tag_name = self.parse_tag()?;
 
-        // Parse the value following the tag
-        let value = self.parse()?;
-
-        // Wrap the result based on value type
-        let result = match value {
-            Yaml::Mapping(mut entries) => {
-                // Insert __type at the beginning
-                entries.insert(
-                    0,
-                    Entry::new(Yaml::Scalar("__type"), Yaml::Scalar(tag_name)),
-                );
-                Yaml::Mapping(entries)
+        // `!!name` is shorthand for the verbatim `!<tag:yaml.org,2002:name>`
+        let standard = tag_name
+            .strip_prefix('!')
+            .or_else(|| tag_name.strip_prefix(YAML_TAG_PREFIX));
+        if let Some(tag) = standard {
+            if STANDARD_TAGS.contains(&tag) {
+                return self.parse_standard_tagged_value(start, tag);
             }
-            other => {
-                // Wrap scalar or sequence in a mapping with __type and __value
-                let entries = vec![
-                    Entry::new(Yaml::Scalar("__type"), Yaml::Scalar(tag_name)),
-                    Entry::new(Yaml::Scalar("__value"), other),
-                ];
-                Yaml::Mapping(entries)
+        }
+        if self.options.compat {
+            let builtin = tag_name.strip_prefix("!python/").and_then(|name| {
+                PYTHON_TAGS
+                    .iter()
+                    .find_map(|&(python, tag)| (python == name).then_some(tag))
+            });
+            if let Some(tag) = builtin {
+                return self.parse_standard_tagged_value(start, tag);
             }
+        }
+
+        // Parse the value following the tag
+        let value = match self.parse()? {
+            // `!int` reads its value as an integer whatever the resolver
+            Yaml::Scalar(text) if tag_name == "int" => {
+                parse_int(text).map_or(Yaml::Scalar(text), Yaml::Int)
+            }
+            value => value,
         };
 
-        Ok(result)
+        Ok(Yaml::tagged(tag_name, value))
     }
 
-    /// Parse a boolean string value.
-    /// Accepts: true/false, yes/no, on/off (case-insensitive)
-    fn parse_bool(s: &str) -> Option<bool> {
-        match s.to_lowercase().as_str() {
-            "true" | "yes" | "on" => Some(true),
-            "false" | "no" | "off" => Some(false),
+    /// Parse the value of a standard tag (`!!int`, `!!map`, ...), which
+    /// decides the type of the value instead of being kept as a tag
+    fn parse_standard_tagged_value(&mut self, start: usize, tag: &str) -> Result<Yaml<'a>> {
+        let collection = matches!(tag, "map" | "seq" | "set" | "omap" | "pairs");
+        let raw = std::mem::replace(&mut self.raw_scalars, !collection);
+        let value = self.parse();
+        self.raw_scalars = raw;
+        let value = match (tag, value?) {
+            ("map", value @ Yaml::Mapping(_))
+            | ("seq", value @ Yaml::Sequence(_))
+            | ("str", value @ (Yaml::Scalar(_) | Yaml::String(_))) => Some(value),
+            // A set is a mapping of keys to nulls, read as the list of its keys
+            ("set", Yaml::Mapping(entries)) => entries
+                .into_iter()
+                .map(|entry| matches!(entry.value, Yaml::Null).then_some(entry.key))
+                .collect::<Option<Vec<_>>>()
+                .map(Yaml::Sequence),
+            // Ordered maps and pairs are sequences of single-entry mappings.
+            // Mappings keep their order already, so an ordered map becomes
+            // one, while pairs may repeat keys and stay a sequence.
+            ("omap", Yaml::Sequence(items)) => items
+                .into_iter()
+                .map(single_entry)
+                .collect::<Option<Vec<_>>>()
+                .filter(|entries| {
+                    // the keys of an ordered map are unique
+                    entries
+                        .iter()
+                        .enumerate()
+                        .all(|(i, entry)| entries[..i].iter().all(|prev| prev.key != entry.key))
+                })
+                .map(Yaml::Mapping),
+            ("pairs", Yaml::Sequence(items)) => items
+                .iter()
+                .all(|item| matches!(item, Yaml::Mapping(entries) if entries.len() == 1))
+                .then_some(Yaml::Sequence(items)),
+            (tag, Yaml::Scalar(text)) => resolve_standard_tag(tag, text),
+            (tag, Yaml::String(text)) => resolve_standard_tag(tag, &text),
             _ => None,
+        };
+        value.map_or_else(
+            || {
+                self.error_at(
+                    start,
+                    match tag {
+                        // `!!binary` values are base64, and stand for the bytes they encode
+                        "binary" => ErrorKind::InvalidBase64,
+                        tag => ErrorKind::InvalidTaggedValue {
+                            tag: snippet(tag, Some(80), false),
+                        },
+                    },
+                )
+            },
+            Ok,
+        )
+    }
+
+    /// Parse the name of an anchor or alias, after the `&` or `*`. Names end
+    /// at whitespace, flow indicators, or a `:` which separates a key from
+    /// its value.
+    fn parse_anchor_name(&mut self) -> Result<&'a str> {
+        self.advance()?;
+        let start = self.idx;
+        let mut accept = |tok: u8, nxt: Option<u8>| match tok {
+            b',' | b'[' | b']' | b'{' | b'}' => false,
+            b':' => nxt.is_some_and(|nxt| !nxt.is_ws() && !nxt.is_linebreak()),
+            tok => !tok.is_ws() && !tok.is_linebreak(),
+        };
+        let (_, end) = self.take_while(&mut accept).unwrap_or_else(|val| val);
+        if start == end {
+            return self.error(ErrorKind::ExpectedAnchorName);
         }
+        self.content_end = end;
+        Ok(self.slice_range((start, end)))
     }
 
-    /// Infer the type of an unquoted scalar value.
-    /// Returns Int, Float, Bool, or Scalar based on the content.
-    fn infer_scalar_type(s: &str) -> Yaml<'_> {
-        // Check for boolean values first
-        if let Some(b) = Self::parse_bool(s) {
-            return Yaml::Bool(b);
+    /// Parse an anchored value (`&name value`), remembering the value so
+    /// that later aliases can refer to it.
+    fn parse_anchored_value(&mut self) -> Result<Yaml<'a>> {
+        let name = self.parse_anchor_name()?;
+        self.chomp_whitespace();
+        let in_flow = matches!(
+            self.context(),
+            Some(ParseContext::FlowIn | ParseContext::FlowOut | ParseContext::FlowKey)
+        );
+        // In block context, an anchor before a scalar on the same line
+        // belongs to that scalar, even if it turns out to be a mapping key
+        if !in_flow && !self.at_end() && self.current.is_scalar_start(self.peek(), self.context()) {
+            let start = self.idx;
+            self.start_context(ParseContextKind::BlockMapping, None, start)?;
+            let node = self.parse_scalar()?;
+            self.end_context(ParseContextKind::BlockMapping)?;
+            self.anchors.insert(name, node.clone());
+            return self.parse_mapping_maybe(node, start);
         }
+        let node = self.parse()?;
+        self.anchors.insert(name, node.clone());
+        Ok(node)
+    }
 
-        // Check for integer (digits with optional leading minus)
-        if let Ok(i) = s.parse::<i64>() {
-            return Yaml::Int(i);
+    /// Parse an alias (`*name`), which stands for a copy of the value most
+    /// recently anchored with that name.
+    fn parse_alias(&mut self) -> Result<Yaml<'a>> {
+        let start = self.idx;
+        let name = self.parse_anchor_name()?;
+        let Some(node) = self.anchors.get(name) else {
+            return self.error_at(
+                start,
+                ErrorKind::UnknownAlias {
+                    name: snippet(name, Some(80), false),
+                },
+            );
+        };
+        self.aliased_nodes += node_count(node);
+        if self.aliased_nodes > MAX_ALIASED_NODES {
+            return self.error_at(
+                start,
+                ErrorKind::AliasLimit {
+                    limit: MAX_ALIASED_NODES,
+                },
+            );
+        }
+        let node = node.clone();
+        match self.context() {
+            Some(ParseContext::FlowIn | ParseContext::FlowOut | ParseContext::FlowKey) => Ok(node),
+            _ => self.parse_mapping_maybe(node, start),
         }
+    }
 
-        // Check for float (contains decimal point or scientific notation)
-        if s.contains('.') || s.contains('e') || s.contains('E') {
-            if let Ok(f) = s.parse::<f64>() {
-                return Yaml::Float(f);
+    /// Infer the type of an unquoted scalar value with the configured
+    /// resolver.
+    fn infer_scalar_type(&self, s: &'a str) -> Yaml<'a> {
+        if self.raw_scalars {
+            return Yaml::Scalar(s);
+        }
+        let resolver: &dyn ScalarResolver = match &self.options.resolver {
+            Some(resolver) => resolver.as_ref(),
+            None => &Yaml11Schema,
+        };
+        let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+        if self.options.numeric_underscores
+            && s.contains('_')
+            && unsigned.starts_with(|c: char| c.is_ascii_digit())
+        {
+            match resolver.resolve(&s.replace('_', "")) {
+                Yaml::Int(i) => return Yaml::Int(i),
+                Yaml::Float(f) => return Yaml::Float(f),
+                _ => {}
             }
         }
-
-        // Default to string
-        Yaml::Scalar(s)
+        resolver.resolve(s)
     }
 
     /// Parse a literal block scalar (|).
@@ -491,99 +1239,77 @@ impl<'a, 'b> Parser<'a> {
 
         // Must have a newline after the indicator
         if !self.current.is_linebreak() {
-            return self.parse_error_with_msg("expected newline after block scalar indicator");
+            return self.error(ErrorKind::ExpectedBlockScalarNewline);
         }
 
         // Skip the newline
-        if !self.bump() {
+        if !self.bump_line_break() {
             // End of input after indicator - return empty string
             return Ok(Yaml::String(String::new()));
         }
 
-        let mut result = String::new();
+        let mut result = BlockText::default();
         let mut trailing_newlines = 0usize;
-        let mut content_indent: Option<usize> = explicit_indent;
+        // the indentation indicator counts from the parent's indentation
+        let mut content_indent: Option<usize> =
+            explicit_indent.map(|extra| self.scalar_min_indent.max(1) + extra - 1);
+        // whether the last content line was more indented, and so not folded
+        let mut after_more_indented: Option<bool> = None;
 
         loop {
-            // Skip empty lines (but track them for later)
-            if self.current.is_linebreak() {
-                trailing_newlines += 1;
-                if !self.bump() {
-                    break;
-                }
-                continue;
-            }
-
-            // Count leading whitespace for this line
-            let mut line_indent = 0;
-            while self.current.is_ws() {
-                line_indent += 1;
-                if !self.bump() {
+            // Look at the line ahead without consuming anything, so that the
+            // first line past the scalar is left for the caller as it is
+            let line_start = self.idx;
+            let rest = &self.bytes[line_start..];
+            let spaces = rest.iter().take_while(|&&byte| byte == b' ').count();
+            let line_len = rest
+                .iter()
+                .position(|byte| byte.is_linebreak())
+                .unwrap_or(rest.len());
+
+            if spaces >= line_len {
+                // a blank line, which belongs to the scalar if more content
+                // follows or chomping keeps it
+                if line_len == rest.len() {
+                    self.bump_to(self.bytes.len());
                     break;
                 }
-            }
-
-            // Check if this is an empty line (only whitespace followed by newline)
-            if self.current.is_linebreak() {
                 trailing_newlines += 1;
-                if !self.bump() {
+                self.bump_to(line_start + line_len);
+                if !self.bump_line_break() {
                     break;
                 }
                 continue;
             }
 
-            // First content line determines the indentation
-            if content_indent.is_none() {
-                content_indent = Some(line_indent);
-            }
-
-            let content_indent = content_indent.unwrap();
-
-            // Check if we've dedented (end of block)
-            if line_indent < content_indent {
-                // We need to "unread" the content we just read
-                // Since we can't, we'll adjust the indent for the caller
-                self.indent = line_indent;
+            // The first content line determines the indentation, which must
+            // be deeper than the parent's
+            let indent = *content_indent.get_or_insert(spaces);
+            if spaces < indent || indent < self.scalar_min_indent {
+                // the scalar has ended; hand back the line from its start
+                self.indent = 0;
                 break;
             }
 
-            // Add any accumulated blank lines
-            for _ in 0..trailing_newlines {
-                result.push('\n');
-            }
-            trailing_newlines = 0;
-
-            // Add newline before content (except for first line)
-            if !result.is_empty() {
-                if fold {
-                    result.push(' ');
-                } else {
-                    result.push('\n');
-                }
-            }
-
-            // Add any extra indentation beyond content_indent
-            for _ in content_indent..line_indent {
-                result.push(' ');
-            }
-
-            // Collect the rest of the line by slicing the original UTF-8 source
-            let line_start = self.idx;
-            while !self.current.is_linebreak() {
-                if !self.bump() {
-                    // End of input
-                    break;
+            let text = self.slice_range((line_start + indent, line_start + line_len));
+            let more_indented = text.starts_with([' ', '\t']);
+            match after_more_indented {
+                None => result.push_repeated(NEWLINES, trailing_newlines),
+                Some(false) if fold && !more_indented => {
+                    if trailing_newlines == 0 {
+                        result.push(" ");
+                    } else {
+                        result.push_repeated(NEWLINES, trailing_newlines);
+                    }
                 }
+                Some(_) => result.push_repeated(NEWLINES, trailing_newlines + 1),
             }
-            let line_end = self.idx;
-            result.push_str(self.slice_range((line_start, line_end)));
+            result.push(text);
+            trailing_newlines = 0;
+            after_more_indented = Some(more_indented);
+            self.content_end = line_start + line_len;
 
-            // Move past the newline if present
-            if self.current.is_linebreak() {
-                if !self.bump() {
-                    break;
-                }
-            } else {
+            if !self.bump_to(line_start + line_len) || !self.bump_line_break() {
                 // End of input
                 break;
             }
@@ -597,100 +1323,138 @@ impl<'a, 'b> Parser<'a> {
             0 => {
                 // Clip: single trailing newline
                 if !result.is_empty() {
-                    result.push('\n');
+                    result.push("\n");
                 }
             }
             1 => {
                 // Keep: preserve all trailing newlines
-                result.push('\n');
-                for _ in 0..trailing_newlines {
-                    result.push('\n');
-                }
+                result.push_repeated(NEWLINES, trailing_newlines + 1);
             }
             _ => {}
         }
 
-        Ok(Yaml::String(result))
+        Ok(Yaml::String(result.into_string()))
     }
 
     fn lookup_line_col(&self) -> (usize, usize) {
-        let err_off: usize = self.idx + 1;
-        let mut off = 0;
-        let mut line_len = 0;
-        let mut chars = self.source.chars().map(|c| (c, c.len_utf8()));
-        let mut line_lens = Vec::new();
-        while let Some((chr, len)) = chars.next() {
-            match chr {
-                '\r' => {
-                    if let Some(('\n', nxtlen)) = chars.next() {
-                        line_lens.push(line_len + nxtlen + len);
-                        line_len = 0;
-                        continue;
-                    }
-                }
-                '\n' => {
-                    line_lens.push(line_len + len);
-                    line_len = 0;
-                    continue;
-                }
-                _ => line_len += len,
-            }
+        if self.at_end() {
+            // the input ended: report the end of its last line with content
+            let end = self.source.trim_end_matches(['\n', '\r']).len();
+            return self.lookup_line_col_at(end);
         }
-        let mut line_num = 0;
-        for ((line_no, _), len) in self.source.lines().enumerate().zip(line_lens) {
-            if err_off >= off && err_off < off + len {
-                return (line_no + 1, err_off - off + 1);
-            }
-            line_num = line_no;
-            off += len;
-        }
-        if err_off >= off {
-            return (line_num + 1, err_off - off + 1);
+        self.lookup_line_col_at(self.idx)
+    }
+
+    /// The 1-based line and column (in characters) of the byte at `idx`
+    fn lookup_line_col_at(&self, idx: usize) -> (usize, usize) {
+        let mut idx = idx.min(self.source.len());
+        while !self.source.is_char_boundary(idx) {
+            idx -= 1;
         }
-        eprintln!("Couldn't find error location, please report this bug");
-        (0, 0)
+        line_col(self.source, idx)
     }
 
     #[allow(unused)]
     fn parse_error<T>(&self) -> Result<T> {
-        let (line, col) = self.lookup_line_col();
-        Err(YamlParseError {
-            line,
-            col,
-            msg: Some(format!(
-                r#"unexpectedly found "{}" while parsing"#,
-                self.current
-            )),
-            source: None,
-        })
+        self.error(ErrorKind::UnexpectedByte { byte: self.current })
     }
 
-    fn make_parse_error_with_msg<S: Into<String>>(&self, msg: S) -> YamlParseError {
-        let (line, col) = self.lookup_line_col();
+    /// An error of the given kind at `line` and `col`, with its message
+    /// from the options
+    fn error_value(&self, (line, col): (usize, usize), kind: ErrorKind) -> YamlParseError {
         YamlParseError {
             line,
             col,
-            msg: Some(msg.into()),
+            msg: Some(self.options.message(&kind)),
+            kind,
             source: None,
+            contexts: self
+                .contexts
+                .iter()
+                .enumerate()
+                // a flow mapping's key or value has a context of its own
+                .filter(|&(idx, &(_, construct, start))| {
+                    !self.contexts[idx + 1..]
+                        .first()
+                        .is_some_and(|&(_, inner, inner_start)| {
+                            (inner, inner_start) == (construct, start)
+                        })
+                })
+                .filter_map(|(_, &(context, construct, start))| {
+                    let (line, col) = line_col(self.source, start);
+                    Some(ContextFrame {
+                        context,
+                        construct: construct?,
+                        line,
+                        col,
+                    })
+                })
+                .collect(),
         }
     }
 
-    fn parse_error_with_msg<T, S: Into<String>>(&self, msg: S) -> Result<T> {
-        Err(self.make_parse_error_with_msg(msg))
+    fn make_error(&self, kind: ErrorKind) -> YamlParseError {
+        self.error_value(self.lookup_line_col(), kind)
+    }
+
+    fn error<T>(&self, kind: ErrorKind) -> Result<T> {
+        Err(self.make_error(kind))
+    }
+
+    /// Error at an earlier position than the current one
+    fn error_at<T>(&self, idx: usize, kind: ErrorKind) -> Result<T> {
+        Err(self.error_value(self.lookup_line_col_at(idx), kind))
+    }
+
+    /// Error for a construct outside the supported subset, at the current position
+    fn unsupported<T>(&self, cause: MiniYamlError) -> Result<T> {
+        let kind = match cause {
+            MiniYamlError::AliasesDisallowed => ErrorKind::AliasesDisallowed,
+            MiniYamlError::AnchorsDisallowed => ErrorKind::AnchorsDisallowed,
+            MiniYamlError::DirectivesDisallowed => ErrorKind::DirectivesDisallowed,
+        };
+        let mut err = self.make_error(kind);
+        err.source = Some(cause);
+        Err(err)
+    }
+
+    /// Error at the first thing other than whitespace and comments after
+    /// the parsed document, including any further document in the stream
+    pub(crate) fn check_complete(&self) -> Result<()> {
+        let error =
+            |start| Err(self.error_value(line_col(self.source, start), ErrorKind::TrailingContent));
+        if let Some(start) = stream::second_document_start(self.source) {
+            return error(start);
+        }
+        // the current byte has not been consumed
+        let mut pos = self.content_end.max(self.idx);
+        for line in self.source[pos..].split_inclusive('\n') {
+            if !stream::is_trivia(line) {
+                return error(pos + line.len() - line.trim_start().len());
+            }
+            pos += line.len();
+        }
+        Ok(())
     }
 
     pub(crate) fn parse_mapping_flow(&mut self) -> Result<Yaml<'a>> {
+        let start = self.idx;
         match self.current {
             b'{' => (),
-            _ => return self.parse_error_with_msg("expected left brace"),
+            _ => return self.error(ErrorKind::ExpectedLeftBrace),
         }
+        // Keys and values push their own contexts; this one is for errors
+        // between entries
+        self.start_context(ParseContextKind::Flow, Some(Construct::FlowMapping), start)?;
         self.advance()?;
-        let mut entries: Vec<Entry<'a>> = Vec::new();
+        let mut entries = MappingEntries::default();
         loop {
             match &self.current {
                 b'}' => {
+                    self.content_end = self.idx + 1;
                     self.bump();
-                    return Ok(Yaml::Mapping(entries));
+                    self.end_context(ParseContextKind::Flow)?;
+                    return Ok(Yaml::Mapping(merge_entries(entries)));
                 }
                 b',' => {
                     self.advance()?;
@@ -698,96 +1462,315 @@ impl<'a, 'b> Parser<'a> {
                 b' ' | b'\t' => self.chomp_whitespace(),
                 b'\n' | b'\r' => {
                     if !self.bump_newline() {
-                        return self
-                            .parse_error_with_msg("unexpected end of input in flow mapping");
+                        return self.error(ErrorKind::UnexpectedEndInFlowMapping);
                     }
                 }
                 b'#' => self.chomp_comment(),
                 _ => {
                     self.expected.push(b':');
-                    self.start_context(ParseContextKind::FlowMapping)?;
+                    self.start_context(
+                        ParseContextKind::FlowMapping,
+                        Some(Construct::FlowMapping),
+                        start,
+                    )?;
+                    let key_start = self.idx;
                     let key = self.parse()?;
+                    let key_span = Span::new(key_start, self.content_end);
                     self.end_context(ParseContextKind::FlowMapping)?;
-                    self.chomp_whitespace();
-                    self.chomp_comment();
+                    // JSON allows the `:` on a later line than its key
+                    loop {
+                        match self.current {
+                            b' ' | b'\t' => self.chomp_whitespace(),
+                            b'\n' | b'\r' => {
+                                if !self.bump_newline() {
+                                    return self.error(ErrorKind::UnexpectedEndInFlowMapping);
+                                }
+                            }
+                            b'#' => self.chomp_comment(),
+                            _ => break,
+                        }
+                    }
                     match self.current {
                         b':' => {
                             self.pop_if_match(b':')?;
                             self.advance()?;
+                            let after_colon = self.idx;
                             self.chomp_whitespace();
-                            self.start_context(ParseContextKind::Flow)?;
-                            let value = self.parse()?;
-                            self.end_context(ParseContextKind::Flow)?;
+                            self.enter_key(&key, key_span);
+                            let value = if matches!(self.current, b',' | b'}') {
+                                // `{a: }` has an empty value, which is null
+                                self.record_span(after_colon);
+                                Yaml::Null
+                            } else {
+                                self.start_context(
+                                    ParseContextKind::Flow,
+                                    Some(Construct::FlowMapping),
+                                    start,
+                                )?;
+                                let value = self.parse()?;
+                                self.end_context(ParseContextKind::Flow)?;
+                                value
+                            };
+                            self.leave();
                             self.chomp_whitespace();
                             self.chomp_comment();
-                            entries.push(Entry { key, value })
+                            self.push_entry(&mut entries, key, key_span, value)?;
+                        }
+                        // A key without a value (`{a, b: 1}`) maps to null
+                        b',' | b'}' => {
+                            self.pop_if_match(b':')?;
+                            self.enter_key(&key, key_span);
+                            self.record_span(key_span.end);
+                            self.leave();
+                            self.push_entry(&mut entries, key, key_span, Yaml::Null)?;
                         }
                         // TODO: Provide error message
-                        _ => return self.parse_error_with_msg("failed to parse flow mapping"),
+                        _ => return self.error(ErrorKind::InvalidFlowMapping),
                     }
                 }
             }
         }
     }
 
-    pub(crate) fn parse_mapping_block(&mut self, start_key: Yaml<'a>) -> Result<Yaml<'a>> {
+    pub(crate) fn parse_mapping_block(
+        &mut self,
+        start_key: Yaml<'a>,
+        start_key_span: Span,
+    ) -> Result<Yaml<'a>> {
         match self.context() {
             Some(ParseContext::FlowIn)
             | Some(ParseContext::FlowKey)
-            | Some(ParseContext::FlowOut) => {
-                return self
-                    .parse_error_with_msg("block mappings may not appear in flow collections")
-            }
+            | Some(ParseContext::FlowOut) => return self.error(ErrorKind::BlockMappingInFlow),
             _ => {}
         }
         let indent = self.indent;
         match self.current {
             b':' => {
-                self.advance()?;
-                let mut entries = Vec::new();
-                self.chomp_whitespace();
-                self.chomp_comment();
-                let value = self.parse()?;
-                entries.push(Entry::new(start_key, value));
-                loop {
-                    match self.current {
-                        _ if self.at_end() => break,
-                        byt if byt.is_linebreak() => {
-                            self.indent = 0;
-                            if self.bump_newline() {
-                                continue;
-                            } else {
-                                break;
-                            }
-                        }
-                        byt if byt.is_ws() => {
-                            self.chomp_indent();
-                        }
-                        b'#' => self.chomp_comment(),
-                        _ if self.indent < indent => break,
-                        _ => {
-                            self.expected.push(b':');
-                            let key = self.parse()?;
-                            self.chomp_whitespace();
-                            self.chomp_comment();
-                            if let b':' = self.current {
-                                self.pop_if_match(b':')?;
-                                self.advance()?;
-                                self.chomp_whitespace();
-                                let value = self.parse()?;
-                                entries.push(Entry::new(key, value));
-                            } else {
-                                // TODO: Provide error message
-                                return self.parse_error_with_msg("failed to parse block mapping");
-                            }
-                        }
+                self.start_context(
+                    ParseContextKind::Block,
+                    Some(Construct::BlockMapping),
+                    start_key_span.start,
+                )?;
+                let mut entries = MappingEntries::default();
+                self.enter_key(&start_key, start_key_span);
+                let value = self.parse_entry_value(indent)?;
+                self.leave();
+                self.push_entry(&mut entries, start_key, start_key_span, value)?;
+                let mapping = self.parse_block_entries(indent, entries)?;
+                self.end_context(ParseContextKind::Block)?;
+                Ok(mapping)
+            }
+            // TODO: Provide error message
+            _ => self.error(ErrorKind::ExpectedColon),
+        }
+    }
+
+    /// Parse the rest of the entries of a block mapping indented by
+    /// `indent`, after `entries`
+    fn parse_block_entries(
+        &mut self,
+        indent: usize,
+        mut entries: MappingEntries<'a>,
+    ) -> Result<Yaml<'a>> {
+        loop {
+            match self.current {
+                _ if self.at_end() => break,
+                byt if byt.is_linebreak() => {
+                    self.indent = 0;
+                    if !self.bump_newline() {
+                        break;
+                    }
+                }
+                byt if byt.is_ws() => {
+                    self.chomp_indent()?;
+                }
+                b'#' => self.chomp_comment(),
+                _ if self.indent < indent => break,
+                b'?' if self.at_explicit_key() => {
+                    let (key, key_span, value) = self.parse_explicit_entry(indent)?;
+                    self.push_entry(&mut entries, key, key_span, value)?;
+                }
+                _ => {
+                    self.expected.push(b':');
+                    let key_start = self.idx;
+                    let key = self.parse()?;
+                    let key_span = Span::new(key_start, self.content_end);
+                    self.chomp_whitespace();
+                    self.chomp_comment();
+                    if let b':' = self.current {
+                        self.pop_if_match(b':')?;
+                        self.enter_key(&key, key_span);
+                        let value = self.parse_entry_value(indent)?;
+                        self.leave();
+                        self.push_entry(&mut entries, key, key_span, value)?;
+                    } else {
+                        // TODO: Provide error message
+                        return self.error_at(key_start, ErrorKind::InvalidBlockMapping);
                     }
                 }
-                Ok(Yaml::Mapping(entries))
             }
-            // TODO: Provide error message
-            _ => self.parse_error_with_msg("failed to parse block mapping, expected ':'"),
         }
+        Ok(Yaml::Mapping(merge_entries(entries)))
+    }
+
+    /// Whether the parser is inside a flow collection
+    fn in_flow(&self) -> bool {
+        matches!(
+            self.context(),
+            Some(ParseContext::FlowIn | ParseContext::FlowOut | ParseContext::FlowKey)
+        )
+    }
+
+    /// Whether the current `?` is the indicator of an explicit mapping key
+    fn at_explicit_key(&self) -> bool {
+        self.current == b'?'
+            && !self.in_flow()
+            && self
+                .bytes
+                .get(self.idx + 1)
+                .is_none_or(|next| next.is_ws() || next.is_linebreak())
+    }
+
+    /// Parse a block mapping whose first key is explicit (`? key`)
+    fn parse_explicit_mapping(&mut self) -> Result<Yaml<'a>> {
+        let indent = self.indent;
+        self.start_context(
+            ParseContextKind::Block,
+            Some(Construct::BlockMapping),
+            self.idx,
+        )?;
+        let (key, key_span, value) = self.parse_explicit_entry(indent)?;
+        let mut entries = MappingEntries::default();
+        self.push_entry(&mut entries, key, key_span, value)?;
+        let mapping = self.parse_block_entries(indent, entries)?;
+        self.end_context(ParseContextKind::Block)?;
+        Ok(mapping)
+    }
+
+    /// Parse a mapping entry with an explicit key: `? key`, then optionally
+    /// `: value` on a later line, indented like the `?`. Either node may
+    /// start on the indicator's line or on the lines below, and a missing
+    /// node is null.
+    fn parse_explicit_entry(&mut self, indent: usize) -> Result<(Yaml<'a>, Span, Yaml<'a>)> {
+        if !self.bump() {
+            let end = self.bytes.len();
+            return Ok((Yaml::Null, Span::new(end, end), Yaml::Null));
+        }
+        let (key, key_span) = self.parse_explicit_node(indent)?;
+        // find the start of the next line with content
+        loop {
+            match self.current {
+                byt if byt.is_linebreak() => {
+                    self.indent = 0;
+                    if !self.bump_newline() {
+                        break;
+                    }
+                }
+                byt if byt.is_ws() => self.chomp_indent()?,
+                b'#' => self.chomp_comment(),
+                _ => break,
+            }
+        }
+        let is_value = self.current == b':'
+            && self.indent == indent
+            && self
+                .peek()
+                .is_none_or(|next| next.is_ws() || next.is_linebreak());
+        if !is_value || !self.bump() {
+            return Ok((key, key_span, Yaml::Null));
+        }
+        self.enter_key(&key, key_span);
+        let (value, _) = self.parse_explicit_node(indent)?;
+        self.leave();
+        Ok((key, key_span, value))
+    }
+
+    /// Parse the node after a `?` or `:` indicator at `indent`
+    fn parse_explicit_node(&mut self, indent: usize) -> Result<(Yaml<'a>, Span)> {
+        self.chomp_whitespace();
+        let Some(start) = self.explicit_node_start(indent) else {
+            return Ok((Yaml::Null, Span::new(self.idx, self.idx)));
+        };
+        if start == self.idx {
+            // content on the indicator's line is indented to its column
+            let line_start = self.source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+            self.indent = start - line_start;
+        }
+        let node = self.parse_block_value(indent)?;
+        Ok((node, Span::new(start, self.content_end)))
+    }
+
+    /// Where the node after an explicit key or value indicator at `indent`
+    /// starts: on the rest of the current line, or else on the next line
+    /// with content if that is indented further. Returns `None` if the node
+    /// is empty.
+    fn explicit_node_start(&self, indent: usize) -> Option<usize> {
+        let rest = &self.source[self.idx..];
+        let line_end = rest.find('\n').map_or(rest.len(), |idx| idx + 1);
+        let first = rest[..line_end].trim_end();
+        if !first.is_empty() && !first.starts_with('#') {
+            return Some(self.idx);
+        }
+        let mut offset = self.idx + line_end;
+        for line in self.source[offset..].split_inclusive('\n') {
+            let content = line.trim_start_matches([' ', '\t']);
+            let trimmed = content.trim_end();
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                let line_indent = line.len() - content.len();
+                return (line_indent > indent).then_some(offset + line_indent);
+            }
+            offset += line.len();
+        }
+        None
+    }
+
+    /// Add an entry to a mapping being parsed, applying the duplicate key
+    /// policy
+    fn push_entry(
+        &self,
+        mapping: &mut MappingEntries<'a>,
+        key: Yaml<'a>,
+        key_span: Span,
+        value: Yaml<'a>,
+    ) -> Result<()> {
+        let merge = self.is_merge_key(&key, key_span);
+        if merge && !is_mergeable(&value) {
+            return self.error_at(key_span.start, ErrorKind::InvalidMerge);
+        }
+        let policy = self.options.duplicate_keys;
+        let key_string = (policy != DuplicateKeys::Keep && !merge).then(|| key.key_string());
+        let earlier = key_string
+            .as_ref()
+            .and_then(|key_string| mapping.index.get(key_string).copied());
+        match (earlier, policy) {
+            (Some(_), DuplicateKeys::Error) => {
+                return self.error_at(
+                    key_span.start,
+                    ErrorKind::DuplicateKey {
+                        key: snippet(key_string.as_deref().unwrap_or_default(), Some(80), false),
+                    },
+                );
+            }
+            (Some(_), DuplicateKeys::FirstWins) => {}
+            (Some(idx), _) => mapping.entries[idx] = Entry::new(key, value),
+            (None, _) => {
+                if merge {
+                    mapping.merge_keys.push(mapping.entries.len());
+                }
+                if let Some(key_string) = key_string {
+                    mapping.index.insert(key_string, mapping.entries.len());
+                }
+                mapping.entries.push(Entry::new(key, value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `key`, found at `key_span`, is the merge key: `<<` written
+    /// plain, as a quoted `"<<"` is an ordinary key
+    fn is_merge_key(&self, key: &Yaml<'a>, key_span: Span) -> bool {
+        matches!(key, Yaml::Scalar("<<"))
+            && !self.source[key_span.start..key_span.end].ends_with(['"', '\''])
     }
 
     fn slice_range(&self, (start, end): (usize, usize)) -> &'a str {
@@ -814,15 +1797,27 @@ impl<'a, 'b> Parser<'a> {
         }
     }
 
-    fn chomp_indent(&mut self) {
+    fn chomp_indent(&mut self) -> Result<()> {
+        let at_line_start = self.idx == 0 || self.bytes[self.idx - 1].is_linebreak();
+        let mut first_tab = None;
         let mut idt = 0;
         while let b' ' | b'\t' = self.current {
+            if self.current == b'\t' {
+                first_tab.get_or_insert(self.idx);
+            }
             if !self.bump() {
                 break;
             }
             idt += 1;
         }
         self.indent = idt;
+        if let Some(tab) = first_tab {
+            let blank = self.current.is_linebreak() || self.current == b'#' || self.at_end();
+            if self.options.reject_tab_indentation && at_line_start && !blank && !self.in_flow() {
+                return self.error_at(tab, ErrorKind::TabIndentation);
+            }
+        }
+        Ok(())
     }
 
     fn chomp_newlines(&mut self) -> Result<()> {
@@ -833,7 +1828,11 @@ impl<'a, 'b> Parser<'a> {
     }
 
     pub(crate) fn parse_sequence_flow(&mut self) -> Result<Yaml<'a>> {
-        self.start_context(ParseContextKind::Flow)?;
+        self.start_context(
+            ParseContextKind::Flow,
+            Some(Construct::FlowSequence),
+            self.idx,
+        )?;
         match self.current {
             b'[' => {
                 self.advance()?;
@@ -841,6 +1840,7 @@ impl<'a, 'b> Parser<'a> {
                 loop {
                     match self.current {
                         b']' => {
+                            self.content_end = self.idx + 1;
                             self.bump();
                             self.end_context(ParseContextKind::Flow)?;
                             return Ok(Yaml::Sequence(elements));
@@ -848,14 +1848,17 @@ impl<'a, 'b> Parser<'a> {
                         b' ' | b'\t' => self.chomp_whitespace(),
                         b'\n' | b'\r' => {
                             if !self.bump_newline() {
-                                return self.parse_error_with_msg(
-                                    "unexpected end of input in flow sequence",
-                                );
+                                return self.error(ErrorKind::UnexpectedEndInFlowSequence);
                             }
                         }
                         b'#' => self.chomp_comment(),
                         _ => {
+                            let elem_start = self.idx;
+                            self.enter_index(elements.len());
+                            self.expected.push(b':');
                             let elem = self.parse()?;
+                            let elem = self.parse_flow_pair(elem, elem_start)?;
+                            self.leave();
                             elements.push(elem);
 
                             // Skip whitespace, newlines, and comments after element
@@ -864,9 +1867,8 @@ impl<'a, 'b> Parser<'a> {
                                     b' ' | b'\t' => self.chomp_whitespace(),
                                     b'\n' | b'\r' => {
                                         if !self.bump_newline() {
-                                            return self.parse_error_with_msg(
-                                                "unexpected end of input in flow sequence",
-                                            );
+                                            return self
+                                                .error(ErrorKind::UnexpectedEndInFlowSequence);
                                         }
                                     }
                                     b'#' => self.chomp_comment(),
@@ -879,22 +1881,46 @@ impl<'a, 'b> Parser<'a> {
                                     self.advance()?;
                                 }
                                 b']' => {
+                                    self.content_end = self.idx + 1;
                                     self.bump();
                                     self.end_context(ParseContextKind::Flow)?;
                                     return Ok(Yaml::Sequence(elements));
                                 }
-                                _ => {
-                                    return self
-                                        .parse_error_with_msg("failed to parse flow sequence")
-                                }
+                                _ => return self.error(ErrorKind::InvalidFlowSequence),
                             }
                         }
                     }
                 }
             }
             // TODO: Provide error message
-            _ => self.parse_error_with_msg("failed to parse flow sequence"),
+            _ => self.error(ErrorKind::InvalidFlowSequence),
+        }
+    }
+
+    /// Parse the rest of a flow sequence item which may be the key of a
+    /// single pair mapping (`[a: 1, b: 2]`), after the `key` which starts
+    /// at `key_start`
+    fn parse_flow_pair(&mut self, key: Yaml<'a>, key_start: usize) -> Result<Yaml<'a>> {
+        let key_span = Span::new(key_start, self.content_end);
+        self.chomp_whitespace();
+        self.pop_if_match(b':')?;
+        if self.current != b':' {
+            return Ok(key);
         }
+        self.advance()?;
+        let after_colon = self.idx;
+        self.chomp_whitespace();
+        self.enter_key(&key, key_span);
+        let value = if matches!(self.current, b',' | b']') {
+            // `[a: ]` has an empty value, which is null
+            self.record_span(after_colon);
+            Yaml::Null
+        } else {
+            self.parse()?
+        };
+        self.leave();
+        self.record_span(key_start);
+        Ok(Yaml::Mapping(vec![Entry::new(key, value)]))
     }
 
     fn check_ahead_1(&self, stop: impl Fn(u8) -> bool) -> bool {
@@ -908,13 +1934,14 @@ impl<'a, 'b> Parser<'a> {
         match self.context() {
             Some(ParseContext::FlowIn)
             | Some(ParseContext::FlowKey)
-            | Some(ParseContext::FlowOut) => {
-                return self
-                    .parse_error_with_msg("block sequences may not appear in flow collections")
-            }
+            | Some(ParseContext::FlowOut) => return self.error(ErrorKind::BlockSequenceInFlow),
             _ => {}
         }
-        self.start_context(ParseContextKind::Block)?;
+        self.start_context(
+            ParseContextKind::Block,
+            Some(Construct::BlockSequence),
+            self.idx,
+        )?;
         let indent = self.indent;
         match self.current {
             b'-' => {
@@ -932,7 +1959,7 @@ impl<'a, 'b> Parser<'a> {
                             }
                         }
                         byt if byt.is_ws() => {
-                            self.chomp_indent();
+                            self.chomp_indent()?;
                         }
                         _ if self.indent < indent => break,
                         b'-' => {
@@ -941,17 +1968,21 @@ impl<'a, 'b> Parser<'a> {
                                 self.advance()?;
                                 self.indent = 0;
                                 if self.current.is_ws() {
-                                    self.chomp_indent();
+                                    self.chomp_indent()?;
                                     if self.indent < indent {
                                         break;
                                     } else {
-                                        let node = self.parse()?;
+                                        self.enter_index(seq.len());
+                                        let node = self.parse_block_value(indent)?;
+                                        self.leave();
                                         seq.push(node);
                                     }
                                 } else if 0 < indent {
                                     break;
                                 } else {
-                                    let node = self.parse()?;
+                                    self.enter_index(seq.len());
+                                    let node = self.parse_block_value(indent)?;
+                                    self.leave();
                                     seq.push(node);
                                 }
                             } else if self.check_ahead_1(ByteExt::is_ws) {
@@ -960,21 +1991,23 @@ impl<'a, 'b> Parser<'a> {
                                 // Update indent to account for "- " prefix
                                 // Content after "- " is effectively at indent + 2
                                 self.indent += 2;
-                                let node = self.parse()?;
+                                self.enter_index(seq.len());
+                                let node = self.parse_block_value(indent)?;
+                                self.leave();
                                 seq.push(node);
                             } else {
-                                return self.parse_error_with_msg("unexpected '-'");
+                                return self.error(ErrorKind::UnexpectedDash);
                             }
                         }
                         _ if self.indent == indent => break,
-                        _ => return self.parse_error_with_msg("expected sequence item"),
+                        _ => return self.error(ErrorKind::ExpectedSequenceItem),
                     }
                 }
                 self.end_context(ParseContextKind::Block)?;
                 Ok(Yaml::Sequence(seq))
             }
             // TODO: Provide error message
-            _ => self.parse_error_with_msg("failed to parse block sequence"),
+            _ => self.error(ErrorKind::InvalidBlockSequence),
         }
     }
 
@@ -993,18 +2026,16 @@ impl<'a, 'b> Parser<'a> {
         F: FnMut(u8, Option<u8>) -> bool,
     {
         let start = self.idx;
-        let mut end = start;
         loop {
-            let peeked = self.peek();
-            if !accept(self.current, peeked) {
+            if self.at_end() {
+                return Err((start, self.idx));
+            }
+            if !accept(self.current, self.peek()) {
                 break;
-            } else if !self.bump() {
-                end += 1;
-                return Err((start, end));
             }
-            end += 1;
+            self.bump();
         }
-        Ok((start, end))
+        Ok((start, self.idx))
     }
 
     fn pop_if_match(&mut self, expect: u8) -> Result<()> {
@@ -1014,7 +2045,308 @@ impl<'a, 'b> Parser<'a> {
                 Ok(())
             }
             // TODO: Provide error message
-            _ => self.parse_error_with_msg("token was not expected"),
+            _ => self.error(ErrorKind::UnexpectedToken),
+        }
+    }
+}
+
+/// The number of nodes in a tree, counting mapping keys
+fn node_count(node: &Yaml<'_>) -> usize {
+    match node {
+        Yaml::Sequence(items) => 1 + items.iter().map(node_count).sum::<usize>(),
+        Yaml::Mapping(entries) => {
+            1 + entries
+                .iter()
+                .map(|entry| node_count(&entry.key) + node_count(&entry.value))
+                .sum::<usize>()
+        }
+        _ => 1,
+    }
+}
+
+/// Whether two mapping keys convert to the same JSON key
+pub(crate) fn same_key(a: &Yaml<'_>, b: &Yaml<'_>) -> bool {
+    match (a, b) {
+        (Yaml::Scalar(a), Yaml::Scalar(b)) => a == b,
+        _ => a.key_string() == b.key_string(),
+    }
+}
+
+/// Whether a value can be merged into a mapping: a mapping or a sequence of
+/// mappings
+fn is_mergeable(value: &Yaml<'_>) -> bool {
+    match value {
+        Yaml::Mapping(_) => true,
+        Yaml::Sequence(items) => items.iter().all(|item| matches!(item, Yaml::Mapping(_))),
+        _ => false,
+    }
+}
+
+/// Replace merge keys (`<< : *base`) with the entries of the mapping, or
+/// sequence of mappings, they hold. Keys written in the mapping itself take
+/// precedence over merged ones, and earlier mappings in a merged sequence
+/// take precedence over later ones.
+fn merge_entries(mapping: MappingEntries<'_>) -> Vec<Entry<'_>> {
+    let MappingEntries {
+        entries,
+        merge_keys,
+        ..
+    } = mapping;
+    if merge_keys.is_empty() {
+        return entries;
+    }
+    let is_merge = |idx: usize| merge_keys.binary_search(&idx).is_ok();
+    let mut seen: HashSet<String> = entries
+        .iter()
+        .enumerate()
+        .filter(|&(idx, _)| !is_merge(idx))
+        .map(|(_, entry)| entry.key.key_string())
+        .collect();
+    let mut merged = Vec::with_capacity(entries.len());
+    for (idx, entry) in entries.into_iter().enumerate() {
+        if !is_merge(idx) {
+            merged.push(entry);
+            continue;
+        }
+        let sources = match entry.value {
+            Yaml::Mapping(map) => vec![map],
+            Yaml::Sequence(items) => items
+                .into_iter()
+                .filter_map(|item| match item {
+                    Yaml::Mapping(map) => Some(map),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        for source in sources {
+            for inherited in source {
+                if seen.insert(inherited.key.key_string()) {
+                    merged.push(inherited);
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// Decode the escape sequences in the text of a double-quoted scalar. On
+/// failure, returns the offset of the bad escape and a message.
+fn unescape(raw: &str) -> std::result::Result<String, (usize, ErrorKind)> {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+    // the length of `out` without the unescaped whitespace at its end
+    let mut content_len = 0;
+    while let Some((idx, c)) = chars.next() {
+        if c == '\r' || c == '\n' {
+            fold_line_break(&mut out, content_len, c, &mut chars);
+            content_len = out.len();
+            continue;
+        }
+        if c != '\\' {
+            out.push(c);
+            if c != ' ' && c != '\t' {
+                content_len = out.len();
+            }
+            continue;
+        }
+        let Some((_, escape)) = chars.next() else {
+            return Err((idx, ErrorKind::UnterminatedEscape));
+        };
+        let hex_len = match escape {
+            'x' => 2,
+            'u' => 4,
+            'U' => 8,
+            _ => 0,
+        };
+        if hex_len > 0 {
+            let digits = raw
+                .get(idx + 2..idx + 2 + hex_len)
+                .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()));
+            let code = digits.and_then(|digits| u32::from_str_radix(digits, 16).ok());
+            // a UTF-16 surrogate pair, as JSON writes characters outside the
+            // Basic Multilingual Plane: `\ud83d\ude00`
+            let low = raw
+                .get(idx + 6..idx + 12)
+                .and_then(|next| next.strip_prefix("\\u"))
+                .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                .filter(|low| (0xDC00..0xE000).contains(low));
+            let (code, escape_len) = match (code, low) {
+                (Some(high @ 0xD800..0xDC00), Some(low)) if escape == 'u' => {
+                    (Some(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)), 10)
+                }
+                _ => (code, hex_len),
+            };
+            let decoded = code.and_then(char::from_u32);
+            let Some(decoded) = decoded else {
+                return Err((
+                    idx,
+                    ErrorKind::InvalidHexEscape {
+                        escape,
+                        digits: hex_len,
+                    },
+                ));
+            };
+            out.push(decoded);
+            for _ in 0..escape_len {
+                chars.next();
+            }
+            continue;
+        }
+        let decoded = match escape {
+            '0' => '\0',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\u{b}',
+            'f' => '\u{c}',
+            'r' => '\r',
+            'e' => '\u{1b}',
+            ' ' => ' ',
+            '"' => '"',
+            '/' => '/',
+            '\\' => '\\',
+            'N' => '\u{85}',
+            '_' => '\u{a0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            '\r' | '\n' => {
+                // an escaped line break joins the lines, dropping the
+                // indentation of the next one
+                if escape == '\r' {
+                    chars.next_if(|&(_, c)| c == '\n');
+                }
+                while chars.next_if(|&(_, c)| c == ' ' || c == '\t').is_some() {}
+                content_len = out.len();
+                continue;
+            }
+            other => {
+                return Err((
+                    idx,
+                    ErrorKind::InvalidEscape {
+                        // a control character is shown escaped rather than
+                        // written into the message
+                        escape: if other.is_control() {
+                            other.escape_default().to_string()
+                        } else {
+                            other.to_string()
+                        },
+                    },
+                ));
+            }
+        };
+        out.push(decoded);
+        content_len = out.len();
+    }
+    Ok(out)
+}
+
+/// The text of a single-quoted scalar: doubled quotes become one quote and
+/// line breaks are folded
+fn unquote_single(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+    let mut content_len = 0;
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '\r' | '\n' => fold_line_break(&mut out, content_len, c, &mut chars),
+            '\'' => {
+                chars.next_if(|&(_, c)| c == '\'');
+                out.push('\'');
+            }
+            c => out.push(c),
+        }
+        if c != ' ' && c != '\t' {
+            content_len = out.len();
+        }
+    }
+    out
+}
+
+/// Fold the line break `first` inside a quoted scalar, with the lines after
+/// it: whitespace around the break is dropped (`out` is cut back to
+/// `content_len`), then a single break becomes a space and each following
+/// blank line a newline.
+fn fold_line_break(
+    out: &mut String,
+    content_len: usize,
+    first: char,
+    chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+) {
+    out.truncate(content_len);
+    if first == '\r' {
+        chars.next_if(|&(_, c)| c == '\n');
+    }
+    let mut blank_lines = 0;
+    loop {
+        while chars.next_if(|&(_, c)| c == ' ' || c == '\t').is_some() {}
+        match chars.next_if(|&(_, c)| c == '\r' || c == '\n') {
+            Some((_, '\r')) => {
+                chars.next_if(|&(_, c)| c == '\n');
+                blank_lines += 1;
+            }
+            Some(_) => blank_lines += 1,
+            None => break,
+        }
+    }
+    if blank_lines == 0 {
+        out.push(' ');
+    } else {
+        out.extend(std::iter::repeat_n('\n', blank_lines));
+    }
+}
+
+/// The entries of a mapping being parsed
+#[derive(Default)]
+struct MappingEntries<'a> {
+    entries: Vec<Entry<'a>>,
+    /// the indices of the entries with a plain `<<` key, which are merged
+    /// into the mapping once it is complete
+    merge_keys: Vec<usize>,
+    /// the index of each key in `entries`, for finding duplicates, unless
+    /// duplicates are kept
+    index: HashMap<String, usize>,
+}
+
+const NEWLINES: &str = "\n\n\n\n\n\n\n\n\n\n\n\n\n\n\n\n";
+
+/// The text of a block scalar as it is read: slices of the source and of
+/// `NEWLINES`, copied into a `String` in one allocation at the end
+/// rather than growing it line by line, which keeps reading multi-megabyte
+/// scalars linear.
+#[derive(Default)]
+struct BlockText<'a> {
+    pieces: Vec<&'a str>,
+    len: usize,
+}
+
+impl<'a> BlockText<'a> {
+    fn push(&mut self, piece: &'a str) {
+        if !piece.is_empty() {
+            self.len += piece.len();
+            self.pieces.push(piece);
+        }
+    }
+
+    /// Push `count` copies of the single-byte character `fill` is made of
+    fn push_repeated(&mut self, fill: &'static str, mut count: usize) {
+        while count > 0 {
+            let chunk = count.min(fill.len());
+            self.push(&fill[..chunk]);
+            count -= chunk;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn into_string(self) -> String {
+        let mut out = String::with_capacity(self.len);
+        for piece in self.pieces {
+            out.push_str(piece);
         }
+        out
     }
 }
@@ -0,0 +1,182 @@
+use crate::errors::PatchError;
+use crate::path::key_matches;
+use crate::source_map::{token_index, unescape};
+use crate::{Entry, Yaml};
+use serde_json::Value;
+
+impl Yaml<'_> {
+    /// Apply a JSON Patch (RFC 6902): an array of `add`, `remove`,
+    /// `replace`, `move`, `copy` and `test` operations, whose paths are JSON
+    /// pointers as taken by [`pointer`](Self::pointer). Values are added as
+    /// [`Yaml::from_json`] converts them, and `test` compares the node's
+    /// [`to_json`](Self::to_json) with its value. Added keys go at the end
+    /// of their mapping, while a replaced value keeps its key's place.
+    ///
+    /// The patch is all or nothing: if an operation fails, the tree is left
+    /// unchanged.
+    /// # Errors
+    /// Returns `Err` with the index of the first operation which is
+    /// malformed, refers to a node which does not exist, or fails its test
+    pub fn apply_patch(&mut self, patch: &Value) -> Result<(), PatchError> {
+        let Value::Array(ops) = patch else {
+            return Err(PatchError {
+                index: 0,
+                message: "a patch must be an array of operations".to_string(),
+            });
+        };
+        let mut patched = self.clone();
+        for (index, op) in ops.iter().enumerate() {
+            apply(&mut patched, op).map_err(|message| PatchError { index, message })?;
+        }
+        *self = patched;
+        Ok(())
+    }
+}
+
+fn apply(doc: &mut Yaml<'_>, op: &Value) -> Result<(), String> {
+    let field = |name: &str| {
+        op.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("missing \"{name}\""))
+    };
+    let value = || {
+        op.get("value")
+            .ok_or_else(|| "missing \"value\"".to_string())
+    };
+    let kind = field("op")?;
+    let path = field("path")?;
+    match kind {
+        "add" => add(doc, path, Yaml::from_json(value()?)),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            let node = doc.pointer_mut(path).ok_or_else(|| not_found(path))?;
+            *node = Yaml::from_json(value()?);
+            Ok(())
+        }
+        "move" => {
+            let from = field("from")?;
+            if from == path {
+                return Ok(());
+            }
+            if path
+                .strip_prefix(from)
+                .is_some_and(|rest| rest.starts_with('/'))
+            {
+                return Err(format!("cannot move \"{from}\" into itself"));
+            }
+            let node = remove(doc, from)?;
+            add(doc, path, node)
+        }
+        "copy" => {
+            let from = field("from")?;
+            let node = doc.pointer(from).ok_or_else(|| not_found(from))?.clone();
+            add(doc, path, node)
+        }
+        "test" => {
+            let node = doc.pointer(path).ok_or_else(|| not_found(path))?;
+            if node.to_json() == *value()? {
+                Ok(())
+            } else {
+                Err(format!("test failed at \"{path}\""))
+            }
+        }
+        other => Err(format!("unknown operation \"{other}\"")),
+    }
+}
+
+fn not_found(pointer: &str) -> String {
+    format!("no value at \"{pointer}\"")
+}
+
+/// Split a pointer to a node below the root into its parent's pointer and
+/// its own unescaped reference token
+fn split(pointer: &str) -> Result<(&str, String), String> {
+    match pointer.rfind('/') {
+        Some(idx) if pointer.starts_with('/') => {
+            Ok((&pointer[..idx], unescape(&pointer[idx + 1..])))
+        }
+        _ => Err(format!("invalid pointer \"{pointer}\"")),
+    }
+}
+
+/// The node holding the children a pointer's tokens refer to: the mapping
+/// of a tagged mapping, whose entries follow its `__type` in JSON, or the
+/// node itself
+fn container<'y, 'a>(node: &'y mut Yaml<'a>) -> &'y mut Yaml<'a> {
+    // checked before matching, as a borrow returned from one arm of a
+    // match cannot be released for the next
+    if !matches!(node, Yaml::Tagged(_, value) if matches!(**value, Yaml::Mapping(_))) {
+        return node;
+    }
+    match node {
+        Yaml::Tagged(_, value) => value,
+        _ => unreachable!(),
+    }
+}
+
+/// Store `value` at `pointer`, replacing a mapping key's value or inserting
+/// a sequence item (`-` appends)
+fn add<'a>(doc: &mut Yaml<'a>, pointer: &str, value: Yaml<'a>) -> Result<(), String> {
+    if pointer.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, token) = split(pointer)?;
+    let node = doc.pointer_mut(parent).ok_or_else(|| not_found(parent))?;
+    match container(node) {
+        Yaml::Mapping(entries) => {
+            match entries
+                .iter_mut()
+                .rev()
+                .find(|e| key_matches(&e.key, &token))
+            {
+                Some(entry) => entry.value = value,
+                None => entries.push(Entry::new(Yaml::String(token), value)),
+            }
+            Ok(())
+        }
+        Yaml::Sequence(items) => {
+            let idx = if token == "-" {
+                Some(items.len())
+            } else {
+                token_index(&token).filter(|&idx| idx <= items.len())
+            };
+            items.insert(idx.ok_or_else(|| not_found(pointer))?, value);
+            Ok(())
+        }
+        Yaml::Tagged(_, inner) if token == "__value" => {
+            **inner = value;
+            Ok(())
+        }
+        _ => Err(format!("cannot add below the scalar at \"{parent}\"")),
+    }
+}
+
+/// Take the node at `pointer` out of its parent; every entry of a mapping
+/// with the key is removed
+fn remove<'a>(doc: &mut Yaml<'a>, pointer: &str) -> Result<Yaml<'a>, String> {
+    if pointer.is_empty() {
+        return Err("cannot remove the root".to_string());
+    }
+    let (parent, token) = split(pointer)?;
+    let node = doc.pointer_mut(parent).ok_or_else(|| not_found(pointer))?;
+    match container(node) {
+        Yaml::Mapping(entries) => {
+            let mut removed = None;
+            entries.retain_mut(|entry| {
+                if key_matches(&entry.key, &token) {
+                    removed = Some(std::mem::replace(&mut entry.value, Yaml::Null));
+                    false
+                } else {
+                    true
+                }
+            });
+            removed.ok_or_else(|| not_found(pointer))
+        }
+        Yaml::Sequence(items) => match token_index(&token) {
+            Some(idx) if idx < items.len() => Ok(items.remove(idx)),
+            _ => Err(not_found(pointer)),
+        },
+        _ => Err(not_found(pointer)),
+    }
+}
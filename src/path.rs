@@ -0,0 +1,188 @@
+use crate::{Entry, Yaml};
+use core::fmt;
+
+/// One step of a path into a Yaml tree
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum PathSegment {
+    /// A mapping key
+    Key(String),
+    /// A sequence index
+    Index(usize),
+}
+
+/// A location inside a Yaml tree, written as `a.b[2].c`.
+///
+/// Keys which contain `.` or `[` can be written in quoted brackets,
+/// e.g. `["+setup[Settings](db://settings)"].title`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Parse a path expression. The empty string refers to the root node.
+    /// Returns `None` if the expression is malformed.
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        let mut segments = Vec::new();
+        let mut rest = input;
+        let mut expect_key = true;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('[') {
+                let (segment, remainder) = Self::parse_bracket(after)?;
+                segments.push(segment);
+                rest = remainder;
+                expect_key = false;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                if expect_key {
+                    // leading or doubled '.'
+                    return None;
+                }
+                rest = after;
+                expect_key = true;
+            } else {
+                if !expect_key {
+                    // key directly after a bracket without a separating '.'
+                    return None;
+                }
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                segments.push(PathSegment::Key(rest[..end].to_string()));
+                rest = &rest[end..];
+                expect_key = false;
+            }
+        }
+        if expect_key && !segments.is_empty() {
+            // trailing '.'
+            return None;
+        }
+        Some(Self { segments })
+    }
+
+    /// Parse the contents of a `[...]` segment, returning the segment and the
+    /// remaining input after the closing bracket
+    fn parse_bracket(input: &str) -> Option<(PathSegment, &str)> {
+        if let quote @ ('"' | '\'') = input.chars().next()? {
+            let body = &input[1..];
+            let close = body.find(quote)?;
+            let rest = body[close + 1..].strip_prefix(']')?;
+            Some((PathSegment::Key(body[..close].to_string()), rest))
+        } else {
+            let close = input.find(']')?;
+            let idx = input[..close].trim().parse::<usize>().ok()?;
+            Some((PathSegment::Index(idx), &input[close + 1..]))
+        }
+    }
+
+    pub(crate) fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub(crate) fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// The path of the node containing this one, and the step leading from
+    /// it to this node. Returns `None` for the root path.
+    pub(crate) fn split_last(&self) -> Option<(Path, &PathSegment)> {
+        let (last, parent) = self.segments.split_last()?;
+        Some((
+            Path {
+                segments: parent.to_vec(),
+            },
+            last,
+        ))
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if key.contains(['.', '[']) => write!(f, "[\"{key}\"]")?,
+                PathSegment::Key(key) if idx == 0 => write!(f, "{key}")?,
+                PathSegment::Key(key) => write!(f, ".{key}")?,
+                PathSegment::Index(i) => write!(f, "[{i}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check whether a mapping key refers to the given path key
+pub(crate) fn key_matches(key: &Yaml<'_>, name: &str) -> bool {
+    match key {
+        Yaml::Scalar(s) => *s == name,
+        Yaml::String(s) => s == name,
+        other => other.key_string() == name,
+    }
+}
+
+/// Find the node at `path`, starting from `root`. When a mapping contains
+/// duplicate keys, the last one wins (matching `to_json`).
+pub(crate) fn resolve<'y, 'a>(root: &'y Yaml<'a>, path: &Path) -> Option<&'y Yaml<'a>> {
+    let mut node = root;
+    for segment in path.segments() {
+        node = match (segment, node) {
+            (PathSegment::Key(name), Yaml::Mapping(entries)) => {
+                &entries
+                    .iter()
+                    .rev()
+                    .find(|e| key_matches(&e.key, name))?
+                    .value
+            }
+            (PathSegment::Index(idx), Yaml::Sequence(items)) => items.get(*idx)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+/// Mutable counterpart of [`resolve`]
+pub(crate) fn resolve_mut<'y, 'a>(root: &'y mut Yaml<'a>, path: &Path) -> Option<&'y mut Yaml<'a>> {
+    let mut node = root;
+    for segment in path.segments() {
+        node = match (segment, node) {
+            (PathSegment::Key(name), Yaml::Mapping(entries)) => {
+                &mut entries
+                    .iter_mut()
+                    .rev()
+                    .find(|e| key_matches(&e.key, name))?
+                    .value
+            }
+            (PathSegment::Index(idx), Yaml::Sequence(items)) => items.get_mut(*idx)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+/// Store `value` at `path`, replacing the existing node. A missing final
+/// mapping key is appended, as is a sequence index one past the end.
+/// Returns `false` if the parent of `path` does not exist.
+pub(crate) fn set<'a>(root: &mut Yaml<'a>, path: &Path, value: Yaml<'a>) -> bool {
+    let Some((parent_path, last)) = path.split_last() else {
+        *root = value;
+        return true;
+    };
+    match (resolve_mut(root, &parent_path), last) {
+        (Some(Yaml::Mapping(entries)), PathSegment::Key(name)) => {
+            match entries.iter_mut().rev().find(|e| key_matches(&e.key, name)) {
+                Some(entry) => entry.value = value,
+                None => entries.push(Entry::new(Yaml::String(name.clone()), value)),
+            }
+            true
+        }
+        (Some(Yaml::Sequence(items)), PathSegment::Index(idx)) if *idx < items.len() => {
+            items[*idx] = value;
+            true
+        }
+        (Some(Yaml::Sequence(items)), PathSegment::Index(idx)) if *idx == items.len() => {
+            items.push(value);
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,357 @@
+use crate::errors::{PassError, PipelineError};
+use crate::path::{Path, PathSegment};
+use crate::snippet::snippet;
+use crate::Yaml;
+use std::collections::HashMap;
+
+/// A transformation applied to a parsed document by a [`Pipeline`]
+pub trait Pass {
+    /// The name the pass is reported under in errors
+    fn name(&self) -> &str;
+
+    /// Transform the document in place
+    /// # Errors
+    /// Returns `Err` if the document cannot be transformed
+    fn run(&self, doc: &mut Yaml<'_>) -> Result<(), PassError>;
+}
+
+/// A sequence of passes run over a parsed document, in the order they were
+/// added. Errors from any pass are reported with the name of the pass.
+#[derive(Default)]
+pub struct Pipeline {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl Pipeline {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a pass to the end of the pipeline
+    #[must_use]
+    pub fn pass(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Add a closure as a pass to the end of the pipeline
+    #[must_use]
+    pub fn pass_fn<F>(self, name: &str, f: F) -> Self
+    where
+        F: Fn(&mut Yaml<'_>) -> Result<(), PassError> + 'static,
+    {
+        self.pass(FnPass {
+            name: name.to_string(),
+            f,
+        })
+    }
+
+    /// The names of the passes, in the order they run
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.passes.iter().map(|pass| pass.name())
+    }
+
+    /// Parse `input` and run every pass over the result
+    /// # Errors
+    /// Returns `Err` if the input is invalid Yaml or a pass fails
+    pub fn run<'a>(&self, input: &'a str) -> Result<Yaml<'a>, PipelineError> {
+        let mut doc = crate::parse(input)?;
+        self.apply(&mut doc)?;
+        Ok(doc)
+    }
+
+    /// Run every pass over an already parsed document, stopping at the
+    /// first failure
+    /// # Errors
+    /// Returns `Err` if a pass fails
+    pub fn apply(&self, doc: &mut Yaml<'_>) -> Result<(), PassError> {
+        for pass in &self.passes {
+            pass.run(doc).map_err(|mut err| {
+                err.pass = pass.name().to_string();
+                err
+            })?;
+        }
+        Ok(())
+    }
+}
+
+struct FnPass<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> Pass for FnPass<F>
+where
+    F: Fn(&mut Yaml<'_>) -> Result<(), PassError>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, doc: &mut Yaml<'_>) -> Result<(), PassError> {
+        (self.f)(doc)
+    }
+}
+
+/// Visit every node, parents before children. Children are visited after
+/// `f` has run, so nodes which `f` replaces are descended into.
+fn walk_mut<'a, F>(node: &mut Yaml<'a>, path: &mut Path, f: &mut F) -> Result<(), PassError>
+where
+    F: FnMut(&mut Yaml<'a>, &Path) -> Result<(), PassError>,
+{
+    f(node, path)?;
+    match node {
+        Yaml::Sequence(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                path.push(PathSegment::Index(idx));
+                walk_mut(item, path, f)?;
+                path.pop();
+            }
+        }
+        Yaml::Mapping(entries) => {
+            for entry in entries.iter_mut() {
+                path.push(PathSegment::Key(entry.key.key_string()));
+                walk_mut(&mut entry.value, path, f)?;
+                path.pop();
+            }
+        }
+        Yaml::Tagged(_, value) => walk_mut(value, path, f)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The tag and value of a tagged scalar or collection (`!tag value`)
+fn tagged_value<'y, 'a>(node: &'y Yaml<'a>) -> Option<(&'y str, &'y Yaml<'a>)> {
+    match node {
+        Yaml::Tagged(tag, value) => Some((tag, value)),
+        _ => None,
+    }
+}
+
+type Lookup = Box<dyn Fn(&str) -> Option<String>>;
+
+/// Replaces `${NAME}` references in string values with the value of the
+/// variable `NAME`, or `${NAME:-default}` with `default` if it is unset.
+/// `$${` is written as a literal `${`.
+///
+/// Substituted values are always strings; they are not re-interpreted as
+/// numbers or booleans.
+pub struct EnvInterpolation {
+    lookup: Lookup,
+}
+
+impl EnvInterpolation {
+    /// Look variables up in the process environment
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::with_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Look variables up with a custom function
+    #[must_use]
+    pub fn with_lookup(lookup: impl Fn(&str) -> Option<String> + 'static) -> Self {
+        Self {
+            lookup: Box::new(lookup),
+        }
+    }
+
+    /// Interpolate `s`, returning `None` if it contains no references
+    fn interpolate(&self, s: &str) -> Result<Option<String>, PassError> {
+        if !s.contains("${") {
+            return Ok(None);
+        }
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(idx) = rest.find("${") {
+            if rest[..idx].ends_with('$') {
+                // escaped "$${"
+                out.push_str(&rest[..idx - 1]);
+                out.push_str("${");
+                rest = &rest[idx + 2..];
+                continue;
+            }
+            out.push_str(&rest[..idx]);
+            let body = &rest[idx + 2..];
+            let close = body.find('}').ok_or_else(|| {
+                let s = snippet(s, Some(80), false);
+                PassError::new(format!("unterminated reference in '{s}'"))
+            })?;
+            let (name, default) = match body[..close].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&body[..close], None),
+            };
+            match ((self.lookup)(name), default) {
+                (Some(value), _) => out.push_str(&value),
+                (None, Some(default)) => out.push_str(default),
+                (None, None) => {
+                    return Err(PassError::new(format!("variable '{name}' is not set")));
+                }
+            }
+            rest = &body[close + 1..];
+        }
+        out.push_str(rest);
+        Ok(Some(out))
+    }
+}
+
+impl Pass for EnvInterpolation {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn run(&self, doc: &mut Yaml<'_>) -> Result<(), PassError> {
+        walk_mut(doc, &mut Path::default(), &mut |node, path| {
+            let text = match node {
+                Yaml::Scalar(s) => *s,
+                Yaml::String(s) => s.as_str(),
+                _ => return Ok(()),
+            };
+            let interpolated = self
+                .interpolate(text)
+                .map_err(|err| err.at(path.to_string()))?;
+            if let Some(value) = interpolated {
+                *node = Yaml::String(value);
+            }
+            Ok(())
+        })
+    }
+}
+
+type Loader = Box<dyn Fn(&str) -> Result<String, String>>;
+
+/// Replaces `!include name` values with the parsed contents of `name`, as
+/// returned by a loader function. Included documents may include others, up
+/// to a nesting depth of 32.
+pub struct Include {
+    loader: Loader,
+}
+
+impl Include {
+    const MAX_DEPTH: usize = 32;
+
+    /// Load included documents with `loader`, which maps the name written
+    /// after `!include` to the document's text
+    #[must_use]
+    pub fn new(loader: impl Fn(&str) -> Result<String, String> + 'static) -> Self {
+        Self {
+            loader: Box::new(loader),
+        }
+    }
+
+    /// Load the documents from the file system, relative to `base`
+    #[must_use]
+    pub fn from_dir(base: impl Into<std::path::PathBuf>) -> Self {
+        let base = base.into();
+        Self::new(move |name| {
+            std::fs::read_to_string(base.join(name)).map_err(|err| err.to_string())
+        })
+    }
+
+    fn load(&self, name: &str, depth: usize) -> Result<Yaml<'static>, PassError> {
+        if depth > Self::MAX_DEPTH {
+            return Err(PassError::new(format!(
+                "includes nested more than {} deep",
+                Self::MAX_DEPTH
+            )));
+        }
+        let text = (self.loader)(name)
+            .map_err(|err| PassError::new(format!("cannot include '{name}': {err}")))?;
+        let mut doc = crate::parse(&text)
+            .map_err(|err| PassError::new(format!("cannot include '{name}': {err}")))?
+            .into_owned();
+        self.expand(&mut doc, depth + 1)?;
+        Ok(doc)
+    }
+
+    fn expand(&self, doc: &mut Yaml<'_>, depth: usize) -> Result<(), PassError> {
+        walk_mut(doc, &mut Path::default(), &mut |node, path| {
+            let name = match tagged_value(node) {
+                Some(("include", Yaml::Scalar(name))) => (*name).to_string(),
+                Some(("include", Yaml::String(name))) => name.clone(),
+                _ => return Ok(()),
+            };
+            *node = self
+                .load(&name, depth)
+                .map_err(|err| err.at(path.to_string()))?;
+            Ok(())
+        })
+    }
+}
+
+impl Pass for Include {
+    fn name(&self) -> &'static str {
+        "include"
+    }
+
+    fn run(&self, doc: &mut Yaml<'_>) -> Result<(), PassError> {
+        self.expand(doc, 0)
+    }
+}
+
+type TagHandler = Box<dyn Fn(&Yaml<'_>) -> Result<Yaml<'static>, String>>;
+
+/// Replaces tagged values (`!name value`) using a handler registered for
+/// each tag name. Values with tags that have no handler are left alone.
+#[derive(Default)]
+pub struct ResolveTags {
+    handlers: HashMap<String, TagHandler>,
+}
+
+impl ResolveTags {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the handler for `tag`. It receives the value after the tag
+    /// and returns the node to replace the tagged value with.
+    #[must_use]
+    pub fn on(
+        mut self,
+        tag: &str,
+        handler: impl Fn(&Yaml<'_>) -> Result<Yaml<'static>, String> + 'static,
+    ) -> Self {
+        self.handlers.insert(tag.to_string(), Box::new(handler));
+        self
+    }
+}
+
+impl Pass for ResolveTags {
+    fn name(&self) -> &'static str {
+        "tags"
+    }
+
+    fn run(&self, doc: &mut Yaml<'_>) -> Result<(), PassError> {
+        walk_mut(doc, &mut Path::default(), &mut |node, path| {
+            let Some((handler, value)) =
+                tagged_value(node).and_then(|(tag, value)| Some((self.handlers.get(tag)?, value)))
+            else {
+                return Ok(());
+            };
+            let resolved = handler(value);
+            *node = resolved.map_err(|msg| PassError::new(msg).at(path.to_string()))?;
+            Ok(())
+        })
+    }
+}
+
+/// Applies the mx transformation (see [`Yaml::to_mx`]) to the document
+pub struct MxTransform;
+
+impl Pass for MxTransform {
+    fn name(&self) -> &'static str {
+        "mx"
+    }
+
+    fn run(&self, doc: &mut Yaml<'_>) -> Result<(), PassError> {
+        let mx = doc.to_mx();
+        if let Some(error) = mx.get("+error") {
+            let msg = error.get("__name").and_then(|msg| msg.as_str());
+            return Err(PassError::new(msg.unwrap_or("invalid mx document")));
+        }
+        *doc = Yaml::from_json(&mx);
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+use crate::errors::QueryError;
+use crate::path::key_matches;
+use crate::Yaml;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// A compiled query selecting nodes of a Yaml tree, in a small jq and
+/// `JSONPath` inspired language. A query is a chain of steps, each applied to
+/// every node the previous steps selected:
+///
+/// - `name` or `.name` selects the value of a mapping key; keys holding
+///   `.`, `[` or spaces are written in quoted brackets, as `["a.b"]`
+/// - `[2]` selects a sequence item, and `[-1]` the last one
+/// - `*`, `.*` or `[*]` selects every item of a sequence or value of a
+///   mapping
+/// - `..` selects a node and everything below it, so `..name` finds the
+///   key at any depth
+/// - `[?path]` keeps the items or values below which `path` exists, and
+///   `[?path op literal]` those where a node at `path` compares to the
+///   literal with `==`, `!=`, `<`, `<=`, `>` or `>=`; `@` is the item
+///   itself
+///
+/// Literals are numbers, `true`, `false`, `null`, quoted strings and bare
+/// words, which are strings. Nodes are compared as their JSON values, and
+/// tags are looked through. For example, `tasks[?retries > 2].name` gives
+/// the names of the tasks retried more than twice, and
+/// `..containers[*].image` every container image in a manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Descend,
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    path: Vec<Step>,
+    test: Option<(Op, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Query {
+    /// Compile a query expression
+    /// # Errors
+    /// Returns `Err` if the expression is malformed
+    pub fn parse(expr: &str) -> Result<Self, QueryError> {
+        let mut parser = QueryParser { expr, pos: 0 };
+        let steps = parser.parse_steps(false)?;
+        if parser.pos < expr.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(Query { steps })
+    }
+
+    /// Every node the query selects in `root`, in document order
+    #[must_use]
+    pub fn select<'y, 'a>(&self, root: &'y Yaml<'a>) -> Vec<&'y Yaml<'a>> {
+        let mut nodes = select(&self.steps, vec![root]);
+        // steps after `..` are applied to each node it found in turn, so
+        // their results may interleave, e.g. `..*` gives the children of
+        // the root before their own children
+        if self.steps.contains(&Step::Descend) {
+            let mut order = Vec::new();
+            descend(root, &mut order);
+            let rank: HashMap<*const Yaml<'a>, usize> = order
+                .into_iter()
+                .enumerate()
+                .map(|(rank, node)| (std::ptr::from_ref(node), rank))
+                .collect();
+            nodes.sort_by_key(|&node| rank.get(&std::ptr::from_ref(node)).copied());
+        }
+        nodes
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(expr: &str) -> Result<Self, QueryError> {
+        Query::parse(expr)
+    }
+}
+
+impl<'a> Yaml<'a> {
+    /// Every node `expr` selects, in document order, e.g.
+    /// `query("tasks[*].name")`; see [`Query`] for the language. Use
+    /// [`Query::parse`] to run a query over several trees.
+    /// # Errors
+    /// Returns `Err` if the expression is malformed
+    pub fn query(&self, expr: &str) -> Result<Vec<&Yaml<'a>>, QueryError> {
+        Ok(Query::parse(expr)?.select(self))
+    }
+}
+
+fn select<'y, 'a>(steps: &[Step], mut nodes: Vec<&'y Yaml<'a>>) -> Vec<&'y Yaml<'a>> {
+    for step in steps {
+        let mut next = Vec::new();
+        for node in nodes {
+            apply(step, node, &mut next);
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+/// Add the nodes `step` selects from `node` to `out`
+fn apply<'y, 'a>(step: &Step, node: &'y Yaml<'a>, out: &mut Vec<&'y Yaml<'a>>) {
+    match (step, node.untagged()) {
+        (Step::Key(name), Yaml::Mapping(entries)) => {
+            // the last of duplicate keys wins, as in `to_json`
+            out.extend(
+                entries
+                    .iter()
+                    .rev()
+                    .find(|entry| key_matches(&entry.key, name))
+                    .map(|entry| &entry.value),
+            );
+        }
+        (Step::Index(idx), Yaml::Sequence(items)) => {
+            let idx = if *idx < 0 {
+                usize::try_from(idx.unsigned_abs())
+                    .ok()
+                    .and_then(|back| items.len().checked_sub(back))
+            } else {
+                usize::try_from(*idx).ok()
+            };
+            out.extend(idx.and_then(|idx| items.get(idx)));
+        }
+        (Step::Wildcard, node) => out.extend(node.iter()),
+        (Step::Descend, _) => descend(node, out),
+        (Step::Filter(filter), node) => {
+            out.extend(node.iter().filter(|child| filter.matches(child)));
+        }
+        _ => {}
+    }
+}
+
+fn descend<'y, 'a>(node: &'y Yaml<'a>, out: &mut Vec<&'y Yaml<'a>>) {
+    out.push(node);
+    for child in node.iter() {
+        descend(child, out);
+    }
+}
+
+impl Filter {
+    fn matches(&self, node: &Yaml<'_>) -> bool {
+        let found = select(&self.path, vec![node]);
+        match &self.test {
+            None => !found.is_empty(),
+            Some((op, literal)) => found
+                .iter()
+                .any(|node| op.holds(compare(&node.to_json(), literal))),
+        }
+    }
+}
+
+impl Op {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match self {
+            Op::Eq => ordering == Some(Ordering::Equal),
+            Op::Ne => ordering != Some(Ordering::Equal),
+            Op::Lt => ordering == Some(Ordering::Less),
+            Op::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Op::Gt => ordering == Some(Ordering::Greater),
+            Op::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+/// How a value orders against a literal: numbers and strings by value,
+/// anything else only as equal or not
+fn compare(value: &Value, literal: &Value) -> Option<Ordering> {
+    match (value, literal) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+struct QueryParser<'e> {
+    expr: &'e str,
+    pos: usize,
+}
+
+impl<'e> QueryParser<'e> {
+    fn error(&self, message: &str) -> QueryError {
+        QueryError {
+            position: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &'e str {
+        &self.expr[self.pos..]
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.pos += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Parse steps up to the end of the expression, or, in a filter, up to
+    /// a space, comparison or `]`
+    fn parse_steps(&mut self, in_filter: bool) -> Result<Vec<Step>, QueryError> {
+        let mut steps = Vec::new();
+        if in_filter {
+            self.eat("@");
+        }
+        let mut first = true;
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() || (in_filter && rest.starts_with(is_filter_end)) {
+                return Ok(steps);
+            }
+            if self.eat("..") {
+                steps.push(Step::Descend);
+                if !self.rest().starts_with('[') {
+                    steps.push(self.parse_name(in_filter)?);
+                }
+            } else if self.eat(".") {
+                steps.push(self.parse_name(in_filter)?);
+            } else if self.eat("[") {
+                steps.push(self.parse_bracket()?);
+            } else if first {
+                steps.push(self.parse_name(in_filter)?);
+            } else {
+                return Err(self.error("expected '.' or '['"));
+            }
+            first = false;
+        }
+    }
+
+    /// Parse a key or `*` after a `.`
+    fn parse_name(&mut self, in_filter: bool) -> Result<Step, QueryError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c| c == '.' || c == '[' || (in_filter && is_filter_end(c)))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("expected a key"));
+        }
+        let name = &rest[..end];
+        self.pos += end;
+        Ok(if name == "*" {
+            Step::Wildcard
+        } else {
+            Step::Key(name.to_string())
+        })
+    }
+
+    /// Parse the inside of `[...]`, after the `[`
+    fn parse_bracket(&mut self) -> Result<Step, QueryError> {
+        self.skip_spaces();
+        let step = if self.eat("*") {
+            Step::Wildcard
+        } else if self.eat("?") {
+            Step::Filter(self.parse_filter()?)
+        } else if self.rest().starts_with(['"', '\'']) {
+            Step::Key(self.parse_quoted()?)
+        } else {
+            let rest = self.rest();
+            let end = rest.find(|c: char| !(c.is_ascii_digit() || c == '-'));
+            let digits = &rest[..end.unwrap_or(rest.len())];
+            let idx = digits
+                .parse()
+                .map_err(|_| self.error("expected an index, '*', '?' or a quoted key"))?;
+            self.pos += digits.len();
+            Step::Index(idx)
+        };
+        self.skip_spaces();
+        if !self.eat("]") {
+            return Err(self.error("expected ']'"));
+        }
+        Ok(step)
+    }
+
+    fn parse_filter(&mut self) -> Result<Filter, QueryError> {
+        self.skip_spaces();
+        let parens = self.eat("(");
+        self.skip_spaces();
+        let path = self.parse_steps(true)?;
+        self.skip_spaces();
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+        let mut test = None;
+        if let Some(&(text, op)) = ops.iter().find(|(text, _)| self.rest().starts_with(text)) {
+            self.pos += text.len();
+            self.skip_spaces();
+            test = Some((op, self.parse_literal()?));
+            self.skip_spaces();
+        }
+        if parens && !self.eat(")") {
+            return Err(self.error("expected ')'"));
+        }
+        Ok(Filter { path, test })
+    }
+
+    fn parse_literal(&mut self) -> Result<Value, QueryError> {
+        if self.rest().starts_with(['"', '\'']) {
+            return self.parse_quoted().map(Value::String);
+        }
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == ']' || c == ')')
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("expected a value"));
+        }
+        let word = &rest[..end];
+        self.pos += end;
+        Ok(match word {
+            "true" | "false" | "null" => serde_json::from_str(word).unwrap_or(Value::Null),
+            _ => serde_json::from_str::<serde_json::Number>(word)
+                .map_or_else(|_| Value::String(word.to_string()), Value::Number),
+        })
+    }
+
+    /// Parse a string in single or double quotes, where a backslash escapes
+    /// the next character
+    fn parse_quoted(&mut self) -> Result<String, QueryError> {
+        let start = self.pos;
+        let mut chars = self.rest().char_indices();
+        let Some((_, quote)) = chars.next() else {
+            return Err(self.error("expected a quoted string"));
+        };
+        let mut out = String::new();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => out.extend(chars.next().map(|(_, c)| c)),
+                c if c == quote => {
+                    self.pos += offset + c.len_utf8();
+                    return Ok(out);
+                }
+                c => out.push(c),
+            }
+        }
+        self.pos = start;
+        Err(self.error("unterminated string"))
+    }
+}
+
+/// Whether `c` ends the path of a filter
+fn is_filter_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ']' | ')' | '=' | '!' | '<' | '>')
+}
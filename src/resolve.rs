@@ -0,0 +1,218 @@
+use crate::{Timestamp, Yaml};
+
+/// Decides what a plain (unquoted) scalar means: null, a boolean, a number,
+/// or a string. Quoted scalars and block scalars are always strings and are
+/// never passed to a resolver.
+///
+/// Set a resolver with [`ParseOptions::resolver`](crate::ParseOptions::resolver)
+/// to recognize more (or fewer) forms than the default [`Yaml11Schema`].
+pub trait ScalarResolver {
+    /// Resolve `plain`, returning `Yaml::Scalar(plain)` to keep it a string
+    fn resolve<'a>(&self, plain: &'a str) -> Yaml<'a>;
+}
+
+/// The resolution rules used unless another resolver is set, close to
+/// YAML 1.1: `~`, `null`, `Null` and `NULL` are null; `true`,
+/// `yes` and `on` and their opposites (in any case) are booleans; and
+/// anything Rust parses as an integer, an integer with a `0x`, `0o` or `0b`
+/// prefix, a float containing `.`, `e` or `E`, `.inf`, `-.inf` or `.nan` is
+/// a number; and dates and times such as `2024-06-01T12:00:00Z` are
+/// timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Yaml11Schema;
+
+impl ScalarResolver for Yaml11Schema {
+    fn resolve<'a>(&self, plain: &'a str) -> Yaml<'a> {
+        if matches!(plain, "~" | "null" | "Null" | "NULL") {
+            return Yaml::Null;
+        }
+        match plain.to_lowercase().as_str() {
+            "true" | "yes" | "on" => return Yaml::Bool(true),
+            "false" | "no" | "off" => return Yaml::Bool(false),
+            _ => {}
+        }
+        if let Some(i) = parse_int(plain) {
+            return Yaml::Int(i);
+        }
+        if let Some(f) = special_float(plain) {
+            return Yaml::Float(f);
+        }
+        if plain.contains(['.', 'e', 'E']) {
+            if let Ok(f) = plain.parse::<f64>() {
+                return Yaml::Float(f);
+            }
+        }
+        if let Some(ts) = Timestamp::parse(plain) {
+            return Yaml::Timestamp(ts);
+        }
+        Yaml::Scalar(plain)
+    }
+}
+
+/// The YAML 1.2 core schema: `null`, `Null`, `NULL` and `~` are null; only
+/// `true` and `false` (lower case, capitalized or upper case) are booleans;
+/// integers may be decimal, `0o` octal or `0x` hexadecimal; and floats
+/// include `.inf`, `-.inf` and `.nan`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoreSchema;
+
+impl ScalarResolver for CoreSchema {
+    fn resolve<'a>(&self, plain: &'a str) -> Yaml<'a> {
+        match plain {
+            "null" | "Null" | "NULL" | "~" => return Yaml::Null,
+            "true" | "True" | "TRUE" => return Yaml::Bool(true),
+            "false" | "False" | "FALSE" => return Yaml::Bool(false),
+            _ => {}
+        }
+        if let Some(f) = special_float(plain) {
+            return Yaml::Float(f);
+        }
+        let unsigned = split_sign(plain).1;
+        let radix = |prefix: &str, radix: u32| {
+            let digits = plain.strip_prefix(prefix)?;
+            let valid = !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix));
+            valid.then(|| i64::from_str_radix(digits, radix).ok())?
+        };
+        if let Some(i) = radix("0o", 8).or_else(|| radix("0x", 16)) {
+            return Yaml::Int(i);
+        }
+        if is_digits(unsigned) {
+            if let Ok(i) = plain.parse::<i64>() {
+                return Yaml::Int(i);
+            }
+        }
+        if is_float(unsigned, true) {
+            if let Ok(f) = plain.parse::<f64>() {
+                return Yaml::Float(f);
+            }
+        }
+        Yaml::Scalar(plain)
+    }
+}
+
+/// The YAML 1.2 JSON schema: only `null`, `true`, `false` and numbers
+/// written as JSON would write them are resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonSchema;
+
+impl ScalarResolver for JsonSchema {
+    fn resolve<'a>(&self, plain: &'a str) -> Yaml<'a> {
+        match plain {
+            "null" => return Yaml::Null,
+            "true" => return Yaml::Bool(true),
+            "false" => return Yaml::Bool(false),
+            _ => {}
+        }
+        let unsigned = plain.strip_prefix('-').unwrap_or(plain);
+        // JSON numbers have no leading zeros
+        let int_part = unsigned.split(['.', 'e', 'E']).next().unwrap_or("");
+        if int_part.len() > 1 && int_part.starts_with('0') {
+            return Yaml::Scalar(plain);
+        }
+        if is_digits(unsigned) {
+            // `-0` keeps its sign, as JSON readers do
+            if plain.len() > unsigned.len() && unsigned.bytes().all(|b| b == b'0') {
+                return Yaml::Float(-0.0);
+            }
+            // an integer too large for `i64` is still a JSON number
+            return plain.parse::<i64>().map_or_else(
+                |_| {
+                    plain
+                        .parse::<f64>()
+                        .map_or(Yaml::Scalar(plain), Yaml::Float)
+                },
+                Yaml::Int,
+            );
+        }
+        if is_float(unsigned, false) {
+            if let Ok(f) = plain.parse::<f64>() {
+                return Yaml::Float(f);
+            }
+        }
+        Yaml::Scalar(plain)
+    }
+}
+
+/// The built-in resolution rules, for picking one with
+/// [`ParseOptions::schema`](crate::ParseOptions::schema)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Schema {
+    /// [`Yaml11Schema`], which also reads `yes`/`no` and `on`/`off` as
+    /// booleans
+    #[default]
+    Yaml11,
+    /// [`CoreSchema`], the strict YAML 1.2 rules, which leave strings such
+    /// as `NO` or `on` alone
+    Core,
+    /// [`JsonSchema`]
+    Json,
+}
+
+impl ScalarResolver for Schema {
+    fn resolve<'a>(&self, plain: &'a str) -> Yaml<'a> {
+        match self {
+            Schema::Yaml11 => Yaml11Schema.resolve(plain),
+            Schema::Core => CoreSchema.resolve(plain),
+            Schema::Json => JsonSchema.resolve(plain),
+        }
+    }
+}
+
+/// Parse `.nan`, or an optionally signed `.inf`, in any of the spellings
+/// YAML allows
+fn special_float(s: &str) -> Option<f64> {
+    if matches!(s, ".nan" | ".NaN" | ".NAN") {
+        return Some(f64::NAN);
+    }
+    let (negative, unsigned) = split_sign(s);
+    matches!(unsigned, ".inf" | ".Inf" | ".INF").then_some(if negative {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    })
+}
+
+/// Parse an optionally signed integer written in decimal, or in
+/// hexadecimal, octal or binary with a `0x`, `0o` or `0b` prefix
+pub(crate) fn parse_int(s: &str) -> Option<i64> {
+    if let Ok(i) = s.parse::<i64>() {
+        return Some(i);
+    }
+    let (negative, unsigned) = split_sign(s);
+    let (digits, radix) = [("0x", 16), ("0o", 8), ("0b", 2)]
+        .into_iter()
+        .find_map(|(prefix, radix)| Some((unsigned.strip_prefix(prefix)?, radix)))?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let magnitude = i128::from_str_radix(digits, radix).ok()?;
+    i64::try_from(if negative { -magnitude } else { magnitude }).ok()
+}
+
+fn split_sign(s: &str) -> (bool, &str) {
+    match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    }
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Whether an unsigned number matches `digits [. digits*] [exponent]`, or
+/// also `. digits [exponent]` if `leading_dot` is set
+fn is_float(s: &str, leading_dot: bool) -> bool {
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(idx) => (&s[..idx], Some(&s[idx + 1..])),
+        None => (s, None),
+    };
+    let mantissa_ok = match mantissa.split_once('.') {
+        Some(("", frac)) => leading_dot && is_digits(frac),
+        Some((int, frac)) => is_digits(int) && (frac.is_empty() || is_digits(frac)),
+        None => is_digits(mantissa),
+    };
+    let exponent_ok = exponent.is_none_or(|exp| is_digits(split_sign(exp).1));
+    mantissa_ok && exponent_ok
+}
@@ -0,0 +1,162 @@
+use crate::base64;
+use crate::errors::SanitizeError;
+use crate::path::{Path, PathSegment};
+use crate::{parse_with_options, Dialect, EmitOptions, Entry, ParseOptions, Yaml};
+
+/// What [`sanitize`] lets through. The default policy strips tags, expands
+/// aliases, rejects mappings with collection keys and sets no size limits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanitizePolicy {
+    /// Keep tags in the output; otherwise tagged nodes are replaced by their
+    /// untagged values and binary data is written as a base64 string
+    pub keep_tags: bool,
+    /// Reject inputs using anchors, aliases or directives, rather than
+    /// writing a copy of the anchored node in place of every alias
+    pub reject_aliases: bool,
+    /// Allow mapping keys which are sequences or mappings, which are written
+    /// as `? key` and which many parsers cannot read
+    pub complex_keys: bool,
+    /// The deepest a node may be nested, with the root at depth 0
+    pub max_depth: Option<usize>,
+    /// The longest a string or binary scalar may be, in bytes
+    pub max_scalar_len: Option<usize>,
+    /// The most items or entries a collection may hold
+    pub max_items: Option<usize>,
+    /// What to do with a node the policy does not allow
+    pub on_violation: OnViolation,
+}
+
+/// How [`sanitize`] handles a node which breaks its [`SanitizePolicy`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnViolation {
+    /// Fail with a [`SanitizeError::Rejected`] naming the node
+    #[default]
+    Reject,
+    /// Leave the node out: items and entries are dropped, collections with
+    /// too many items keep the first ones, and a root which is not allowed
+    /// becomes null
+    Remove,
+}
+
+/// Parse `input` and print it again as plain block Yaml holding only what
+/// `policy` allows, for passing documents from untrusted sources on to
+/// other parsers. The output has no anchors, aliases, comments or
+/// directives, and strings are quoted wherever another parser could read
+/// them as something else, following [`Dialect::PyYamlDefault`], which also
+/// quotes dates and base 60 numbers.
+/// # Errors
+/// Returns `Err` if the input is invalid Yaml, or if a node breaks the
+/// policy and [`SanitizePolicy::on_violation`] is [`OnViolation::Reject`]
+pub fn sanitize(input: &str, policy: &SanitizePolicy) -> Result<String, SanitizeError> {
+    let options = ParseOptions {
+        reject_unsupported: policy.reject_aliases,
+        ..ParseOptions::default()
+    };
+    let doc = parse_with_options(input, &options)?;
+    let clean = policy
+        .clean(doc, &mut Path::default(), 0)?
+        .unwrap_or(Yaml::Null);
+    Ok(clean.emit(&EmitOptions {
+        dialect: Dialect::PyYamlDefault,
+        ..EmitOptions::default()
+    }))
+}
+
+impl SanitizePolicy {
+    /// The node with everything the policy does not allow taken out, or
+    /// `None` if the node itself is to be left out
+    fn clean<'a>(
+        &self,
+        node: Yaml<'a>,
+        path: &mut Path,
+        depth: usize,
+    ) -> Result<Option<Yaml<'a>>, SanitizeError> {
+        if self.max_depth.is_some_and(|max| depth > max) {
+            return self.violation(path, "too deeply nested");
+        }
+        match node {
+            Yaml::Tagged(tag, value) if self.keep_tags => Ok(self
+                .clean(*value, path, depth)?
+                .map(|value| Yaml::Tagged(tag, Box::new(value)))),
+            Yaml::Tagged(_, value) => self.clean(*value, path, depth),
+            Yaml::Scalar(s) if self.too_long(s.len()) => self.violation(path, "scalar too long"),
+            Yaml::String(s) if self.too_long(s.len()) => self.violation(path, "scalar too long"),
+            Yaml::Bytes(bytes) if self.too_long(bytes.len()) => {
+                self.violation(path, "scalar too long")
+            }
+            Yaml::Bytes(bytes) if !self.keep_tags => Ok(Some(Yaml::String(base64::encode(&bytes)))),
+            Yaml::Sequence(items) => {
+                let items = self.limit(items, path)?;
+                let mut clean = Vec::with_capacity(items.len());
+                for (idx, item) in items.into_iter().enumerate() {
+                    path.push(PathSegment::Index(idx));
+                    let item = self.clean(item, path, depth + 1)?;
+                    path.pop();
+                    clean.extend(item);
+                }
+                Ok(Some(Yaml::Sequence(clean)))
+            }
+            Yaml::Mapping(entries) => {
+                let entries = self.limit(entries, path)?;
+                let mut clean = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    path.push(PathSegment::Key(entry.key.key_string()));
+                    let entry = self.clean_entry(entry, path, depth + 1)?;
+                    path.pop();
+                    clean.extend(entry);
+                }
+                Ok(Some(Yaml::Mapping(clean)))
+            }
+            other => Ok(Some(other)),
+        }
+    }
+
+    /// The entry with its key and value cleaned, or `None` if either is to
+    /// be left out
+    fn clean_entry<'a>(
+        &self,
+        entry: Entry<'a>,
+        path: &mut Path,
+        depth: usize,
+    ) -> Result<Option<Entry<'a>>, SanitizeError> {
+        if !self.complex_keys
+            && matches!(entry.key.untagged(), Yaml::Sequence(_) | Yaml::Mapping(_))
+        {
+            return self.violation(path, "collection used as a mapping key");
+        }
+        let Some(key) = self.clean(entry.key, path, depth)? else {
+            return Ok(None);
+        };
+        let Some(value) = self.clean(entry.value, path, depth)? else {
+            return Ok(None);
+        };
+        Ok(Some(Entry::new(key, value)))
+    }
+
+    fn too_long(&self, len: usize) -> bool {
+        self.max_scalar_len.is_some_and(|max| len > max)
+    }
+
+    /// The items of a collection, cut down to `max_items` if the policy
+    /// removes what it does not allow
+    fn limit<T>(&self, mut items: Vec<T>, path: &Path) -> Result<Vec<T>, SanitizeError> {
+        match self.max_items {
+            Some(max) if items.len() > max => {
+                self.violation::<()>(path, "too many items")?;
+                items.truncate(max);
+                Ok(items)
+            }
+            _ => Ok(items),
+        }
+    }
+
+    fn violation<T>(&self, path: &Path, reason: &str) -> Result<Option<T>, SanitizeError> {
+        match self.on_violation {
+            OnViolation::Reject => Err(SanitizeError::Rejected {
+                path: path.to_string(),
+                reason: reason.to_string(),
+            }),
+            OnViolation::Remove => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,435 @@
+use crate::{Dialect, EmitOptions, Entry, SerdeError, Timestamp, Yaml, YamlOwned};
+use serde::ser::{self, Serialize, SerializeMap, SerializeTuple};
+use std::fmt::Display;
+
+/// Serialize a value as Yaml text, quoting strings which would otherwise
+/// read back as another type (the [`Dialect::SerdeYaml`] conventions)
+///
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Server<'a> {
+///     host: &'a str,
+///     port: u16,
+/// }
+///
+/// let text = mini_yaml_rs::to_string(&Server { host: "localhost", port: 8080 }).unwrap();
+/// assert_eq!(text, "host: localhost\nport: 8080\n");
+/// ```
+/// # Errors
+/// Returns `Err` if `value`'s `Serialize` implementation fails, or it has
+/// an integer out of the range of Yaml's 64-bit integers
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    to_string_with_options(
+        value,
+        &EmitOptions {
+            dialect: Dialect::SerdeYaml,
+            ..EmitOptions::default()
+        },
+    )
+}
+
+/// Serialize a value as Yaml text printed with `opts`
+/// # Errors
+/// Returns `Err` if `value` cannot be serialized, as for [`to_string`]
+pub fn to_string_with_options<T: Serialize + ?Sized>(
+    value: &T,
+    opts: &EmitOptions,
+) -> Result<String, SerdeError> {
+    Ok(to_yaml(value)?.emit(opts))
+}
+
+/// Serialize a value into a Yaml tree. Enum variants with content become
+/// values tagged with the variant's name (`!Circle 1.5`), which
+/// [`from_str`](crate::from_str) reads back.
+/// # Errors
+/// Returns `Err` if `value` cannot be serialized, as for [`to_string`]
+pub fn to_yaml<T: Serialize + ?Sized>(value: &T) -> Result<YamlOwned, SerdeError> {
+    value.serialize(Serializer)
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Invalid {
+            path: String::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// A Yaml value is serialized as the data it holds: a string, number,
+/// boolean, unit for null, bytes, sequence or map (with keys serialized as
+/// values, which formats allowing only string keys may reject). A timestamp
+/// is serialized as its ISO 8601 string. A tagged value is serialized as a
+/// map whose first entry is `__type: tag`, followed by the entries of a
+/// tagged mapping or `__value: value` for anything else, as in `to_json`.
+impl Serialize for Yaml<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Yaml::Scalar(s) => serializer.serialize_str(s),
+            Yaml::String(s) => serializer.serialize_str(s),
+            Yaml::Int(i) => serializer.serialize_i64(*i),
+            Yaml::Float(f) => serializer.serialize_f64(*f),
+            Yaml::Bool(b) => serializer.serialize_bool(*b),
+            Yaml::Null => serializer.serialize_unit(),
+            Yaml::Timestamp(ts) => ts.serialize(serializer),
+            Yaml::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Yaml::Sequence(items) => serializer.collect_seq(items),
+            Yaml::Mapping(entries) => {
+                serializer.collect_map(entries.iter().map(|entry| (&entry.key, &entry.value)))
+            }
+            Yaml::Tagged(tag, value) => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("__type", tag)?;
+                match &**value {
+                    Yaml::Mapping(entries) => {
+                        for entry in entries {
+                            map.serialize_entry(&entry.key, &entry.value)?;
+                        }
+                    }
+                    value => map.serialize_entry("__value", value)?,
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// An entry is serialized as a `(key, value)` tuple
+impl Serialize for Entry<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.key)?;
+        tuple.serialize_element(&self.value)?;
+        tuple.end()
+    }
+}
+
+/// A timestamp is serialized as its ISO 8601 string
+impl Serialize for Timestamp {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+fn int<T: TryInto<i64> + Display + Copy>(value: T) -> Result<YamlOwned, SerdeError> {
+    value.try_into().map(YamlOwned::Int).map_err(|_| {
+        ser::Error::custom(format!(
+            "integer {value} is out of the range of Yaml integers"
+        ))
+    })
+}
+
+/// Builds a Yaml tree from a serializable value
+struct Serializer;
+
+macro_rules! serialize_int {
+    ($($method: ident: $ty: ty)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<YamlOwned, SerdeError> {
+                int(value)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+    type SerializeSeq = Sequence;
+    type SerializeTuple = Sequence;
+    type SerializeTupleStruct = Sequence;
+    type SerializeTupleVariant = Sequence;
+    type SerializeMap = Mapping;
+    type SerializeStruct = Mapping;
+    type SerializeStructVariant = Mapping;
+
+    serialize_int! {
+        serialize_i8: i8 serialize_i16: i16 serialize_i32: i32 serialize_i64: i64
+        serialize_i128: i128 serialize_u8: u8 serialize_u16: u16 serialize_u32: u32
+        serialize_u64: u64 serialize_u128: u128
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Bool(value))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Float(value.into()))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Float(value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::String(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::String(value.to_string()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Bytes(value.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<YamlOwned, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::Scalar(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<YamlOwned, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<YamlOwned, SerdeError> {
+        Ok(YamlOwned::tagged(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Sequence, SerdeError> {
+        Ok(Sequence {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Sequence, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Sequence, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Sequence, SerdeError> {
+        Ok(Sequence {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Mapping, SerdeError> {
+        Ok(Mapping {
+            variant: None,
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Mapping, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Mapping, SerdeError> {
+        Ok(Mapping {
+            variant: Some(variant),
+            entries: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+}
+
+/// A sequence being serialized, tagged with the variant it is the content
+/// of, if any
+struct Sequence {
+    variant: Option<&'static str>,
+    items: Vec<YamlOwned>,
+}
+
+impl Sequence {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> YamlOwned {
+        let value = YamlOwned::Sequence(self.items);
+        match self.variant {
+            Some(variant) => YamlOwned::tagged(variant, value),
+            None => value,
+        }
+    }
+}
+
+impl ser::SerializeSeq for Sequence {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for Sequence {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for Sequence {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for Sequence {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+/// A mapping being serialized, tagged with the variant it is the content
+/// of, if any
+struct Mapping {
+    variant: Option<&'static str>,
+    entries: Vec<Entry<'static>>,
+    /// the key whose value is next
+    key: Option<YamlOwned>,
+}
+
+impl Mapping {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        key: YamlOwned,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = value.serialize(Serializer)?;
+        self.entries.push(Entry::new(key, value));
+        Ok(())
+    }
+
+    fn finish(self) -> YamlOwned {
+        let value = YamlOwned::Mapping(self.entries);
+        match self.variant {
+            Some(variant) => YamlOwned::tagged(variant, value),
+            None => value,
+        }
+    }
+}
+
+impl ser::SerializeMap for Mapping {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("value serialized before its key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for Mapping {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(YamlOwned::Scalar(key), value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for Mapping {
+    type Ok = YamlOwned;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.insert(YamlOwned::Scalar(key), value)
+    }
+
+    fn end(self) -> Result<YamlOwned, SerdeError> {
+        Ok(self.finish())
+    }
+}
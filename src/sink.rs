@@ -0,0 +1,165 @@
+use crate::{base64, BytesFormat, Entry, JsonOptions, MxOptions, TimestampJson, Yaml};
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Receives a JSON value piece by piece from [`Yaml::to_json_visit`], with
+/// keys and strings borrowed from the tree rather than copied into a
+/// `serde_json::Value`, e.g. for writing JSON text or building another
+/// representation directly.
+///
+/// Arrays and objects are given as `start_*` with the number of items, the
+/// items (for objects, each preceded by its key), then `end_*`.
+pub trait JsonSink {
+    fn null(&mut self);
+    fn bool(&mut self, value: bool);
+    fn int(&mut self, value: i64);
+    /// Only called with finite numbers; JSON has no infinities or NaN,
+    /// which are given as `int(0)`
+    fn float(&mut self, value: f64);
+    fn str(&mut self, value: &str);
+    fn start_array(&mut self, len: usize);
+    fn end_array(&mut self);
+    fn start_object(&mut self, len: usize);
+    fn key(&mut self, key: &str);
+    fn end_object(&mut self);
+}
+
+/// Pass `node` to `sink` as [`Yaml::to_json_with_options`] converts it
+pub(crate) fn visit(node: &Yaml<'_>, sink: &mut dyn JsonSink, opts: &JsonOptions) {
+    match node {
+        Yaml::Scalar(s) => sink.str(s),
+        Yaml::String(s) => sink.str(s),
+        Yaml::Int(i) => sink.int(*i),
+        Yaml::Float(f) if f.is_finite() => sink.float(*f),
+        Yaml::Float(_) => sink.int(0),
+        Yaml::Bool(b) => sink.bool(*b),
+        Yaml::Null => sink.null(),
+        Yaml::Timestamp(ts) => match opts.timestamps {
+            TimestampJson::Iso8601 => sink.str(&ts.to_string()),
+            TimestampJson::Rfc3339 => sink.str(&ts.to_rfc3339()),
+            TimestampJson::EpochMillis => sink.int(ts.unix_millis()),
+        },
+        Yaml::Bytes(bytes) => match opts.bytes {
+            BytesFormat::Base64 => sink.str(&base64::encode(bytes)),
+            BytesFormat::Array => {
+                sink.start_array(bytes.len());
+                for &byte in bytes {
+                    sink.int(byte.into());
+                }
+                sink.end_array();
+            }
+        },
+        Yaml::Sequence(items) => {
+            sink.start_array(items.len());
+            for item in items {
+                visit(item, sink, opts);
+            }
+            sink.end_array();
+        }
+        Yaml::Mapping(entries) => {
+            sink.start_object(entries.len());
+            visit_entries(entries, sink, opts);
+            sink.end_object();
+        }
+        Yaml::Tagged(tag, value) => {
+            sink.start_object(match &**value {
+                Yaml::Mapping(entries) => entries.len() + 1,
+                _ => 2,
+            });
+            sink.key("__type");
+            sink.str(tag);
+            match &**value {
+                Yaml::Mapping(entries) => visit_entries(entries, sink, opts),
+                value => {
+                    sink.key("__value");
+                    visit(value, sink, opts);
+                }
+            }
+            sink.end_object();
+        }
+    }
+}
+
+fn visit_entries(entries: &[Entry<'_>], sink: &mut dyn JsonSink, opts: &JsonOptions) {
+    for entry in entries {
+        sink.key(&key_str(&entry.key));
+        visit(&entry.value, sink, opts);
+    }
+}
+
+/// The key of an entry, borrowed if it is a string
+fn key_str<'y>(key: &'y Yaml<'_>) -> Cow<'y, str> {
+    match key {
+        Yaml::Scalar(s) => Cow::Borrowed(s),
+        Yaml::String(s) => Cow::Borrowed(s),
+        key => Cow::Owned(key.key_string()),
+    }
+}
+
+/// Pass `node` to `sink` as [`Yaml::to_mx_with_options`] converts it
+pub(crate) fn visit_mx(node: &Yaml<'_>, sink: &mut dyn JsonSink, opts: &MxOptions) {
+    let json = JsonOptions::default();
+    let Yaml::Mapping(entries) = node else {
+        // a top level scalar key, or an error: small values either way
+        return visit_value(&node.to_mx_with_options(opts), sink);
+    };
+    let mut keys = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match Yaml::parse_mx_key(&key_str(&entry.key)) {
+            Some(key) => keys.push(key),
+            None => return visit_value(&node.to_mx_with_options(opts), sink),
+        }
+    }
+    sink.start_object(entries.len());
+    for (entry, (name, label, value)) in entries.iter().zip(keys) {
+        sink.key(&format!("+{name}"));
+        let extra = 1 + usize::from(value.is_some());
+        match &entry.value {
+            Yaml::Mapping(fields) => {
+                sink.start_object(fields.len() + extra);
+                visit_entries(fields, sink, &json);
+            }
+            other => {
+                sink.start_object(1 + extra);
+                sink.key("__content");
+                visit(other, sink, &json);
+            }
+        }
+        sink.key("__name");
+        sink.str(&label);
+        if let Some(value) = value {
+            sink.key("__value");
+            sink.str(&value);
+        }
+        sink.end_object();
+    }
+    sink.end_object();
+}
+
+/// Pass an already converted value to `sink`
+fn visit_value(value: &Value, sink: &mut dyn JsonSink) {
+    match value {
+        Value::Null => sink.null(),
+        Value::Bool(b) => sink.bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => sink.int(i),
+            None => sink.float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => sink.str(s),
+        Value::Array(items) => {
+            sink.start_array(items.len());
+            for item in items {
+                visit_value(item, sink);
+            }
+            sink.end_array();
+        }
+        Value::Object(map) => {
+            sink.start_object(map.len());
+            for (key, value) in map {
+                sink.key(key);
+                visit_value(value, sink);
+            }
+            sink.end_object();
+        }
+    }
+}
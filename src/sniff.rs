@@ -0,0 +1,49 @@
+use crate::stream::has_several_documents;
+
+/// What kind of text an input looks like, as guessed by [`sniff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    /// a single Yaml document
+    Yaml,
+    /// a JSON object or array, which [`parse`](crate::parse) also reads
+    Json,
+    /// a stream of several Yaml documents, for
+    /// [`parse_all`](crate::parse_all)
+    MultiDocYaml,
+    /// nothing but whitespace, or text which is not Yaml at all
+    Unknown,
+}
+
+/// Guess what kind of text `input` is without parsing it, for loaders which
+/// accept Yaml or JSON and dispatch on the format.
+///
+/// Input is JSON if it starts with `{` followed by a quoted key (or `}`), or
+/// with `[`, and ends with the matching bracket. Only document markers are
+/// looked at beyond that, so the guess says nothing about whether the input
+/// is valid.
+#[must_use]
+pub fn sniff(input: &str) -> InputKind {
+    let text = input.trim_start_matches('\u{feff}').trim();
+    if text.is_empty() || text.contains('\0') {
+        return InputKind::Unknown;
+    }
+    if looks_like_json(text) {
+        InputKind::Json
+    } else if has_several_documents(text) {
+        InputKind::MultiDocYaml
+    } else {
+        InputKind::Yaml
+    }
+}
+
+fn looks_like_json(text: &str) -> bool {
+    let (open, close) = match text.as_bytes()[0] {
+        b'{' => ('{', '}'),
+        b'[' => ('[', ']'),
+        _ => return false,
+    };
+    let inner = text[1..].trim_start();
+    // `{a: 1}` is a Yaml flow mapping; JSON keys are quoted
+    let opens_like_json = open == '[' || inner.starts_with(['"', '}']);
+    opens_like_json && text.ends_with(close)
+}
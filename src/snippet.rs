@@ -0,0 +1,93 @@
+/// Whether `c` extends the grapheme cluster before it rather than starting a
+/// new one: combining marks, variation selectors, emoji modifiers, tag
+/// characters and the zero width joiner
+fn is_extend(c: char) -> bool {
+    matches!(
+        u32::from(c),
+        0x0300..=0x036F // combining diacritical marks
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0900..=0x0903 // devanagari signs
+            | 0x093A..=0x094F
+            | 0x1160..=0x11FF // hangul jungseong and jongseong
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C..=0x200D // zero width (non-)joiner
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F // variation selectors
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF // emoji skin tone modifiers
+            | 0xE0020..=0xE007F // emoji tag sequences
+            | 0xE0100..=0xE01EF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(u32::from(c), 0x1F1E6..=0x1F1FF)
+}
+
+/// The byte offsets at which grapheme clusters start in `text`, followed by
+/// `text.len()`. Clusters are approximated: a character joins the previous
+/// cluster if it is a combining mark or modifier, follows a zero width
+/// joiner, or completes a pair of regional indicators (a flag).
+fn cluster_bounds(text: &str) -> Vec<usize> {
+    let mut bounds = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut regional_run = 0;
+    for (idx, c) in text.char_indices() {
+        let joins = match prev {
+            None => false,
+            Some('\r') => c == '\n',
+            Some('\u{200D}') => true,
+            Some(p) if is_regional_indicator(p) && is_regional_indicator(c) => {
+                regional_run % 2 == 1
+            }
+            Some(_) => is_extend(c),
+        };
+        regional_run = if is_regional_indicator(c) {
+            regional_run + 1
+        } else {
+            0
+        };
+        if !joins {
+            bounds.push(idx);
+        }
+        prev = Some(c);
+    }
+    bounds.push(text.len());
+    bounds
+}
+
+/// Prepare user content for embedding in an error message. Control
+/// characters are removed, except line feeds and tabs when `multiline` is
+/// set; otherwise line breaks and tabs become spaces. The result is then cut
+/// to at most `limit` approximate grapheme clusters (see [`cluster_bounds`]),
+/// counting the `…` that ends it if anything was cut. A limit of zero gives
+/// an empty string.
+pub(crate) fn snippet(text: &str, limit: Option<usize>, multiline: bool) -> String {
+    let clean: String = text
+        .chars()
+        .filter_map(|c| match c {
+            '\n' | '\t' if multiline => Some(c),
+            '\r' if multiline => None,
+            '\n' | '\t' | '\r' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect();
+    let Some(limit) = limit else {
+        return clean;
+    };
+    if limit == 0 {
+        return String::new();
+    }
+    let bounds = cluster_bounds(&clean);
+    // bounds holds one entry per cluster plus the end offset
+    if bounds.len() <= limit + 1 {
+        return clean;
+    }
+    let cut = bounds[limit - 1];
+    format!("{}…", &clean[..cut])
+}
@@ -0,0 +1,239 @@
+use crate::document::Document;
+use crate::path::{Path, PathSegment};
+use crate::span::{LineIndex, Span};
+use crate::{JsonOptions, MxOptions, Yaml};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Where a value in the JSON converted from a document came from in the
+/// source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceRange {
+    /// The byte range of the node in the source
+    pub span: Span,
+    /// The 1-based line and column (in characters) of the start of the node
+    pub start: (usize, usize),
+    /// The 1-based line and column (in characters) just past the end of the
+    /// node
+    pub end: (usize, usize),
+    /// The byte range of the node's key, for mapping values
+    pub key: Option<Span>,
+}
+
+/// The source location of each value in the JSON converted from a
+/// document, by JSON pointer (`/spec/containers/0/image`, `""` for the
+/// root), so that errors found in the JSON, such as by a JSON Schema
+/// validator, can be reported in the Yaml source.
+///
+/// Values with no place of their own in the source, such as the content of
+/// an alias, have no entry; [`SourceMap::find`] gives the nearest enclosing
+/// value which has one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// pointers and their ranges, in document order
+    entries: Vec<(String, SourceRange)>,
+    index: HashMap<String, usize>,
+}
+
+impl SourceMap {
+    /// The source range of the value at a JSON pointer
+    #[must_use]
+    pub fn get(&self, pointer: &str) -> Option<&SourceRange> {
+        self.index.get(pointer).map(|&idx| &self.entries[idx].1)
+    }
+
+    /// The source range of the value at a JSON pointer, or if it has none
+    /// of the nearest value enclosing it which does, with that value's
+    /// pointer
+    #[must_use]
+    pub fn find<'m>(&'m self, pointer: &'m str) -> Option<(&'m str, &'m SourceRange)> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(range) = self.get(pointer) {
+                return Some((pointer, range));
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+
+    /// Every pointer and its source range, in document order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SourceRange)> {
+        self.entries
+            .iter()
+            .map(|(pointer, range)| (pointer.as_str(), range))
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn insert(&mut self, pointer: String, range: SourceRange) {
+        self.index.insert(pointer.clone(), self.entries.len());
+        self.entries.push((pointer, range));
+    }
+}
+
+impl Document<'_> {
+    /// Convert the document to JSON like [`Yaml::to_json_with_options`],
+    /// along with the source location of each value in it
+    #[must_use]
+    pub fn to_json_with_source_map(&self, opts: &JsonOptions) -> (Value, SourceMap) {
+        let lines = LineIndex::new(self.source());
+        let mut map = SourceMap::default();
+        self.map_node(
+            self.root(),
+            &mut Path::default(),
+            &mut String::new(),
+            &lines,
+            &mut map,
+        );
+        (self.root().to_json_with_options(opts), map)
+    }
+
+    /// Convert the document to mx JSON like [`Yaml::to_mx_with_options`],
+    /// along with the source location of each value in it. The map is
+    /// empty if the document is not in the mx format.
+    #[must_use]
+    pub fn to_mx_with_source_map(&self, opts: &MxOptions) -> (Value, SourceMap) {
+        let json = self.root().to_mx_with_options(opts);
+        let mut map = SourceMap::default();
+        let Yaml::Mapping(entries) = self.root() else {
+            return (json, map);
+        };
+        let mut names = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match Yaml::parse_mx_key(&entry.key.key_string()) {
+                Some((name, ..)) => names.push(name),
+                None => return (json, map),
+            }
+        }
+        let lines = LineIndex::new(self.source());
+        self.map_path(&Path::default(), String::new(), &lines, &mut map);
+        for (entry, name) in entries.iter().zip(names) {
+            let mut path = Path::default();
+            path.push(PathSegment::Key(entry.key.key_string()));
+            // a value which is not an object is wrapped in one
+            let mut pointer = format!("/{}", escape(&format!("+{name}")));
+            if !matches!(entry.value, Yaml::Mapping(_)) {
+                self.map_path(&path, pointer.clone(), &lines, &mut map);
+                pointer.push_str("/__content");
+            }
+            self.map_node(&entry.value, &mut path, &mut pointer, &lines, &mut map);
+        }
+        (json, map)
+    }
+
+    /// Add the ranges of `node` at `path` and everything below it, which
+    /// are at `pointer` in the JSON
+    fn map_node(
+        &self,
+        node: &Yaml<'_>,
+        path: &mut Path,
+        pointer: &mut String,
+        lines: &LineIndex<'_>,
+        map: &mut SourceMap,
+    ) {
+        self.map_path(path, pointer.clone(), lines, map);
+        self.map_children(node, path, pointer, lines, map);
+    }
+
+    /// Add the ranges of everything below `node`
+    fn map_children(
+        &self,
+        node: &Yaml<'_>,
+        path: &mut Path,
+        pointer: &mut String,
+        lines: &LineIndex<'_>,
+        map: &mut SourceMap,
+    ) {
+        let len = pointer.len();
+        match node {
+            // a tagged mapping's entries follow its `__type`, while other
+            // tagged values are under `__value`, with the node's span
+            Yaml::Tagged(_, value) if matches!(**value, Yaml::Mapping(_)) => {
+                self.map_children(value, path, pointer, lines, map);
+            }
+            Yaml::Tagged(_, value) => {
+                pointer.push_str("/__value");
+                self.map_node(value, path, pointer, lines, map);
+            }
+            Yaml::Mapping(entries) => {
+                for entry in entries {
+                    let key = entry.key.key_string();
+                    pointer.push('/');
+                    pointer.push_str(&escape(&key));
+                    path.push(PathSegment::Key(key));
+                    self.map_node(&entry.value, path, pointer, lines, map);
+                    path.pop();
+                    pointer.truncate(len);
+                }
+            }
+            Yaml::Sequence(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    pointer.push('/');
+                    pointer.push_str(&idx.to_string());
+                    path.push(PathSegment::Index(idx));
+                    self.map_node(item, path, pointer, lines, map);
+                    path.pop();
+                    pointer.truncate(len);
+                }
+            }
+            _ => {}
+        }
+        pointer.truncate(len);
+    }
+
+    fn map_path(&self, path: &Path, pointer: String, lines: &LineIndex<'_>, map: &mut SourceMap) {
+        let Some(node) = self.spans().get(path) else {
+            return;
+        };
+        map.insert(
+            pointer,
+            SourceRange {
+                span: node.span,
+                start: lines.line_col(node.span.start),
+                end: lines.line_col(node.span.end),
+                key: node.key,
+            },
+        );
+    }
+}
+
+/// Escape a key as a JSON pointer reference token
+pub(crate) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// The reference tokens of a JSON pointer, unescaped, or `None` if it is
+/// neither empty nor starts with `/`
+pub(crate) fn tokens(pointer: &str) -> Option<impl Iterator<Item = String> + '_> {
+    let rest = match pointer {
+        "" => None,
+        _ => Some(pointer.strip_prefix('/')?),
+    };
+    Some(
+        rest.into_iter()
+            .flat_map(|rest| rest.split('/'))
+            .map(unescape),
+    )
+}
+
+/// Unescape a JSON pointer reference token
+pub(crate) fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// The sequence index a reference token stands for: digits without a
+/// leading zero
+pub(crate) fn token_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
@@ -0,0 +1,188 @@
+use crate::path::Path;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A range of byte offsets into the parsed source text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// offset of the first byte of the range
+    pub start: usize,
+    /// offset one past the last byte of the range
+    pub end: usize,
+}
+
+impl Span {
+    #[must_use]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The number of bytes covered by the span
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A value together with the span of source text it was parsed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    #[must_use]
+    pub fn new(value: T, span: Span) -> Self {
+        Self { value, span }
+    }
+}
+
+/// The 1-based line and column (in characters) of a byte offset
+pub(crate) fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let line = before.matches('\n').count() + 1;
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// The start of each line of a source text, so that the line and column of
+/// many offsets can be found without rescanning the source for each
+pub(crate) struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The 1-based line and column (in characters) of a byte offset, as
+    /// [`line_col`] gives it
+    pub(crate) fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let start = self.line_starts[line - 1];
+        (line, self.source[start..offset].chars().count() + 1)
+    }
+}
+
+/// The byte offset of a 1-based line and column (in characters), clamped
+/// to the end of the line, or of the source for lines past its end
+pub(crate) fn offset_of(source: &str, line: usize, col: usize) -> usize {
+    let mut start = 0;
+    for _ in 1..line {
+        match source[start..].find('\n') {
+            Some(idx) => start += idx + 1,
+            None => return source.len(),
+        }
+    }
+    let text = &source[start..];
+    let text = &text[..text.find('\n').unwrap_or(text.len())];
+    start
+        + text
+            .char_indices()
+            .nth(col.saturating_sub(1))
+            .map_or(text.len(), |(idx, _)| idx)
+}
+
+/// Converts byte offsets in a source text to UTF-16 code unit offsets, as
+/// JavaScript strings and editors such as Monaco and `CodeMirror` count them.
+/// The source is scanned once, so each conversion after that is a binary
+/// search.
+#[derive(Debug, Clone)]
+pub struct Utf16Index<'a> {
+    source: Cow<'a, str>,
+    /// the byte offset of the start of each line
+    line_starts: Vec<usize>,
+    /// the byte offset after each non-ASCII character, with the number of
+    /// bytes more than UTF-16 code units up to there
+    wide: Vec<(usize, usize)>,
+}
+
+impl<'a> Utf16Index<'a> {
+    #[must_use]
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        let mut wide = Vec::new();
+        let mut extra = 0;
+        for (idx, c) in source.char_indices() {
+            if c == '\n' {
+                line_starts.push(idx + 1);
+            } else if !c.is_ascii() {
+                extra += c.len_utf8() - c.len_utf16();
+                wide.push((idx + c.len_utf8(), extra));
+            }
+        }
+        Self {
+            source: Cow::Borrowed(source),
+            line_starts,
+            wide,
+        }
+    }
+
+    /// The index with its own copy of the source, e.g. to keep it across
+    /// calls from JavaScript
+    #[must_use]
+    pub fn into_owned(self) -> Utf16Index<'static> {
+        Utf16Index {
+            source: Cow::Owned(self.source.into_owned()),
+            line_starts: self.line_starts,
+            wide: self.wide,
+        }
+    }
+
+    /// The UTF-16 offset of the byte offset `offset`. Offsets past the end of
+    /// the source give the length of the source.
+    #[must_use]
+    pub fn offset(&self, offset: usize) -> usize {
+        let offset = offset.min(self.source.len());
+        let before = self.wide.partition_point(|&(end, _)| end <= offset);
+        let extra = before.checked_sub(1).map_or(0, |idx| self.wide[idx].1);
+        offset - extra
+    }
+
+    /// `span` with both ends in UTF-16 code units
+    #[must_use]
+    pub fn span(&self, span: Span) -> Span {
+        Span::new(self.offset(span.start), self.offset(span.end))
+    }
+
+    /// The UTF-16 offset of a 1-based line and column (in characters), as
+    /// given by [`YamlParseError`](crate::YamlParseError) and
+    /// [`Diagnostic`](crate::Diagnostic). Columns past the end of the line
+    /// give the end of the line.
+    #[must_use]
+    pub fn position(&self, line: usize, col: usize) -> usize {
+        let Some(&start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return self.offset(self.source.len());
+        };
+        let text = &self.source[start..];
+        let text = &text[..text.find('\n').unwrap_or(text.len())];
+        let byte = text
+            .char_indices()
+            .nth(col.saturating_sub(1))
+            .map_or(text.len(), |(idx, _)| idx);
+        self.offset(start + byte)
+    }
+}
+
+/// Where a node, and the key it is stored under (if any), were found in the source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct NodeSpan {
+    pub(crate) span: Span,
+    pub(crate) key: Option<Span>,
+}
+
+pub(crate) type SpanTable = HashMap<Path, NodeSpan>;
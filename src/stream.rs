@@ -0,0 +1,356 @@
+use crate::{invalid_input_error, Result, Yaml, YamlOwned};
+
+/// Parse every document in a stream of Yaml documents separated by `---`
+/// (and optionally ended by `...`). Documents with no content are
+/// `Yaml::Null`; a stream with no content has no documents.
+/// # Errors
+/// Returns `Err` for the first document which is invalid Yaml. Line numbers
+/// in the error count from the start of the stream.
+pub fn parse_all(input: &str) -> Result<Vec<Yaml<'_>>> {
+    parse_documents(input).collect()
+}
+
+/// Iterate over the documents in a stream, parsing each one only when it is
+/// reached. See [`parse_all`].
+#[must_use]
+pub fn parse_documents(input: &str) -> Documents<'_> {
+    Documents {
+        input,
+        pos: 0,
+        line: 0,
+    }
+}
+
+/// An iterator over the documents in a Yaml stream, created by
+/// [`parse_documents`]
+#[derive(Debug, Clone)]
+pub struct Documents<'a> {
+    input: &'a str,
+    /// offset of the start of the next line to scan
+    pos: usize,
+    /// number of lines before `pos`
+    line: usize,
+}
+
+impl<'a> Documents<'a> {
+    /// The line starting at `pos`, including its line break
+    fn line_at(&self, pos: usize) -> &'a str {
+        let rest = &self.input[pos..];
+        match rest.find('\n') {
+            Some(idx) => &rest[..=idx],
+            None => rest,
+        }
+    }
+
+    /// Find the next document, returning its text and the number of lines
+    /// before it
+    fn next_document(&mut self) -> Option<(&'a str, usize)> {
+        // skip anything between documents which is not content
+        loop {
+            if self.pos >= self.input.len() {
+                return None;
+            }
+            let line = self.line_at(self.pos);
+            if !is_trivia(line)
+                && !is_marker(line, "...")
+                && !line.trim_start_matches('\u{feff}').starts_with('%')
+            {
+                break;
+            }
+            self.pos += line.len();
+            self.line += 1;
+        }
+        let start = self.pos;
+        let start_line = self.line;
+        let mut first = true;
+        while self.pos < self.input.len() {
+            let line = self.line_at(self.pos);
+            if is_marker(line, "---") && !first {
+                break;
+            }
+            first = false;
+            self.pos += line.len();
+            self.line += 1;
+            if is_marker(line, "...") {
+                return Some((&self.input[start..self.pos - line.len()], start_line));
+            }
+        }
+        Some((&self.input[start..self.pos], start_line))
+    }
+}
+
+impl<'a> Iterator for Documents<'a> {
+    type Item = Result<Yaml<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (text, lines_before) = self.next_document()?;
+        Some(parse_document_text(text, lines_before))
+    }
+}
+
+/// Parse the text of one document of a stream, which starts after
+/// `lines_before` lines
+fn parse_document_text(text: &str, lines_before: usize) -> Result<Yaml<'_>> {
+    let body = if is_marker(text, "---") {
+        &text[3..]
+    } else {
+        text
+    };
+    if body.lines().all(is_trivia) {
+        return Ok(Yaml::Null);
+    }
+    crate::parse(text).map_err(|mut err| {
+        err.line += lines_before;
+        err
+    })
+}
+
+/// Parses a stream of UTF-8 Yaml documents which arrives in chunks, such as
+/// from a socket or a pipe, without waiting for all of it. Each chunk is
+/// given to [`feed`](Self::feed), which returns the documents it completed,
+/// and [`finish`](Self::finish) returns the rest once the input has ended.
+/// A document is complete once the `---` or `...` marker after it has been
+/// read, so a stream of a single document is parsed by `finish`.
+///
+/// Documents are split as by [`parse_documents`], and each one is parsed,
+/// or fails to, on its own: a document holding invalid UTF-8 is returned as
+/// an error, and parsing resumes at the next marker. Line numbers in errors
+/// count from the start of the stream. Only the input after the last
+/// complete document is kept.
+///
+/// ```
+/// use mini_yaml_rs::{StreamParser, Yaml};
+///
+/// let mut parser = StreamParser::new();
+/// assert!(parser.feed(b"a: 1\n--").is_empty());
+/// let done = parser.feed(b"-\nb: 2\n");
+/// assert_eq!(done.len(), 1);
+/// assert_eq!(done[0].as_ref().unwrap()["a"], Yaml::Int(1));
+/// let rest = parser.finish();
+/// assert_eq!(rest[0].as_ref().unwrap()["b"], Yaml::Int(2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamParser {
+    /// the input after the last complete document
+    buffer: Vec<u8>,
+    /// number of bytes before `buffer`
+    offset: usize,
+    /// number of lines before `buffer`
+    line: usize,
+    /// whether the rest of a document which failed is being skipped
+    skipping: bool,
+    /// number of bytes of whole lines at the start of `buffer` which are
+    /// valid UTF-8 and hold no marker ending a document
+    scanned: usize,
+    /// number of bytes at the start of `buffer` already searched for the
+    /// last line break
+    searched: usize,
+}
+
+impl StreamParser {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next chunk of input, returning the documents it completed,
+    /// in order. A chunk may end anywhere, even inside a character.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Result<YamlOwned>> {
+        self.buffer.extend_from_slice(chunk);
+        self.parse_buffer(false)
+    }
+
+    /// End the input, returning the documents which were not yet complete
+    #[must_use]
+    pub fn finish(mut self) -> Vec<Result<YamlOwned>> {
+        self.parse_buffer(true)
+    }
+
+    /// Parse the complete documents in the buffer, which at the end of the
+    /// input are all of them. Only the bytes fed since the last call are
+    /// searched for line breaks and markers, and checked to be UTF-8.
+    fn parse_buffer(&mut self, at_end: bool) -> Vec<Result<YamlOwned>> {
+        let mut parsed = Vec::new();
+        loop {
+            if self.skipping && !self.skip_failed_document(at_end) {
+                return parsed;
+            }
+            // only whole lines are scanned, which never end inside a
+            // character
+            let len = if at_end {
+                self.buffer.len()
+            } else {
+                let Some(idx) = self.buffer[self.searched..]
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                else {
+                    self.searched = self.buffer.len();
+                    return parsed;
+                };
+                self.searched + idx + 1
+            };
+            self.searched = self.buffer.len();
+            match std::str::from_utf8(&self.buffer[self.scanned..len]) {
+                Ok(lines) => {
+                    // a document is only completed by a marker line, or by
+                    // the end of the input
+                    let complete = at_end
+                        || lines
+                            .split_inclusive('\n')
+                            .any(|line| is_marker(line, "---") || is_marker(line, "..."));
+                    self.scanned = len;
+                    if complete {
+                        self.parse_documents(len, at_end, &mut parsed);
+                    }
+                    return parsed;
+                }
+                Err(err) => self.fail_invalid_line(self.scanned + err.valid_up_to(), &mut parsed),
+            }
+        }
+    }
+
+    /// Fail the document holding the invalid UTF-8 `valid` bytes into the
+    /// buffer, after parsing those before it, and skip the rest of it
+    fn fail_invalid_line(&mut self, valid: usize, parsed: &mut Vec<Result<YamlOwned>>) {
+        let error = self.encoding_error(valid);
+        let line_start = self.buffer[..valid]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |idx| idx + 1);
+        let line_end = self.buffer[valid..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(self.buffer.len(), |idx| valid + idx + 1);
+        let before = self.offset;
+        self.parse_documents(line_start, false, parsed);
+        parsed.push(Err(error));
+        self.consume(line_end - (self.offset - before));
+        self.skipping = true;
+        self.scanned = 0;
+        self.searched = 0;
+    }
+
+    /// Parse the documents in the first `len` bytes of the buffer, which
+    /// are valid UTF-8, leaving the last one unless the input is at its end
+    /// or a marker ends it
+    fn parse_documents(&mut self, len: usize, at_end: bool, parsed: &mut Vec<Result<YamlOwned>>) {
+        let Ok(text) = std::str::from_utf8(&self.buffer[..len]) else {
+            return;
+        };
+        let mut documents = Documents {
+            input: text,
+            pos: 0,
+            line: self.line,
+        };
+        let mut consumed = 0;
+        loop {
+            let Some((doc, lines_before)) = documents.next_document() else {
+                // nothing but comments and markers after the last document
+                consumed = documents.pos;
+                break;
+            };
+            let end = doc.as_ptr() as usize - text.as_ptr() as usize + doc.len();
+            // a document running up to the end of the input may go on in
+            // the next chunk, unless a `...` after it was read
+            if !at_end && documents.pos == text.len() && end == text.len() {
+                break;
+            }
+            parsed.push(parse_document_text(doc, lines_before).map(Yaml::into_owned));
+            consumed = documents.pos;
+        }
+        self.consume(consumed);
+    }
+
+    /// Drop the lines of a failed document up to the next marker, returning
+    /// whether it was found
+    fn skip_failed_document(&mut self, at_end: bool) -> bool {
+        loop {
+            let line_len = match self.buffer[self.searched..]
+                .iter()
+                .position(|&b| b == b'\n')
+            {
+                Some(idx) => self.searched + idx + 1,
+                None if at_end => self.buffer.len(),
+                None => {
+                    self.searched = self.buffer.len();
+                    return false;
+                }
+            };
+            if line_len == 0 {
+                return false;
+            }
+            let line = String::from_utf8_lossy(&self.buffer[..line_len]);
+            if is_marker(&line, "---") {
+                self.skipping = false;
+                return true;
+            }
+            let end = is_marker(&line, "...");
+            self.consume(line_len);
+            if end {
+                self.skipping = false;
+                return true;
+            }
+        }
+    }
+
+    /// The error for invalid UTF-8 `valid` bytes into the buffer
+    fn encoding_error(&self, valid: usize) -> crate::YamlParseError {
+        let text = std::str::from_utf8(&self.buffer[..valid]).unwrap_or_default();
+        let mut err = invalid_input_error(text, "UTF-8", self.offset + valid);
+        err.line += self.line;
+        err
+    }
+
+    /// Drop the first `len` bytes of the buffer
+    fn consume(&mut self, len: usize) {
+        self.line += self.buffer.drain(..len).filter(|&b| b == b'\n').count();
+        self.offset += len;
+        self.scanned = self.scanned.saturating_sub(len);
+        self.searched = self.searched.saturating_sub(len);
+    }
+}
+
+/// Whether the stream holds more than one document, found by scanning the
+/// document markers without parsing anything
+pub(crate) fn has_several_documents(input: &str) -> bool {
+    second_document_start(input).is_some()
+}
+
+/// The offset of the second document in the stream, if there is one
+pub(crate) fn second_document_start(input: &str) -> Option<usize> {
+    let mut documents = parse_documents(input);
+    documents.next_document()?;
+    let (text, _) = documents.next_document()?;
+    Some(text.as_ptr() as usize - input.as_ptr() as usize)
+}
+
+/// The length of the first document in `input` if it is ended by a `...`
+/// marker with nothing but comments and more markers after it, as `PyYAML`
+/// writes a document holding a single scalar; otherwise the whole length
+pub(crate) fn single_document_len(input: &str) -> usize {
+    // the first `...` of the run of markers and trivia lines the input ends
+    // with, if there is one
+    let mut end = None;
+    let mut pos = 0;
+    for line in input.split_inclusive('\n') {
+        if is_marker(line, "...") {
+            end.get_or_insert(pos);
+        } else if !is_trivia(line) {
+            end = None;
+        }
+        pos += line.len();
+    }
+    end.unwrap_or(input.len())
+}
+
+/// Whether `line` starts with a document marker (`---` or `...`)
+pub(crate) fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+}
+
+/// Whether a line holds nothing but whitespace and comments
+pub(crate) fn is_trivia(line: &str) -> bool {
+    let line = line.trim_start_matches('\u{feff}').trim_start();
+    line.is_empty() || line.starts_with('#')
+}
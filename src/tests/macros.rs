@@ -128,3 +128,9 @@ macro_rules! map {
         $crate::Yaml::Mapping(vec![$($crate::Entry { key: $key.into() , value: $val.into() }),*])
     }
 }
+
+macro_rules! tagged {
+    ($tag: expr, $val: expr) => {
+        crate::Yaml::tagged($tag, $val.into())
+    };
+}
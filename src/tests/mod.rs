@@ -3,13 +3,45 @@
 #[macro_use]
 mod macros;
 
+mod test_anchors;
 mod test_block;
+mod test_bytes;
+mod test_cache;
+mod test_capabilities;
+mod test_comments;
+mod test_cst;
+#[cfg(feature = "serde")]
+mod test_de;
+mod test_diagnostics;
+#[cfg(feature = "differential")]
+mod test_differential;
 mod test_display;
+mod test_document;
+mod test_embedded;
+mod test_emit;
+mod test_errors;
 mod test_flow;
+mod test_interop;
 mod test_json;
+mod test_json_superset;
 mod test_misc;
+mod test_options;
+mod test_pipeline;
+mod test_query;
+mod test_resolve;
+mod test_sanitize;
 mod test_scalars;
+#[cfg(feature = "serde")]
+mod test_ser;
+mod test_sink;
+mod test_sniff;
+mod test_source_map;
+mod test_stream;
 mod test_tags;
+mod test_timestamp;
+mod test_utf16;
 
+#[cfg(feature = "corpus")]
+mod test_corpus;
 #[cfg(feature = "wasm")]
 mod test_wasm;
@@ -0,0 +1,252 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::Yaml;
+
+// Anchor and alias tests
+
+mk_test!(
+alias to block mapping;
+r"
+defaults: &defaults
+  adapter: postgres
+  host: localhost
+development: *defaults
+" => map! {
+    "defaults" => map! { "adapter" => "postgres"; "host" => "localhost" };
+    "development" => map! { "adapter" => "postgres"; "host" => "localhost" }
+}
+);
+
+mk_test!(
+alias to block sequence;
+r"
+a: &items
+  - 1
+  - 2
+b: *items
+" => map! {
+    "a" => seq!(Yaml::Int(1), Yaml::Int(2));
+    "b" => seq!(Yaml::Int(1), Yaml::Int(2))
+}
+);
+
+mk_test!(
+aliases in sequences;
+r"
+- &first one
+- two
+- *first
+- &m
+  x: 1
+- *m
+" => seq!("one", "two", "one", map! { "x" => Yaml::Int(1) }, map! { "x" => Yaml::Int(1) })
+);
+
+mk_test!(
+aliases in flow collections;
+r"{a: &x [1, 2], b: *x, c: [&y z, *y]}" => map! {
+    "a" => seq!(Yaml::Int(1), Yaml::Int(2));
+    "b" => seq!(Yaml::Int(1), Yaml::Int(2));
+    "c" => seq!("z", "z")
+}
+);
+
+mk_test!(
+anchored and aliased keys;
+r"
+&k name: first
+other: *k
+*k : second
+" => map! { "name" => "first"; "other" => "name"; "name" => "second" }
+);
+
+mk_test!(
+later anchor replaces earlier;
+r"
+- &a one
+- *a
+- &a two
+- *a
+" => seq!("one", "one", "two", "two")
+);
+
+mk_test!(
+unknown alias;
+r"
+a: 1
+b: *missing
+" => err crate::YamlParseError { line: 3, col: 4, kind: crate::ErrorKind::UnknownAlias { name: "missing".into() }, msg: Some("unknown alias '*missing'".into()), source: None,
+    contexts: vec![crate::ContextFrame {
+        context: crate::ParseContext::BlockOut,
+        construct: crate::Construct::BlockMapping,
+        line: 2,
+        col: 1,
+    }] }
+);
+
+mk_test!(
+alias before its anchor;
+r"
+a: *later
+b: &later 1
+" => fail
+);
+
+#[test]
+fn test_alias_expansion_is_bounded() {
+    let mut input = String::from("l0: &l0 [x, x, x, x, x, x, x, x, x, x]\n");
+    for level in 1..8 {
+        let refs = vec![format!("*l{}", level - 1); 10].join(", ");
+        input.push_str(&format!("l{level}: &l{level} [{refs}]\n"));
+    }
+    let err = crate::parse(&input).unwrap_err();
+    assert_eq!(
+        err.message(),
+        Some("aliases expand to more than 1000000 nodes")
+    );
+}
+
+#[test]
+fn test_anchors_in_cst() {
+    use crate::{NodeKind, TokenKind};
+
+    let input = "base: &b {a: 1}\ncopy: *b\n";
+    let tree = crate::parse_cst(input).unwrap();
+    assert_eq!(tree.to_string(), input);
+    let entries: Vec<_> = tree
+        .root()
+        .child_nodes()
+        .next()
+        .unwrap()
+        .child_nodes()
+        .collect();
+    let copy = entries[1].child_nodes().nth(1).unwrap();
+    assert_eq!(copy.kind(), NodeKind::Scalar);
+    assert_eq!(
+        copy.tokens().iter().map(|tok| tok.kind).collect::<Vec<_>>(),
+        vec![TokenKind::Alias]
+    );
+}
+
+// Merge key tests
+
+mk_test!(
+merge key with override;
+r"
+base: &base
+  image: ubuntu
+  retries: 2
+job:
+  <<: *base
+  retries: 5
+" => map! {
+    "base" => map! { "image" => "ubuntu"; "retries" => Yaml::Int(2) };
+    "job" => map! { "image" => "ubuntu"; "retries" => Yaml::Int(5) }
+}
+);
+
+mk_test!(
+merge key overridden before it;
+r"
+base: &base {a: 1, b: 2}
+job:
+  b: 3
+  <<: *base
+" => map! {
+    "base" => map! { "a" => Yaml::Int(1); "b" => Yaml::Int(2) };
+    "job" => map! { "b" => Yaml::Int(3); "a" => Yaml::Int(1) }
+}
+);
+
+mk_test!(
+merge key sequence earlier wins;
+r"
+one: &one {a: 1, b: 1}
+two: &two {b: 2, c: 2}
+job: {<<: [*one, *two], d: 4}
+" => map! {
+    "one" => map! { "a" => Yaml::Int(1); "b" => Yaml::Int(1) };
+    "two" => map! { "b" => Yaml::Int(2); "c" => Yaml::Int(2) };
+    "job" => map! { "a" => Yaml::Int(1); "b" => Yaml::Int(1); "c" => Yaml::Int(2); "d" => Yaml::Int(4) }
+}
+);
+
+mk_test!(
+merge key of inline mapping;
+r"
+job:
+  <<: {image: alpine}
+  name: build
+" => map! { "job" => map! { "image" => "alpine"; "name" => "build" } }
+);
+
+mk_test!(
+merge key chained;
+r"
+a: &a {x: 1}
+b: &b
+  <<: *a
+  y: 2
+c:
+  <<: *b
+  z: 3
+" => map! {
+    "a" => map! { "x" => Yaml::Int(1) };
+    "b" => map! { "x" => Yaml::Int(1); "y" => Yaml::Int(2) };
+    "c" => map! { "x" => Yaml::Int(1); "y" => Yaml::Int(2); "z" => Yaml::Int(3) }
+}
+);
+
+mk_test!(
+merge key with scalar value;
+r"
+job:
+  <<: 3
+" => err crate::YamlParseError { line: 3, col: 3, kind: crate::ErrorKind::InvalidMerge, msg: Some("the value of '<<' must be a mapping or a sequence of mappings".into()), source: None,
+    contexts: vec![
+        crate::ContextFrame {
+            context: crate::ParseContext::BlockOut,
+            construct: crate::Construct::BlockMapping,
+            line: 2,
+            col: 1,
+        },
+        crate::ContextFrame {
+            context: crate::ParseContext::BlockIn,
+            construct: crate::Construct::BlockMapping,
+            line: 3,
+            col: 3,
+        },
+    ] }
+);
+
+#[test]
+fn test_anchors_at_end_of_input() {
+    // an anchor with nothing after it anchors a null
+    for input in ["&ab", "&é", "&😀"] {
+        assert_eq!(crate::parse(input).unwrap(), Yaml::Null, "{input:?}");
+    }
+    assert_eq!(crate::parse("a: &x").unwrap(), map! { "a" => Yaml::Null });
+    assert_eq!(crate::parse("a: &é").unwrap(), map! { "a" => Yaml::Null });
+    assert_eq!(crate::parse("- &é").unwrap(), seq!(Yaml::Null));
+    assert_eq!(crate::parse("&é x").unwrap(), Yaml::Scalar("x"));
+    assert!(crate::parse_cst("[&😀").is_err());
+    let tokens = crate::tokenize("[&😀");
+    assert_eq!(tokens.last().unwrap().span.end, "[&😀".len());
+}
+
+#[test]
+fn test_quoted_merge_key_is_ordinary() {
+    assert_eq!(
+        crate::parse("\"<<\": 1").unwrap(),
+        map! { "<<" => Yaml::Int(1) }
+    );
+    assert_eq!(
+        crate::parse("base: &b {x: 1}\nm:\n  '<<': *b\n").unwrap()["m"],
+        map! { "<<" => map! { "x" => Yaml::Int(1) } }
+    );
+    assert_eq!(
+        crate::parse("{\"<<\": [1], <<: {y: 2}}").unwrap(),
+        map! { "<<" => seq!(Yaml::Int(1)); "y" => Yaml::Int(2) }
+    );
+}
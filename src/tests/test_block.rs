@@ -1,6 +1,8 @@
 #![cfg(test)]
 #![allow(clippy::pedantic)]
 
+use crate::Yaml;
+
 // Block Sequence
 
 mk_test!(
@@ -173,3 +175,187 @@ and: done
     "and" => "done"
 }
 );
+
+mk_test!(
+block mapping empty values;
+r#"
+a:
+b: # comment
+nested:
+  c:
+
+  # comment
+  d:
+list:
+- x
+e:"# => map! {
+    "a" => Yaml::Null;
+    "b" => Yaml::Null;
+    "nested" => map! { "c" => Yaml::Null; "d" => Yaml::Null };
+    "list" => seq!("x");
+    "e" => Yaml::Null
+}
+);
+
+mk_test!(
+block mapping empty values in sequence;
+"- a:\n  b:\n- c:\n" => seq!(map! { "a" => Yaml::Null; "b" => Yaml::Null }, map! { "c" => Yaml::Null })
+);
+
+// Explicit keys
+
+mk_test!(
+explicit key;
+r#"
+? a
+: 1
+"# => map! { "a" => Yaml::Int(1) }
+);
+
+mk_test!(
+explicit collection keys;
+r#"
+? - a
+  - b
+: seq
+? x: 1
+  y: 2
+:
+  - v
+"# => map! {
+    seq!("a", "b") => "seq";
+    map! { "x" => Yaml::Int(1); "y" => Yaml::Int(2) } => seq!("v")
+}
+);
+
+mk_test!(
+explicit key without value;
+r#"
+? a # comment
+
+? b
+: 2
+c: 3
+"# => map! { "a" => Yaml::Null; "b" => Yaml::Int(2); "c" => Yaml::Int(3) }
+);
+
+mk_test!(
+explicit key block scalar;
+r#"
+outer:
+  ? |
+    multi
+    line
+  : x
+  z: 1
+"# => map! {
+    "outer" => map! {
+        Yaml::String("multi\nline\n".to_string()) => "x";
+        "z" => Yaml::Int(1)
+    }
+}
+);
+
+mk_test!(
+explicit key in sequence;
+r#"
+- ? a
+  : 1
+- b
+"# => seq!(map! { "a" => Yaml::Int(1) }, "b")
+);
+
+// Block scalar termination
+
+mk_test!(
+block scalars in sequence of mappings;
+r#"
+steps:
+  - name: build
+    run: |
+      make
+      make install
+    env:
+      CC: gcc
+  - run: >
+      folded
+      command
+  - |
+    bare item
+next: done
+"# => map! {
+    "steps" => seq!(
+        map! {
+            "name" => "build";
+            "run" => Yaml::String("make\nmake install\n".to_string());
+            "env" => map! { "CC" => "gcc" }
+        },
+        map! { "run" => Yaml::String("folded command\n".to_string()) },
+        Yaml::String("bare item\n".to_string())
+    );
+    "next" => "done"
+}
+);
+
+mk_test!(
+empty block scalar before sibling;
+"a: |\nb: 1\n" => map! { "a" => Yaml::String(String::new()); "b" => Yaml::Int(1) }
+);
+
+mk_test!(
+block scalar indentation indicator is relative;
+"a:\n  b: |2\n      x\n  c: 1\n" => map! {
+    "a" => map! { "b" => Yaml::String("  x\n".to_string()); "c" => Yaml::Int(1) }
+}
+);
+
+mk_test!(
+folded block scalar keeps more indented lines;
+"k: >-\n  a\n\n  b\n    c\n  d\nz: 1\n" => map! {
+    "k" => Yaml::String("a\nb\n  c\nd".to_string());
+    "z" => Yaml::Int(1)
+}
+);
+
+mk_test!(
+block scalar crlf;
+"a: |\r\n  x\r\n\r\n  y\r\nb: 1\r\n" => map! {
+    "a" => Yaml::String("x\n\ny\n".to_string());
+    "b" => Yaml::Int(1)
+}
+);
+
+#[test]
+fn test_crlf_parses_like_lf() {
+    let inputs = [
+        "a: 1\nb:\n  - x\n  - c: 1\n",
+        "a: |\n  one\n  two\nb: x\n",
+        "a: >\n  one\n  two\n\n  three\nb: x\n",
+        "a: |+\n  one\n\n",
+        "a: >-\n  x\n   y\n  z\n",
+        "a: plain\n  continued\n\n  more\nb: 1\n",
+        "a: \"quoted\n  continued\"\nb: 'single\n\n  quoted'\n",
+        "? k\n: v\n",
+        "a: [1,\n  2]\nb: {x: 1,\n  y: 2}\n",
+        "# c\na: 1 # c\n\n\nb: 2\n",
+        "%YAML 1.2\n---\na: 1\n...\n",
+        "a: &x\n  b: !!binary |\n    aGVs\n    bG8=\nc: *x\n",
+    ];
+    for lf in inputs {
+        let crlf = lf.replace('\n', "\r\n");
+        assert_eq!(
+            crate::parse(&crlf).unwrap(),
+            crate::parse(lf).unwrap(),
+            "{crlf:?}"
+        );
+        let doc = crate::parse_document(&crlf).unwrap();
+        if let Some(value) = doc.source_slice("a") {
+            assert!(!value.ends_with('\r'), "{crlf:?} gave {value:?}");
+        }
+    }
+    let err = crate::parse("a: 1\r\nb: [1\r\nc: 2\r\n").unwrap_err();
+    assert_eq!(
+        err.line(),
+        crate::parse("a: 1\nb: [1\nc: 2\n").unwrap_err().line()
+    );
+}
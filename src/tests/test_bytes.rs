@@ -0,0 +1,131 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse_bytes, parse_bytes_lossy, parse_reader, ReadError};
+
+// Byte input tests
+
+#[test]
+fn test_parse_bytes_valid_utf8() {
+    assert_eq!(
+        parse_bytes("name: café\n".as_bytes()).unwrap(),
+        map! { "name" => "café" }
+    );
+}
+
+#[test]
+fn test_parse_bytes_reports_invalid_offset() {
+    // "café" in Windows-1252
+    let err = parse_bytes(b"a: 1\nname: caf\xe9\n").unwrap_err();
+    assert_eq!(err.message(), Some("invalid UTF-8 at byte offset 14"));
+    assert_eq!((err.line(), err.col()), (2, 10));
+}
+
+#[test]
+fn test_parse_bytes_lossy() {
+    assert_eq!(
+        parse_bytes_lossy(b"name: caf\xe9\n").unwrap().to_json(),
+        serde_json::json!({ "name": "caf\u{FFFD}" })
+    );
+    assert!(parse_bytes_lossy(b"[\xff").is_err());
+}
+
+fn utf16(text: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+    let bom = bom.then_some(0xFEFF);
+    bom.into_iter()
+        .chain(text.encode_utf16())
+        .flat_map(|unit| {
+            if big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_bytes_skips_utf8_bom() {
+    let input = "\u{feff}name: café\n";
+    assert_eq!(
+        parse_bytes(input.as_bytes()).unwrap(),
+        map! { "name" => "café" }
+    );
+    assert_eq!(crate::parse(input).unwrap(), map! { "name" => "café" });
+    assert_eq!(
+        crate::parse("\u{feff}%YAML 1.2\n---\n[1]\n").unwrap(),
+        seq!(crate::Yaml::Int(1))
+    );
+    assert!(parse_bytes(b"\xef\xbb\xbf").is_err());
+
+    // offsets count the byte order mark
+    let err = parse_bytes(b"\xef\xbb\xbfa: caf\xe9\n").unwrap_err();
+    assert_eq!(err.message(), Some("invalid UTF-8 at byte offset 9"));
+
+    // spans still index the text as given
+    let doc = crate::parse_document(input).unwrap();
+    assert_eq!(doc.source_slice("name"), Some("café"));
+}
+
+#[test]
+fn test_parse_bytes_utf16() {
+    let text = "# config\nname: café 😀\nlist: [1, 2]\n";
+    let expected = crate::parse(text).unwrap().to_json();
+    for big_endian in [false, true] {
+        for bom in [true, false] {
+            let input = utf16(text, big_endian, bom);
+            assert_eq!(
+                parse_bytes(&input).unwrap().to_json(),
+                expected,
+                "big endian: {big_endian}, bom: {bom}"
+            );
+            assert_eq!(parse_bytes_lossy(&input).unwrap().to_json(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_parse_bytes_invalid_utf16() {
+    // an unpaired high surrogate after "a: b\n"
+    let mut input = utf16("a: b\nc: ", false, true);
+    input.extend([0x3D, 0xD8, b'x', 0]);
+    let err = parse_bytes(&input).unwrap_err();
+    assert_eq!(err.message(), Some("invalid UTF-16 at byte offset 18"));
+    assert_eq!(err.line(), 2);
+    assert_eq!(
+        parse_bytes_lossy(&input).unwrap().to_json(),
+        serde_json::json!({ "a": "b", "c": "\u{FFFD}x" })
+    );
+
+    let mut input = utf16("a: 1", true, true);
+    input.push(0);
+    assert_eq!(
+        parse_bytes(&input).unwrap_err().message(),
+        Some("invalid UTF-16 at byte offset 10")
+    );
+}
+
+#[test]
+fn test_parse_reader() {
+    use std::io;
+
+    let tree = parse_reader(io::Cursor::new("name: café\nports: [80, 443]\n")).unwrap();
+    assert_eq!(tree["name"].to_string(), "café");
+    assert_eq!(tree["ports"][1], crate::Yaml::Int(443));
+    // decoded as by parse_bytes
+    let tree = parse_reader(&b"\xff\xfea\x00:\x00 \x001\x00"[..]).unwrap();
+    assert_eq!(tree["a"], crate::Yaml::Int(1));
+
+    let err = parse_reader(&b"a: [1"[..]).unwrap_err();
+    assert!(matches!(err, ReadError::Parse(_)));
+
+    struct Failing;
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disconnected"))
+        }
+    }
+    let err = parse_reader(Failing).unwrap_err();
+    assert!(matches!(err, ReadError::Io(_)));
+    assert_eq!(err.to_string(), "error reading the input: disconnected");
+}
@@ -0,0 +1,84 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{ParseCache, Yaml, YamlOwned};
+use std::sync::Arc;
+
+// Parse cache tests
+
+#[test]
+fn test_cache_hits_share_tree() {
+    let mut cache = ParseCache::new(4);
+    let first = cache.parse("a: 1\nb: [x, y]\n").unwrap();
+    let input = String::from("a: 1\nb: [x, y]\n");
+    let second = cache.parse(&input).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(*first, crate::parse(&input).unwrap().into_owned());
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 1, 1));
+}
+
+#[test]
+fn test_cache_owns_trees() {
+    let mut cache = ParseCache::new(4);
+    let tree: Arc<YamlOwned> = {
+        let input = String::from("name: !svc {port: 80}\n");
+        cache.parse(&input).unwrap()
+    };
+    assert_eq!(
+        tree.to_string(),
+        crate::parse("name: !svc {port: 80}\n").unwrap().to_string()
+    );
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let mut cache = ParseCache::new(2);
+    cache.parse("a").unwrap();
+    cache.parse("b").unwrap();
+    // touch "a" so "b" is the oldest
+    assert!(cache.get("a").is_some());
+    cache.parse("c").unwrap();
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("b").is_none());
+    assert_eq!(
+        cache.get("a").as_deref(),
+        Some(&Yaml::String("a".to_string()))
+    );
+    assert!(cache.get("c").is_some());
+}
+
+#[test]
+fn test_cache_does_not_store_errors() {
+    let mut cache = ParseCache::new(2);
+    assert!(cache.parse("a: [").is_err());
+    assert!(cache.is_empty());
+
+    let mut disabled = ParseCache::new(0);
+    disabled.parse("a").unwrap();
+    assert!(disabled.is_empty());
+    cache.parse("a").unwrap();
+    cache.clear();
+    assert!(cache.get("a").is_none());
+}
+
+#[test]
+fn test_cache_memory_limit() {
+    let small = crate::parse("a").unwrap().into_owned().estimate_size();
+    let mut cache = ParseCache::new(10).with_memory_limit(2 * small);
+    assert_eq!(cache.memory_limit(), Some(2 * small));
+    cache.parse("a").unwrap();
+    cache.parse("b").unwrap();
+    assert_eq!((cache.len(), cache.memory_usage()), (2, 2 * small));
+    // over budget, so the least recently used tree goes
+    cache.parse("c").unwrap();
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("a").is_none());
+
+    // a tree larger than the budget is not cached at all
+    let big = "[1, 2, 3, 4, 5, 6, 7, 8]";
+    assert!(cache.parse(big).is_ok());
+    assert!(cache.get(big).is_none());
+    assert_eq!(cache.len(), 2);
+    cache.clear();
+    assert_eq!(cache.memory_usage(), 0);
+}
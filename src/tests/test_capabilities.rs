@@ -0,0 +1,48 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::capabilities;
+
+// Capabilities tests
+
+#[test]
+fn test_capabilities_match_parser() {
+    let caps = capabilities();
+    assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(caps.anchors, crate::parse("a: &x 1\nb: *x").is_ok());
+    assert_eq!(
+        caps.merge_keys,
+        crate::parse("a: &x {b: 1}\nc:\n  <<: *x").is_ok()
+    );
+    assert_eq!(
+        caps.directives,
+        crate::parse("%YAML 1.2\n---\na: 1").is_ok()
+    );
+    assert_eq!(caps.explicit_keys, crate::parse("? a\n: 1\n").is_ok());
+    assert_eq!(
+        caps.multi_line_plain_scalars,
+        crate::parse("a: x\n  y\n").is_ok()
+    );
+    for tag in caps.secondary_tags {
+        let value = match *tag {
+            "int" | "float" => "1",
+            "bool" => "true",
+            "map" | "set" => "{}",
+            "seq" | "omap" | "pairs" => "[]",
+            _ => "''",
+        };
+        let input = format!("!!{tag} {value}");
+        let parsed = crate::parse(&input).unwrap();
+        assert_eq!(parsed.to_json().get("__type"), None, "{tag}");
+    }
+}
+
+#[test]
+fn test_capabilities_to_json() {
+    let json = capabilities().to_json();
+    assert_eq!(json["anchors"], true);
+    assert_eq!(
+        json["schemas"],
+        serde_json::json!(["yaml-1.1", "core", "json"])
+    );
+}
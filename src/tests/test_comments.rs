@@ -0,0 +1,102 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse, parse_document, Comments, EmitOptions};
+
+// Comment tests
+
+const CONFIG: &str = "# head
+a: 1 # one
+# before b
+b: # list
+  # first
+  - x # ex
+  - # item
+    k: v
+  - c: 1
+    # before d
+    d: 2
+# tail
+";
+
+fn comments(leading: &[&'static str], trailing: Option<&'static str>) -> Comments<'static> {
+    Comments {
+        leading: leading.to_vec(),
+        trailing,
+    }
+}
+
+#[test]
+fn test_comments_attach_to_nodes() {
+    let doc = parse_document(CONFIG).unwrap();
+    assert_eq!(
+        doc.comments("a"),
+        Some(&comments(&["# head"], Some("# one")))
+    );
+    assert_eq!(
+        doc.comments("b"),
+        Some(&comments(&["# before b"], Some("# list")))
+    );
+    assert_eq!(
+        doc.comments("b[0]"),
+        Some(&comments(&["# first"], Some("# ex")))
+    );
+    assert_eq!(doc.comments("b[1]"), Some(&comments(&[], Some("# item"))));
+    assert_eq!(
+        doc.comments("b[2].d"),
+        Some(&comments(&["# before d"], None))
+    );
+    assert_eq!(doc.comments("b[2]"), None);
+    assert_eq!(doc.comments("missing"), None);
+    assert_eq!(doc.end_comments(), ["# tail"]);
+
+    let doc = parse_document("# top\r\nscalar # end\r\n").unwrap();
+    assert_eq!(doc.comments(""), Some(&comments(&["# top"], Some("# end"))));
+}
+
+#[test]
+fn test_emit_comments() {
+    let opts = EmitOptions {
+        comments: true,
+        ..Default::default()
+    };
+    let doc = parse_document(CONFIG).unwrap();
+    assert_eq!(doc.emit(&opts), CONFIG);
+    assert!(!doc.emit(&EmitOptions::default()).contains('#'));
+
+    for src in [
+        "# top\nscalar # end\n",
+        "- # c\n  a: 1\n- b # b\n",
+        "m:\n  # inner\n  n: [1, 2] # flow\n",
+    ] {
+        let out = parse_document(src).unwrap().emit(&opts);
+        assert_eq!(parse(&out).unwrap(), parse(src).unwrap(), "{out}");
+        assert_eq!(parse_document(&out).unwrap().emit(&opts), out);
+    }
+}
+
+#[test]
+fn test_emit_comment_after_first_entry_of_item() {
+    let opts = EmitOptions {
+        comments: true,
+        ..Default::default()
+    };
+    for src in [
+        "- k: v # kv\n  j: 2\n",
+        "- k: v # kv\n  j: 2 # j\n- x # x\n",
+    ] {
+        assert_eq!(parse_document(src).unwrap().emit(&opts), src);
+    }
+}
+
+#[test]
+fn test_comments_of_long_document() {
+    let mut src = String::new();
+    for idx in 0..500 {
+        src.push_str(&format!("# key {idx}\nkey{idx}: [{idx}] # value {idx}\n"));
+    }
+    let doc = parse_document(&src).unwrap();
+    let comments = doc.comments("key321").unwrap();
+    assert_eq!(comments.leading, ["# key 321"]);
+    assert_eq!(comments.trailing, Some("# value 321"));
+}
@@ -0,0 +1,58 @@
+#![cfg(all(test, feature = "corpus"))]
+#![allow(clippy::pedantic)]
+
+// Corpus runner tests
+
+use crate::{
+    run_builtin_corpus, run_corpus, CorpusFailureKind, CorpusOptions, CorpusReport, Dialect,
+    EmitOptions,
+};
+
+fn round_trip() -> CorpusOptions {
+    CorpusOptions {
+        round_trip: Some(EmitOptions {
+            dialect: Dialect::SerdeYaml,
+            ..EmitOptions::default()
+        }),
+    }
+}
+
+#[test]
+fn test_builtin_corpus() {
+    let opts = round_trip();
+    let report = run_builtin_corpus(&opts);
+    report.assert_ok();
+    assert!(report.parsed > 0 && report.parsed < report.inputs);
+}
+
+#[test]
+fn test_corpus_directory_matches_builtin() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/corpus");
+    let report = run_corpus(dir, &CorpusOptions::default()).unwrap();
+    report.assert_ok();
+    assert_eq!(
+        report.inputs,
+        run_builtin_corpus(&CorpusOptions::default()).inputs
+    );
+}
+
+#[test]
+fn test_corpus_reports_failures() {
+    let mut report = CorpusReport::default();
+    let opts = round_trip();
+    report.check("ok.yaml", b"a: [1, 2]\n", &opts);
+    report.check("bad.yaml", b"a: [1\n", &opts);
+    assert!(report.is_ok());
+    assert_eq!((report.inputs, report.parsed), (2, 1));
+    // the default dialect prints strings verbatim
+    let verbatim = CorpusOptions {
+        round_trip: Some(EmitOptions::default()),
+    };
+    report.check("true.yaml", b"a: 'true'\n", &verbatim);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].kind, CorpusFailureKind::RoundTrip);
+    assert!(report.failures[0]
+        .to_string()
+        .starts_with("true.yaml: round trip: "));
+    assert!(run_corpus("/nonexistent/corpus", &opts).is_err());
+}
@@ -0,0 +1,250 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{
+    parse_cst, tokenize, Indicator, Lexer, NodeKind, SyntaxElement, SyntaxNode, SyntaxTree,
+    TokenKind,
+};
+
+// Lossless syntax tree tests
+
+fn assert_lossless(input: &str) {
+    let tree = parse_cst(input).unwrap();
+    assert_eq!(tree.to_string(), input);
+    assert_eq!(tree.to_yaml(), &crate::parse(input).unwrap());
+
+    // tokens are contiguous and cover the whole input
+    let mut pos = 0;
+    for token in tree.root().tokens() {
+        assert_eq!(token.span.start, pos, "gap before {:?}", token);
+        assert!(!token.span.is_empty(), "empty token {:?}", token);
+        pos = token.span.end;
+    }
+    assert_eq!(pos, input.len());
+}
+
+fn kinds(node: &SyntaxNode) -> Vec<NodeKind> {
+    node.child_nodes().map(SyntaxNode::kind).collect()
+}
+
+fn token_texts<'a>(tree: &SyntaxTree<'a>, node: &SyntaxNode) -> Vec<(TokenKind, &'a str)> {
+    node.children()
+        .iter()
+        .filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some((token.kind, tree.text(token.span))),
+            SyntaxElement::Node(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_cst_round_trips() {
+    assert_lossless("plain\n");
+    assert_lossless("---\na: 1\n");
+    assert_lossless("# header\n\nkey:    value   # aligned\nother:  2\n\n\n# footer");
+    assert_lossless("  a:  1   \n  b:    2\n");
+    assert_lossless("list:\n  - a\n  -   b\n\n  - k: v\n    j: [1, \"2\", {z: 'x'}]\n");
+    assert_lossless("block: |\n  hi\n  there\nfolded: >-\n  one\n  two\n");
+    assert_lossless("!t\na: 1\nb: !u [1, 2]\nc: !v {x: 1}\n");
+    assert_lossless("- - a\n  - b\n- !t [1, {a: b}]\n");
+    assert_lossless("a: 1\r\nb: 2\r\n");
+    assert_lossless("a: one\n  two\nb: 1\n");
+    assert_lossless("a: \"x\n  y\"\nb: 'p\n\n  q'\nc: 1\n");
+}
+
+#[test]
+fn test_cst_structure() {
+    let input = "# top\nserver:   # note\n  host: x\n  ports:\n    - 80\n";
+    let tree = parse_cst(input).unwrap();
+    let root = tree.root();
+    assert_eq!(root.kind(), NodeKind::Document);
+    assert_eq!(
+        token_texts(&tree, root),
+        vec![
+            (TokenKind::Comment, "# top"),
+            (TokenKind::Newline, "\n"),
+            (TokenKind::Newline, "\n"),
+        ]
+    );
+
+    let mapping = root.child_nodes().next().unwrap();
+    assert_eq!(mapping.kind(), NodeKind::Mapping);
+    let server = mapping.child_nodes().next().unwrap();
+    assert_eq!(kinds(server), vec![NodeKind::Scalar, NodeKind::Mapping]);
+    assert_eq!(
+        token_texts(&tree, server),
+        vec![
+            (TokenKind::Indicator, ":"),
+            (TokenKind::Whitespace, "   "),
+            (TokenKind::Comment, "# note"),
+            (TokenKind::Newline, "\n"),
+            (TokenKind::Whitespace, "  "),
+        ]
+    );
+
+    let inner = server.child_nodes().nth(1).unwrap();
+    assert_eq!(kinds(inner), vec![NodeKind::Entry, NodeKind::Entry]);
+    let ports = inner.child_nodes().nth(1).unwrap();
+    let seq = ports.child_nodes().nth(1).unwrap();
+    assert_eq!(seq.kind(), NodeKind::Sequence);
+    let item = seq.child_nodes().next().unwrap();
+    assert_eq!(item.kind(), NodeKind::Item);
+    assert_eq!(tree.text(item.span()), "- 80");
+}
+
+#[test]
+fn test_cst_scalar_tokens() {
+    let tree = parse_cst("a: !t 'q'\nb: \"d\"\nc: |\n  x\n").unwrap();
+    let tokens: Vec<_> = tree
+        .root()
+        .tokens()
+        .into_iter()
+        .filter(|token| !token.kind.is_trivia())
+        .map(|token| (token.kind, tree.text(token.span)))
+        .collect();
+    assert_eq!(
+        tokens,
+        vec![
+            (TokenKind::Plain, "a"),
+            (TokenKind::Indicator, ":"),
+            (TokenKind::Tag, "!t"),
+            (TokenKind::SingleQuoted, "'q'"),
+            (TokenKind::Plain, "b"),
+            (TokenKind::Indicator, ":"),
+            (TokenKind::DoubleQuoted, "\"d\""),
+            (TokenKind::Plain, "c"),
+            (TokenKind::Indicator, ":"),
+            (TokenKind::BlockScalar, "|\n  x"),
+        ]
+    );
+}
+
+#[test]
+fn test_cst_verbatim_tag_token() {
+    let input = "a: !<tag:example.com,2000:t> [x]\n";
+    assert_lossless(input);
+    let tree = parse_cst(input).unwrap();
+    let tags: Vec<_> = tree
+        .root()
+        .tokens()
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Tag)
+        .map(|token| tree.text(token.span))
+        .collect();
+    assert_eq!(tags, vec!["!<tag:example.com,2000:t>"]);
+}
+
+/// The tokens of `input`, checking that they cover all of it
+fn lex(input: &str) -> Vec<(TokenKind, &str)> {
+    let tokens = tokenize(input);
+    let mut pos = 0;
+    for token in &tokens {
+        assert_eq!(token.span.start, pos, "gap before {:?}", token);
+        assert!(!token.span.is_empty(), "empty token {:?}", token);
+        pos = token.span.end;
+    }
+    assert_eq!(pos, input.len());
+    tokens
+        .iter()
+        .map(|token| (token.kind, &input[token.span.start..token.span.end]))
+        .collect()
+}
+
+#[test]
+fn test_tokenize_valid_input() {
+    let input = "# c\na: [1, 'x']\nb: |\n  text\n";
+    assert_eq!(tokenize(input), parse_cst(input).unwrap().root().tokens());
+}
+
+#[test]
+fn test_tokenize_invalid_input() {
+    let tokens = lex("a: [1, 2}\nb: 'x' # note\nc: |\n  text\n");
+    assert!(tokens.contains(&(TokenKind::Error, "}")));
+    // lexing carries on after the error
+    assert!(tokens.contains(&(TokenKind::SingleQuoted, "'x'")));
+    assert!(tokens.contains(&(TokenKind::Comment, "# note")));
+    assert!(tokens.contains(&(TokenKind::BlockScalar, "|\n  text")));
+
+    let tokens = lex("a: 'open\nb: {x: 1}\n");
+    assert_eq!(tokens[3], (TokenKind::Error, "'open"));
+    assert!(tokens.contains(&(TokenKind::Plain, "x")));
+    assert!(tokens.contains(&(TokenKind::Indicator, "}")));
+
+    // a parse error which is not lexical marks the token it is at
+    let tokens = lex("a: 1\nb: *missing\n");
+    assert_eq!(tokens[8], (TokenKind::Error, "*missing"));
+    assert!(lex("{\"a\": [1, \"x\\\"y\"], é: -1}\n]\n")
+        .iter()
+        .any(|&(kind, text)| kind == TokenKind::DoubleQuoted && text == "\"x\\\"y\""));
+}
+
+#[test]
+fn test_lexer() {
+    // for valid input the lexer agrees with the syntax tree
+    let input = "# c\na: [1, 'x']\nb: |\n  text\n";
+    assert_eq!(
+        Lexer::new(input).collect::<Vec<_>>(),
+        parse_cst(input).unwrap().root().tokens()
+    );
+
+    let input = "- {\"k\":v}  # note\n";
+    let tokens: Vec<_> = Lexer::new(input)
+        .map(|token| (token.kind, &input[token.span.start..token.span.end]))
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            (TokenKind::Indicator, "-"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Indicator, "{"),
+            (TokenKind::DoubleQuoted, "\"k\""),
+            (TokenKind::Indicator, ":"),
+            (TokenKind::Plain, "v"),
+            (TokenKind::Indicator, "}"),
+            (TokenKind::Whitespace, "  "),
+            (TokenKind::Comment, "# note"),
+            (TokenKind::Newline, "\n"),
+        ]
+    );
+
+    // errors do not stop it, and nothing is parsed
+    let mut lexer = Lexer::new("a: 'open\n]");
+    assert_eq!(lexer.nth(3).map(|token| token.kind), Some(TokenKind::Error));
+    assert_eq!(lexer.last().map(|token| token.kind), Some(TokenKind::Error));
+    assert_eq!(Lexer::new("").next(), None);
+}
+
+#[test]
+fn test_lexer_indicators_and_keys() {
+    let input = "- &a key: [x, 'y': 1]\n  \"q\" : v\n? e\n: f\n";
+    let mut lexer = Lexer::new(input);
+    let mut indicators = Vec::new();
+    let mut keys = Vec::new();
+    while let Some(token) = lexer.next() {
+        indicators.extend(token.indicator(input));
+        if lexer.is_key() {
+            keys.push(&input[token.span.start..token.span.end]);
+        }
+    }
+    assert_eq!(
+        indicators,
+        [
+            Indicator::Dash,
+            Indicator::Colon,
+            Indicator::OpenBracket,
+            Indicator::Comma,
+            Indicator::Colon,
+            Indicator::CloseBracket,
+            Indicator::Colon,
+            Indicator::Question,
+            Indicator::Colon,
+        ]
+    );
+    assert_eq!(keys, ["key", "'y'", "\"q\""]);
+
+    // `:` inside a plain scalar or a tag is not an indicator
+    let input = "a:b !t:x";
+    let mut lexer = Lexer::new(input);
+    assert!(lexer.all(|token| token.indicator(input).is_none()));
+    assert!(!Lexer::new("").is_key());
+}
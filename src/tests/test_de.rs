@@ -0,0 +1,151 @@
+#![cfg(all(test, feature = "serde"))]
+#![allow(clippy::pedantic)]
+
+// Deserializer tests
+
+use crate::{from_str, from_yaml, SerdeError, Yaml};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config<'a> {
+    name: &'a str,
+    version: String,
+    port: u16,
+    ratio: f32,
+    debug: bool,
+    tags: Vec<String>,
+    owner: Option<String>,
+    #[serde(default)]
+    limits: BTreeMap<String, u32>,
+    servers: Vec<Server>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[test]
+fn test_struct() {
+    let config: Config = from_str(
+        "name: app\nversion: 2\nport: 8080\nratio: 0.5\ndebug: true\ntags: [a, b]\nowner: ~\nservers:\n  - host: x\n    port: 1\n  - {host: y, port: 2}\n",
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "app",
+            version: "2".to_string(),
+            port: 8080,
+            ratio: 0.5,
+            debug: true,
+            tags: vec!["a".to_string(), "b".to_string()],
+            owner: None,
+            limits: BTreeMap::new(),
+            servers: vec![
+                Server {
+                    host: "x".to_string(),
+                    port: 1
+                },
+                Server {
+                    host: "y".to_string(),
+                    port: 2
+                },
+            ],
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Point(i32, i32),
+    Rect { w: u32, h: u32 },
+}
+
+#[test]
+fn test_enums() {
+    let shapes: Vec<Shape> =
+        from_str("- Empty\n- !Circle 1.5\n- Point: [1, 2]\n- !Rect {w: 3, h: 4}\n").unwrap();
+    assert_eq!(
+        shapes,
+        vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Point(1, 2),
+            Shape::Rect { w: 3, h: 4 }
+        ]
+    );
+}
+
+#[test]
+fn test_block_scalars_and_tags() {
+    let map: BTreeMap<String, String> = from_str("a: |\n  line\nb: !custom text\n").unwrap();
+    assert_eq!(map["a"], "line\n");
+    assert_eq!(map["b"], "text");
+    let value: Vec<(i64, String)> = from_yaml(crate::parse("- [1, x]").unwrap()).unwrap();
+    assert_eq!(value, vec![(1, "x".to_string())]);
+}
+
+#[test]
+fn test_errors() {
+    let err = from_str::<Config>("name: app\nport: [1]\n").unwrap_err();
+    assert!(matches!(err, SerdeError::Invalid { ref path, .. } if path == "port"));
+    let err =
+        from_str::<Vec<Server>>("- {host: a, port: 1}\n- {host: b, port: big}\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at '[1].port': invalid type: string \"big\", expected u16"
+    );
+    let err = from_str::<Server>("host: a\n").unwrap_err();
+    assert_eq!(err.to_string(), "missing field `port`");
+    // quoted numbers stay strings
+    assert!(from_str::<u16>("'8080'").is_err());
+    // integers too large for i64 are left strings by parsing, but still
+    // fit a u64
+    assert_eq!(from_str::<u64>("18446744073709551615").unwrap(), u64::MAX);
+    assert_eq!(
+        from_str::<Vec<u128>>("[9223372036854775808]").unwrap(),
+        [9223372036854775808]
+    );
+    assert!(from_str::<u32>("18446744073709551615").is_err());
+    assert!(from_str::<u64>("18446744073709551616").is_err());
+    let err = from_str::<Server>("host: [a").unwrap_err();
+    assert!(matches!(err, SerdeError::Parse(_)));
+}
+
+#[test]
+fn test_deserialize_yaml() {
+    let json = r#"{"a": [1, 2.5, true, null, "text"], "b": {"__type": "point", "x": 1}, "c": {"__type": "id", "__value": 7}}"#;
+    let yaml: crate::Yaml = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        yaml,
+        map! {
+            "a" => seq!(Yaml::Int(1), Yaml::Float(2.5), Yaml::Bool(true), Yaml::Null, "text");
+            "b" => tagged!("point", map! { "x" => Yaml::Int(1) });
+            "c" => tagged!("id", Yaml::Int(7))
+        }
+    );
+    // strings without escapes are borrowed
+    assert!(matches!(yaml, Yaml::Mapping(ref entries) if entries[0].key == Yaml::Scalar("a")));
+    assert_eq!(
+        crate::Yaml::from_json(&serde_json::from_str(json).unwrap()),
+        yaml.clone().into_owned()
+    );
+
+    let entries: Vec<crate::Entry> = serde_json::from_str(r#"[["k", 1]]"#).unwrap();
+    assert_eq!(
+        entries,
+        vec![crate::Entry::new(Yaml::Scalar("k"), Yaml::Int(1))]
+    );
+    let ts: crate::Timestamp = serde_json::from_str(r#""2024-06-01""#).unwrap();
+    assert_eq!(serde_json::to_string(&ts).unwrap(), r#""2024-06-01""#);
+    assert!(serde_json::from_str::<crate::Timestamp>(r#""June""#).is_err());
+
+    // from_str passes over tags
+    let value: Yaml = from_str("a: !t 1\n").unwrap();
+    assert_eq!(value, map! { "a" => Yaml::Int(1) });
+}
@@ -0,0 +1,61 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{validate, Diagnostic, Diagnostics, Severity};
+
+// Diagnostics tests
+
+#[test]
+fn test_validate_clean_input() {
+    let diagnostics = validate("a: 1\nb: [1, 2]\n");
+    assert!(diagnostics.is_empty());
+    assert_eq!(diagnostics.to_string(), "");
+}
+
+#[test]
+fn test_validate_duplicate_keys() {
+    let diagnostics = validate("a: 1\nb:\n  x: 1\n  x: 2\n  x: 3\na: 2\n");
+    assert!(!diagnostics.has_errors());
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics.to_string(),
+        "warning at line 6, column 1: duplicate key 'a', only the last value is used\n\
+         warning at line 5, column 3: duplicate key 'x', only the last value is used"
+    );
+}
+
+#[test]
+fn test_validate_parse_error() {
+    let diagnostics = validate("a: [1, 2\n");
+    assert!(diagnostics.has_errors());
+    assert_eq!(diagnostics.errors.len(), 1);
+    assert_eq!(diagnostics.errors[0].severity, Severity::Error);
+}
+
+#[test]
+fn test_diagnostics_iterate_errors_first() {
+    let warning = Diagnostic {
+        severity: Severity::Warning,
+        line: 1,
+        col: 1,
+        message: "w".to_string(),
+    };
+    let error = Diagnostic {
+        severity: Severity::Error,
+        line: 2,
+        col: 3,
+        message: "e".to_string(),
+    };
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.push(warning.clone());
+    diagnostics.push(error.clone());
+
+    let borrowed: Vec<&Diagnostic> = (&diagnostics).into_iter().collect();
+    assert_eq!(borrowed, vec![&error, &warning]);
+    assert_eq!(
+        diagnostics.to_string(),
+        "error at line 2, column 3: e\nwarning at line 1, column 1: w"
+    );
+    let owned: Vec<Diagnostic> = diagnostics.into_iter().collect();
+    assert_eq!(owned, vec![error, warning]);
+}
@@ -0,0 +1,85 @@
+#![cfg(all(test, feature = "differential"))]
+#![allow(clippy::pedantic)]
+
+// Differential harness tests
+
+use crate::{
+    parse_with_options, run_builtin_differential, run_differential, DifferentialReport,
+    FnReference, Outcome, ParseOptions, Schema,
+};
+
+/// A stand-in for a YAML 1.2 parser, which reads `yes` and `on` as strings
+fn core_schema() -> FnReference<impl Fn(&str) -> Result<serde_json::Value, String>> {
+    FnReference::new("core schema", |input| {
+        parse_with_options(input, &ParseOptions::default().schema(Schema::Core))
+            .map(|doc| doc.to_json())
+            .map_err(|err| err.to_string())
+    })
+}
+
+#[test]
+fn test_differential_reports_first_difference() {
+    let reference = core_schema();
+    let mut report = DifferentialReport::new(&reference);
+    report.check("same.yaml", "a: 1\nb: [x, y]\n", &reference);
+    report.check("broken.yaml", "a: [1, 2\n", &reference);
+    report.check(
+        "bools.yaml",
+        "spec:\n  ports: [80, 443]\n  tls: on\n",
+        &reference,
+    );
+    assert_eq!((report.inputs, report.agreed), (3, 2));
+    assert!(!report.is_ok());
+    let divergence = &report.divergences[0];
+    assert_eq!(divergence.path, "spec.tls");
+    assert_eq!(
+        divergence.ours,
+        Outcome::Value(serde_json::json!({"spec": {"ports": [80, 443], "tls": true}}))
+    );
+    assert_eq!(
+        divergence.to_string(),
+        "bools.yaml at 'spec.tls': ours true, reference \"on\""
+    );
+    assert!(report
+        .to_string()
+        .starts_with("2 of 3 inputs agree with core schema\n"));
+}
+
+#[test]
+fn test_differential_failure_on_one_side() {
+    let reference = FnReference::new("strict", |input: &str| {
+        if input.contains('&') {
+            Err("anchors are not supported".to_string())
+        } else {
+            crate::parse(input)
+                .map(|doc| doc.to_json())
+                .map_err(|err| err.to_string())
+        }
+    });
+    let mut report = DifferentialReport::new(&reference);
+    report.check("anchors.yaml", "a: &x 1\nb: *x\n", &reference);
+    let divergence = &report.divergences[0];
+    assert_eq!(divergence.path, "");
+    assert_eq!(
+        divergence.theirs,
+        Outcome::Error("anchors are not supported".to_string())
+    );
+}
+
+#[test]
+fn test_differential_corpus() {
+    let reference = FnReference::new("self", |input: &str| {
+        crate::parse(input)
+            .map(|doc| doc.to_json())
+            .map_err(|err| err.to_string())
+    });
+    let report = run_builtin_differential(&reference);
+    assert!(report.is_ok(), "{report}");
+    assert!(report.inputs > 0);
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/corpus");
+    let from_dir = run_differential(dir, &reference).unwrap();
+    assert_eq!(
+        (from_dir.inputs, from_dir.agreed),
+        (report.inputs, report.agreed)
+    );
+}
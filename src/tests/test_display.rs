@@ -112,20 +112,26 @@ fn test_print_yaml_task_list() {
     assert_eq!(printed, yaml);
 }
 
-// __type printing as !tag tests
+// Tagged value printing tests
 
 #[test]
-fn test_print_type_as_tag_with_value() {
-    // data: {__type: "str", __value: "hello"} should print as "data: !str hello"
-    let yaml = map! { "data" => map! { "__type": "str", "__value": "hello" } };
+fn test_print_tagged_scalar() {
+    let yaml = map! { "data" => tagged!("str", "hello") };
     assert_eq!(yaml.to_string(), "data: !str hello\n");
 }
 
 #[test]
-fn test_print_type_as_tag_with_fields() {
-    // user: {__type: "person", name: "John"} should print as "user: !person\n  name: John"
-    let yaml = map! { "user" => map! { "__type" => "person"; "name" => "John" } };
+fn test_print_tagged_mapping() {
+    let yaml = map! { "user" => tagged!("person", map! { "name" => "John" }) };
     assert_eq!(yaml.to_string(), "user: !person\n  name: John\n");
+    assert_eq!(format!("{yaml:#}"), "{user: !person {name: John}}");
+}
+
+#[test]
+fn test_type_keys_are_plain_entries() {
+    // a `__type` key is an ordinary key, not a tag
+    let yaml = map! { "data" => map! { "__type" => "str"; "__value" => "hello" } };
+    assert_eq!(yaml.to_string(), "data:\n  __type: str\n  __value: hello\n");
 }
 
 #[test]
@@ -183,3 +189,47 @@ fn test_multiple_sibling_tags_round_trip() {
     let printed = parsed.to_string();
     assert_eq!(printed, yaml);
 }
+
+#[test]
+fn test_tagged_root_mapping_round_trip() {
+    let yaml = "!config\nname: app\nnested: !inner\n  k: v\n";
+    let parsed = crate::parse(yaml).unwrap();
+    assert_eq!(parsed.to_string(), yaml);
+}
+
+#[test]
+fn test_tagged_root_sequence_round_trip() {
+    let yaml = "!config\n- a\n- b\n";
+    let parsed = crate::parse(yaml).unwrap();
+    assert_eq!(parsed.to_string(), yaml);
+}
+
+#[test]
+fn test_tagged_empty_root_round_trip() {
+    for yaml in ["!config {}\n", "!config []\n"] {
+        let parsed = crate::parse(yaml).unwrap();
+        assert_eq!(parsed.to_string(), yaml);
+    }
+}
+
+#[test]
+fn test_tagged_collections_in_sequence_round_trip() {
+    let yaml = "list: !l\n  - !item\n    a: 1\n  - !s\n    - 1\n    - 2\n  - !e {}\n";
+    let parsed = crate::parse(yaml).unwrap();
+    assert_eq!(parsed.to_string(), yaml);
+}
+
+#[test]
+fn test_alternate_display_is_flow() {
+    let input = "name: web\nports: [80, 443]\nlabels:\n  tier: 'a, b'\n  note: \"two\\nlines\"\nempty: {}\ntagged: !point {x: 1}\nlist:\n  - - nested\n  - !wrapped [1]\n";
+    let value = crate::parse(input).unwrap();
+    let flow = format!("{value:#}");
+    assert_eq!(
+        flow,
+        r#"{name: web, ports: [80, 443], labels: {tier: 'a, b', note: "two\nlines"}, empty: {}, tagged: !point {x: 1}, list: [[nested], !wrapped [1]]}"#
+    );
+    assert_eq!(crate::parse(&flow).unwrap(), value);
+    // `{}` keeps the block form
+    assert!(value.to_string().starts_with("name: web\nports:\n"));
+    assert_eq!(format!("{:#}", crate::Yaml::Int(3)), "3");
+}
@@ -0,0 +1,535 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse_document, EditError, Entry, Newline, Span, Spanned, TextEdit, Yaml};
+
+// Source span and text edit tests
+
+const CONFIG: &str = r#"server:
+  host: example.com
+  port: 8080
+  tls:
+    enabled: true
+hosts:
+  - a
+  - b
+users:
+  - name: x
+    age: 3
+flow: {a: 1, b: [1, 2]}
+"#;
+
+fn edit(path: &str, value: Yaml<'_>) -> String {
+    let doc = parse_document(CONFIG).unwrap();
+    let edits = doc.edit(path, &value).unwrap();
+    TextEdit::apply(CONFIG, &edits)
+}
+
+fn slice(path: &str) -> &'static str {
+    let doc = parse_document(CONFIG).unwrap();
+    let span = doc.span(path).unwrap();
+    &CONFIG[span.start..span.end]
+}
+
+#[test]
+fn test_spans() {
+    assert_eq!(slice("server.port"), "8080");
+    assert_eq!(slice("server.tls"), "enabled: true");
+    assert_eq!(slice("hosts[1]"), "b");
+    assert_eq!(slice("users[0].age"), "3");
+    assert_eq!(slice("flow"), "{a: 1, b: [1, 2]}");
+    assert_eq!(slice("flow.b[1]"), "2");
+
+    let doc = parse_document(CONFIG).unwrap();
+    assert_eq!(doc.span("server.port"), Some(Span::new(36, 40)));
+    assert_eq!(doc.span("server.missing"), None);
+}
+
+#[test]
+fn test_edit_scalar() {
+    let doc = parse_document(CONFIG).unwrap();
+    let edits = doc.edit("server.port", &Yaml::Int(9090)).unwrap();
+    assert_eq!(
+        edits,
+        vec![TextEdit {
+            range: Span::new(36, 40),
+            replacement: "9090".to_string(),
+        }]
+    );
+    assert_eq!(
+        TextEdit::apply(CONFIG, &edits),
+        CONFIG.replace("port: 8080", "port: 9090")
+    );
+}
+
+#[test]
+fn test_edit_scalar_to_mapping() {
+    let value = Yaml::Mapping(vec![Entry::new(Yaml::Scalar("a"), Yaml::Int(1))]);
+    assert_eq!(
+        edit("server.port", value),
+        CONFIG.replace("port: 8080", "port:\n    a: 1")
+    );
+}
+
+#[test]
+fn test_edit_mapping_to_scalar() {
+    assert_eq!(
+        edit("server.tls", Yaml::Bool(false)),
+        CONFIG.replace("tls:\n    enabled: true", "tls: false")
+    );
+}
+
+#[test]
+fn test_edit_sequence_items() {
+    assert_eq!(
+        edit("hosts[0]", Yaml::Scalar("z")),
+        CONFIG.replace("- a", "- z")
+    );
+    assert_eq!(
+        edit("hosts[2]", Yaml::Scalar("c")),
+        CONFIG.replace("- b", "- b\n  - c")
+    );
+    let value = Yaml::Sequence(vec![Yaml::Int(1), Yaml::Int(2)]);
+    assert_eq!(
+        edit("users[0].age", value),
+        CONFIG.replace("age: 3", "age:\n      - 1\n      - 2")
+    );
+}
+
+#[test]
+fn test_edit_insert_key() {
+    assert_eq!(
+        edit("server.timeout", Yaml::Int(30)),
+        CONFIG.replace("enabled: true", "enabled: true\n  timeout: 30")
+    );
+}
+
+#[test]
+fn test_edit_flow() {
+    assert_eq!(
+        edit("flow.a", Yaml::Int(5)),
+        CONFIG.replace("{a: 1", "{a: 5")
+    );
+    assert_eq!(
+        edit("flow.c", Yaml::Int(5)),
+        CONFIG.replace("2]}", "2], c: 5}")
+    );
+    assert_eq!(
+        edit("flow.b[2]", Yaml::Int(3)),
+        CONFIG.replace("[1, 2]", "[1, 2, 3]")
+    );
+
+    let doc = parse_document(CONFIG).unwrap();
+    let value = Yaml::Sequence(vec![Yaml::Int(1)]);
+    assert!(matches!(
+        doc.edit("flow.a", &value),
+        Err(EditError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn test_edit_strings_needing_quotes() {
+    let doc = parse_document(CONFIG).unwrap();
+    for text in [
+        "",
+        "yes",
+        "x: y",
+        "#hash",
+        "line1\nline2",
+        "p, q",
+        "[x]",
+        "1.5",
+    ] {
+        let value = Yaml::String(text.to_string());
+        for path in [
+            "server.host",
+            "hosts[0]",
+            "flow.a",
+            "flow.b[0]",
+            "server.new",
+            "flow.new",
+        ] {
+            let edits = doc
+                .edit(path, &value)
+                .unwrap_or_else(|err| panic!("{path} = {text:?}: {err}"));
+            let source = TextEdit::apply(CONFIG, &edits);
+            let edited = crate::parse(&source).unwrap();
+            assert_eq!(
+                crate::path::resolve(&edited, &crate::path::Path::parse(path).unwrap())
+                    .and_then(|node| <&str>::try_from(node).ok()),
+                Some(text),
+                "{path} = {text:?}"
+            );
+        }
+    }
+    assert_eq!(
+        edit("flow.a", Yaml::String("p, q".to_string())),
+        CONFIG.replace("{a: 1", "{a: 'p, q'")
+    );
+    assert_eq!(
+        edit("server.host", Yaml::String("p, q".to_string())),
+        CONFIG.replace("example.com", "p, q")
+    );
+
+    // keys are quoted too
+    let edits = doc.edit(r#"flow["k: v"]"#, &Yaml::Int(1)).unwrap();
+    assert_eq!(
+        TextEdit::apply(CONFIG, &edits),
+        CONFIG.replace("2]}", "2], 'k: v': 1}")
+    );
+    let edits = doc.rename_key("flow.a", "p, q").unwrap();
+    assert_eq!(
+        TextEdit::apply(CONFIG, &edits),
+        CONFIG.replace("{a: 1", "{'p, q': 1")
+    );
+}
+
+#[test]
+fn test_edit_flow_omitted_values() {
+    let source = "flow: {a, b: , c: 1}\n";
+    let doc = parse_document(source).unwrap();
+    assert_eq!(doc.span("flow.a"), Some(Span::new(8, 8)));
+    let apply = |path: &str| TextEdit::apply(source, &doc.edit(path, &Yaml::Int(2)).unwrap());
+    assert_eq!(apply("flow.a"), "flow: {a: 2, b: , c: 1}\n");
+    assert_eq!(apply("flow.b"), "flow: {a, b: 2 , c: 1}\n");
+}
+
+#[test]
+fn test_flow_sequence_pairs() {
+    let source = "list: [a: 1, b: x]\n";
+    let doc = parse_document(source).unwrap();
+    assert_eq!(doc.source_slice("list[1]"), Some("b: x"));
+    assert_eq!(doc.key_source_slice("list[1].b"), Some("b"));
+    let edits = doc.edit("list[0].a", &Yaml::Int(5)).unwrap();
+    assert_eq!(TextEdit::apply(source, &edits), "list: [a: 5, b: x]\n");
+}
+
+#[test]
+fn test_edit_errors() {
+    let doc = parse_document(CONFIG).unwrap();
+    assert_eq!(
+        doc.edit("a..b", &Yaml::Int(1)),
+        Err(EditError::InvalidPath("a..b".to_string()))
+    );
+    assert_eq!(
+        doc.edit("nope.x", &Yaml::Int(1)),
+        Err(EditError::NotFound("nope.x".to_string()))
+    );
+    assert_eq!(
+        doc.edit("hosts[5]", &Yaml::Int(1)),
+        Err(EditError::NotFound("hosts[5]".to_string()))
+    );
+}
+
+#[test]
+fn test_key_and_value_spans() {
+    let doc = parse_document(CONFIG).unwrap();
+    let key = doc.key_span("server.port").unwrap();
+    assert_eq!(&CONFIG[key.start..key.end], "port");
+    let value = doc.value_span("server.port").unwrap();
+    assert_eq!(&CONFIG[value.start..value.end], "8080");
+
+    let key = doc.key_span("flow.b").unwrap();
+    assert_eq!(&CONFIG[key.start..key.end], "b");
+
+    // sequence items and the root are not mapping entries
+    assert_eq!(doc.key_span("hosts[0]"), None);
+    assert_eq!(doc.value_span("hosts[0]"), None);
+    assert_eq!(doc.key_span(""), None);
+}
+
+#[test]
+fn test_source_slices() {
+    let source = String::from("name: app\nport: 8080\nquoted: 'a''b'\ntags: !t [1, 2]\n");
+    let doc = parse_document(&source).unwrap();
+    assert_eq!(doc.source_slice("port"), Some("8080"));
+    assert_eq!(doc.source_slice("quoted"), Some("'a''b'"));
+    assert_eq!(doc.source_slice("tags"), Some("!t [1, 2]"));
+    assert_eq!(doc.key_source_slice("port"), Some("port"));
+    assert_eq!(doc.key_source_slice("tags[0]"), None);
+    assert_eq!(doc.source_slice("missing"), None);
+
+    // only plain scalars keep their text in the tree
+    let Yaml::Mapping(entries) = doc.root() else {
+        panic!("expected a mapping");
+    };
+    let name = entries[0].value.as_source_slice().unwrap();
+    assert_eq!(name, "app");
+    assert!(std::ptr::eq(name.as_ptr(), source[6..].as_ptr()));
+    assert_eq!(entries[0].key.as_source_slice(), Some("name"));
+    assert_eq!(entries[1].value.as_source_slice(), None);
+}
+
+#[test]
+fn test_rename_key_keeps_value_text() {
+    let src = "db:\n  conn: |\n    host=a\n    port=1\n  opts: {a: 1,   b: 2}\n";
+    let doc = parse_document(src).unwrap();
+
+    let edits = doc.rename_key("db.conn", "connection").unwrap();
+    assert_eq!(
+        TextEdit::apply(src, &edits),
+        src.replace("conn:", "connection:")
+    );
+
+    let edits = doc.rename_key("db.opts.b", "yes").unwrap();
+    assert_eq!(
+        TextEdit::apply(src, &edits),
+        src.replace("b: 2", "'yes': 2")
+    );
+}
+
+#[test]
+fn test_rename_key_errors() {
+    let doc = parse_document(CONFIG).unwrap();
+    assert_eq!(
+        doc.rename_key("server.port", "host"),
+        Err(EditError::KeyExists("host".to_string()))
+    );
+    assert_eq!(
+        doc.rename_key("hosts[0]", "x"),
+        Err(EditError::NotFound("hosts[0]".to_string()))
+    );
+    assert!(doc.rename_key("server.port", "port").is_ok());
+}
+
+// Sequence item tests
+
+const DEPS: &str = r#"name: app
+deps:
+  # pinned for the 2.x API
+  - serde  # serialization
+  - name: tokio
+    features: [full]
+
+  # only needed for tests
+  # remove once upstreamed
+  - paste
+other: [x, y, z]
+"#;
+
+fn text(span: Span) -> &'static str {
+    &DEPS[span.start..span.end]
+}
+
+#[test]
+fn test_item_spans_and_comments() {
+    let doc = parse_document(DEPS).unwrap();
+    assert_eq!(text(doc.item_span("deps[0]").unwrap()), "- serde");
+    assert_eq!(
+        text(doc.item_span("deps[1]").unwrap()),
+        "- name: tokio\n    features: [full]"
+    );
+    assert_eq!(text(doc.item_span("other[1]").unwrap()), "y");
+    assert_eq!(doc.item_span("deps"), None);
+    assert_eq!(doc.item_span("deps[3]"), None);
+
+    assert_eq!(
+        doc.item_comment("deps[0]"),
+        Some("# pinned for the 2.x API")
+    );
+    assert_eq!(doc.item_comment("deps[1]"), None);
+    assert_eq!(
+        doc.item_comment("deps[2]"),
+        Some("# only needed for tests\n  # remove once upstreamed")
+    );
+    assert_eq!(doc.item_comment("other[0]"), None);
+
+    assert_eq!(
+        text(doc.item_extent("deps[0]").unwrap()),
+        "# pinned for the 2.x API\n  - serde  # serialization"
+    );
+    assert_eq!(
+        text(doc.item_extent("deps[2]").unwrap()),
+        "# only needed for tests\n  # remove once upstreamed\n  - paste"
+    );
+}
+
+#[test]
+fn test_move_item_block() {
+    let doc = parse_document(DEPS).unwrap();
+    let moved = TextEdit::apply(DEPS, &doc.move_item("deps[2]", 0).unwrap());
+    assert_eq!(
+        moved,
+        r#"name: app
+deps:
+  # only needed for tests
+  # remove once upstreamed
+  - paste
+  # pinned for the 2.x API
+  - serde  # serialization
+
+  - name: tokio
+    features: [full]
+other: [x, y, z]
+"#
+    );
+
+    let moved = TextEdit::apply(DEPS, &doc.move_item("deps[0]", 1).unwrap());
+    assert_eq!(
+        crate::parse(&moved).unwrap().to_json()["deps"],
+        serde_json::json!([{"name": "tokio", "features": ["full"]}, "serde", "paste"])
+    );
+    assert!(moved.contains("  # pinned for the 2.x API\n  - serde  # serialization\n"));
+}
+
+#[test]
+fn test_move_item_flow_and_nested() {
+    let doc = parse_document(DEPS).unwrap();
+    let moved = TextEdit::apply(DEPS, &doc.move_item("other[0]", 2).unwrap());
+    assert!(moved.ends_with("other: [y, z, x]\n"));
+
+    let input = "- - a\n  - b\n- c\n";
+    let doc = parse_document(input).unwrap();
+    let moved = TextEdit::apply(input, &doc.move_item("[0][1]", 0).unwrap());
+    assert_eq!(moved, "- - b\n  - a\n- c\n");
+    assert_eq!(doc.move_item("[0]", 1).unwrap().len(), 2);
+    assert!(doc.move_item("[1]", 1).unwrap().is_empty());
+}
+
+#[test]
+fn test_move_item_errors() {
+    let doc = parse_document(DEPS).unwrap();
+    assert_eq!(
+        doc.move_item("deps[0]", 3),
+        Err(EditError::NotFound("deps[3]".to_string()))
+    );
+    assert_eq!(
+        doc.move_item("deps[5]", 0),
+        Err(EditError::NotFound("deps[5]".to_string()))
+    );
+    assert_eq!(
+        doc.move_item("name", 0),
+        Err(EditError::NotFound("name".to_string()))
+    );
+    assert_eq!(
+        doc.move_item("deps[", 0),
+        Err(EditError::InvalidPath("deps[".to_string()))
+    );
+}
+
+#[test]
+fn test_edits_keep_crlf_line_breaks() {
+    let source = CONFIG.replace('\n', "\r\n");
+    let doc = parse_document(&source).unwrap();
+    assert_eq!(doc.newline(), Newline::CrLf);
+    let value = crate::parse("x: [1, 2]\ny: z\n").unwrap();
+    for path in ["server.port", "server.new", "hosts[2]", "flow.a"] {
+        let edits = match doc.edit(path, &value) {
+            Ok(edits) => edits,
+            Err(EditError::Unsupported(_)) => continue,
+            Err(err) => panic!("{path}: {err}"),
+        };
+        let edited = TextEdit::apply(&source, &edits);
+        assert_eq!(
+            edited.matches('\n').count(),
+            edited.matches("\r\n").count(),
+            "{path}: {edited:?}"
+        );
+    }
+    let edits = doc.edit("server.port", &Yaml::Int(1)).unwrap();
+    assert_eq!(edits[0].replacement, "1");
+}
+
+#[test]
+fn test_edit_empty_value() {
+    let src = "a:   # note\nb:\n";
+    let doc = parse_document(src).unwrap();
+    assert_eq!(doc.span("a"), Some(Span::new(2, 2)));
+    assert_eq!(doc.source_slice("b"), Some(""));
+    let edit = |path, value: Yaml<'_>| TextEdit::apply(src, &doc.edit(path, &value).unwrap());
+    assert_eq!(edit("a", Yaml::Int(1)), "a: 1   # note\nb:\n");
+    assert_eq!(edit("b", Yaml::Int(2)), "a:   # note\nb: 2\n");
+    assert_eq!(
+        edit("b", Yaml::Sequence(vec![Yaml::Int(1)])),
+        "a:   # note\nb:\n  - 1\n"
+    );
+}
+
+#[test]
+fn test_node_metadata() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Finding(&'static str);
+
+    let mut doc = parse_document("server:\n  port: 80\nhosts: [a, b]\n").unwrap();
+    assert_eq!(doc.set_meta("hosts[1]", Finding("unknown host")), Ok(None));
+    assert_eq!(doc.set_meta("server.port", Finding("privileged")), Ok(None));
+    assert_eq!(doc.set_meta("server.port", 7usize), Ok(None));
+    assert_eq!(
+        doc.set_meta("server.port", Finding("reserved")),
+        Ok(Some(Finding("privileged")))
+    );
+    assert_eq!(
+        doc.set_meta("server.host", 1usize),
+        Err(EditError::NotFound("server.host".to_string()))
+    );
+
+    assert_eq!(
+        doc.meta::<Finding>("server.port"),
+        Some(&Finding("reserved"))
+    );
+    assert_eq!(doc.meta::<usize>("server.port"), Some(&7));
+    assert_eq!(doc.meta::<usize>("hosts[1]"), None);
+    *doc.meta_mut::<usize>("server.port").unwrap() += 1;
+    assert_eq!(
+        doc.all_meta::<Finding>(),
+        vec![
+            ("server.port".to_string(), &Finding("reserved")),
+            ("hosts[1]".to_string(), &Finding("unknown host")),
+        ]
+    );
+
+    let copy = doc.clone();
+    assert_eq!(doc.remove_meta::<usize>("server.port"), Some(8));
+    assert_eq!(doc.meta::<usize>("server.port"), None);
+    assert_eq!(copy.meta::<usize>("server.port"), Some(&8));
+}
+
+#[test]
+fn test_spanned_nodes() {
+    let source = "a: &x {k: 1}\nb: *x\nc: !t [1, \"q\"]\n";
+    let doc = parse_document(source).unwrap();
+    let nodes: Vec<_> = doc
+        .spanned_nodes()
+        .into_iter()
+        .map(|(path, node)| (path, &source[node.span.start..node.span.end]))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("".to_string(), source.trim_end()),
+            ("a".to_string(), "&x {k: 1}"),
+            ("a.k".to_string(), "1"),
+            // not `b.k`, which is only a copy
+            ("b".to_string(), "*x"),
+            ("c".to_string(), "!t [1, \"q\"]"),
+            ("c[0]".to_string(), "1"),
+            ("c[1]".to_string(), "\"q\""),
+        ]
+    );
+
+    assert_eq!(
+        doc.spanned("c[1]"),
+        Some(Spanned::new(&Yaml::Scalar("q"), Span::new(29, 32)))
+    );
+    assert_eq!(doc.spanned("b.k").map(|node| node.value), None);
+    assert_eq!(doc.spanned("missing"), None);
+}
+
+#[test]
+fn test_spanned_nodes_with_duplicate_keys() {
+    let source = "a: 1\nb: x\na: 22\n";
+    let doc = parse_document(source).unwrap();
+    let nodes: Vec<_> = doc
+        .spanned_nodes()
+        .into_iter()
+        .map(|(path, node)| (path, node.value, &source[node.span.start..node.span.end]))
+        .collect();
+    assert_eq!(
+        nodes[1..],
+        [
+            ("b".to_string(), &Yaml::Scalar("x"), "x"),
+            ("a".to_string(), &Yaml::Int(22), "22"),
+        ]
+    );
+    assert_eq!(doc.spanned("a").unwrap().value, &Yaml::Int(22));
+}
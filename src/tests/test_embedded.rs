@@ -0,0 +1,67 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse_document, EmbeddedOptions};
+
+// Embedded block scalar tests
+
+const SOURCE: &str = r#"+test.settings[Demo]():
+  pinned_apps:
+    - name: Magix Docs
+      command: |
+        { open } = import('test');
+        open("Magix-Introduction.md");
+    - name: Query
+      language: sql
+      command: >-
+        select *
+        from apps
+  hook_script: !py |
+    print("hi")
+  inline: |
+    not a command
+  title: "command: |"
+"#;
+
+#[test]
+fn test_embedded_blocks() {
+    let doc = parse_document(SOURCE).unwrap();
+    let opts = EmbeddedOptions {
+        keys: vec!["command".into(), "*_script".into()],
+        ..EmbeddedOptions::default()
+    };
+    let blocks = doc.embedded_blocks(&opts);
+    assert_eq!(blocks.len(), 3);
+
+    let js = &blocks[0];
+    assert_eq!(
+        js.path,
+        r#"["+test.settings[Demo]()"].pinned_apps[0].command"#
+    );
+    assert_eq!(doc.span(&js.path), Some(js.span));
+    assert_eq!(
+        js.text,
+        "{ open } = import('test');\nopen(\"Magix-Introduction.md\");\n"
+    );
+    assert_eq!(js.language, None);
+    assert_eq!((js.line, js.indent), (5, 8));
+    assert!(SOURCE[js.span.start..js.span.end].starts_with("|\n"));
+    // lines of the text map back to the source
+    let second = SOURCE.lines().nth(js.line).unwrap();
+    assert_eq!(&second[js.indent..], js.text.lines().nth(1).unwrap());
+
+    assert_eq!(blocks[1].text, "select * from apps");
+    assert_eq!(blocks[1].language.as_deref(), Some("sql"));
+    assert_eq!(blocks[1].line, 10);
+
+    assert_eq!(blocks[2].path, r#"["+test.settings[Demo]()"].hook_script"#);
+    assert_eq!(blocks[2].language.as_deref(), Some("py"));
+    assert_eq!(blocks[2].text, "print(\"hi\")\n");
+    assert_eq!((blocks[2].line, blocks[2].indent), (13, 4));
+}
+
+#[test]
+fn test_embedded_blocks_skip_other_scalars() {
+    let doc = parse_document("command: ls -la\nrun:\n  command: 'a > b'\n").unwrap();
+    assert!(doc.embedded_blocks(&EmbeddedOptions::default()).is_empty());
+}
@@ -0,0 +1,354 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{Dialect, EmitOptions, Newline, TrailingNewline};
+
+// Subtree emission tests
+
+const CONFIG: &str = r#"server:
+  host: localhost
+  tls:
+    port: 8443
+    ciphers:
+      - aes
+      - chacha
+clients:
+  - name: web
+    retries: 3
+  - name: cli
+"#;
+
+#[test]
+fn test_emit_subtree_scalar() {
+    let parsed = crate::parse(CONFIG).unwrap();
+    let out = parsed
+        .emit_subtree("server.tls.port", &EmitOptions::default())
+        .unwrap();
+    assert_eq!(out, "8443");
+}
+
+#[test]
+fn test_emit_subtree_nested_mapping() {
+    let parsed = crate::parse(CONFIG).unwrap();
+    let opts = EmitOptions {
+        indent: 4,
+        ..EmitOptions::default()
+    };
+    let out = parsed.emit_subtree("server.tls", &opts).unwrap();
+    assert_eq!(out, "port: 8443\n    ciphers:\n      - aes\n      - chacha");
+}
+
+#[test]
+fn test_emit_subtree_splices_back() {
+    // Replacing the node's text with the emitted subtree reproduces the document
+    let parsed = crate::parse(CONFIG).unwrap();
+    let opts = EmitOptions {
+        indent: 4,
+        ..EmitOptions::default()
+    };
+    let out = parsed.emit_subtree("clients[0]", &opts).unwrap();
+    assert_eq!(out, "name: web\n    retries: 3");
+
+    let start = CONFIG.find("name: web").unwrap();
+    let end = start + "name: web\n    retries: 3".len();
+    let spliced = format!("{}{}{}", &CONFIG[..start], out, &CONFIG[end..]);
+    assert_eq!(crate::parse(&spliced).unwrap(), parsed);
+}
+
+#[test]
+fn test_emit_subtree_quoted_key() {
+    let yaml = "+setup[Settings](db://settings):\n  title: Settings\n";
+    let parsed = crate::parse(yaml).unwrap();
+    let out = parsed
+        .emit_subtree(
+            r#"["+setup[Settings](db://settings)"].title"#,
+            &EmitOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(out, "Settings");
+}
+
+#[test]
+fn test_emit_subtree_missing_path() {
+    let parsed = crate::parse(CONFIG).unwrap();
+    let opts = EmitOptions::default();
+    assert_eq!(parsed.emit_subtree("server.missing", &opts), None);
+    assert_eq!(parsed.emit_subtree("clients[5]", &opts), None);
+    assert_eq!(parsed.emit_subtree("server..tls", &opts), None);
+}
+
+#[test]
+fn test_emit_whole_document_with_indent() {
+    let parsed = crate::parse("a: 1\nb:\n  - x\n").unwrap();
+    let opts = EmitOptions {
+        indent: 2,
+        ..EmitOptions::default()
+    };
+    assert_eq!(parsed.emit(&opts), "  a: 1\n  b:\n    - x\n");
+    assert_eq!(parsed.emit(&EmitOptions::default()), parsed.to_string());
+}
+
+// Dialect tests
+
+fn emit_json(json: &str, dialect: Dialect) -> String {
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    let opts = EmitOptions {
+        dialect,
+        ..EmitOptions::default()
+    };
+    crate::Yaml::from_json(&value).emit(&opts)
+}
+
+#[test]
+fn test_dialect_mini_yaml_is_verbatim() {
+    let out = emit_json(r#"{"a": "yes", "b": "123", "c": 1.0}"#, Dialect::MiniYaml);
+    assert_eq!(out, "a: yes\nb: 123\nc: 1\n");
+}
+
+#[test]
+fn test_dialect_serde_yaml_quoting() {
+    let json = r#"{"plain": "hello world", "bool": "true", "yes": "yes", "y": "y",
+        "int": "123", "float": "1.5e3", "hex": "0x1F", "null": "null", "tilde": "~",
+        "empty": "", "colon": "a: b", "dash": "- x", "quote": "it's", "true": 1,
+        "date": "2001-12-14", "time": "1:30"}"#;
+    assert_eq!(
+        emit_json(json, Dialect::SerdeYaml),
+        "plain: hello world\nbool: 'true'\n'yes': 'yes'\n'y': 'y'\nint: '123'\n\
+         float: '1.5e3'\nhex: '0x1F'\n'null': 'null'\ntilde: '~'\nempty: ''\n\
+         colon: 'a: b'\ndash: '- x'\nquote: it's\n'true': 1\ndate: 2001-12-14\ntime: 1:30\n"
+    );
+}
+
+#[test]
+fn test_dialect_pyyaml_quoting() {
+    let json = r#"{"y": "y", "yes": "on", "date": "2001-12-14", "time": "1:30", "v": "v1.2"}"#;
+    assert_eq!(
+        emit_json(json, Dialect::PyYamlDefault),
+        "y: y\n'yes': 'on'\ndate: '2001-12-14'\ntime: '1:30'\nv: v1.2\n"
+    );
+}
+
+#[test]
+fn test_dialect_quotes_markers_and_merge_key() {
+    let json = r#"{"---": ["---", "...", "... end", "--- x", "---x"], "<<": "<<"}"#;
+    for dialect in [Dialect::SerdeYaml, Dialect::PyYamlDefault] {
+        let out = emit_json(json, dialect);
+        assert_eq!(
+            out,
+            "'---':\n  - '---'\n  - '...'\n  - '... end'\n  - '--- x'\n  - ---x\n'<<': '<<'\n"
+        );
+        let markers = out.split("'<<'").next().unwrap();
+        let parsed = crate::parse(markers).unwrap();
+        assert_eq!(parsed["---"][1], crate::Yaml::Scalar("..."));
+    }
+}
+
+#[test]
+fn test_dialect_double_quotes_control_characters() {
+    let out = emit_json(r#"{"a": "line\nbreak \"q\""}"#, Dialect::SerdeYaml);
+    assert_eq!(out, "a: \"line\\nbreak \\\"q\\\"\"\n");
+}
+
+#[test]
+fn test_dialect_floats_and_spelling() {
+    let yaml = crate::Yaml::Sequence(vec![
+        crate::Yaml::Float(1.0),
+        crate::Yaml::Float(2.5),
+        crate::Yaml::Float(f64::NEG_INFINITY),
+        crate::Yaml::Float(f64::NAN),
+        crate::Yaml::Bool(false),
+    ]);
+    let opts = EmitOptions {
+        dialect: Dialect::SerdeYaml,
+        ..EmitOptions::default()
+    };
+    assert_eq!(yaml.emit(&opts), "- 1.0\n- 2.5\n- -.inf\n- .nan\n- false\n");
+
+    assert_eq!(Dialect::MiniYaml.null_str(), "~");
+    assert_eq!(Dialect::SerdeYaml.null_str(), "null");
+    assert_eq!(Dialect::PyYamlDefault.null_str(), "null");
+}
+
+#[test]
+fn test_dialect_requotes_parsed_scalars() {
+    let parsed = crate::parse("a: yes\nb: '123'\n").unwrap();
+    let opts = EmitOptions {
+        dialect: Dialect::SerdeYaml,
+        ..EmitOptions::default()
+    };
+    assert_eq!(parsed.emit(&opts), "a: true\nb: '123'\n");
+    assert_eq!(parsed.to_string(), "a: true\nb: 123\n");
+}
+
+// Document header tests
+
+#[test]
+fn test_explicit_document_start() {
+    let value = crate::parse("a: 1\nb: [x]\n").unwrap();
+    let opts = EmitOptions {
+        explicit_document_start: true,
+        ..EmitOptions::default()
+    };
+    let out = value.emit(&opts);
+    assert_eq!(out, format!("---\n{value}"));
+
+    let stream = format!("{out}{out}");
+    assert_eq!(
+        crate::parse_all(&stream).unwrap(),
+        vec![value.clone(), value]
+    );
+}
+
+#[test]
+fn test_version_directive() {
+    let value = crate::parse("a: 1\n").unwrap();
+    let opts = EmitOptions {
+        version_directive: Some("1.2".to_string()),
+        ..EmitOptions::default()
+    };
+    assert_eq!(value.emit(&opts), "%YAML 1.2\n---\na: 1\n");
+    assert_eq!(crate::parse_all(&value.emit(&opts)).unwrap(), vec![value]);
+}
+
+#[test]
+fn test_subtree_has_no_document_header() {
+    let value = crate::parse("a:\n  b: 1\n").unwrap();
+    let opts = EmitOptions {
+        explicit_document_start: true,
+        version_directive: Some("1.2".to_string()),
+        ..EmitOptions::default()
+    };
+    assert_eq!(value.emit_subtree("a", &opts).unwrap(), "b: 1");
+}
+
+// Newline tests
+
+#[test]
+fn test_crlf_newlines() {
+    let value = crate::parse("a: 1\nb:\n  - x\n  - !t\n    c: d\n").unwrap();
+    let opts = EmitOptions {
+        newline: Newline::CrLf,
+        explicit_document_start: true,
+        ..EmitOptions::default()
+    };
+    assert_eq!(
+        value.emit(&opts),
+        "---\r\na: 1\r\nb:\r\n  - x\r\n  - !t\r\n    c: d\r\n"
+    );
+
+    // line breaks inside strings are converted too, without doubling `\r`
+    let value = crate::Yaml::String("one\ntwo\r\nthree".to_string());
+    assert_eq!(value.emit(&opts), "---\r\none\r\ntwo\r\nthree");
+    assert_eq!(EmitOptions::default().newline.as_str(), "\n");
+}
+
+#[test]
+fn test_detect_newline() {
+    assert_eq!(Newline::detect("a: 1\r\nb: 2\r\n"), Newline::CrLf);
+    assert_eq!(Newline::detect("a: 1\nb: 2\r\n"), Newline::Lf);
+    assert_eq!(Newline::detect("a: 1"), Newline::Lf);
+
+    // a document read from a Windows file can be written back the same way
+    let doc = crate::parse_document("a: 1\r\nb: [x]\r\n").unwrap();
+    let opts = EmitOptions {
+        newline: doc.newline(),
+        ..EmitOptions::default()
+    };
+    assert_eq!(doc.emit(&opts), "a: 1\r\nb:\r\n  - x\r\n");
+}
+
+// Trailing newline tests
+
+#[test]
+fn test_trailing_newline_policy() {
+    let value = crate::parse("a: 1\n").unwrap();
+    let emit = |trailing_newline| {
+        value.emit(&EmitOptions {
+            trailing_newline,
+            ..EmitOptions::default()
+        })
+    };
+    assert_eq!(emit(TrailingNewline::Preserve), "a: 1\n");
+    assert_eq!(emit(TrailingNewline::Always), "a: 1\n");
+    assert_eq!(emit(TrailingNewline::Never), "a: 1");
+
+    let scalar = crate::Yaml::Scalar("x");
+    let opts = EmitOptions {
+        trailing_newline: TrailingNewline::Always,
+        newline: Newline::CrLf,
+        ..EmitOptions::default()
+    };
+    assert_eq!(scalar.emit(&opts), "x\r\n");
+}
+
+#[test]
+fn test_document_preserves_final_newline() {
+    let doc = crate::parse_document("a: 1\nb: [x]\n").unwrap();
+    assert!(doc.ends_with_newline());
+    assert_eq!(doc.emit(&EmitOptions::default()), "a: 1\nb:\n  - x\n");
+
+    let doc = crate::parse_document("a: 1\nb: [x]").unwrap();
+    assert!(!doc.ends_with_newline());
+    assert_eq!(doc.emit(&EmitOptions::default()), "a: 1\nb:\n  - x");
+    let opts = EmitOptions {
+        trailing_newline: TrailingNewline::Always,
+        ..EmitOptions::default()
+    };
+    assert_eq!(doc.emit(&opts), "a: 1\nb:\n  - x\n");
+}
+
+#[test]
+fn test_key_order() {
+    use crate::KeyOrder;
+    let value = crate::parse(
+        "metadata: {name: web}\nspec:\n  replicas: 2\n  containers:\n    - image: nginx\n      name: web\n      ports: [80]\n  selector: {app: web}\nkind: Deployment\napiVersion: apps/v1\n",
+    )
+    .unwrap();
+    let opts = EmitOptions {
+        key_order: vec![
+            KeyOrder::new("", &["apiVersion", "kind", "metadata", "name"]),
+            KeyOrder::new("spec", &["selector"]),
+            KeyOrder::new("spec.containers[0]", &["name", "image"]),
+        ],
+        ..EmitOptions::default()
+    };
+    assert_eq!(
+        value.emit(&opts),
+        "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\nspec:\n  selector:\n    app: web\n  replicas: 2\n  containers:\n    - name: web\n      image: nginx\n      ports:\n        - 80\n"
+    );
+    assert_eq!(
+        crate::parse(&value.emit(&opts)).unwrap().to_json(),
+        value.to_json()
+    );
+
+    // the order of the tree is kept without rules
+    assert!(value.emit(&EmitOptions::default()).starts_with("metadata:"));
+}
+
+#[test]
+fn test_collection_keys() {
+    let value = crate::parse("? [a, b]\n: c\nd:\n  ? {e: 1}\n  : f\n").unwrap();
+    assert_eq!(
+        value.to_string(),
+        "?\n  - a\n  - b\n: c\nd:\n  ?\n    e: 1\n  : f\n"
+    );
+    let opts = EmitOptions {
+        dialect: Dialect::SerdeYaml,
+        ..EmitOptions::default()
+    };
+    assert_eq!(
+        value.emit(&opts),
+        "?\n  - a\n  - b\n: c\nd:\n  ?\n    e: 1\n  : f\n"
+    );
+    for text in [value.to_string(), value.emit(&opts)] {
+        assert_eq!(crate::parse(&text).unwrap(), value);
+    }
+}
+
+#[test]
+fn test_empty_collections() {
+    let value = crate::parse("a: {}\nb: []\nc:\n  - {}\n  - []\n").unwrap();
+    assert_eq!(value.to_string(), "a: {}\nb: []\nc:\n  - {}\n  - []\n");
+    assert_eq!(crate::parse(&value.to_string()).unwrap(), value);
+    assert_eq!(crate::parse("[]").unwrap().to_string(), "[]");
+}
@@ -0,0 +1,216 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{
+    parse, parse_with_options, Construct, ErrorKind, ParseContext, ParseOptions, MESSAGE_CATALOG,
+};
+use std::collections::HashSet;
+
+// Error kind and message catalog tests
+
+#[test]
+fn test_error_kinds() {
+    for (input, kind, message) in [
+        ("", ErrorKind::ExpectedInput, "expected input"),
+        (
+            "a: *nope\n",
+            ErrorKind::UnknownAlias {
+                name: "nope".into(),
+            },
+            "unknown alias '*nope'",
+        ),
+        (
+            "a: 'open\n",
+            ErrorKind::UnclosedQuote { quote: '\'' },
+            "unexpected end of input; expected '''",
+        ),
+        (
+            "a: \"\\q\"\n",
+            ErrorKind::InvalidEscape { escape: "q".into() },
+            "invalid escape sequence '\\q'",
+        ),
+        (
+            "a: \"\\u12\"\n",
+            ErrorKind::InvalidHexEscape {
+                escape: 'u',
+                digits: 4,
+            },
+            "invalid escape sequence '\\u': expected 4 hex digits of a valid character",
+        ),
+        (
+            "a: !!int x\n",
+            ErrorKind::InvalidTaggedValue { tag: "int".into() },
+            "invalid '!!int' value",
+        ),
+        (
+            "a: ]\n",
+            ErrorKind::UnexpectedSymbol { symbol: ']' },
+            "unexpected symbol ']'",
+        ),
+    ] {
+        let err = parse(input).unwrap_err();
+        assert_eq!(err.kind(), &kind, "{input:?}");
+        assert_eq!(err.message(), Some(message), "{input:?}");
+        assert_eq!(kind.to_string(), message);
+    }
+    let err = crate::parse_bytes(b"a: \xff\n").unwrap_err();
+    assert_eq!(err.kind().key(), "invalid-encoding");
+}
+
+#[test]
+fn test_message_catalog() {
+    let keys: HashSet<_> = MESSAGE_CATALOG.iter().map(|(key, _)| *key).collect();
+    assert_eq!(keys.len(), MESSAGE_CATALOG.len());
+    let kind = ErrorKind::AliasLimit { limit: 10 };
+    assert!(keys.contains(kind.key()));
+    assert_eq!(kind.template(), "aliases expand to more than {limit} nodes");
+    assert_eq!(
+        kind.render("plus de {limit} nœuds ({other})"),
+        "plus de 10 nœuds ({other})"
+    );
+}
+
+#[test]
+fn test_localized_messages() {
+    let opts = ParseOptions::default().messages(|kind| match kind {
+        ErrorKind::UnknownAlias { .. } => Some(kind.render("alias inconnu « *{name} »")),
+        ErrorKind::ExpectedInput => Some("entrée vide".to_string()),
+        _ => None,
+    });
+    let err = parse_with_options("a: *x\n", &opts).unwrap_err();
+    assert_eq!(err.message(), Some("alias inconnu « *x »"));
+    assert_eq!(err.kind(), &ErrorKind::UnknownAlias { name: "x".into() });
+    assert_eq!(err.line(), parse("a: *x\n").unwrap_err().line());
+    let err = parse_with_options("", &opts).unwrap_err();
+    assert_eq!(err.message(), Some("entrée vide"));
+    // kinds the hook leaves alone keep their English message
+    let err = parse_with_options("a: ]\n", &opts).unwrap_err();
+    assert_eq!(err.message(), Some("unexpected symbol ']'"));
+}
+
+#[test]
+fn test_error_contexts() {
+    let err = parse("a:\n  - [1, {b: 2]\n").unwrap_err();
+    let frames: Vec<_> = err
+        .contexts()
+        .iter()
+        .map(|frame| (frame.construct, frame.line, frame.col))
+        .collect();
+    assert_eq!(
+        frames,
+        [
+            (Construct::BlockMapping, 1, 1),
+            (Construct::BlockSequence, 2, 3),
+            (Construct::FlowSequence, 2, 5),
+            (Construct::FlowMapping, 2, 9),
+        ]
+    );
+    assert_eq!(err.contexts()[2].context, ParseContext::FlowIn);
+    assert!(err
+        .to_string()
+        .ends_with("(while parsing flow mapping started at line 2, column 9)"));
+
+    let err = parse("{a: [1, 2}").unwrap_err();
+    assert_eq!(
+        err.contexts().last().unwrap().construct,
+        Construct::FlowSequence
+    );
+    // a scalar is inside whichever block collection holds it, whether it
+    // is a key, a value or an item
+    for (input, construct) in [
+        ("a: \"abc", Construct::BlockMapping),
+        ("a: 1\n\"abc", Construct::BlockMapping),
+        ("- \"abc", Construct::BlockSequence),
+    ] {
+        let err = parse(input).unwrap_err();
+        let frames: Vec<_> = err
+            .contexts()
+            .iter()
+            .map(|frame| (frame.construct, frame.line, frame.col))
+            .collect();
+        assert_eq!(frames, [(construct, 1, 1)], "{input:?}");
+    }
+    let err = parse("? [a\n").unwrap_err();
+    assert_eq!(err.contexts()[0].construct, Construct::BlockMapping);
+    assert!(parse("a: \"abc")
+        .unwrap_err()
+        .to_string()
+        .ends_with("(while parsing block mapping started at line 1, column 1)"));
+    // errors outside any collection have no contexts
+    for input in ["*x\n", "\"abc"] {
+        assert!(parse(input).unwrap_err().contexts().is_empty(), "{input:?}");
+    }
+}
+
+#[test]
+fn test_parse_complete() {
+    for input in [
+        "a: 1\n# end\n",
+        "[1, 2] # end\n\n",
+        "a:\nb:",
+        "x\n...\n# end\n",
+    ] {
+        assert_eq!(crate::parse_complete(input), parse(input), "{input:?}");
+    }
+    for (input, line, col) in [
+        ("[1] x\n", 1, 5),
+        ("[1]]", 1, 4),
+        ("{a: 1}}\n", 1, 7),
+        ("- a\n- b\nc\n", 3, 1),
+        ("a: 1\n---\nb: 2\n", 2, 1),
+        ("a\n...\nb\n", 3, 1),
+    ] {
+        assert!(parse(input).is_ok(), "{input:?}");
+        let err = crate::parse_complete(input).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::TrailingContent, "{input:?}");
+        assert_eq!((err.line, err.col), (line, col), "{input:?}");
+    }
+}
+
+#[test]
+fn test_error_positions() {
+    for (input, line, col) in [
+        ("a: ]\n", 1, 4),
+        ("a: 1\nb: ]", 2, 4),
+        ("a: 1\r\nb: ]\r\n", 2, 4),
+        // columns count characters, not bytes
+        ("é: ü\nb: ]\n", 2, 4),
+        // an error at the end of the input is at the end of the last line
+        ("a: \"open", 1, 9),
+        ("a:\n  - 1\n  - \"x\n", 3, 7),
+        ("a: [1,\n", 1, 7),
+        ("[1, 2\n\n\n", 1, 6),
+        ("x: -", 1, 4),
+        // an error before the end of the input is where it occurred
+        ("a: 1\n- x\n", 2, 1),
+        ("key: |\n  lit\n- x\n", 3, 1),
+        ("[-]", 1, 2),
+    ] {
+        let err = parse(input).unwrap_err();
+        assert_eq!((err.line(), err.col()), (line, col), "{input:?}");
+    }
+    assert_eq!(parse("").unwrap_err().line(), 1);
+}
+
+#[test]
+fn test_input_ending_in_a_comment() {
+    // the last byte is not read again once the input has ended
+    assert_eq!(parse("#a").unwrap(), crate::Yaml::Null);
+    assert_eq!(parse("#é").unwrap(), crate::Yaml::Null);
+    assert_eq!(parse("a: 1 #é").unwrap(), parse("a: 1").unwrap());
+}
+
+#[test]
+fn test_user_text_in_messages_is_sanitized() {
+    let err = parse(&format!("a: *{}\n", "x".repeat(500))).unwrap_err();
+    let message = err.message().unwrap();
+    assert!(message.len() < 120, "{message}");
+    assert!(message.ends_with("…'"), "{message}");
+
+    let err = parse("a: *\u{1b}x\n").unwrap_err();
+    assert_eq!(err.message(), Some("unknown alias '*x'"));
+
+    let err = parse("a: \"\\\u{1b}\"\n").unwrap_err();
+    assert!(!err.to_string().contains('\u{1b}'), "{err}");
+    assert!(err.to_string().contains("\\u{1b}"), "{err}");
+}
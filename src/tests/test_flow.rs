@@ -157,3 +157,41 @@ mk_test!(
   ]
 }"# => map!{ "items" => seq!("a", "b", "c") }
 );
+
+mk_test!(
+    flow mapping omitted values;
+    "{a, b: 1, c: , d}" => map!{
+        "a" => crate::Yaml::Null;
+        "b" => crate::Yaml::Int(1);
+        "c" => crate::Yaml::Null;
+        "d" => crate::Yaml::Null
+    }
+);
+
+mk_test!(
+    flow mapping omitted value at end;
+    "{x: [1], y:}" => map!{ "x" => seq!(crate::Yaml::Int(1)); "y" => crate::Yaml::Null }
+);
+
+mk_test!(
+    flow sequence single pair mappings;
+    "[a: 1, b, \"c\":2, d: ]" => seq!(
+        map!{ "a" => crate::Yaml::Int(1) },
+        "b",
+        map!{ "c" => crate::Yaml::Int(2) },
+        map!{ "d" => crate::Yaml::Null }
+    )
+);
+
+mk_test!(
+    flow sequence pair with collection value;
+    "[x: [1, 2], y: {z: w}]" => seq!(
+        map!{ "x" => seq!(crate::Yaml::Int(1), crate::Yaml::Int(2)) },
+        map!{ "y" => map!{ "z" : "w" } }
+    )
+);
+
+mk_test!(
+    flow sequence colon without space stays in scalar;
+    "[a:1]" => seq!("a:1")
+);
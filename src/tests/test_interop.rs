@@ -0,0 +1,177 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{capabilities, parse, parse_with_options, ParseOptions, Producer, Support, Yaml};
+
+// Interop tests
+
+fn compat() -> ParseOptions {
+    ParseOptions {
+        compat: true,
+        ..Default::default()
+    }
+}
+
+fn has_tag(node: &Yaml) -> bool {
+    match node {
+        Yaml::Tagged(..) => true,
+        Yaml::Mapping(entries) => entries.iter().any(|entry| has_tag(&entry.value)),
+        Yaml::Sequence(items) => items.iter().any(has_tag),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_interop_matrix_holds() {
+    for feature in capabilities().interop {
+        let example = feature.example;
+        let default = parse(example);
+        let compat = parse_with_options(example, &compat());
+        match feature.support {
+            Support::Full => {
+                let value = default.unwrap_or_else(|err| panic!("{example:?}: {err}"));
+                assert!(!has_tag(&value), "{example:?}");
+                assert_eq!(compat.unwrap(), value, "{example:?}");
+            }
+            Support::Compat => {
+                let value = compat.unwrap_or_else(|err| panic!("{example:?}: {err}"));
+                assert!(!has_tag(&value), "{example:?}");
+                assert_ne!(default.unwrap(), value, "{example:?}");
+            }
+            Support::TagOnly => assert!(has_tag(&compat.unwrap()), "{example:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_interop_matrix_json() {
+    let json = capabilities().to_json();
+    let features = json["interop"].as_array().unwrap();
+    assert_eq!(features.len(), capabilities().interop.len());
+    assert_eq!(features[0]["producer"], Producer::PyYaml.name());
+    assert!(features
+        .iter()
+        .all(|f| matches!(f["support"].as_str(), Some("full" | "compat" | "tag-only"))));
+}
+
+#[test]
+fn test_pyyaml_fixture() {
+    // yaml.dump({...}, version=(1, 1)) of a small config
+    let input = "%YAML 1.1
+---
+'001': leading zeros
+anchors:
+- &id001
+  name: a
+  tags: [x, y]
+- *id001
+description: a long description which PyYAML wraps because it is longer than
+  eighty characters
+empty: ''
+flags: !!python/tuple
+- true
+- false
+none: null
+pair: !!python/tuple [1, 2]
+title: !!python/unicode 'café'
+...
+";
+    assert_eq!(
+        parse_with_options(input, &compat()).unwrap(),
+        map! {
+            "001" => "leading zeros";
+            "anchors" => seq!(
+                map! { "name" => "a"; "tags" => seq!("x", "y") },
+                map! { "name" => "a"; "tags" => seq!("x", "y") }
+            );
+            "description" => Yaml::String(
+                "a long description which PyYAML wraps because it is longer than eighty characters".into()
+            );
+            "empty" => "";
+            "flags" => seq!(Yaml::Bool(true), Yaml::Bool(false));
+            "none" => Yaml::Null;
+            "pair" => seq!(Yaml::Int(1), Yaml::Int(2));
+            "title" => "caf\u{e9}"
+        }
+    );
+}
+
+#[test]
+fn test_go_yaml_fixture() {
+    // yaml.Marshal of a struct with yaml.v3's four-space indentation
+    let input = "# service settings
+name: api
+replicas: 3
+ratio: 0.5
+enabled: \"yes\"
+ports:
+    - 80
+    - 443
+labels: {}
+script: |-
+    echo one
+    echo two
+defaults: &defaults
+    timeout: 30s
+prod:
+    <<: *defaults
+    region: null
+";
+    assert_eq!(
+        parse(input).unwrap(),
+        map! {
+            "name" => "api";
+            "replicas" => Yaml::Int(3);
+            "ratio" => Yaml::Float(0.5);
+            "enabled" => "yes";
+            "ports" => seq!(Yaml::Int(80), Yaml::Int(443));
+            "labels" => Yaml::Mapping(vec![]);
+            "script" => Yaml::String("echo one\necho two".into());
+            "defaults" => map! { "timeout" => "30s" };
+            "prod" => map! { "timeout" => "30s"; "region" => Yaml::Null }
+        }
+    );
+}
+
+#[test]
+fn test_python_objects_keep_their_tag() {
+    let value = parse_with_options("!!python/object:app.models.User {id: 7}\n", &compat()).unwrap();
+    assert_eq!(
+        value,
+        tagged!(
+            "!python/object:app.models.User",
+            map! { "id" => Yaml::Int(7) }
+        )
+    );
+    assert_eq!(
+        crate::parse(&value.to_string()).unwrap(),
+        value,
+        "emitted as {value}"
+    );
+}
+
+#[test]
+fn test_document_end_and_directives() {
+    assert_eq!(parse("text\n...\n").unwrap(), Yaml::Scalar("text"));
+    assert_eq!(
+        parse("a: 1\n...\n# trailing\n").unwrap(),
+        map! { "a" => Yaml::Int(1) }
+    );
+    assert_eq!(
+        parse("# c\n%YAML 1.2\n%TAG ! tag:example.com,2000:\n--- \na: 1\n").unwrap(),
+        map! { "a" => Yaml::Int(1) }
+    );
+    let err = parse("%YAML 1.2\na: 1\n").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert!(
+        err.to_string().contains("expected '---' after directives"),
+        "{err}"
+    );
+    // a second document is still an error for `parse`
+    assert!(parse("a: 1\n...\nb: 2\n").is_err());
+    assert!(parse("a: 1\n...\nb: 2\n...\n").is_err());
+    // markers and comments after the first `...` are passed over, however
+    // many there are
+    let input = format!("a: 1\n{}", "...\n# c\n".repeat(50_000));
+    assert_eq!(parse(&input).unwrap(), map! { "a" => Yaml::Int(1) });
+}
@@ -20,6 +20,37 @@ enabled: true
     assert_eq!(obj.get("enabled").unwrap().as_bool().unwrap(), true);
 }
 
+#[test]
+fn test_null_values() {
+    let yaml = "a: ~\nb: null\nc: Null\nd: NULL\ne: 'null'\nf: nullable\ng: [~, null]\n";
+    let parsed = crate::parse(yaml).unwrap();
+    match &parsed {
+        crate::Yaml::Mapping(map) => assert_eq!(map[0].value, crate::Yaml::Null),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(
+        parsed.to_json(),
+        serde_json::json!({
+            "a": null, "b": null, "c": null, "d": null,
+            "e": "null", "f": "nullable", "g": [null, null]
+        })
+    );
+}
+
+#[test]
+fn test_null_from_json_round_trip() {
+    let json = serde_json::json!({"a": null, "b": [1, null]});
+    let yaml = crate::Yaml::from_json(&json);
+    assert_eq!(yaml.to_string(), "a: ~\nb:\n  - 1\n  - ~\n");
+    assert_eq!(crate::parse(&yaml.to_string()).unwrap().to_json(), json);
+
+    let opts = crate::EmitOptions {
+        dialect: crate::Dialect::SerdeYaml,
+        ..crate::EmitOptions::default()
+    };
+    assert_eq!(yaml.emit(&opts), "a: null\nb:\n  - 1\n  - null\n");
+}
+
 #[test]
 fn test_to_json_basic() {
     let yaml = r#"
@@ -143,6 +174,110 @@ invalid_key:
         .contains("does not match"));
 }
 
+fn mx_error_value(yaml: &crate::Yaml, opts: &crate::MxOptions) -> String {
+    let json = yaml.to_mx_with_options(opts);
+    json["+error"]["__value"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn test_to_mx_error_snippet_truncated() {
+    let long = crate::Yaml::Sequence(vec![crate::Yaml::String("x".repeat(1000))]);
+    let opts = crate::MxOptions {
+        snippet_limit: Some(200),
+        ..Default::default()
+    };
+    let value = mx_error_value(&long, &opts);
+    assert_eq!(value.chars().count(), 200);
+    assert!(value.ends_with('…'));
+
+    let opts = crate::MxOptions {
+        snippet_limit: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(mx_error_value(&long, &opts), "");
+
+    // by default content is embedded in full
+    assert!(mx_error_value(&long, &crate::MxOptions::default()).len() > 1000);
+}
+
+#[test]
+fn test_to_mx_error_snippet_grapheme_boundaries() {
+    // "é" written as e + combining acute accent, and flags made of two
+    // regional indicators, must never be split
+    let opts = crate::MxOptions {
+        snippet_limit: Some(5),
+        ..Default::default()
+    };
+    let accents = crate::Yaml::Sequence(vec![crate::Yaml::String("e\u{301}".repeat(10))]);
+    assert_eq!(mx_error_value(&accents, &opts), "- e\u{301}e\u{301}…");
+
+    let flags = crate::Yaml::Sequence(vec![crate::Yaml::String("\u{1F1EB}\u{1F1F7}".repeat(10))]);
+    assert_eq!(
+        mx_error_value(&flags, &opts),
+        "- \u{1F1EB}\u{1F1F7}\u{1F1EB}\u{1F1F7}…"
+    );
+
+    let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    let zwj = crate::Yaml::Sequence(vec![crate::Yaml::String(family.repeat(10))]);
+    assert_eq!(
+        mx_error_value(&zwj, &opts),
+        format!("- {}{}…", family, family)
+    );
+}
+
+#[test]
+fn test_to_mx_error_snippet_strips_control_chars() {
+    let yaml = crate::Yaml::Sequence(vec![crate::Yaml::String(
+        "red\u{1b}[31m\u{7}\rtext".to_string(),
+    )]);
+    let value = mx_error_value(&yaml, &crate::MxOptions::default());
+    assert_eq!(value, "- red[31mtext\n");
+
+    let key = crate::Yaml::Mapping(vec![crate::Entry::new(
+        crate::Yaml::String("bad\u{1b}]0;title\u{7}\nkey".to_string()),
+        crate::Yaml::Scalar("x"),
+    )]);
+    let json = key.to_mx();
+    assert_eq!(
+        json["+error"]["__name"],
+        "Key 'bad]0;title key' does not match expected format +name[label](value)"
+    );
+}
+
+#[test]
+fn test_to_mx_error_without_body() {
+    let yaml = "+a[A]: {secret: hunter2}\nbad key: {password: hunter2}\n";
+    let parsed = crate::parse(yaml).unwrap();
+    let opts = crate::MxOptions {
+        include_body: false,
+        ..Default::default()
+    };
+    let json = parsed.to_mx_with_options(&opts);
+    assert_eq!(
+        json,
+        serde_json::json!({"+error": {
+            "__name": "Key 'bad key' does not match expected format +name[label](value)",
+            "__key": "bad key",
+            "__index": 1
+        }})
+    );
+    assert!(!json.to_string().contains("hunter2"));
+
+    let json = parsed.to_mx();
+    assert_eq!(json["+error"]["__key"], "bad key");
+    assert!(json["+error"]["__value"]
+        .as_str()
+        .unwrap()
+        .contains("hunter2"));
+
+    let seq = crate::parse("- hunter2").unwrap();
+    let json = seq.to_mx_with_options(&opts);
+    assert_eq!(
+        json,
+        serde_json::json!({"+error": {"__name": "Top level value must be an object"}})
+    );
+}
+
 #[test]
 fn test_to_mx_empty_mx_value() {
     // Mx key with empty parentheses, no colon - parsed as scalar
@@ -252,3 +387,129 @@ fn test_field_order_preserved_in_mx() {
         .collect();
     assert_eq!(field_keys, vec!["zField", "aField", "mField"]);
 }
+
+#[test]
+fn test_sequence_to_json_mixed_items() {
+    let yaml = crate::parse("[1, -2.5, .inf, text, 'quoted', ~, true, [3], {a: 4}, !!binary AQ==]")
+        .unwrap();
+    assert_eq!(
+        yaml.to_json(),
+        serde_json::json!([1, -2.5, 0, "text", "quoted", null, true, [3], {"a": 4}, "AQ=="])
+    );
+    let opts = crate::JsonOptions {
+        bytes: crate::BytesFormat::Array,
+        ..Default::default()
+    };
+    assert_eq!(yaml.to_json_with_options(&opts)[9], serde_json::json!([1]));
+}
+
+#[test]
+fn test_pointer_matches_json() {
+    let mut value = crate::parse(
+        "spec:\n  containers:\n    - image: app:1\n    - image: db:2\na/b: {\"~x\": 1}\n\"\": empty\nitem: !ref {id: 7}\nsize: !px 10\nseq: !list [1, 2]\n",
+    )
+    .unwrap();
+    let json = value.to_json();
+    for pointer in [
+        "",
+        "/spec",
+        "/spec/containers/1/image",
+        "/a~1b/~0x",
+        "/",
+        "/item/id",
+        "/size/__value",
+        "/seq/__value/1",
+        "/spec/containers/01",
+        "/spec/containers/+1",
+        "/spec/containers/2",
+        "/spec/missing",
+        "spec",
+    ] {
+        assert_eq!(
+            value.pointer(pointer).map(|node| node.to_json()).as_ref(),
+            json.pointer(pointer),
+            "{pointer}"
+        );
+    }
+    assert_eq!(
+        value.pointer("/size"),
+        Some(&crate::Yaml::tagged("px", crate::Yaml::Int(10)))
+    );
+
+    *value.pointer_mut("/spec/containers/0/image").unwrap() = crate::Yaml::Scalar("app:2");
+    *value.pointer_mut("/size/__value").unwrap() = crate::Yaml::Int(12);
+    assert_eq!(
+        value["spec"]["containers"][0]["image"],
+        crate::Yaml::Scalar("app:2")
+    );
+    assert_eq!(value.pointer("/size/__value"), Some(&crate::Yaml::Int(12)));
+    assert_eq!(value.pointer_mut("/seq/0"), None);
+}
+
+#[test]
+fn test_apply_patch() {
+    let mut value = crate::parse(
+        "name: app\nreplicas: 1\nports: [80, 443]\nlabels: !meta {tier: web}\nold: x\n",
+    )
+    .unwrap();
+    let patch = serde_json::json!([
+        {"op": "test", "path": "/name", "value": "app"},
+        {"op": "replace", "path": "/replicas", "value": 3},
+        {"op": "add", "path": "/ports/1", "value": 8080},
+        {"op": "add", "path": "/ports/-", "value": 9090},
+        {"op": "add", "path": "/labels/env", "value": "prod"},
+        {"op": "remove", "path": "/ports/0"},
+        {"op": "move", "from": "/old", "path": "/new"},
+        {"op": "copy", "from": "/labels/tier", "path": "/tier"},
+    ]);
+    value.apply_patch(&patch).unwrap();
+    assert_eq!(
+        value.to_string(),
+        "name: app\nreplicas: 3\nports:\n  - 8080\n  - 443\n  - 9090\nlabels: !meta\n  tier: web\n  env: prod\nnew: x\ntier: web\n"
+    );
+
+    let before = value.clone();
+    for (patch, error) in [
+        (
+            serde_json::json!([{"op": "replace", "path": "/name", "value": "b"}, {"op": "test", "path": "/replicas", "value": 4}]),
+            "patch operation 1 failed: test failed at \"/replicas\"",
+        ),
+        (
+            serde_json::json!([{"op": "remove", "path": "/missing"}]),
+            "patch operation 0 failed: no value at \"/missing\"",
+        ),
+        (
+            serde_json::json!([{"op": "add", "path": "/ports/7", "value": 1}]),
+            "patch operation 0 failed: no value at \"/ports/7\"",
+        ),
+        (
+            serde_json::json!([{"op": "add", "path": "/name/x", "value": 1}]),
+            "patch operation 0 failed: cannot add below the scalar at \"/name\"",
+        ),
+        (
+            serde_json::json!([{"op": "move", "from": "/labels", "path": "/labels/inner"}]),
+            "patch operation 0 failed: cannot move \"/labels\" into itself",
+        ),
+        (
+            serde_json::json!([{"op": "frobnicate", "path": ""}]),
+            "patch operation 0 failed: unknown operation \"frobnicate\"",
+        ),
+        (
+            serde_json::json!([{"op": "add", "path": "/a"}]),
+            "patch operation 0 failed: missing \"value\"",
+        ),
+        (
+            serde_json::json!({"op": "add"}),
+            "patch operation 0 failed: a patch must be an array of operations",
+        ),
+    ] {
+        assert_eq!(value.apply_patch(&patch).unwrap_err().to_string(), error);
+        // a failed patch leaves the tree unchanged
+        assert_eq!(value, before);
+    }
+
+    value
+        .apply_patch(&serde_json::json!([{"op": "add", "path": "", "value": [1]}]))
+        .unwrap();
+    assert_eq!(value.to_json(), serde_json::json!([1]));
+}
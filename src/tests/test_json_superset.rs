@@ -0,0 +1,102 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse_json, Yaml};
+
+// JSON superset tests
+
+/// Valid JSON documents covering the grammar: every kind of value, every
+/// escape, number forms, whitespace between tokens, and strings which would
+/// mean something else if they were plain Yaml
+const CORPUS: &[&str] = &[
+    // values at the top level
+    "null",
+    "true",
+    "false",
+    "0",
+    "-12",
+    "3.25",
+    r#""""#,
+    r#""text""#,
+    "{}",
+    "[]",
+    // numbers
+    "[0, -0, 1, -1, 10, 1.5, -0.25, 1e5, 1E5, 1e+5, 1e-5, -2.5E-3, 0.0]",
+    "[9223372036854775807, -9223372036854775808]",
+    "[123456789012345678901234567890, -98765432109876543210]",
+    // escapes
+    r#"["\"", "\\", "\/", "\b", "\f", "\n", "\r", "\t"]"#,
+    r#"["\u0041", "\u00e9", "\u4e2d", "\ud83d\ude00", "a\u0000b"]"#,
+    r#"{"caf\u00e9": "\u00e9t\u00e9"}"#,
+    // whitespace between tokens
+    "{\"a\":1,\"b\":[2,3],\"c\":{\"d\":null}}",
+    "{ \"a\" : 1 , \"b\" :[ 2 ,3 ] }",
+    "{\n\t\"a\": [\n\t\t1,\n\t\t2\n\t],\r\n\t\"b\":\r\n\ttrue\n}\n",
+    "  [ 1 ]  ",
+    "{\"a\"\n: 1, \"b\"\n:\n2}",
+    "[\n]",
+    "{\n}",
+    // structure
+    r#"[[], {}, [[]], [{}], {"a": []}, {"a": {}}]"#,
+    r#"{"a": {"b": {"c": {"d": [1, [2, [3, {"e": "f"}]]]}}}}"#,
+    r#"[{"id": 1, "tags": ["x", "y"]}, {"id": 2, "tags": []}]"#,
+    r#"{"": "", " ": " ", "a b": "c d"}"#,
+    r#"{"a": 1, "a": 2}"#,
+    // strings which look like Yaml syntax
+    r#"["yes", "no", "on", "~", "Null", "0x1A", ".inf", "2024-06-01", "1_000"]"#,
+    r##"["# not a comment", "a #b", "key: value", "- item", "? key", "--- doc", "... end"]"##,
+    r#"["{not: flow}", "[x]", "&anchor", "*alias", "!tag", "|", ">", "%", "@", "`"]"#,
+    r#"["a,b", "c]d", "e}f", "'single'", "tab\tin", "  lead", "trail  "]"#,
+    r#"{"url": "http://example.com/a?b=c#d", "path": "C:\\dir\\file"}"#,
+];
+
+#[test]
+fn test_json_corpus() {
+    for input in CORPUS {
+        let expected: serde_json::Value = serde_json::from_str(input).unwrap();
+        let parsed = parse_json(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+        assert_eq!(parsed.to_json(), expected, "{input}");
+    }
+}
+
+#[test]
+fn test_json_mode_large_integers() {
+    assert_eq!(
+        parse_json("[1, 18446744073709551615]").unwrap(),
+        seq!(Yaml::Int(1), Yaml::Float(18446744073709551615.0))
+    );
+    // the default rules keep an integer they cannot hold as text
+    assert_eq!(
+        crate::parse("18446744073709551615").unwrap(),
+        Yaml::Scalar("18446744073709551615")
+    );
+}
+
+#[test]
+fn test_json_mode_accepts_yaml() {
+    assert_eq!(
+        parse_json("a: [1, yes]\nb: null\n").unwrap(),
+        map! { "a" => seq!(Yaml::Int(1), "yes"); "b" => Yaml::Null }
+    );
+}
+
+#[test]
+fn test_trailing_commas() {
+    // Yaml allows one trailing comma in a flow collection, which lenient
+    // JSON writers (and hand-edited config) leave behind
+    for input in [
+        "[1, 2,]",
+        "{\"a\": 1,}",
+        "{\n  \"a\": [1, 2,],\n  \"b\": {\"c\": null,},\n}\n",
+    ] {
+        let strict = input
+            .replace(",]", "]")
+            .replace(",}", "}")
+            .replace(",\n}", "\n}");
+        let expected: serde_json::Value = serde_json::from_str(&strict).unwrap();
+        assert_eq!(parse_json(input).unwrap().to_json(), expected, "{input}");
+        assert_eq!(crate::parse(input).unwrap().to_json(), expected, "{input}");
+    }
+    assert!(parse_json("[1,,2]").is_err());
+    assert!(parse_json("[,]").is_err());
+}
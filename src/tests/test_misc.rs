@@ -3,12 +3,6 @@
 
 use crate::YamlParseError;
 
-impl<'a> From<&'a str> for crate::Yaml<'a> {
-    fn from(other: &'a str) -> Self {
-        crate::Yaml::Scalar(other)
-    }
-}
-
 // Misc tests
 
 mk_test!(
@@ -45,15 +39,21 @@ r#"
 mk_test!(
 input with error;
 r#"
-{key: value, missing : }
-"# => err YamlParseError{ line: 2, col: 25, msg: Some(String::from(r#"unexpected symbol '}'"#)), source: None }
+{key: value, missing : ]
+"# => err YamlParseError{ line: 2, col: 24, kind: crate::ErrorKind::UnexpectedSymbol { symbol: ']' }, msg: Some(String::from(r#"unexpected symbol ']'"#)), source: None,
+    contexts: vec![crate::ContextFrame {
+        context: crate::ParseContext::FlowIn,
+        construct: crate::Construct::FlowMapping,
+        line: 2,
+        col: 1,
+    }] }
 );
 
 mk_test!(
 error msg;
 r#"
-{key: value, missing : }
-"# => err msg r#"error occurred parsing the input at line 2, column 25 : unexpected symbol '}'"#
+{key: value, missing : ]
+"# => err msg r#"error occurred parsing the input at line 2, column 24 : unexpected symbol ']' (while parsing flow mapping started at line 2, column 1)"#
 );
 
 mk_test!(
@@ -150,7 +150,7 @@ a: block
 mapping: missing
 a value for this key:
 
-" => err YamlParseError { line: 5, col: 1, msg: Some("unexpected end of input".into()), source: None}
+" => map! { "a" => "block"; "mapping" => "missing"; "a value for this key" => crate::Yaml::Null }
 );
 
 mk_test!(
@@ -300,3 +300,844 @@ fn test_utf8_mixed_content_block_scalar() {
         panic!("Expected mapping");
     }
 }
+
+// Re-borrowing tests
+
+#[test]
+fn test_deep_clone_into_unchanged_source() {
+    let old = String::from("name: app\ntags: [a, b]\nkind: !svc {port: 80}\n");
+    let parsed = crate::parse(&old).unwrap();
+    let new = old.clone();
+    let moved = parsed.deep_clone_into(&old, &new).unwrap();
+    drop(parsed);
+    drop(old);
+    assert_eq!(moved, crate::parse(&new).unwrap());
+    match &moved {
+        crate::Yaml::Mapping(map) => match map[0].value {
+            crate::Yaml::Scalar(s) => assert_eq!(s.as_ptr(), new[6..].as_ptr()),
+            ref other => panic!("unexpected {:?}", other),
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_deep_clone_into_changed_source() {
+    let old = "name: app\nport: 80\n";
+    let parsed = crate::parse(old).unwrap();
+    assert_eq!(parsed.deep_clone_into(old, "name: app\nport: 81\n"), None);
+    assert_eq!(parsed.deep_clone_into(old, "name: api\nport: 80\n"), None);
+    assert_eq!(parsed.deep_clone_into(old, "\nname: app\nport: 80\n"), None);
+    assert_eq!(parsed.deep_clone_into(old, "name"), None);
+}
+
+// Unique key construction tests
+
+#[test]
+fn test_mapping_from_unique() {
+    use crate::{DuplicateKeyError, Entry, Yaml};
+
+    let mapping = Yaml::mapping_from_unique(vec![
+        Entry::new(Yaml::Scalar("a"), Yaml::Int(1)),
+        Entry::new(Yaml::Scalar("b"), Yaml::Int(2)),
+    ])
+    .unwrap();
+    assert_eq!(mapping, crate::parse("{a: 1, b: 2}").unwrap());
+
+    let err = Yaml::mapping_from_unique(vec![
+        Entry::new(Yaml::Scalar("a"), Yaml::Int(1)),
+        Entry::new(Yaml::Int(1), Yaml::Int(2)),
+        Entry::new(Yaml::String("a".to_string()), Yaml::Int(3)),
+        Entry::new(Yaml::String("1".to_string()), Yaml::Int(4)),
+    ])
+    .unwrap_err();
+    assert_eq!(
+        err,
+        DuplicateKeyError {
+            key: "a".to_string()
+        }
+    );
+    assert_eq!(err.to_string(), "duplicate mapping key 'a'");
+}
+
+#[test]
+fn test_insert_unique() {
+    use crate::Yaml;
+
+    let mut mapping = Yaml::Mapping(Vec::new());
+    mapping
+        .insert_unique(Yaml::Int(1), Yaml::Scalar("one"))
+        .unwrap();
+    mapping
+        .insert_unique(Yaml::Bool(true), Yaml::Scalar("two"))
+        .unwrap();
+    let err = mapping
+        .insert_unique(Yaml::Scalar("1"), Yaml::Scalar("uno"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        crate::InsertError::DuplicateKey(crate::DuplicateKeyError {
+            key: "1".to_string()
+        })
+    );
+    assert_eq!(mapping, crate::parse("{1: one, true: two}").unwrap());
+}
+
+#[test]
+fn test_insert_unique_not_a_mapping() {
+    let mut seq = crate::Yaml::Sequence(Vec::new());
+    let err = seq
+        .insert_unique(crate::Yaml::Scalar("a"), crate::Yaml::Null)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        crate::InsertError::NotAMapping {
+            found: "a sequence"
+        }
+    );
+    assert_eq!(err.to_string(), "expected a mapping, found a sequence");
+    assert_eq!(seq, crate::Yaml::Sequence(Vec::new()));
+}
+
+// Size estimate tests
+
+#[test]
+fn test_estimate_size() {
+    let node_size = std::mem::size_of::<crate::Yaml>();
+    assert_eq!(crate::Yaml::Int(1).estimate_size(), node_size);
+    // borrowed text belongs to the input
+    let borrowed = crate::parse("some text").unwrap();
+    assert_eq!(borrowed.estimate_size(), node_size);
+    assert_eq!(
+        borrowed.estimate_owned_size(),
+        node_size + "some text".len()
+    );
+    let owned = borrowed.into_owned();
+    assert!(owned.estimate_size() >= node_size + "some text".len());
+
+    let input = "a: [1, 2, 3]\nb: {c: text}\n";
+    let tree = crate::parse(input).unwrap();
+    assert!(tree.estimate_size() > 3 * node_size);
+    assert!(tree.estimate_owned_size() > tree.estimate_size());
+    let owned = tree.clone().into_owned();
+    assert_eq!(owned.estimate_size(), owned.estimate_owned_size());
+}
+
+#[test]
+fn test_parse_owned_outlives_input() {
+    fn load(name: &str) -> crate::YamlOwned {
+        let input = format!("name: {name}\nitems: [a, 'b']\n");
+        crate::parse_owned(&input).unwrap()
+    }
+    let tree = load("demo");
+    let expected = map! { "name" => "demo"; "items" => seq!("a", "b") };
+    assert_eq!(tree, expected.into_owned());
+    assert_eq!(tree.estimate_size(), tree.estimate_owned_size());
+    assert!(crate::parse_owned("[").is_err());
+}
+
+// Retain tests
+
+#[test]
+fn test_retain_strips_metadata_keys() {
+    let mut value = crate::parse(
+        "__meta: {id: 1}\nservice: !svc\n  name: api\n  __rev: 3\nlist:\n  - {__k: x, v: 1}\n",
+    )
+    .unwrap();
+    value.retain(|path, _| {
+        let key = path.rsplit(['.', '[']).next().unwrap_or_default();
+        !key.starts_with("__")
+    });
+    assert_eq!(
+        value,
+        map! {
+            "service" => tagged!("svc", map! { "name" => "api" });
+            "list" => seq!(map! { "v" => crate::Yaml::Int(1) })
+        }
+    );
+}
+
+#[test]
+fn test_retain_drops_disabled_items() {
+    let mut value = crate::parse(
+        "components:\n  - name: a\n    enabled: false\n  - name: b\n  - name: c\n    enabled: false\n  - name: d\n",
+    )
+    .unwrap();
+    let mut visited = Vec::new();
+    value.retain(|path, node| {
+        visited.push(path.to_string());
+        !matches!(node, crate::Yaml::Mapping(entries)
+            if entries.iter().any(|e| e.key == crate::Yaml::Scalar("enabled")
+                && e.value == crate::Yaml::Bool(false)))
+    });
+    assert_eq!(
+        value,
+        map! { "components" => seq!(map! { "name" => "b" }, map! { "name" => "d" }) }
+    );
+    // paths are those of the original tree; removed nodes are not entered
+    assert_eq!(
+        visited,
+        [
+            "components",
+            "components[0]",
+            "components[1]",
+            "components[1].name",
+            "components[2]",
+            "components[3]",
+            "components[3].name"
+        ]
+    );
+}
+
+// Key rename tests
+
+fn snake_case(key: &str) -> Option<String> {
+    if !key.contains(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let mut out = String::new();
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    Some(out)
+}
+
+#[test]
+fn test_rename_keys_recursively() {
+    let mut value = crate::parse(
+        "maxRetries: 3\nserverList:\n  - hostName: a\n    port: 1\nplain: {innerKey: x}\n200: ok\n",
+    )
+    .unwrap();
+    value.rename_keys(snake_case);
+    assert_eq!(
+        value,
+        map! {
+            crate::Yaml::String("max_retries".into()) => crate::Yaml::Int(3);
+            crate::Yaml::String("server_list".into()) => seq!(map! {
+                crate::Yaml::String("host_name".into()) => "a";
+                "port" => crate::Yaml::Int(1)
+            });
+            // unchanged keys stay borrowed
+            "plain" => map! { crate::Yaml::String("inner_key".into()) => "x" };
+            crate::Yaml::Int(200) => "ok"
+        }
+    );
+}
+
+#[test]
+fn test_rename_keys_keeps_tags() {
+    let mut value = crate::parse("a: !point {x: 1}\nb: !wrapped [1]\n").unwrap();
+    value.rename_keys(|key| Some(format!("cfg_{key}")));
+    assert_eq!(
+        value.to_json(),
+        serde_json::json!({
+            "cfg_a": { "__type": "point", "cfg_x": 1 },
+            "cfg_b": { "__type": "wrapped", "__value": [1] }
+        })
+    );
+    assert!(value.to_string().contains("!point"));
+}
+
+#[test]
+fn test_sort_by_field() {
+    let mut value = crate::parse(
+        "- {name: web, port: 80}\n- {name: 'db', port: 5432.0}\n- {port: 22}\n- {name: api, port: 8080}\n- {name: db, port: 1}\n",
+    )
+    .unwrap();
+    value.sort_by_field("name").unwrap();
+    let names: Vec<_> = value
+        .to_json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["name"].clone())
+        .collect();
+    assert_eq!(
+        names,
+        ["api", "db", "db", "web"]
+            .map(serde_json::Value::from)
+            .into_iter()
+            .chain([serde_json::Value::Null])
+            .collect::<Vec<_>>()
+    );
+    // stable: the two `db` items keep their order
+    assert_eq!(value.to_json()[1]["port"], serde_json::json!(5432.0));
+
+    value.sort_by_field("port").unwrap();
+    assert_eq!(
+        value
+            .to_json()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["port"].as_f64().unwrap())
+            .collect::<Vec<_>>(),
+        [1.0, 22.0, 80.0, 5432.0, 8080.0]
+    );
+
+    let mut value = crate::parse("[b, 2, null, a, 10, true]").unwrap();
+    value.sort_by_field("").unwrap();
+    assert_eq!(
+        value.to_json(),
+        serde_json::json!([null, true, 2, 10, "a", "b"])
+    );
+
+    value
+        .sort_by(|a, b| b.to_string().cmp(&a.to_string()))
+        .unwrap();
+    assert_eq!(
+        value.to_json(),
+        serde_json::json!([null, true, "b", "a", 2, 10])
+    );
+}
+
+#[test]
+fn test_dedup_by_field() {
+    let mut value = crate::parse(
+        "- {id: 1, v: a}\n- {id: 2, v: b}\n- {v: c}\n- {id: 1, v: d}\n- {v: e}\n- x\n- 'x'\n",
+    )
+    .unwrap();
+    value.dedup_by_field("id").unwrap();
+    assert_eq!(
+        value.to_json(),
+        serde_json::json!([{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"v": "c"}, {"v": "e"}, "x", "x"])
+    );
+    value.dedup_by_field("").unwrap();
+    assert_eq!(value.to_json().as_array().unwrap().len(), 5);
+
+    let mut value = crate::parse("[1, 2, 11, 3, 12]").unwrap();
+    value
+        .dedup_by(|a, b| a.to_string().len() == b.to_string().len())
+        .unwrap();
+    assert_eq!(value.to_json(), serde_json::json!([1, 11]));
+}
+
+#[test]
+fn test_reorder() {
+    let mut value = crate::parse("[a, b, c, d]").unwrap();
+    value.reorder(&[2, 0]).unwrap();
+    assert_eq!(value.to_json(), serde_json::json!(["c", "a", "b", "d"]));
+    value.reorder(&[]).unwrap();
+    assert_eq!(value.to_json(), serde_json::json!(["c", "a", "b", "d"]));
+}
+
+#[test]
+fn test_reorder_repeated_index() {
+    use crate::SequenceError;
+
+    let mut value = crate::parse("[a, b]").unwrap();
+    assert_eq!(value.reorder(&[1, 1]), Err(SequenceError::BadIndex(1)));
+    assert_eq!(value.reorder(&[0, 2]), Err(SequenceError::BadIndex(2)));
+    // left unchanged
+    assert_eq!(value.to_json(), serde_json::json!(["a", "b"]));
+}
+
+#[test]
+fn test_sort_not_a_sequence() {
+    use crate::SequenceError;
+
+    let mut value = crate::parse("a: 1").unwrap();
+    let err = value.sort_by_field("a").unwrap_err();
+    assert_eq!(err, SequenceError::NotASequence { found: "a mapping" });
+    assert_eq!(err.to_string(), "expected a sequence, found a mapping");
+    assert_eq!(
+        value.dedup_by(|_, _| true),
+        Err(SequenceError::NotASequence { found: "a mapping" })
+    );
+    let mut value = crate::parse("[1]").unwrap();
+    assert_eq!(
+        value.dedup_by_field("a["),
+        Err(SequenceError::InvalidPath("a[".to_string()))
+    );
+}
+
+#[test]
+fn test_apply_updates() {
+    use crate::Yaml;
+    let mut value = crate::parse("server: {port: 80}\nhosts: [a]\n").unwrap();
+    value
+        .apply(&[
+            ("server.port", Yaml::Int(8080)),
+            ("hosts[1]", Yaml::Scalar("b")),
+            ("tls", map! { "enabled" => Yaml::Bool(false) }),
+            ("tls.enabled", Yaml::Bool(true)),
+        ])
+        .unwrap();
+    assert_eq!(
+        value.to_json(),
+        serde_json::json!({
+            "server": {"port": 8080},
+            "hosts": ["a", "b"],
+            "tls": {"enabled": true}
+        })
+    );
+}
+
+#[test]
+fn test_apply_updates_is_atomic() {
+    use crate::{EditError, UpdateError, Yaml};
+    let original = crate::parse("a: 1\nlist: [x]\n").unwrap();
+    for (updates, expected) in [
+        (
+            vec![("a", Yaml::Int(2)), ("missing.key", Yaml::Null)],
+            UpdateError {
+                index: 1,
+                path: "missing.key".into(),
+                reason: EditError::NotFound("missing.key".into()),
+            },
+        ),
+        (
+            vec![("a", Yaml::Int(2)), ("list[3]", Yaml::Null)],
+            UpdateError {
+                index: 1,
+                path: "list[3]".into(),
+                reason: EditError::NotFound("list[3]".into()),
+            },
+        ),
+        (
+            vec![("a", Yaml::Int(2)), ("list[", Yaml::Null)],
+            UpdateError {
+                index: 1,
+                path: "list[".into(),
+                reason: EditError::InvalidPath("list[".into()),
+            },
+        ),
+    ] {
+        let mut value = original.clone();
+        assert_eq!(value.apply(&updates), Err(expected));
+        assert_eq!(value, original);
+    }
+}
+
+#[test]
+fn test_index() {
+    let mut value = crate::parse(
+        "server:\n  port: 80\n  hosts: [a, b]\n  port: 8080\ntagged: !t {x: 1}\nnothing:\n",
+    )
+    .unwrap();
+    assert_eq!(value["server"]["port"], crate::Yaml::Int(8080));
+    assert_eq!(value["server"]["hosts"][1], crate::Yaml::Scalar("b"));
+    assert_eq!(value["tagged"]["x"], crate::Yaml::Int(1));
+    assert_eq!(value["server"]["missing"]["deeper"][3], crate::Yaml::Null);
+    assert_eq!(value["server"][0], crate::Yaml::Null);
+    assert_eq!(value.get("nothing"), Some(&crate::Yaml::Null));
+    assert_eq!(value.get("missing"), None);
+    assert_eq!(value["server"]["hosts"].get(2), None);
+    let key = String::from("server");
+    assert!(value.get(&key).is_some());
+
+    value["server"]["port"] = crate::Yaml::Int(443);
+    value["server"]["hosts"][0] = crate::Yaml::Scalar("c");
+    value["nothing"]["added"] = crate::Yaml::Bool(true);
+    value["new"] = crate::Yaml::Int(1);
+    if let Some(x) = value.get_mut("tagged").and_then(|t| t.get_mut("x")) {
+        *x = crate::Yaml::Int(2);
+    }
+    assert_eq!(
+        value.to_json(),
+        serde_json::json!({
+            "server": {"port": 443, "hosts": ["c", "b"]},
+            "tagged": {"__type": "t", "x": 2},
+            "nothing": {"added": true},
+            "new": 1,
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "cannot index a sequence of length 2 with 2")]
+fn test_index_mut_out_of_range() {
+    let mut value = crate::parse("[a, b]").unwrap();
+    value[2] = crate::Yaml::Null;
+}
+
+#[test]
+#[should_panic(expected = "cannot index a scalar with \"a\"")]
+fn test_index_mut_scalar() {
+    let mut value = crate::parse("text").unwrap();
+    value["a"] = crate::Yaml::Null;
+}
+
+#[test]
+fn test_get_path() {
+    let mut value = crate::parse(
+        "server:\n  tls:\n    port: 443\n  hosts:\n    - name: a\n    - name: b\n\"a.b\": {c: 1}\n",
+    )
+    .unwrap();
+    assert_eq!(
+        value.get_path("server.tls.port"),
+        Some(&crate::Yaml::Int(443))
+    );
+    assert_eq!(
+        value.get_path("server.hosts[1].name"),
+        Some(&crate::Yaml::Scalar("b"))
+    );
+    assert_eq!(value.get_path(r#"["a.b"].c"#), Some(&crate::Yaml::Int(1)));
+    assert_eq!(value.get_path(""), Some(&value.clone()));
+    assert_eq!(value.get_path("server.hosts[2]"), None);
+    assert_eq!(value.get_path("server.tls.port.x"), None);
+    assert_eq!(value.get_path("server[0]"), None);
+    assert_eq!(value.get_path("server..tls"), None);
+
+    *value.get_path_mut("server.tls.port").unwrap() = crate::Yaml::Int(8443);
+    assert_eq!(value["server"]["tls"]["port"], crate::Yaml::Int(8443));
+}
+
+#[test]
+fn test_merge() {
+    use crate::MergeStrategy;
+
+    let defaults = crate::parse(
+        "server:\n  host: localhost\n  port: 80\n  tls: {enabled: false}\nplugins: [auth, log]\n1: one\nlevel: !env info\n",
+    )
+    .unwrap();
+    let overrides = crate::parse(
+        "server:\n  port: 8080\n  tls: {enabled: true, cert: a.pem}\nplugins: [log, metrics]\n\"1\": uno\nlevel: debug\nextra: ~\n",
+    )
+    .unwrap();
+
+    let mut merged = defaults.clone();
+    merged.merge(overrides.clone(), MergeStrategy::Replace);
+    assert_eq!(
+        merged.to_string(),
+        "server:\n  host: localhost\n  port: 8080\n  tls:\n    enabled: true\n    cert: a.pem\nplugins:\n  - log\n  - metrics\n1: uno\nlevel: !env debug\nextra: ~\n"
+    );
+
+    let mut merged = defaults.clone();
+    merged.merge(overrides.clone(), MergeStrategy::Append);
+    assert_eq!(
+        merged["plugins"].to_json(),
+        serde_json::json!(["auth", "log", "log", "metrics"])
+    );
+
+    let mut merged = defaults.clone();
+    merged.merge(overrides, MergeStrategy::Unique);
+    assert_eq!(
+        merged["plugins"].to_json(),
+        serde_json::json!(["auth", "log", "metrics"])
+    );
+
+    // a tag on the override replaces the base's, and a non-mapping replaces
+    // a mapping
+    let mut merged = crate::parse("a: !old {x: 1}\nb: {y: 2}\n").unwrap();
+    merged.merge(
+        crate::parse("a: !new {z: 3}\nb: [1]\n").unwrap(),
+        MergeStrategy::default(),
+    );
+    assert_eq!(merged.to_string(), "a: !new\n  x: 1\n  z: 3\nb:\n  - 1\n");
+}
+
+#[test]
+fn test_diff() {
+    let old = crate::parse(
+        "name: app\nserver:\n  port: 80\n  debug: true\nhosts: [a, b, c]\nsize: !px 10\nid: 1\n",
+    )
+    .unwrap();
+    let new = crate::parse(
+        "name: app\nserver:\n  port: 8080\n  tls: {enabled: true}\nhosts: [a, x]\nsize: !em 10\nid: \"1\"\n",
+    )
+    .unwrap();
+    let ops = crate::diff(&old, &new);
+    let lines: Vec<String> = ops.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        lines,
+        [
+            "~ server.port: 80 -> 8080",
+            "- server.debug: true",
+            "+ server.tls: {enabled: true}",
+            "~ hosts[1]: b -> x",
+            "- hosts[2]: c",
+            "~ size: !px 10 -> !em 10",
+            "~ id: 1 -> '1'",
+        ]
+    );
+    assert_eq!(ops[2].path(), "server.tls");
+    assert!(matches!(
+        ops[0],
+        crate::DiffOp::Changed {
+            new: crate::Yaml::Int(8080),
+            ..
+        }
+    ));
+
+    assert!(crate::diff(&old, &old.clone()).is_empty());
+    // a key repeated in the old tree counts by its last value
+    let dup = crate::parse("a: 1\na: 2\n").unwrap();
+    assert!(crate::diff(&dup, &crate::parse("a: 2").unwrap()).is_empty());
+    let root = crate::diff(&crate::Yaml::Int(1), &crate::Yaml::Null);
+    assert_eq!(root[0].to_string(), "~ 1 -> ~");
+}
+
+#[test]
+fn test_mutation_helpers() {
+    use crate::Yaml;
+
+    let mut value =
+        crate::parse("name: app\nports: [80]\nlabels: !meta {tier: web}\nx: 1\nx: 2\n").unwrap();
+    assert!(value.contains_key("name") && !value.contains_key("tier"));
+    assert!(value["labels"].contains_key("tier"));
+
+    assert_eq!(
+        value.insert("name", Yaml::Scalar("api")),
+        Some(Yaml::Scalar("app"))
+    );
+    assert_eq!(value.insert("replicas", Yaml::Int(2)), None);
+    assert_eq!(value["labels"].insert("env", Yaml::Scalar("prod")), None);
+    assert_eq!(value.remove("x"), Some(Yaml::Int(2)));
+    assert_eq!(value.remove("x"), None);
+
+    let ports = value.get_mut("ports").unwrap();
+    ports.push(Yaml::Int(443));
+    ports.insert(0, Yaml::Int(22));
+    assert_eq!(ports.remove(1), Some(Yaml::Int(80)));
+    assert_eq!(ports.remove(5), None);
+    assert_eq!(ports.remove("a"), None);
+
+    let mut empty = Yaml::Null;
+    empty.push(Yaml::Int(1));
+    let mut config = Yaml::Null;
+    config.insert("debug", Yaml::Bool(true));
+
+    assert_eq!(
+        value.to_string(),
+        "name: api\nports:\n  - 22\n  - 443\nlabels: !meta\n  tier: web\n  env: prod\nreplicas: 2\n"
+    );
+    assert_eq!(empty.to_json(), serde_json::json!([1]));
+    assert_eq!(config.to_json(), serde_json::json!({"debug": true}));
+}
+
+#[test]
+#[should_panic(expected = "cannot insert into a sequence of length 1 at 3")]
+fn test_insert_past_end() {
+    let mut value = crate::parse("[a]").unwrap();
+    value.insert(3, crate::Yaml::Null);
+}
+
+#[test]
+#[should_panic(expected = "cannot push onto a mapping")]
+fn test_push_onto_mapping() {
+    let mut value = crate::parse("a: 1").unwrap();
+    value.push(crate::Yaml::Null);
+}
+
+#[test]
+fn test_entry() {
+    use crate::{MapEntry, Yaml};
+
+    let mut value = crate::parse("hosts: [a]\ncount: 1\n").unwrap();
+    value
+        .entry("hosts")
+        .or_insert_with(|| Yaml::Sequence(Vec::new()))
+        .push(Yaml::Scalar("b"));
+    value
+        .entry("ports")
+        .or_insert_with(|| Yaml::Sequence(Vec::new()))
+        .push(Yaml::Int(80));
+    value
+        .entry("count")
+        .and_modify(|count| *count = Yaml::Int(2))
+        .or_insert(Yaml::Int(0));
+    value
+        .entry("missing")
+        .and_modify(|_| unreachable!())
+        .or_default();
+    match value.entry("count") {
+        MapEntry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), &Yaml::Scalar("count"));
+            assert_eq!(entry.insert(Yaml::Int(3)), Yaml::Int(2));
+            assert_eq!(entry.get(), &Yaml::Int(3));
+        }
+        MapEntry::Vacant(_) => unreachable!(),
+    }
+    match value.entry("missing") {
+        MapEntry::Occupied(entry) => assert_eq!(entry.remove(), Yaml::Null),
+        MapEntry::Vacant(_) => unreachable!(),
+    }
+    match value.entry("new") {
+        MapEntry::Vacant(entry) => assert_eq!(entry.key(), "new"),
+        MapEntry::Occupied(_) => unreachable!(),
+    }
+    assert_eq!(
+        value.to_string(),
+        "hosts:\n  - a\n  - b\ncount: 3\nports:\n  - 80\n"
+    );
+
+    let mut built = Yaml::Null;
+    *built.entry("a").or_default() = Yaml::Int(1);
+    assert_eq!(built.to_json(), serde_json::json!({"a": 1}));
+}
+
+#[test]
+fn test_conversions() {
+    use crate::Yaml;
+    use std::collections::BTreeMap;
+
+    let mut limits = BTreeMap::new();
+    limits.insert("memory", "1Gi");
+    limits.insert("cpu", "500m");
+    let value: Yaml = vec![
+        ("name", Yaml::from("web")),
+        ("replicas", Yaml::from(3)),
+        ("ratio", Yaml::from(0.5)),
+        ("enabled", Yaml::from(true)),
+        ("ports", Yaml::from(vec![80u16, 443])),
+        ("owner", Yaml::from(None::<String>)),
+        ("image", Yaml::from(String::from("app:1"))),
+        ("limits", Yaml::from(limits)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        value.to_string(),
+        "name: web\nreplicas: 3\nratio: 0.5\nenabled: true\nports:\n  - 80\n  - 443\nowner: ~\nimage: app:1\nlimits:\n  cpu: 500m\n  memory: 1Gi\n"
+    );
+    assert_eq!(
+        Yaml::from(serde_json::json!({"a": [1]})),
+        Yaml::Mapping(vec![crate::Entry::new(
+            Yaml::String("a".into()),
+            Yaml::Sequence(vec![Yaml::Int(1)])
+        )])
+    );
+
+    assert_eq!(Yaml::from(7u64), Yaml::Int(7));
+    assert_eq!(Yaml::from(vec![1usize, 2]), crate::parse("[1, 2]").unwrap());
+    assert_eq!(Yaml::from(u64::MAX), Yaml::Float(u64::MAX as f64));
+    // precision is lost beyond i64::MAX
+    assert_eq!(Yaml::from(u64::MAX - 1), Yaml::from(u64::MAX));
+    assert_eq!(
+        Yaml::from(i64::MAX as u64 + 1),
+        Yaml::Float(9223372036854775808.0)
+    );
+
+    let map: std::collections::HashMap<_, _> = [("a", 1)].into_iter().collect();
+    assert_eq!(Yaml::from(map), crate::parse("a: 1").unwrap());
+    #[cfg(feature = "indexmap")]
+    {
+        let map: indexmap::IndexMap<_, _> = [("z", 1), ("a", 2)].into_iter().collect();
+        assert_eq!(Yaml::from(map), crate::parse("{z: 1, a: 2}").unwrap());
+    }
+
+    assert_eq!(i64::try_from(&value["replicas"]), Ok(3));
+    assert_eq!(f64::try_from(&value["replicas"]), Ok(3.0));
+    assert_eq!(bool::try_from(&value["enabled"]), Ok(true));
+    assert_eq!(<&str>::try_from(&value["name"]), Ok("web"));
+    assert_eq!(String::try_from(&value["image"]).unwrap(), "app:1");
+    let tagged = crate::parse("!port 8080").unwrap();
+    assert_eq!(i64::try_from(&tagged), Ok(8080));
+    let err = i64::try_from(&value["name"]).unwrap_err();
+    assert_eq!(err.to_string(), "expected an integer, found a string");
+    assert_eq!(
+        bool::try_from(&value["ports"]).unwrap_err().to_string(),
+        "expected a boolean, found a sequence"
+    );
+}
+
+#[test]
+fn test_walk() {
+    use crate::{Visitor, Yaml};
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'a> Visitor<'a> for Recorder {
+        fn enter_mapping(&mut self, path: &str, _: &Yaml<'a>) {
+            self.0.push(format!("enter mapping '{path}'"));
+        }
+        fn exit_mapping(&mut self, path: &str, _: &Yaml<'a>) {
+            self.0.push(format!("exit mapping '{path}'"));
+        }
+        fn enter_sequence(&mut self, path: &str, _: &Yaml<'a>) {
+            self.0.push(format!("enter sequence '{path}'"));
+        }
+        fn exit_sequence(&mut self, path: &str, _: &Yaml<'a>) {
+            self.0.push(format!("exit sequence '{path}'"));
+        }
+        fn scalar(&mut self, path: &str, node: &Yaml<'a>) {
+            self.0.push(format!("scalar '{path}' {node:#}"));
+        }
+    }
+
+    let yaml = crate::parse("name: web\nports: !list [80, 443]\n\"a.b\": {x: ~}").unwrap();
+    let mut recorder = Recorder::default();
+    yaml.walk(&mut recorder);
+    assert_eq!(
+        recorder.0,
+        [
+            "enter mapping ''",
+            "scalar 'name' web",
+            "enter sequence 'ports'",
+            "scalar 'ports[0]' 80",
+            "scalar 'ports[1]' 443",
+            "exit sequence 'ports'",
+            "enter mapping '[\"a.b\"]'",
+            "scalar '[\"a.b\"].x' ~",
+            "exit mapping '[\"a.b\"]'",
+            "exit mapping ''",
+        ]
+    );
+
+    // only the callbacks a visitor needs have to be written
+    struct Secrets(Vec<String>);
+    impl<'a> Visitor<'a> for Secrets {
+        fn scalar(&mut self, path: &str, _: &Yaml<'a>) {
+            if path.ends_with("password") {
+                self.0.push(path.to_string());
+            }
+        }
+    }
+    let yaml = crate::parse("db:\n  password: x\nusers:\n  - password: y").unwrap();
+    let mut secrets = Secrets(Vec::new());
+    yaml.walk(&mut secrets);
+    assert_eq!(secrets.0, ["db.password", "users[0].password"]);
+}
+
+#[test]
+fn test_iterators() {
+    use crate::Yaml;
+
+    let mut yaml =
+        crate::parse("name: web\nports: !list [80, 443]\nlabels: {tier: front, \"a.b\": ~}")
+            .unwrap();
+    let keys: Vec<String> = yaml.keys().map(|key| key.to_string()).collect();
+    assert_eq!(keys, ["name", "ports", "labels"]);
+    assert_eq!(yaml.values().count(), 3);
+    assert_eq!(yaml.iter().count(), 3);
+    let ports: Vec<i64> = yaml["ports"]
+        .iter()
+        .map(|port| i64::try_from(port).unwrap())
+        .collect();
+    assert_eq!(ports, [80, 443]);
+    assert_eq!(yaml["name"].iter().count(), 0);
+    assert_eq!(yaml["ports"].keys().count(), 0);
+
+    for port in yaml["ports"].iter_mut() {
+        *port = Yaml::Int(i64::try_from(&*port).unwrap() + 8000);
+    }
+    assert_eq!(yaml["ports"][1], Yaml::Int(8443));
+
+    let paths: Vec<String> = yaml.descendants().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        [
+            "name",
+            "ports",
+            "ports[0]",
+            "ports[1]",
+            "labels",
+            "labels.tier",
+            "labels[\"a.b\"]",
+        ]
+    );
+    assert_eq!(
+        yaml.descendants()
+            .filter(|(_, node)| **node == Yaml::Null)
+            .count(),
+        1
+    );
+    assert!(Yaml::Int(1).descendants().next().is_none());
+}
@@ -0,0 +1,228 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::errors::MiniYamlError;
+use crate::{parse_with_options, ParseOptions};
+
+// Parse option tests
+
+fn strict() -> ParseOptions {
+    ParseOptions {
+        reject_unsupported: true,
+        ..Default::default()
+    }
+}
+
+fn unsupported(input: &str) -> (usize, Option<MiniYamlError>, String) {
+    let err = parse_with_options(input, &strict()).unwrap_err();
+    (
+        err.line(),
+        err.source.clone(),
+        err.message().unwrap().to_string(),
+    )
+}
+
+#[test]
+fn test_reject_unsupported_anchors() {
+    assert_eq!(
+        unsupported("a: 1\nb: &anchor 2\n"),
+        (
+            2,
+            Some(MiniYamlError::AnchorsDisallowed),
+            "anchors are not supported".to_string()
+        )
+    );
+    assert_eq!(
+        unsupported("a: [1, &x 2]").1,
+        Some(MiniYamlError::AnchorsDisallowed)
+    );
+}
+
+#[test]
+fn test_reject_unsupported_aliases() {
+    assert_eq!(
+        unsupported("- a\n- *ref\n"),
+        (
+            2,
+            Some(MiniYamlError::AliasesDisallowed),
+            "aliases are not supported".to_string()
+        )
+    );
+    assert_eq!(
+        unsupported("a: {b: *x}").1,
+        Some(MiniYamlError::AliasesDisallowed)
+    );
+}
+
+#[test]
+fn test_reject_unsupported_directives() {
+    let err = parse_with_options("%YAML 1.2\n---\na: 1\n", &strict()).unwrap_err();
+    assert_eq!(err.source, Some(MiniYamlError::DirectivesDisallowed));
+    assert_eq!(
+        err.to_string(),
+        "error occurred parsing the input at line 1, column 1 : directives are not supported"
+    );
+}
+
+#[test]
+fn test_reject_unsupported_is_opt_in() {
+    let input = "a: &x 1\nb: *x\n";
+    assert_eq!(
+        parse_with_options(input, &ParseOptions::default()).unwrap(),
+        crate::parse("a: 1\nb: 1\n").unwrap()
+    );
+
+    // supported input parses the same either way
+    let input = "a: [1, 2]\nb: !tag x\n";
+    assert_eq!(
+        parse_with_options(input, &strict()).unwrap(),
+        crate::parse(input).unwrap()
+    );
+}
+
+#[test]
+fn test_numeric_underscores() {
+    let input = "[1_000_000, -1_0.5, 0x_FF, _1, 1_a, '1_0']";
+    let opts = ParseOptions {
+        numeric_underscores: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_with_options(input, &opts).unwrap(),
+        seq!(
+            crate::Yaml::Int(1_000_000),
+            crate::Yaml::Float(-10.5),
+            crate::Yaml::Int(255),
+            "_1",
+            "1_a",
+            "1_0"
+        )
+    );
+
+    // off by default
+    assert_eq!(crate::parse("n: 1_000").unwrap(), map! { "n" => "1_000" });
+}
+
+#[test]
+fn test_reject_tab_indentation() {
+    let opts = ParseOptions {
+        reject_tab_indentation: true,
+        ..Default::default()
+    };
+    for (input, line) in [
+        ("a:\n\tb: 1\n", 2),
+        ("a:\n  \tb: 1\n", 2),
+        ("\ta: 1\n", 1),
+        ("a:\n- 1\n\t- 2\n", 3),
+    ] {
+        let err = parse_with_options(input, &opts).unwrap_err();
+        assert_eq!(err.line(), line, "{input:?}");
+        assert_eq!(err.message(), Some("tab used for indentation"), "{input:?}");
+    }
+
+    // tabs which are not indentation are fine
+    for input in [
+        "a:\tb\n",
+        "a: 1\n\t\nb: 2\n",
+        "a: 1\n\t# comment\nb: 2\n",
+        "- a\n-\tb\n",
+        "{a: 1,\n\tb: 2}\n",
+        "a: |\n  \tx\n",
+        "a: \"x\n\ty\"\n",
+    ] {
+        assert_eq!(
+            parse_with_options(input, &opts).unwrap(),
+            crate::parse(input).unwrap(),
+            "{input:?}"
+        );
+    }
+
+    // off by default, where a tab counts as a space
+    assert_eq!(
+        crate::parse("a:\n\tb: 1\n").unwrap(),
+        map! { "a" => map! { "b" => crate::Yaml::Int(1) } }
+    );
+}
+
+#[test]
+fn test_duplicate_key_policy() {
+    use crate::DuplicateKeys;
+    let input = "a: 1\nb: 2\n'a': 3\nm: {x: 1, x: 2}\n1: one\n\"1\": uno\n";
+    let parse = |duplicate_keys| {
+        parse_with_options(
+            input,
+            &ParseOptions {
+                duplicate_keys,
+                ..Default::default()
+            },
+        )
+    };
+    assert_eq!(
+        parse(DuplicateKeys::Keep).unwrap(),
+        crate::parse(input).unwrap()
+    );
+    assert_eq!(
+        parse(DuplicateKeys::FirstWins).unwrap().to_json(),
+        serde_json::json!({"a": 1, "b": 2, "m": {"x": 1}, "1": "one"})
+    );
+    let last = parse(DuplicateKeys::LastWins).unwrap();
+    assert_eq!(last.to_json(), crate::parse(input).unwrap().to_json());
+    assert_eq!(last.to_string(), "a: 3\nb: 2\nm:\n  x: 2\n1: uno\n");
+
+    let err = parse(DuplicateKeys::Error).unwrap_err();
+    assert_eq!(err.message(), Some("duplicate key 'a'"));
+    assert_eq!(err.line(), 3);
+    let err = parse_with_options(
+        "? [k]\n: 1\nz:\n  - {y: 1, y: 2}\n",
+        &ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.message(), Some("duplicate key 'y'"));
+    assert_eq!(err.line(), 4);
+
+    // merge keys are not duplicates, and merged keys can be overridden
+    let merged = parse_with_options(
+        "base: &b {x: 1}\nother: &o {y: 1}\nc:\n  <<: *b\n  <<: *o\n  x: 2\n",
+        &ParseOptions {
+            duplicate_keys: DuplicateKeys::Error,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(merged.to_json()["c"], serde_json::json!({"x": 2, "y": 1}));
+}
+
+#[test]
+fn test_templates() {
+    let opts = ParseOptions {
+        templates: true,
+        ..Default::default()
+    };
+    let input = "image: {{ .Values.image }}:{{ .Values.tag }}\nname: ${NAME:-{app}} # c\nhosts: [${HOST}, {{ .Values.host }}]\nenv: {mode: {{ .Values.mode | default \"a, b\" }}}\n{{ .Values.key }}: v\nport: ${PORT}: 80\n";
+    let parsed = parse_with_options(input, &opts).unwrap();
+    assert_eq!(
+        parsed,
+        map! {
+            "image" => "{{ .Values.image }}:{{ .Values.tag }}";
+            "name" => "${NAME:-{app}}";
+            "hosts" => seq!("${HOST}", "{{ .Values.host }}");
+            "env" => map! { "mode" => "{{ .Values.mode | default \"a, b\" }}" };
+            "{{ .Values.key }}" => "v";
+            "port" => map! { "${PORT}" => crate::Yaml::Int(80) }
+        }
+    );
+    // printed back as written
+    assert_eq!(
+        parse_with_options(&parsed.to_string(), &opts).unwrap(),
+        parsed
+    );
+
+    // off by default, `{{` starts a flow mapping
+    assert!(matches!(
+        crate::parse("image: {{ .Values.image }}\n").unwrap(),
+        crate::Yaml::Mapping(ref entries) if matches!(entries[0].value, crate::Yaml::Mapping(_))
+    ));
+}
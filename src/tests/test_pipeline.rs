@@ -0,0 +1,128 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{
+    EnvInterpolation, Include, MxTransform, PassError, Pipeline, PipelineError, ResolveTags, Yaml,
+};
+use serde_json::json;
+
+// Pipeline tests
+
+fn env() -> EnvInterpolation {
+    EnvInterpolation::with_lookup(|name| match name {
+        "HOST" => Some("db.local".to_string()),
+        "PORT" => Some("5432".to_string()),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_pipeline_env_interpolation() {
+    let input = "url: postgres://${HOST}:${PORT}/app\nuser: ${USER:-admin}\nraw: $${HOST}\n";
+    let doc = Pipeline::new().pass(env()).run(input).unwrap();
+    assert_eq!(
+        doc.to_json(),
+        json!({"url": "postgres://db.local:5432/app", "user": "admin", "raw": "${HOST}"})
+    );
+}
+
+#[test]
+fn test_pipeline_reports_pass_and_path() {
+    let input = "db:\n  hosts:\n    - ${HOST}\n    - ${MISSING}\n";
+    let err = Pipeline::new().pass(env()).run(input).unwrap_err();
+    assert_eq!(
+        err,
+        PipelineError::Pass(PassError {
+            pass: "env".to_string(),
+            path: Some("db.hosts[1]".to_string()),
+            message: "variable 'MISSING' is not set".to_string(),
+        })
+    );
+    assert_eq!(
+        err.to_string(),
+        "pass 'env' failed at 'db.hosts[1]': variable 'MISSING' is not set"
+    );
+
+    let err = Pipeline::new().pass(env()).run("a: [").unwrap_err();
+    assert!(matches!(err, PipelineError::Parse(_)));
+}
+
+#[test]
+fn test_pipeline_include() {
+    let include = Include::new(|name| match name {
+        "db.yaml" => Ok("host: ${HOST}\nport: 5432\n".to_string()),
+        "nested.yaml" => Ok("db: !include db.yaml\n".to_string()),
+        "loop.yaml" => Ok("again: !include loop.yaml\n".to_string()),
+        _ => Err("not found".to_string()),
+    });
+    let pipeline = Pipeline::new().pass(include).pass(env());
+
+    let doc = pipeline.run("app: !include nested.yaml\n").unwrap();
+    assert_eq!(
+        doc.to_json(),
+        json!({"app": {"db": {"host": "db.local", "port": 5432}}})
+    );
+
+    let err = pipeline.run("a: !include missing.yaml\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "pass 'include' failed at 'a': cannot include 'missing.yaml': not found"
+    );
+    assert!(pipeline.run("a: !include loop.yaml\n").is_err());
+}
+
+#[test]
+fn test_pipeline_resolve_tags() {
+    let tags = ResolveTags::new()
+        .on("upper", |value| match value {
+            Yaml::Scalar(s) => Ok(Yaml::String(s.to_uppercase())),
+            _ => Err("expected a scalar".to_string()),
+        })
+        .on("count", |value| match value {
+            Yaml::Sequence(items) => Ok(Yaml::Int(items.len() as i64)),
+            _ => Err("expected a sequence".to_string()),
+        });
+    let pipeline = Pipeline::new().pass(tags);
+
+    let doc = pipeline
+        .run("name: !upper widget\nsizes: !count [1, 2, 3]\nother: !keep x\n")
+        .unwrap();
+    assert_eq!(
+        doc.to_json(),
+        json!({"name": "WIDGET", "sizes": 3, "other": {"__type": "keep", "__value": "x"}})
+    );
+
+    let err = pipeline.run("n: !count x\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "pass 'tags' failed at 'n': expected a sequence"
+    );
+}
+
+#[test]
+fn test_pipeline_custom_pass_and_mx() {
+    let pipeline = Pipeline::new()
+        .pass_fn("title-check", |doc| match doc {
+            Yaml::Mapping(map) if !map.is_empty() => Ok(()),
+            _ => Err(PassError::new("expected a mapping")),
+        })
+        .pass(MxTransform);
+    assert_eq!(
+        pipeline.names().collect::<Vec<_>>(),
+        vec!["title-check", "mx"]
+    );
+
+    let doc = pipeline
+        .run("+setup[Settings](db://settings):\n  title: Settings\n")
+        .unwrap();
+    assert_eq!(
+        doc.to_json(),
+        json!({"+setup": {"__name": "Settings", "__value": "db://settings", "title": "Settings"}})
+    );
+
+    let err = pipeline.run("- a\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "pass 'title-check' failed: expected a mapping"
+    );
+}
@@ -0,0 +1,125 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+// Query engine tests
+
+use crate::{parse, Query, Yaml};
+
+const TASKS: &str = "\
+tasks:
+  - name: build
+    retries: 1
+    tags: [ci]
+  - name: test
+    retries: 3
+  - name: deploy
+    retries: 5
+    env: {region: eu}
+\"a.b\": {c: 1}
+";
+
+fn names<'y>(nodes: Vec<&'y Yaml<'y>>) -> Vec<String> {
+    nodes
+        .iter()
+        .map(|node| node.to_string().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn test_query_paths_and_wildcards() {
+    let doc = parse(TASKS).unwrap();
+    assert_eq!(
+        names(doc.query("tasks[*].name").unwrap()),
+        ["build", "test", "deploy"]
+    );
+    assert_eq!(
+        names(doc.query(".tasks.*.name").unwrap()),
+        ["build", "test", "deploy"]
+    );
+    assert_eq!(names(doc.query("tasks[-1].name").unwrap()), ["deploy"]);
+    assert_eq!(names(doc.query("tasks[1].retries").unwrap()), ["3"]);
+    assert_eq!(names(doc.query(r#"["a.b"].c"#).unwrap()), ["1"]);
+    assert!(doc.query("tasks[7].name").unwrap().is_empty());
+    assert!(doc.query("missing[*]").unwrap().is_empty());
+    assert_eq!(doc.query("").unwrap(), vec![&doc]);
+}
+
+#[test]
+fn test_query_recursive_descent() {
+    let doc = parse(TASKS).unwrap();
+    assert_eq!(names(doc.query("..region").unwrap()), ["eu"]);
+    assert_eq!(names(doc.query("..retries").unwrap()), ["1", "3", "5"]);
+    assert_eq!(
+        names(doc.query("tasks..[0]").unwrap()),
+        ["name: build\nretries: 1\ntags:\n  - ci", "ci"]
+    );
+
+    // results come in document order, not in the order `..` found their
+    // parents
+    let doc = parse("a: {x: 1, name: inner}\nb: 2\nname: outer\n").unwrap();
+    assert_eq!(
+        names(doc.query("..*").unwrap()),
+        ["x: 1\nname: inner", "1", "inner", "2", "outer"]
+    );
+    assert_eq!(names(doc.query("..name").unwrap()), ["inner", "outer"]);
+}
+
+#[test]
+fn test_query_filters() {
+    let doc = parse(TASKS).unwrap();
+    assert_eq!(
+        names(doc.query("tasks[?retries > 2].name").unwrap()),
+        ["test", "deploy"]
+    );
+    assert_eq!(
+        names(doc.query("tasks[?(@.retries <= 3)].name").unwrap()),
+        ["build", "test"]
+    );
+    assert_eq!(
+        names(doc.query("tasks[?name == 'test'].retries").unwrap()),
+        ["3"]
+    );
+    assert_eq!(
+        names(doc.query("tasks[?name != test].name").unwrap()),
+        ["build", "deploy"]
+    );
+    assert_eq!(names(doc.query("tasks[?env].name").unwrap()), ["deploy"]);
+    assert_eq!(
+        names(doc.query("tasks[?env.region == eu].name").unwrap()),
+        ["deploy"]
+    );
+    assert_eq!(
+        names(doc.query("tasks[?tags[0] == ci].name").unwrap()),
+        ["build"]
+    );
+    assert_eq!(
+        names(doc.query("tasks[*].retries[?@ >= 3]").unwrap()),
+        Vec::<String>::new()
+    );
+
+    let tagged = parse("items: !list [1, 5, 9]").unwrap();
+    assert_eq!(names(tagged.query("items[?@ > 4]").unwrap()), ["5", "9"]);
+}
+
+#[test]
+fn test_query_errors() {
+    for (expr, position) in [
+        ("tasks[", 6),
+        ("tasks[x]", 6),
+        ("tasks[0", 7),
+        ("tasks.", 6),
+        ("tasks[?name ==]", 14),
+        ("tasks[?(name == a]", 17),
+        ("['a", 1),
+    ] {
+        let err = Query::parse(expr).unwrap_err();
+        assert_eq!(err.position, position, "{expr}: {err}");
+    }
+    assert_eq!(
+        Query::parse("a[x]").unwrap_err().to_string(),
+        "invalid query at offset 2: expected an index, '*', '?' or a quoted key"
+    );
+    let query: Query = "tasks[*].name".parse().unwrap();
+    let other = parse("tasks: [{name: x}]").unwrap();
+    assert_eq!(names(query.select(&other)), ["x"]);
+}
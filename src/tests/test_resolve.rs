@@ -0,0 +1,232 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{
+    parse_with_options, CoreSchema, JsonSchema, ParseOptions, ScalarResolver, Schema, Yaml,
+    Yaml11Schema,
+};
+
+// Scalar resolver tests
+
+fn resolve_all(resolver: impl ScalarResolver, inputs: &[&'static str]) -> Vec<Yaml<'static>> {
+    inputs.iter().map(|s| resolver.resolve(s)).collect()
+}
+
+#[test]
+fn test_yaml11_schema_is_default() {
+    let input = "[yes, Off, ~, 42, 1.5, 1e3, 0x1A, text]";
+    assert_eq!(
+        parse_with_options(input, &ParseOptions::default().resolver(Yaml11Schema)).unwrap(),
+        crate::parse(input).unwrap()
+    );
+    assert_eq!(
+        resolve_all(Yaml11Schema, &["yes", "Off", "~", "42", "1.5", "0x1A"]),
+        vec![
+            Yaml::Bool(true),
+            Yaml::Bool(false),
+            Yaml::Null,
+            Yaml::Int(42),
+            Yaml::Float(1.5),
+            Yaml::Int(26),
+        ]
+    );
+}
+
+#[test]
+fn test_core_schema() {
+    assert_eq!(
+        resolve_all(
+            CoreSchema,
+            &["NULL", "~", "True", "FALSE", "yes", "NO", "on", "tRUE"]
+        ),
+        vec![
+            Yaml::Null,
+            Yaml::Null,
+            Yaml::Bool(true),
+            Yaml::Bool(false),
+            Yaml::Scalar("yes"),
+            Yaml::Scalar("NO"),
+            Yaml::Scalar("on"),
+            Yaml::Scalar("tRUE"),
+        ]
+    );
+    assert_eq!(
+        resolve_all(
+            CoreSchema,
+            &["+12", "-7", "0o17", "0x1A", "0x", "0o8", "1.", ".5", "-1.5e-3", "1e5", "1_000"]
+        ),
+        vec![
+            Yaml::Int(12),
+            Yaml::Int(-7),
+            Yaml::Int(15),
+            Yaml::Int(26),
+            Yaml::Scalar("0x"),
+            Yaml::Scalar("0o8"),
+            Yaml::Float(1.0),
+            Yaml::Float(0.5),
+            Yaml::Float(-1.5e-3),
+            Yaml::Float(1e5),
+            Yaml::Scalar("1_000"),
+        ]
+    );
+    assert_eq!(CoreSchema.resolve(".inf"), Yaml::Float(f64::INFINITY));
+    assert_eq!(CoreSchema.resolve("-.Inf"), Yaml::Float(f64::NEG_INFINITY));
+    assert!(matches!(CoreSchema.resolve(".NaN"), Yaml::Float(f) if f.is_nan()));
+    assert_eq!(CoreSchema.resolve("inf"), Yaml::Scalar("inf"));
+}
+
+#[test]
+fn test_json_schema() {
+    assert_eq!(
+        resolve_all(
+            JsonSchema,
+            &["null", "Null", "~", "true", "True", "0", "-3", "012", "+3", "1.5", "-0.5e2", ".5"]
+        ),
+        vec![
+            Yaml::Null,
+            Yaml::Scalar("Null"),
+            Yaml::Scalar("~"),
+            Yaml::Bool(true),
+            Yaml::Scalar("True"),
+            Yaml::Int(0),
+            Yaml::Int(-3),
+            Yaml::Scalar("012"),
+            Yaml::Scalar("+3"),
+            Yaml::Float(1.5),
+            Yaml::Float(-50.0),
+            Yaml::Scalar(".5"),
+        ]
+    );
+}
+
+#[test]
+fn test_quoted_scalars_are_not_resolved() {
+    let opts = ParseOptions::default().resolver(CoreSchema);
+    assert_eq!(
+        parse_with_options("a: 'true'\nb: \"1\"\nc: true\n", &opts).unwrap(),
+        map! { "a" => "true"; "b" => "1"; "c" => Yaml::Bool(true) }
+    );
+}
+
+struct Dates;
+
+impl ScalarResolver for Dates {
+    fn resolve<'a>(&self, plain: &'a str) -> Yaml<'a> {
+        let is_date = plain.len() == 10
+            && plain.bytes().enumerate().all(|(idx, b)| {
+                matches!(idx, 4 | 7) == (b == b'-') && (b == b'-' || b.is_ascii_digit())
+            });
+        if is_date {
+            Yaml::String(format!("date:{plain}"))
+        } else {
+            CoreSchema.resolve(plain)
+        }
+    }
+}
+
+#[test]
+fn test_custom_resolver() {
+    let opts = ParseOptions::default().resolver(Dates);
+    assert_eq!(
+        parse_with_options("- 2024-06-01\n- 0x10\n- yes\n", &opts).unwrap(),
+        seq!(
+            Yaml::String("date:2024-06-01".to_string()),
+            Yaml::Int(16),
+            "yes"
+        )
+    );
+    assert_eq!(opts.clone(), opts);
+    assert_ne!(opts, ParseOptions::default().resolver(Dates));
+}
+
+#[test]
+fn test_strict_core_schema_option() {
+    let input = "country: NO\nenabled: on\nflag: true\nnone: null\nport: 8080\n";
+    let opts = ParseOptions::default().schema(Schema::Core);
+    assert_eq!(
+        parse_with_options(input, &opts).unwrap(),
+        map! {
+            "country" => Yaml::Scalar("NO");
+            "enabled" => Yaml::Scalar("on");
+            "flag" => Yaml::Bool(true);
+            "none" => Yaml::Null;
+            "port" => Yaml::Int(8080)
+        }
+    );
+    let opts = ParseOptions::default().schema(Schema::Yaml11);
+    assert_eq!(
+        parse_with_options(input, &opts).unwrap(),
+        crate::parse(input).unwrap()
+    );
+}
+
+#[test]
+fn test_radix_integers() {
+    assert_eq!(
+        resolve_all(
+            Yaml11Schema,
+            &[
+                "0x1A",
+                "0o17",
+                "0b1010",
+                "-0x10",
+                "+0b1",
+                "0x",
+                "0b12",
+                "0xFFFFFFFFFFFFFFFFF"
+            ]
+        ),
+        vec![
+            Yaml::Int(26),
+            Yaml::Int(15),
+            Yaml::Int(10),
+            Yaml::Int(-16),
+            Yaml::Int(1),
+            Yaml::Scalar("0x"),
+            Yaml::Scalar("0b12"),
+            Yaml::Scalar("0xFFFFFFFFFFFFFFFFF"),
+        ]
+    );
+    assert_eq!(
+        resolve_all(Yaml11Schema, &["-0x8000000000000000"]),
+        vec![Yaml::Int(i64::MIN)]
+    );
+}
+
+#[test]
+fn test_int_tag_reads_radix_integers() {
+    let opts = ParseOptions::default().resolver(JsonSchema);
+    assert_eq!(
+        parse_with_options("[!int 0b1010, !int 0x1A, 0x1A]", &opts).unwrap(),
+        seq!(
+            tagged!("int", Yaml::Int(10)),
+            tagged!("int", Yaml::Int(26)),
+            "0x1A"
+        )
+    );
+}
+
+#[test]
+fn test_special_floats() {
+    let resolved = resolve_all(
+        Yaml11Schema,
+        &[".inf", "-.Inf", "+.INF", ".nan", ".NaN", "inf"],
+    );
+    assert_eq!(
+        resolved[..3],
+        [
+            Yaml::Float(f64::INFINITY),
+            Yaml::Float(f64::NEG_INFINITY),
+            Yaml::Float(f64::INFINITY)
+        ]
+    );
+    assert!(matches!(resolved[3], Yaml::Float(f) if f.is_nan()));
+    assert!(matches!(resolved[4], Yaml::Float(f) if f.is_nan()));
+    assert_eq!(resolved[5], Yaml::Scalar("inf"));
+}
+
+#[test]
+fn test_special_floats_display() {
+    let yaml = "a: .inf\nb: -.inf\nc: .nan\n";
+    assert_eq!(crate::parse(yaml).unwrap().to_string(), yaml);
+}
@@ -0,0 +1,148 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+// Sanitizer tests
+
+use crate::{parse, sanitize, OnViolation, SanitizeError, SanitizePolicy};
+
+#[test]
+fn test_sanitize_strips_tags_and_aliases() {
+    let input =
+        "base: &base\n  image: !!str app\n  tag: !custom v1\nother: *base\ndata: !!binary aGk=\n";
+    let out = sanitize(input, &SanitizePolicy::default()).unwrap();
+    assert!(
+        !out.contains('!') && !out.contains('&') && !out.contains('*'),
+        "{out}"
+    );
+    assert_eq!(
+        parse(&out).unwrap().to_json(),
+        serde_json::json!({
+            "base": {"image": "app", "tag": "v1"},
+            "other": {"image": "app", "tag": "v1"},
+            "data": "aGk=",
+        })
+    );
+
+    let policy = SanitizePolicy {
+        keep_tags: true,
+        ..SanitizePolicy::default()
+    };
+    assert!(sanitize("a: !custom v1\n", &policy)
+        .unwrap()
+        .contains("!custom"));
+
+    let policy = SanitizePolicy {
+        reject_aliases: true,
+        ..SanitizePolicy::default()
+    };
+    assert!(matches!(
+        sanitize(input, &policy),
+        Err(SanitizeError::Parse(_))
+    ));
+}
+
+#[test]
+fn test_sanitize_quotes_ambiguous_strings() {
+    let out = sanitize(
+        "a: 'yes'\nb: \"1.0\"\nc: ': x'\n",
+        &SanitizePolicy::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        parse(&out).unwrap().to_json(),
+        serde_json::json!({"a": "yes", "b": "1.0", "c": ": x"})
+    );
+}
+
+#[test]
+fn test_sanitize_limits() {
+    let input = "name: a very long name\nitems: [1, 2, 3]\nnested: {a: {b: {c: 1}}}\n? [k]\n: v\n";
+    let policy = SanitizePolicy {
+        max_depth: Some(2),
+        max_scalar_len: Some(8),
+        max_items: Some(4),
+        ..SanitizePolicy::default()
+    };
+    let err = sanitize(input, &policy).unwrap_err();
+    assert_eq!(err.to_string(), "at 'name': scalar too long");
+    let err = sanitize("a: [1, 2, 3, 4, 5]\n", &policy).unwrap_err();
+    assert_eq!(err.to_string(), "at 'a': too many items");
+    let err = sanitize("a: {b: {c: 1}}\n", &policy).unwrap_err();
+    assert_eq!(err.to_string(), "at 'a.b.c': too deeply nested");
+    let err = sanitize("? [k]\n: v\n", &SanitizePolicy::default()).unwrap_err();
+    assert!(
+        matches!(err, SanitizeError::Rejected { reason, .. } if reason == "collection used as a mapping key")
+    );
+
+    let policy = SanitizePolicy {
+        on_violation: OnViolation::Remove,
+        ..policy
+    };
+    let out = sanitize(input, &policy).unwrap();
+    assert_eq!(
+        parse(&out).unwrap().to_json(),
+        serde_json::json!({"items": [1, 2, 3], "nested": {"a": {}}})
+    );
+    assert_eq!(sanitize("a very long string", &policy).unwrap(), "null");
+}
+
+#[test]
+fn test_sanitize_output_reads_back_the_same() {
+    let strings = [
+        "2001-12-14",
+        "2001-12-14 21:59:43.10 -5",
+        "1:20",
+        "-1:20:30",
+        "---",
+        "...",
+        "--- x",
+        "<<",
+        "yes",
+        "yEs",
+        "OFF",
+        "null",
+        "~",
+        "y",
+        "0x1F",
+        "0o17",
+        "1_000",
+        "1e3",
+        ".5",
+        "-.inf",
+        ".NaN",
+        "- x",
+        "? x",
+        ": x",
+        "a: b",
+        "a #b",
+        "#a",
+        "&a",
+        "*a",
+        "!a",
+        "%a",
+        "@a",
+        "`a",
+        "|",
+        ">",
+        "{a}",
+        "[a]",
+        "'a'",
+        "\"a\"",
+        " a",
+        "a ",
+        "",
+        "line\nbreak",
+        "tab\there",
+        "ü",
+    ];
+    for s in strings {
+        let json = serde_json::json!({ s: [s, { s: s }] });
+        let input = serde_json::to_string(&json).unwrap();
+        let out = sanitize(&input, &SanitizePolicy::default()).unwrap();
+        let parsed = parse(&out).unwrap_or_else(|err| panic!("{s:?}: {out}\n{err}"));
+        assert_eq!(parsed.to_json(), json, "{s:?}: {out}");
+    }
+    // a quoted merge key stays an ordinary key
+    let out = sanitize("\"<<\": 1\n", &SanitizePolicy::default()).unwrap();
+    assert_eq!(parse(&out).unwrap().to_json(), serde_json::json!({"<<": 1}));
+}
@@ -73,6 +73,34 @@ fn test_literal_block_scalar_strip() {
     }
 }
 
+#[test]
+fn test_large_block_scalars() {
+    let lines: Vec<String> = (0..20_000).map(|i| format!("line {i}")).collect();
+    let body = lines.join("\n  ");
+    let yaml = format!("script: |\n  {body}\nnext: 1\n");
+    let parsed = crate::parse(&yaml).unwrap();
+    assert_eq!(
+        parsed.to_json()["script"],
+        format!("{}\n", lines.join("\n"))
+    );
+
+    // runs of blank lines and indentation longer than the chunks they are
+    // built from
+    let blank = "\n".repeat(40);
+    let yaml = format!("a: |+\n  x\n{blank}  {}y\n\n\n", " ".repeat(40));
+    let expected = format!("x\n{blank}{}y\n\n\n", " ".repeat(40));
+    assert_eq!(crate::parse(&yaml).unwrap().to_json()["a"], expected);
+}
+
+#[test]
+fn test_block_scalar_at_end_of_input() {
+    assert_eq!(crate::parse("a: |\n  xyz").unwrap().to_json()["a"], "xyz\n");
+    assert_eq!(
+        crate::parse("a: >-\n  x\n  yz").unwrap().to_json()["a"],
+        "x yz"
+    );
+}
+
 #[test]
 fn test_literal_block_in_complex_yaml() {
     let yaml = r#"
@@ -93,3 +121,160 @@ fn test_literal_block_in_complex_yaml() {
     let result = crate::parse(yaml);
     assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
 }
+
+// Double-quoted escape tests
+
+#[test]
+fn test_double_quoted_escapes() {
+    let parsed = crate::parse(r#"["a\nb", "tab\there", "say \"hi\"", "back\\slash", "\u00e9\x41\U0001F600", "\/\ \0\e\N\_\L\P"]"#)
+        .unwrap();
+    assert_eq!(
+        parsed,
+        seq!(
+            crate::Yaml::String("a\nb".to_string()),
+            crate::Yaml::String("tab\there".to_string()),
+            crate::Yaml::String("say \"hi\"".to_string()),
+            crate::Yaml::String("back\\slash".to_string()),
+            crate::Yaml::String("éA😀".to_string()),
+            crate::Yaml::String("/ \0\u{1b}\u{85}\u{a0}\u{2028}\u{2029}".to_string())
+        )
+    );
+}
+
+#[test]
+fn test_double_quoted_without_escapes_borrows() {
+    let input = r#"key: "no escapes here""#;
+    match crate::parse(input).unwrap() {
+        crate::Yaml::Mapping(entries) => {
+            assert_eq!(entries[0].value, crate::Yaml::Scalar("no escapes here"))
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_double_quoted_escaped_quote_as_key() {
+    assert_eq!(
+        crate::parse(r#"{"a\"b": 1, c: "\"\""}"#).unwrap(),
+        map! {
+            crate::Yaml::String("a\"b".to_string()) => crate::Yaml::Int(1);
+            "c" => crate::Yaml::String("\"\"".to_string())
+        }
+    );
+}
+
+#[test]
+fn test_double_quoted_escaped_line_break() {
+    assert_eq!(
+        crate::parse("key: \"one \\\n    two\"\n").unwrap(),
+        map! { "key" => crate::Yaml::String("one two".to_string()) }
+    );
+}
+
+#[test]
+fn test_multi_line_double_quoted() {
+    let string = |s: &str| crate::Yaml::String(s.to_string());
+    assert_eq!(
+        crate::parse("a: \"first  \n   second\"\nb: \"one\n\n  two\n\n\n  three\"\n").unwrap(),
+        map! { "a" => string("first second"); "b" => string("one\ntwo\n\nthree") }
+    );
+    // escaped whitespace before a break is content, and an escaped break
+    // joins the lines
+    assert_eq!(
+        crate::parse("- \"tab\\t\n  x\"\n- \"no \\\n  space\"\n- \"crlf\r\n  y\"\n").unwrap(),
+        seq!(string("tab\t x"), string("no space"), string("crlf y"))
+    );
+}
+
+#[test]
+fn test_multi_line_single_quoted() {
+    assert_eq!(
+        crate::parse("a: 'it''s\n  folded  \n\n  here'\nb: 1\n").unwrap(),
+        map! {
+            "a" => crate::Yaml::String("it's folded\nhere".to_string());
+            "b" => crate::Yaml::Int(1)
+        }
+    );
+}
+
+#[test]
+fn test_double_quoted_invalid_escapes() {
+    let err = crate::parse("a: \"bad \\q\"\n").unwrap_err();
+    assert_eq!(err.message(), Some("invalid escape sequence '\\q'"));
+    assert_eq!((err.line(), err.col()), (1, 9));
+
+    let err = crate::parse("a: \"\\u12\"\n").unwrap_err();
+    assert_eq!(
+        err.message(),
+        Some("invalid escape sequence '\\u': expected 4 hex digits of a valid character")
+    );
+    crate::parse("a: \"\\uD800\"\n").unwrap_err();
+}
+
+// Single-quoted escape tests
+
+mk_test!(
+    single quote doubled quotes;
+    r"['it''s', '''quoted''', '', 'a''''b', 'plain']" => seq!(
+        crate::Yaml::String("it's".to_string()),
+        crate::Yaml::String("'quoted'".to_string()),
+        "",
+        crate::Yaml::String("a''b".to_string()),
+        "plain"
+    )
+);
+
+mk_test!(
+    single quote doubled quotes in mapping;
+    "'don''t': 'can''t'\nnext: 1\n" => map! {
+        crate::Yaml::String("don't".to_string()) => crate::Yaml::String("can't".to_string());
+        "next" => crate::Yaml::Int(1)
+    }
+);
+
+mk_test!(
+    single quote backslash is literal;
+    r"'C:\path\'" => r"C:\path\"
+);
+
+// Multi-line plain scalar tests
+
+mk_test!(
+    plain scalar folds continuation lines;
+    "description: a long\n  sentence split\n    over lines\nnext: 1\n" => map! {
+        "description" => crate::Yaml::String("a long sentence split over lines".to_string());
+        "next" => crate::Yaml::Int(1)
+    }
+);
+
+mk_test!(
+    plain scalar blank lines become newlines;
+    "a: one\n  two\n\n  three\n\n\n  four\n" => map! {
+        "a" => crate::Yaml::String("one two\nthree\n\nfour".to_string())
+    }
+);
+
+mk_test!(
+    plain scalar in sequences;
+    "- first\n  item\n- k: v\n    w\n  m: n\n" => seq!(
+        crate::Yaml::String("first item".to_string()),
+        map! {
+            "k" => crate::Yaml::String("v w".to_string());
+            "m" => "n"
+        }
+    )
+);
+
+#[test]
+fn test_plain_scalar_continuation_ends() {
+    // a sibling key, a comment or a flow collection's line ends the scalar
+    assert_eq!(
+        crate::parse("a: x\nb: y # note\n  # more\nc: [p,\n  q]\n").unwrap(),
+        map! { "a" => "x"; "b" => "y"; "c" => seq!("p", "q") }
+    );
+    // a folded scalar is a string, even if its words are numbers
+    assert_eq!(
+        crate::parse("n: 1\n  2\n").unwrap(),
+        map! { "n" => crate::Yaml::String("1 2".to_string()) }
+    );
+}
@@ -0,0 +1,103 @@
+#![cfg(all(test, feature = "serde"))]
+#![allow(clippy::pedantic)]
+
+// Serializer tests
+
+use crate::{from_str, to_string, to_string_with_options, to_yaml, EmitOptions, SerdeError, Yaml};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Point(i32, i32),
+    Rect { w: u32, h: u32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    version: String,
+    enabled: bool,
+    owner: Option<String>,
+    ports: Vec<u16>,
+    limits: BTreeMap<String, f64>,
+    shapes: Vec<Shape>,
+}
+
+fn config() -> Config {
+    Config {
+        name: "app: main".to_string(),
+        version: "1.0".to_string(),
+        enabled: true,
+        owner: None,
+        ports: vec![80, 443],
+        limits: BTreeMap::from([("cpu".to_string(), 0.5)]),
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Point(1, 2),
+            Shape::Rect { w: 3, h: 4 },
+        ],
+    }
+}
+
+#[test]
+fn test_to_string() {
+    assert_eq!(
+        to_string(&config()).unwrap(),
+        "name: 'app: main'\nversion: '1.0'\nenabled: true\nowner: null\nports:\n  - 80\n  - 443\nlimits:\n  cpu: 0.5\nshapes:\n  - Empty\n  - !Circle 1.5\n  - !Point\n    - 1\n    - 2\n  - !Rect\n    w: 3\n    h: 4\n"
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let text = to_string(&config()).unwrap();
+    assert_eq!(from_str::<Config>(&text).unwrap(), config());
+    let opts = EmitOptions {
+        indent: 4,
+        ..EmitOptions::default()
+    };
+    assert!(to_string_with_options(&vec![1, 2], &opts)
+        .unwrap()
+        .starts_with("    - 1"));
+}
+
+#[test]
+fn test_to_yaml() {
+    assert_eq!(
+        to_yaml(&("x", 'y', Some(2u8), ())).unwrap(),
+        seq!(
+            Yaml::String("x".to_string()),
+            Yaml::String("y".to_string()),
+            Yaml::Int(2),
+            Yaml::Null
+        )
+    );
+    assert_eq!(
+        to_yaml(&u64::MAX).unwrap_err(),
+        SerdeError::Invalid {
+            path: String::new(),
+            message: "integer 18446744073709551615 is out of the range of Yaml integers"
+                .to_string()
+        }
+    );
+}
+
+#[test]
+fn test_serialize_yaml() {
+    let yaml = crate::parse(
+        "a: [1, 2.5, true, ~, text]\nb: !point {x: 1}\nc: !id 7\nd: 2024-06-01\n1: one\n",
+    )
+    .unwrap();
+    assert_eq!(serde_json::to_value(&yaml).unwrap(), yaml.to_json());
+    let entry = crate::Entry::new(Yaml::Scalar("k"), Yaml::Int(1));
+    assert_eq!(
+        serde_json::to_value(&entry).unwrap(),
+        serde_json::json!(["k", 1])
+    );
+    // tags come back through the `__type` entries
+    let tree = to_yaml(&yaml).unwrap();
+    assert_eq!(tree.to_json(), yaml.to_json());
+}
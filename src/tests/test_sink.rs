@@ -0,0 +1,128 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+// JSON sink tests
+
+use crate::{BytesFormat, JsonOptions, JsonSink, MxOptions};
+use serde_json::{Map, Value};
+
+/// Builds a `Value` from what it is given, noting whether keys were
+/// borrowed from `source`
+struct ValueSink<'s> {
+    source: &'s str,
+    stack: Vec<(Value, Option<String>)>,
+    result: Option<Value>,
+    borrowed_keys: usize,
+}
+
+impl<'s> ValueSink<'s> {
+    fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            stack: Vec::new(),
+            result: None,
+            borrowed_keys: 0,
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        match self.stack.last_mut() {
+            Some((Value::Array(items), _)) => items.push(value),
+            Some((Value::Object(map), key)) => {
+                map.insert(key.take().unwrap(), value);
+            }
+            _ => self.result = Some(value),
+        }
+    }
+
+    fn end(&mut self) {
+        let (value, _) = self.stack.pop().unwrap();
+        self.push(value);
+    }
+}
+
+impl JsonSink for ValueSink<'_> {
+    fn null(&mut self) {
+        self.push(Value::Null);
+    }
+    fn bool(&mut self, value: bool) {
+        self.push(value.into());
+    }
+    fn int(&mut self, value: i64) {
+        self.push(value.into());
+    }
+    fn float(&mut self, value: f64) {
+        self.push(value.into());
+    }
+    fn str(&mut self, value: &str) {
+        self.push(value.into());
+    }
+    fn start_array(&mut self, _: usize) {
+        self.stack.push((Value::Array(Vec::new()), None));
+    }
+    fn end_array(&mut self) {
+        self.end();
+    }
+    fn start_object(&mut self, _: usize) {
+        self.stack.push((Value::Object(Map::new()), None));
+    }
+    fn key(&mut self, key: &str) {
+        let range = self.source.as_bytes().as_ptr_range();
+        if range.contains(&key.as_ptr()) {
+            self.borrowed_keys += 1;
+        }
+        self.stack.last_mut().unwrap().1 = Some(key.to_string());
+    }
+    fn end_object(&mut self) {
+        self.end();
+    }
+}
+
+const SOURCE: &str = r#"
+name: web
+ports: [80, 443]
+ratio: .5
+inf: .inf
+on: yes
+none: ~
+when: 2001-12-14
+data: !!binary aGk=
+1: one
+nested:
+  - {a: 1, "quoted": x}
+"#;
+
+#[test]
+fn test_json_visit_matches_to_json() {
+    let yaml = crate::parse(SOURCE).unwrap();
+    let mut sink = ValueSink::new(SOURCE);
+    yaml.to_json_visit(&mut sink);
+    assert_eq!(sink.result, Some(yaml.to_json()));
+    // every key but `1` and `on`, which resolve to a number and a boolean, is
+    // borrowed
+    assert_eq!(sink.borrowed_keys, 10);
+
+    let opts = JsonOptions {
+        bytes: BytesFormat::Array,
+        ..JsonOptions::default()
+    };
+    let mut sink = ValueSink::new(SOURCE);
+    yaml.to_json_visit_with_options(&mut sink, &opts);
+    assert_eq!(sink.result, Some(yaml.to_json_with_options(&opts)));
+}
+
+#[test]
+fn test_mx_visit_matches_to_mx() {
+    let opts = MxOptions::default();
+    for source in [
+        "+app[App](db://x):\n  title: T\n+list[L]: [1, 2]\n",
+        "+shop[Name]()",
+        "plain: key\n",
+        "- not a mapping\n",
+    ] {
+        let yaml = crate::parse(source).unwrap();
+        let mut sink = ValueSink::new(source);
+        yaml.to_mx_visit(&mut sink, &opts);
+        assert_eq!(sink.result, Some(yaml.to_mx()), "{source}");
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{sniff, InputKind};
+
+// Input kind detection tests
+
+#[test]
+fn test_sniff_kinds() {
+    for (input, kind) in [
+        ("{\"a\": 1}", InputKind::Json),
+        ("\u{feff}  [1, 2]\n", InputKind::Json),
+        ("{\n  \"a\": [\n    {}\n  ]\n}\n", InputKind::Json),
+        ("{}", InputKind::Json),
+        ("{a: 1}", InputKind::Yaml),
+        ("[1, 2]\nextra", InputKind::Yaml),
+        ("a: 1\nb: [x]\n", InputKind::Yaml),
+        ("---\na: 1\n...\n", InputKind::Yaml),
+        ("a: 1\n---\nb: 2\n", InputKind::MultiDocYaml),
+        ("--- 1\n--- 2\n", InputKind::MultiDocYaml),
+        ("", InputKind::Unknown),
+        (" \n\t\n", InputKind::Unknown),
+        ("a\0b", InputKind::Unknown),
+    ] {
+        assert_eq!(sniff(input), kind, "{input:?}");
+    }
+}
+
+#[test]
+fn test_parse_reads_json() {
+    for input in [
+        r#"{"a" : 1, "b" :[ 1 ,2 ] , "c":{ } }"#,
+        r#"{"a b": "c d", "e": "😀", "f": "tab\there", "g": "q\"uote", "h": "é\/"}"#,
+        r#"[1e5, 0.5E-3, 123456789012, -12.5e+2, true, false, null]"#,
+        "{\n\t\"a\": [\n\t\t1\n\t]\n}",
+        r#"{"nested": [[[]]], "x": [{}, {"y": [1, {"z": null}]}]}"#,
+        r#"{"url": "http://a.b/c?d=e#f", "colon": "a: b", "hash": " # x", "dash": "- y"}"#,
+        r#"{"empty": "", "spaces": "  lead", "bool": "true", "num": "12"}"#,
+        r#"{"a":{"b":{"c":[1,2,{"d":"e"}]}}}"#,
+    ] {
+        assert_eq!(sniff(input), InputKind::Json, "{input}");
+        let expected: serde_json::Value = serde_json::from_str(input).unwrap();
+        assert_eq!(crate::parse(input).unwrap().to_json(), expected, "{input}");
+    }
+}
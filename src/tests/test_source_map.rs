@@ -0,0 +1,88 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse_document, JsonOptions, MxOptions, Span};
+
+// JSON source map tests
+
+#[test]
+fn test_json_source_map() {
+    let input = "spec:\n  name: web\n  ports:\n    - 80\n    - 443\n  a/b: x\n";
+    let doc = parse_document(input).unwrap();
+    let (json, map) = doc.to_json_with_source_map(&JsonOptions::default());
+    assert_eq!(json, doc.root().to_json());
+    let range = map.get("/spec/ports/1").unwrap();
+    assert_eq!(&input[range.span.start..range.span.end], "443");
+    assert_eq!(range.start, (5, 7));
+    assert_eq!(range.end, (5, 10));
+    assert_eq!(range.key, None);
+    let range = map.get("/spec/name").unwrap();
+    assert_eq!(range.key, Some(Span::new(8, 12)));
+    assert_eq!(range.start, (2, 9));
+    let range = map.get("/spec/a~1b").unwrap();
+    assert_eq!(&input[range.span.start..range.span.end], "x");
+    assert!(map.get("").is_some());
+    let pointers: Vec<&str> = map.iter().map(|(pointer, _)| pointer).collect();
+    assert_eq!(
+        pointers,
+        [
+            "",
+            "/spec",
+            "/spec/name",
+            "/spec/ports",
+            "/spec/ports/0",
+            "/spec/ports/1",
+            "/spec/a~1b",
+        ]
+    );
+    assert_eq!(map.len(), 7);
+}
+
+#[test]
+fn test_source_map_find() {
+    let input = "base: &b\n  x: 1\ncopy: *b\ntagged: !point [1, 2]\n";
+    let doc = parse_document(input).unwrap();
+    let (_, map) = doc.to_json_with_source_map(&JsonOptions::default());
+    // the content of an alias has no place of its own
+    assert!(map.get("/copy/x").is_none());
+    let (pointer, range) = map.find("/copy/x").unwrap();
+    assert_eq!(pointer, "/copy");
+    assert_eq!(&input[range.span.start..range.span.end], "*b");
+    // tagged values are objects holding their content
+    let range = map.get("/tagged/__value/1").unwrap();
+    assert_eq!(&input[range.span.start..range.span.end], "2");
+    assert!(map.get("/tagged/__value").is_some());
+    assert_eq!(map.find("/missing").unwrap().0, "");
+}
+
+#[test]
+fn test_source_map_positions_match_line_col() {
+    let input = "é: [ü, 1]\n\nnested:\n  - 😀 text\n  - {k: ö}\n";
+    let doc = parse_document(input).unwrap();
+    let (_, map) = doc.to_json_with_source_map(&JsonOptions::default());
+    assert_eq!(map.get("/nested/0").unwrap().start, (4, 5));
+    assert_eq!(map.get("/nested/1/k").unwrap().end, (5, 10));
+    for (pointer, range) in map.iter() {
+        let start = crate::span::line_col(input, range.span.start);
+        let end = crate::span::line_col(input, range.span.end);
+        assert_eq!((range.start, range.end), (start, end), "{pointer}");
+    }
+}
+
+#[test]
+fn test_mx_source_map() {
+    let input = "+shop[Main]:\n  owner: ann\n+note[N](1): hello\n";
+    let doc = parse_document(input).unwrap();
+    let (json, map) = doc.to_mx_with_source_map(&MxOptions::default());
+    assert_eq!(json, doc.root().to_mx());
+    let range = map.get("/+shop/owner").unwrap();
+    assert_eq!(&input[range.span.start..range.span.end], "ann");
+    let range = map.get("/+note/__content").unwrap();
+    assert_eq!(&input[range.span.start..range.span.end], "hello");
+    assert_eq!(range.start, (3, 14));
+    assert!(map.get("/+note").is_some());
+
+    let doc = parse_document("not: mx\n").unwrap();
+    let (_, map) = doc.to_mx_with_source_map(&MxOptions::default());
+    assert!(map.is_empty());
+}
@@ -0,0 +1,209 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{parse_all, parse_documents, StreamParser, Yaml, YamlOwned, YamlParseError};
+
+// Multi-document stream tests
+
+#[test]
+fn test_parse_all_manifests() {
+    let input = r"
+apiVersion: v1
+kind: Service
+---
+apiVersion: apps/v1
+kind: Deployment
+---
+- just
+- a list
+";
+    assert_eq!(
+        parse_all(input).unwrap(),
+        vec![
+            map! { "apiVersion" => "v1"; "kind" => "Service" },
+            map! { "apiVersion" => "apps/v1"; "kind" => "Deployment" },
+            seq!("just", "a list"),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_all_markers_and_trivia() {
+    let input = r"# leading comment
+%YAML 1.2
+--- {a: 1}
+---
+# only a comment
+...
+# between documents
+---
+b: 2
+...
+bare: document
+";
+    assert_eq!(
+        parse_all(input).unwrap(),
+        vec![
+            map! { "a" => Yaml::Int(1) },
+            Yaml::Null,
+            map! { "b" => Yaml::Int(2) },
+            map! { "bare" => "document" },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_all_single_and_empty() {
+    assert_eq!(
+        parse_all("a: 1\n").unwrap(),
+        vec![crate::parse("a: 1\n").unwrap()]
+    );
+    assert_eq!(parse_all("").unwrap(), Vec::<Yaml>::new());
+    assert_eq!(parse_all("# nothing\n\n").unwrap(), Vec::<Yaml>::new());
+    assert_eq!(parse_all("---\n").unwrap(), vec![Yaml::Null]);
+}
+
+#[test]
+fn test_parse_all_marker_lookalikes() {
+    assert_eq!(
+        parse_all("- ---x\n- ...y\n").unwrap(),
+        vec![seq!("---x", "...y")]
+    );
+    assert_eq!(parse_all("key: |\n  ---\n  text\n").unwrap().len(), 1);
+}
+
+#[test]
+fn test_parse_all_error_lines() {
+    let err = parse_all("---\na: 1\n---\nb: }\n").unwrap_err();
+    assert_eq!(err.line(), 4);
+    assert_eq!(err.message(), Some("unexpected symbol '}'"));
+}
+
+#[test]
+fn test_parse_documents_is_lazy() {
+    let mut docs = parse_documents("a: 1\n---\nb: }\n---\nc: 3\n");
+    assert_eq!(docs.next().unwrap().unwrap(), map! { "a" => Yaml::Int(1) });
+    assert!(docs.next().unwrap().is_err());
+    assert_eq!(docs.next().unwrap().unwrap(), map! { "c" => Yaml::Int(3) });
+    assert!(docs.next().is_none());
+}
+
+#[test]
+fn test_parse_documents_borrow_input() {
+    let input = String::from("---\nname: first\n---\nname: second\n");
+    let names: Vec<&str> = parse_documents(&input)
+        .map(|doc| match doc.unwrap() {
+            Yaml::Mapping(entries) => match entries[0].value {
+                Yaml::Scalar(s) => s,
+                ref other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        })
+        .collect();
+    assert_eq!(names, vec!["first", "second"]);
+}
+
+/// Feed `input` to a `StreamParser` in chunks of `size` bytes, returning the
+/// documents and after how many chunks each one came out
+fn feed_chunks(input: &[u8], size: usize) -> Vec<(usize, Result<YamlOwned, YamlParseError>)> {
+    let mut parser = StreamParser::new();
+    let mut out = Vec::new();
+    for (idx, chunk) in input.chunks(size).enumerate() {
+        out.extend(parser.feed(chunk).into_iter().map(|doc| (idx + 1, doc)));
+    }
+    let chunks = input.len().div_ceil(size);
+    out.extend(parser.finish().into_iter().map(|doc| (chunks + 1, doc)));
+    out
+}
+
+#[test]
+fn test_stream_parser_matches_parse_all() {
+    let input = "# head\n%YAML 1.2\n---\nname: café\n---\n- a\n- b\n...\n# between\n--- |\n  text\n---\n\n---\n{x: 1}\n";
+    let expected: Vec<_> = parse_all(input)
+        .unwrap()
+        .into_iter()
+        .map(Yaml::into_owned)
+        .collect();
+    for size in [1, 2, 3, 7, input.len()] {
+        let docs: Vec<_> = feed_chunks(input.as_bytes(), size)
+            .into_iter()
+            .map(|(_, doc)| doc.unwrap())
+            .collect();
+        assert_eq!(docs, expected, "chunks of {size}");
+    }
+}
+
+#[test]
+fn test_stream_parser_emits_documents_early() {
+    let input = b"a: 1\n---\nb: 2\n...\nc: 3\n";
+    let chunks: Vec<_> = feed_chunks(input, 5)
+        .into_iter()
+        .map(|(chunk, doc)| (chunk, doc.unwrap().to_string()))
+        .collect();
+    // `a` once the `---` line is read, `b` once the `...` line is, and `c`
+    // only at the end
+    assert_eq!(
+        chunks,
+        [
+            (2, "a: 1\n".to_string()),
+            (4, "b: 2\n".to_string()),
+            (6, "c: 3\n".to_string()),
+        ]
+    );
+    assert!(StreamParser::new().finish().is_empty());
+}
+
+#[test]
+fn test_stream_parser_errors() {
+    // a document which fails does not stop the stream, and its line
+    // counts from the start of it
+    let docs = feed_chunks(b"a: 1\n---\nb: [1\n---\nc: 3\n", 4);
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs[1].1.as_ref().unwrap_err().line(), 3);
+    assert_eq!(docs[2].1.as_ref().unwrap()["c"], Yaml::Int(3));
+
+    // as does one holding invalid UTF-8, wherever the chunks end
+    for size in [1, 5, 64] {
+        let docs = feed_chunks(b"a: 1\n---\nb: \xff\nd: 4\n---\nc: 3\n", size);
+        assert_eq!(docs.len(), 3);
+        assert_eq!(docs[0].1.as_ref().unwrap()["a"], Yaml::Int(1));
+        let err = docs[1].1.as_ref().unwrap_err();
+        assert_eq!((err.line(), err.col()), (3, 4));
+        assert!(err.to_string().contains("UTF-8"));
+        assert_eq!(docs[2].1.as_ref().unwrap()["c"], Yaml::Int(3));
+    }
+    let docs = feed_chunks(b"a: \xff", 2);
+    assert_eq!(docs.len(), 1);
+    assert!(docs[0].1.is_err());
+}
+
+#[test]
+fn test_stream_parser_long_documents() {
+    // a document fed a byte at a time, with lines longer than the chunks
+    let mut input = String::from("# comment\n");
+    for idx in 0..200 {
+        input.push_str(&format!("key{idx}: {}\n", "é".repeat(idx % 7 * 10)));
+    }
+    input.push_str("...\n# after\n---\n- last\n");
+    let expected: Vec<_> = parse_all(&input)
+        .unwrap()
+        .into_iter()
+        .map(Yaml::into_owned)
+        .collect();
+    for size in [1, 3, 100] {
+        let docs: Vec<_> = feed_chunks(input.as_bytes(), size)
+            .into_iter()
+            .map(|(_, doc)| doc.unwrap())
+            .collect();
+        assert_eq!(docs, expected, "chunks of {size}");
+    }
+
+    // invalid UTF-8 after lines which were already checked
+    let mut input = input.into_bytes();
+    input.extend_from_slice(b"- \xff\n---\nx: 1\n");
+    let docs = feed_chunks(&input, 1);
+    assert_eq!(docs.len(), 3);
+    let err = docs[1].1.as_ref().unwrap_err();
+    assert_eq!((err.line(), err.col()), (206, 3));
+    assert_eq!(docs[2].1.as_ref().unwrap()["x"], Yaml::Int(1));
+}
@@ -5,32 +5,32 @@
 
 mk_test!(
     tag with quoted scalar;
-    r#"!str "hello world""# => map!{ "__type" : "str", "__value" : "hello world" }
+    r#"!str "hello world""# => tagged!("str", "hello world")
 );
 
 mk_test!(
     tag with flow sequence;
-    r"!list [a, b, c]" => map!{ "__type" => "list"; "__value" => seq!("a", "b", "c") }
+    r"!list [a, b, c]" => tagged!("list", seq!("a", "b", "c"))
 );
 
 mk_test!(
     tag with flow mapping;
-    r"!person {name: John, age: 30}" => map!{ "__type" => "person"; "name" => "John"; "age" => crate::Yaml::Int(30) }
+    r"!person {name: John, age: 30}" => tagged!("person", map!{ "name" => "John"; "age" => crate::Yaml::Int(30) })
 );
 
 mk_test!(
     tag in flow mapping value;
-    r"{key: !tagged value}" => map!{ "key" => map!{ "__type" : "tagged", "__value" : "value" } }
+    r"{key: !tagged value}" => map!{ "key" => tagged!("tagged", "value") }
 );
 
 mk_test!(
     tag with hyphen in name;
-    r"!my-custom-tag value" => map!{ "__type" : "my-custom-tag", "__value" : "value" }
+    r"!my-custom-tag value" => tagged!("my-custom-tag", "value")
 );
 
 mk_test!(
     tag with underscore in name;
-    r"!my_tag value" => map!{ "__type" : "my_tag", "__value" : "value" }
+    r"!my_tag value" => tagged!("my_tag", "value")
 );
 
 mk_test!(
@@ -38,54 +38,37 @@ mk_test!(
     r"! value" => fail
 );
 
-// Tag tests - all tags create __type mappings
+// Tag tests - local tags are kept with their value
 
 #[test]
-fn test_int_tag_creates_type_mapping() {
+fn test_int_tag_keeps_its_tag() {
     let parsed = crate::parse("!int 42").unwrap();
-    if let crate::Yaml::Mapping(entries) = parsed {
-        assert_eq!(entries.len(), 2);
-        assert_eq!(entries[0].key, crate::Yaml::Scalar("__type"));
-        assert_eq!(entries[0].value, crate::Yaml::Scalar("int"));
-        assert_eq!(entries[1].key, crate::Yaml::Scalar("__value"));
-        assert_eq!(entries[1].value, crate::Yaml::Int(42));
-    } else {
-        panic!("Expected mapping");
-    }
+    assert_eq!(parsed, tagged!("int", crate::Yaml::Int(42)));
+    assert_eq!(parsed.tag(), Some("int"));
+    assert_eq!(parsed.untagged(), &crate::Yaml::Int(42));
+    assert_eq!(
+        parsed.to_json(),
+        serde_json::json!({"__type": "int", "__value": 42})
+    );
 }
 
 #[test]
 fn test_int_tag_with_non_numeric() {
     // Now accepts any value - no validation
     let parsed = crate::parse("!int abc").unwrap();
-    if let crate::Yaml::Mapping(entries) = parsed {
-        assert_eq!(entries[0].value, crate::Yaml::Scalar("int"));
-        assert_eq!(entries[1].value, crate::Yaml::Scalar("abc"));
-    } else {
-        panic!("Expected mapping");
-    }
+    assert_eq!(parsed, tagged!("int", "abc"));
 }
 
 #[test]
-fn test_float_tag_creates_type_mapping() {
+fn test_float_tag_keeps_its_tag() {
     let parsed = crate::parse("!float 3.14").unwrap();
-    if let crate::Yaml::Mapping(entries) = parsed {
-        assert_eq!(entries[0].value, crate::Yaml::Scalar("float"));
-        assert_eq!(entries[1].value, crate::Yaml::Float(3.14));
-    } else {
-        panic!("Expected mapping");
-    }
+    assert_eq!(parsed, tagged!("float", crate::Yaml::Float(3.14)));
 }
 
 #[test]
-fn test_bool_tag_creates_type_mapping() {
+fn test_bool_tag_keeps_its_tag() {
     let parsed = crate::parse("!bool true").unwrap();
-    if let crate::Yaml::Mapping(entries) = parsed {
-        assert_eq!(entries[0].value, crate::Yaml::Scalar("bool"));
-        assert_eq!(entries[1].value, crate::Yaml::Bool(true));
-    } else {
-        panic!("Expected mapping");
-    }
+    assert_eq!(parsed, tagged!("bool", crate::Yaml::Bool(true)));
 }
 
 #[test]
@@ -165,23 +148,10 @@ fn test_generic_tag_seq() {
 tags: !seq<string> [rust, yaml, parser]
 "#;
     let parsed = crate::parse(yaml).unwrap();
-    if let crate::Yaml::Mapping(entries) = &parsed {
-        if let crate::Yaml::Mapping(inner) = &entries[0].value {
-            assert_eq!(inner[0].value, crate::Yaml::Scalar("seq<string>"));
-            assert_eq!(
-                inner[1].value,
-                crate::Yaml::Sequence(vec![
-                    crate::Yaml::Scalar("rust"),
-                    crate::Yaml::Scalar("yaml"),
-                    crate::Yaml::Scalar("parser")
-                ])
-            );
-        } else {
-            panic!("Expected inner mapping");
-        }
-    } else {
-        panic!("Expected mapping");
-    }
+    assert_eq!(
+        parsed,
+        map! { "tags" => tagged!("seq<string>", seq!("rust", "yaml", "parser")) }
+    );
 }
 
 #[test]
@@ -230,11 +200,7 @@ fn test_generic_tag_nested() {
     // Test nested generics like !seq<option<string>>
     let yaml = r#"!seq<option<string>> [hello, world]"#;
     let parsed = crate::parse(yaml).unwrap();
-    if let crate::Yaml::Mapping(entries) = &parsed {
-        assert_eq!(entries[0].value, crate::Yaml::Scalar("seq<option<string>>"));
-    } else {
-        panic!("Expected mapping");
-    }
+    assert_eq!(parsed.tag(), Some("seq<option<string>>"));
 }
 
 #[test]
@@ -276,3 +242,167 @@ fn test_generic_tag_with_spaces() {
     // This should fail because space breaks the tag, leaving unclosed '<'
     assert!(result.is_err());
 }
+
+// Binary tests
+
+#[test]
+fn test_binary_tag_decodes_base64() {
+    let parsed =
+        crate::parse("a: !!binary aGVsbG8=\nb: !!binary |\n  aGVs\n  bG8h\nc: !!binary ''\n")
+            .unwrap();
+    assert_eq!(
+        parsed,
+        map! {
+            "a" => crate::Yaml::Bytes(b"hello".to_vec());
+            "b" => crate::Yaml::Bytes(b"hello!".to_vec());
+            "c" => crate::Yaml::Bytes(Vec::new())
+        }
+    );
+    let err = crate::parse("a: !!binary aGVsbG8").unwrap_err();
+    assert_eq!(err.message(), Some("invalid base64 in '!!binary' value"));
+}
+
+#[test]
+fn test_binary_json_and_display() {
+    let parsed = crate::parse("- !!binary aGk=\n- !!binary AP8K\n").unwrap();
+    assert_eq!(parsed.to_json(), serde_json::json!(["aGk=", "AP8K"]));
+    let opts = crate::JsonOptions {
+        bytes: crate::BytesFormat::Array,
+        ..Default::default()
+    };
+    assert_eq!(
+        parsed.to_json_with_options(&opts),
+        serde_json::json!([[104, 105], [0, 255, 10]])
+    );
+    assert_eq!(parsed.to_string(), "- !!binary aGk=\n- !!binary AP8K\n");
+    assert_eq!(crate::parse(&parsed.to_string()).unwrap(), parsed);
+}
+
+#[test]
+fn test_standard_tags_resolve_values() {
+    assert_eq!(
+        crate::parse("[!!str 42, !!str true, !!int '0x1A', !!float 1, !!bool True, !!null '', !!seq [1], !!map {a: 1}]")
+            .unwrap(),
+        seq!(
+            "42",
+            "true",
+            crate::Yaml::Int(26),
+            crate::Yaml::Float(1.0),
+            crate::Yaml::Bool(true),
+            crate::Yaml::Null,
+            seq!(crate::Yaml::Int(1)),
+            map! { "a" => crate::Yaml::Int(1) }
+        )
+    );
+    assert_eq!(
+        crate::parse("a: !!str 1\nb: !!map\n  c: 2\n").unwrap(),
+        map! { "a" => "1"; "b" => map! { "c" => crate::Yaml::Int(2) } }
+    );
+}
+
+#[test]
+fn test_standard_tags_reject_other_types() {
+    for (input, message) in [
+        ("a: !!int x", "invalid '!!int' value"),
+        ("a: !!bool yes", "invalid '!!bool' value"),
+        ("a: !!map [1]", "invalid '!!map' value"),
+        ("a: !!str [1]", "invalid '!!str' value"),
+    ] {
+        let err = crate::parse(input).unwrap_err();
+        assert_eq!(err.message(), Some(message), "{input}");
+    }
+}
+
+#[test]
+fn test_secondary_tags_keep_their_handle() {
+    assert_eq!(crate::parse("!!custom x").unwrap(), tagged!("!custom", "x"));
+    assert!(crate::parse("!! x").is_err());
+}
+
+#[test]
+fn test_set_tag() {
+    assert_eq!(crate::parse("!!set\n? a\n? b\n").unwrap(), seq!("a", "b"));
+    assert_eq!(
+        crate::parse("tags: !!set {x: null, y: ~}\n").unwrap(),
+        map! { "tags" => seq!("x", "y") }
+    );
+    let parsed = crate::parse("!!set\na:\nb:\n").unwrap();
+    assert_eq!(parsed.to_json(), serde_json::json!(["a", "b"]));
+    assert!(crate::parse("!!set {a: 1}").is_err());
+    assert!(crate::parse("!!set [a, b]").is_err());
+}
+
+#[test]
+fn test_ordered_map_tags() {
+    assert_eq!(
+        crate::parse("!!omap\n- b: 1\n- a: 2\n").unwrap(),
+        map! { "b" => crate::Yaml::Int(1); "a" => crate::Yaml::Int(2) }
+    );
+    assert_eq!(
+        crate::parse("x: !!omap [{z: 1}, {y: 2}]\n")
+            .unwrap()
+            .to_json(),
+        serde_json::json!({"x": {"z": 1, "y": 2}})
+    );
+    assert_eq!(
+        crate::parse("!!omap [b: 1, a: 2]").unwrap(),
+        crate::parse("!!omap\n- b: 1\n- a: 2\n").unwrap()
+    );
+    assert!(crate::parse("!!omap\n- a: 1\n- a: 2\n").is_err());
+    assert!(crate::parse("!!omap\n- a\n").is_err());
+    assert_eq!(
+        crate::parse("!!pairs\n- a: 1\n- a: 2\n").unwrap(),
+        seq!(
+            map! { "a" => crate::Yaml::Int(1) },
+            map! { "a" => crate::Yaml::Int(2) }
+        )
+    );
+    assert!(crate::parse("!!pairs\n- {a: 1, b: 2}\n").is_err());
+}
+
+#[test]
+fn test_verbatim_tags() {
+    assert_eq!(
+        crate::parse("a: !<tag:yaml.org,2002:int> '12'\nb: !<tag:yaml.org,2002:str> x\n").unwrap(),
+        map! { "a" => crate::Yaml::Int(12); "b" => "x" }
+    );
+    let tagged = crate::parse("- !<tag:example.com,2000:app/foo> {x: 1}\n- !<!local> v\n").unwrap();
+    assert_eq!(
+        tagged,
+        seq!(
+            tagged!(
+                "tag:example.com,2000:app/foo",
+                map! { "x" => crate::Yaml::Int(1) }
+            ),
+            tagged!("local", "v")
+        )
+    );
+    // URIs are written back in verbatim form
+    let emitted = tagged.to_string();
+    assert!(emitted.contains("!<tag:example.com,2000:app/foo>"));
+    assert!(emitted.contains("!local v"));
+    assert_eq!(crate::parse(&emitted).unwrap(), tagged);
+}
+
+#[test]
+fn test_verbatim_tag_errors() {
+    for (input, message) in [
+        ("a: !<tag:x", "unclosed '<' in verbatim tag"),
+        ("a: !<tag x> y", "unclosed '<' in verbatim tag"),
+        ("a: !<> y", "expected tag after '!<'"),
+    ] {
+        let err = crate::parse(input).unwrap_err();
+        assert_eq!(err.message(), Some(message), "{input}");
+    }
+}
+
+#[test]
+fn test_tagged_json_round_trip() {
+    let parsed = crate::parse("a: !point {x: 1}\nb: !id 7\n").unwrap();
+    let json = parsed.to_json();
+    assert_eq!(
+        json,
+        serde_json::json!({"a": {"__type": "point", "x": 1}, "b": {"__type": "id", "__value": 7}})
+    );
+    assert_eq!(crate::Yaml::from_json(&json), parsed.into_owned());
+}
@@ -0,0 +1,158 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+use crate::{Time, Timestamp, Yaml};
+
+// Timestamp tests
+
+fn ts(s: &str) -> Option<String> {
+    Timestamp::parse(s).map(|ts| ts.to_string())
+}
+
+#[test]
+fn test_timestamp_forms() {
+    assert_eq!(ts("2001-12-14"), Some("2001-12-14".to_string()));
+    assert_eq!(
+        ts("2001-12-14t21:59:43.10-05:00"),
+        Some("2001-12-14T21:59:43.1-05:00".to_string())
+    );
+    assert_eq!(
+        ts("2001-12-14 21:59:43.10 -5"),
+        Some("2001-12-14T21:59:43.1-05:00".to_string())
+    );
+    assert_eq!(
+        ts("2001-12-15T2:59:43.1Z"),
+        Some("2001-12-15T02:59:43.1Z".to_string())
+    );
+    assert_eq!(
+        ts("2002-12-14 21:59:43"),
+        Some("2002-12-14T21:59:43".to_string())
+    );
+    assert_eq!(
+        Timestamp::parse("2024-06-01T12:00:00.123456789123+05:30"),
+        Some(Timestamp {
+            year: 2024,
+            month: 6,
+            day: 1,
+            time: Some(Time {
+                hour: 12,
+                minute: 0,
+                second: 0,
+                nanosecond: 123_456_789,
+                offset: Some(330),
+            }),
+        })
+    );
+}
+
+#[test]
+fn test_not_timestamps() {
+    for s in [
+        "2001-1-14",
+        "2001-02-29",
+        "2000-13-01",
+        "2001-12-14T25:00:00",
+        "2001-12-14T21:59",
+        "2001-12-14x",
+        "2001-12-14T21:59:43 EST",
+        "01-12-14",
+    ] {
+        assert_eq!(ts(s), None, "{s}");
+    }
+    assert_eq!(ts("2000-02-29"), Some("2000-02-29".to_string()));
+}
+
+#[test]
+fn test_timestamps_resolved() {
+    let parsed = crate::parse("created: 2024-06-01T12:00:00Z\nquoted: '2024-06-01'\n").unwrap();
+    let Yaml::Mapping(entries) = &parsed else {
+        panic!("expected a mapping");
+    };
+    assert!(matches!(entries[0].value, Yaml::Timestamp(_)));
+    assert_eq!(entries[1].value, Yaml::Scalar("2024-06-01"));
+    assert_eq!(
+        parsed.to_json(),
+        serde_json::json!({"created": "2024-06-01T12:00:00Z", "quoted": "2024-06-01"})
+    );
+    assert_eq!(
+        parsed.to_string(),
+        "created: 2024-06-01T12:00:00Z\nquoted: 2024-06-01\n"
+    );
+
+    // the YAML 1.2 schemas have no timestamps
+    let opts = crate::ParseOptions::default().resolver(crate::CoreSchema);
+    assert_eq!(
+        crate::parse_with_options("2024-06-01", &opts).unwrap(),
+        Yaml::Scalar("2024-06-01")
+    );
+}
+
+#[test]
+fn test_unix_millis() {
+    let millis = |s: &str| Timestamp::parse(s).unwrap().unix_millis();
+    assert_eq!(millis("1970-01-01"), 0);
+    assert_eq!(millis("1969-12-31"), -86_400_000);
+    assert_eq!(millis("2000-03-01"), 951_868_800_000);
+    assert_eq!(millis("2001-12-14t21:59:43.10-05:00"), 1_008_385_183_100);
+    assert_eq!(millis("2001-12-15 2:59:43.10"), 1_008_385_183_100);
+}
+
+#[test]
+fn test_timestamp_emit_formats() {
+    let input = "a: 2001-12-14 21:59:43.10 -5\nb: 2002-12-14t21:59:43\nc: 2002-12-14\n";
+    let doc = crate::parse_document(input).unwrap();
+    let emit = |timestamp| {
+        doc.emit(&crate::EmitOptions {
+            timestamp,
+            ..Default::default()
+        })
+    };
+    assert_eq!(
+        emit(crate::TimestampFormat::Iso8601),
+        "a: 2001-12-14T21:59:43.1-05:00\nb: 2002-12-14T21:59:43\nc: 2002-12-14\n"
+    );
+    assert_eq!(
+        emit(crate::TimestampFormat::Rfc3339),
+        "a: 2001-12-14T21:59:43.1-05:00\nb: 2002-12-14T21:59:43Z\nc: 2002-12-14\n"
+    );
+    assert_eq!(
+        emit(crate::TimestampFormat::DateOnly),
+        "a: 2001-12-14\nb: 2002-12-14\nc: 2002-12-14\n"
+    );
+    assert_eq!(emit(crate::TimestampFormat::Original), input);
+
+    // tags and anchors are printed apart from the text they were on
+    let input = "a: !t 2001-12-14t21:59:43\nb: &x 2002-12-14 01:02:03\nc: *x\n";
+    let doc = crate::parse_document(input).unwrap();
+    let out = doc.emit(&crate::EmitOptions {
+        timestamp: crate::TimestampFormat::Original,
+        ..Default::default()
+    });
+    assert_eq!(
+        out,
+        "a: !t 2001-12-14t21:59:43\nb: 2002-12-14 01:02:03\nc: 2002-12-14T01:02:03\n"
+    );
+}
+
+#[test]
+fn test_timestamp_json_formats() {
+    let parsed = crate::parse("[2001-12-14t21:59:43.10-05:00, 2002-12-14t21:59:43]").unwrap();
+    let json = |timestamps| {
+        parsed.to_json_with_options(&crate::JsonOptions {
+            timestamps,
+            ..Default::default()
+        })
+    };
+    assert_eq!(
+        json(crate::TimestampJson::Iso8601),
+        serde_json::json!(["2001-12-14T21:59:43.1-05:00", "2002-12-14T21:59:43"])
+    );
+    assert_eq!(
+        json(crate::TimestampJson::Rfc3339),
+        serde_json::json!(["2001-12-14T21:59:43.1-05:00", "2002-12-14T21:59:43Z"])
+    );
+    assert_eq!(
+        json(crate::TimestampJson::EpochMillis),
+        serde_json::json!([1_008_385_183_100_i64, 1_039_903_183_000_i64])
+    );
+}
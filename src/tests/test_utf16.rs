@@ -0,0 +1,52 @@
+#![cfg(test)]
+#![allow(clippy::pedantic)]
+
+// UTF-16 offset tests
+
+use crate::{Span, Utf16Index};
+
+#[test]
+fn test_utf16_offsets() {
+    let source = "a: é\nb: 😀 x\n";
+    let index = Utf16Index::new(source);
+    assert_eq!(index.offset(0), 0);
+    // `é` is two bytes but one code unit
+    assert_eq!(index.offset(6), 5);
+    // `😀` is four bytes and a surrogate pair
+    let x = source.find('x').unwrap();
+    assert_eq!(index.offset(x), source[..x].encode_utf16().count());
+    assert_eq!(index.offset(source.len()), source.encode_utf16().count());
+    assert_eq!(
+        index.offset(source.len() + 10),
+        source.encode_utf16().count()
+    );
+}
+
+#[test]
+fn test_utf16_spans() {
+    let source = "naïve: 日本\n";
+    let doc = crate::parse_document(source).unwrap();
+    let index = Utf16Index::new(source);
+    assert_eq!(index.span(doc.key_span("naïve").unwrap()), Span::new(0, 5));
+    assert_eq!(index.span(doc.span("naïve").unwrap()), Span::new(7, 9));
+}
+
+#[test]
+fn test_utf16_positions() {
+    let source = "ü: 1\n😀: [\n";
+    let index = Utf16Index::new(source);
+    assert_eq!(index.position(1, 1), 0);
+    assert_eq!(index.position(2, 1), 5);
+    assert_eq!(index.position(2, 2), 7);
+    // past the end of a line or the source
+    assert_eq!(index.position(1, 20), 4);
+    assert_eq!(index.position(9, 1), 11);
+
+    let err = crate::parse(source).unwrap_err();
+    assert_eq!((err.line(), err.col()), (2, 5));
+    assert_eq!(index.position(err.line(), err.col()), 10);
+
+    // an owned index outlives the source it was built from
+    let owned = Utf16Index::new(&String::from(source)).into_owned();
+    assert_eq!(owned.position(2, 2), 7);
+}
@@ -11,7 +11,7 @@ fn test_parse_yaml_returns_plain_object() {
 name: test
 value: 123
 "#;
-    let result = crate::wasm::parse_yaml_to_json(yaml).unwrap();
+    let result = crate::wasm::parse_yaml_to_json(yaml, None, None).unwrap();
 
     // Verify it's a plain Object, not a Map
     assert!(result.is_object());
@@ -50,7 +50,7 @@ outer:
   inner:
     key: value
 "#;
-    let result = crate::wasm::parse_yaml_to_json(yaml).unwrap();
+    let result = crate::wasm::parse_yaml_to_json(yaml, None, None).unwrap();
 
     // Get nested object and verify it's also a plain object
     let obj = result.dyn_ref::<js_sys::Object>().unwrap();
@@ -72,7 +72,7 @@ alpha: 2
 mike: 3
 beta: 4
 "#;
-    let result = crate::wasm::parse_yaml_to_json(yaml).unwrap();
+    let result = crate::wasm::parse_yaml_to_json(yaml, None, None).unwrap();
     let obj = result.dyn_ref::<js_sys::Object>().unwrap();
     let keys = js_sys::Object::keys(obj);
 
@@ -118,7 +118,7 @@ fn test_parse_yaml_utf8_chinese_preserved() {
   你好世界
   测试中文
 "#;
-    let result = crate::wasm::parse_yaml_to_json(yaml).unwrap();
+    let result = crate::wasm::parse_yaml_to_json(yaml, None, None).unwrap();
     let obj = result.dyn_ref::<js_sys::Object>().unwrap();
 
     let info = js_sys::Reflect::get(obj, &"info".into()).unwrap();
@@ -135,3 +135,27 @@ fn test_parse_yaml_utf8_chinese_preserved() {
         info_str
     );
 }
+
+#[wasm_bindgen_test]
+fn test_parse_yaml_with_warnings() {
+    let result = crate::wasm::parse_yaml_to_json("a: 1\na: 2\n", Some(true), None).unwrap();
+    let value = js_sys::Reflect::get(&result, &"value".into()).unwrap();
+    assert!(value.is_object());
+    let warnings = js_sys::Reflect::get(&result, &"warnings".into()).unwrap();
+    let warnings = warnings.dyn_into::<js_sys::Array>().unwrap();
+    assert_eq!(warnings.length(), 1);
+}
+
+#[wasm_bindgen_test]
+fn test_parse_error_has_utf16_offset() {
+    let err = crate::wasm::parse_yaml_to_json("é: 😀\nb: [1\n", None, None).unwrap_err();
+    assert!(err.has_type::<js_sys::Error>());
+    let get = |key: &str| js_sys::Reflect::get(&err, &key.into()).unwrap();
+    assert_eq!(get("line"), wasm_bindgen::JsValue::from(2));
+    assert_eq!(get("offset"), wasm_bindgen::JsValue::from(11));
+
+    let positions = crate::wasm::Utf16Positions::new("é: 😀\nb: 1\n");
+    assert_eq!(positions.position(1, 4), 3);
+    assert_eq!(positions.position(2, 1), 6);
+    assert_eq!(positions.offset("é: 😀\n".len()), 6);
+}
@@ -0,0 +1,254 @@
+use core::fmt;
+
+/// A date, or a date and time, written as a YAML 1.1 timestamp
+/// (`2001-12-14`, `2001-12-14t21:59:43.10-05:00`, `2001-12-14 21:59:43.10 -5`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    pub year: u16,
+    /// 1 to 12
+    pub month: u8,
+    /// 1 to the number of days in the month
+    pub day: u8,
+    /// the time of day, if one was given
+    pub time: Option<Time>,
+}
+
+/// The time of day of a [`Timestamp`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+    /// the offset from UTC in minutes, if a time zone was given
+    pub offset: Option<i16>,
+}
+
+impl Timestamp {
+    /// Parse a timestamp in any of the forms YAML 1.1 allows: a date on its
+    /// own, or a date followed by a time (after `T`, `t` or spaces), an
+    /// optional fraction of a second and an optional time zone (`Z`, or an
+    /// offset in hours and optionally minutes, possibly after spaces).
+    /// Returns `None` if `s` is not a timestamp or names a date or time
+    /// which does not exist.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Timestamp> {
+        let mut cursor = Cursor(s);
+        let year = cursor.number(4, 4)?;
+        cursor.expect('-')?;
+        let month = cursor.number(1, 2)?;
+        cursor.expect('-')?;
+        let day = cursor.number(1, 2)?;
+        let time = if cursor.0.is_empty() {
+            // a date on its own must use two digits for the month and day
+            if s.len() != 10 {
+                return None;
+            }
+            None
+        } else {
+            Some(parse_time(&mut cursor)?)
+        };
+        let timestamp = Timestamp {
+            year: u16::try_from(year).ok()?,
+            month: u8::try_from(month).ok()?,
+            day: u8::try_from(day).ok()?,
+            time,
+        };
+        let month_ok = (1..=12).contains(&timestamp.month);
+        let day_ok = (1..=days_in_month(timestamp.year, timestamp.month)).contains(&timestamp.day);
+        (month_ok && day_ok).then_some(timestamp)
+    }
+}
+
+impl Timestamp {
+    /// The date alone, without the time of day
+    #[must_use]
+    pub fn date(&self) -> Timestamp {
+        Timestamp {
+            time: None,
+            ..*self
+        }
+    }
+
+    /// Format as RFC 3339. A time without a zone is given the zone `Z`, as
+    /// YAML reads such times as UTC; a date on its own stays a date.
+    #[must_use]
+    pub fn to_rfc3339(&self) -> String {
+        match self.time {
+            Some(time @ Time { offset: None, .. }) => Timestamp {
+                time: Some(Time {
+                    offset: Some(0),
+                    ..time
+                }),
+                ..*self
+            }
+            .to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// The number of milliseconds since the Unix epoch, reading a date on its
+    /// own as midnight UTC and a time without a zone as UTC
+    #[must_use]
+    pub fn unix_millis(&self) -> i64 {
+        let days = days_from_epoch(self.year, self.month, self.day);
+        let Some(time) = self.time else {
+            return days * 86_400_000;
+        };
+        let seconds = days * 86_400
+            + i64::from(time.hour) * 3600
+            + i64::from(time.minute) * 60
+            + i64::from(time.second)
+            - i64::from(time.offset.unwrap_or(0)) * 60;
+        seconds * 1000 + i64::from(time.nanosecond / 1_000_000)
+    }
+}
+
+/// The number of days from 1970-01-01 to the given date, in the proleptic
+/// Gregorian calendar
+fn days_from_epoch(year: u16, month: u8, day: u8) -> i64 {
+    // count from March, so that the leap day ends the year
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse the part of a timestamp after the date
+fn parse_time(cursor: &mut Cursor<'_>) -> Option<Time> {
+    if !cursor.eat(['T', 't']) && !cursor.skip_blanks() {
+        return None;
+    }
+    let hour = cursor.number(1, 2)?;
+    cursor.expect(':')?;
+    let minute = cursor.number(2, 2)?;
+    cursor.expect(':')?;
+    let second = cursor.number(2, 2)?;
+    let mut nanosecond = 0;
+    if cursor.eat(['.']) {
+        let digits = cursor.digits();
+        // keep nanosecond precision, ignoring any further digits
+        for (idx, digit) in digits.bytes().take(9).enumerate() {
+            let place = 10u32.pow(8 - u32::try_from(idx).ok()?);
+            nanosecond += u32::from(digit - b'0') * place;
+        }
+    }
+    cursor.skip_blanks();
+    let offset = if cursor.0.is_empty() {
+        None
+    } else if cursor.eat(['Z']) {
+        Some(0)
+    } else {
+        let negative = cursor.0.starts_with('-');
+        if !cursor.eat(['-', '+']) {
+            return None;
+        }
+        let hours = cursor.number(1, 2)?;
+        let minutes = if cursor.eat([':']) {
+            cursor.number(2, 2)?
+        } else {
+            0
+        };
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        let offset = i16::try_from(hours * 60 + minutes).ok()?;
+        Some(if negative { -offset } else { offset })
+    };
+    if !cursor.0.is_empty() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(Time {
+        hour: u8::try_from(hour).ok()?,
+        minute: u8::try_from(minute).ok()?,
+        second: u8::try_from(second).ok()?,
+        nanosecond,
+        offset,
+    })
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The rest of the text being parsed
+struct Cursor<'a>(&'a str);
+
+impl<'a> Cursor<'a> {
+    /// Take the leading ASCII digits
+    fn digits(&mut self) -> &'a str {
+        let end = self
+            .0
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.0.len());
+        let (digits, rest) = self.0.split_at(end);
+        self.0 = rest;
+        digits
+    }
+
+    /// Take a number written with between `min` and `max` digits
+    fn number(&mut self, min: usize, max: usize) -> Option<u32> {
+        let digits = self.digits();
+        if !(min..=max).contains(&digits.len()) {
+            return None;
+        }
+        digits.parse().ok()
+    }
+
+    fn eat<const N: usize>(&mut self, chars: [char; N]) -> bool {
+        match self.0.strip_prefix(chars) {
+            Some(rest) => {
+                self.0 = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        self.eat([c]).then_some(())
+    }
+
+    /// Skip spaces and tabs, returning whether there were any
+    fn skip_blanks(&mut self) -> bool {
+        let rest = self.0.trim_start_matches([' ', '\t']);
+        let skipped = rest.len() < self.0.len();
+        self.0 = rest;
+        skipped
+    }
+}
+
+/// Formats as ISO 8601: `2001-12-14`, or `2001-12-14T21:59:43.1-05:00`,
+/// with a `Z` for UTC and no zone if none was given
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)?;
+        let Some(time) = self.time else {
+            return Ok(());
+        };
+        write!(f, "T{:02}:{:02}:{:02}", time.hour, time.minute, time.second)?;
+        if time.nanosecond > 0 {
+            let fraction = format!("{:09}", time.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        match time.offset {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)
+            }
+        }
+    }
+}
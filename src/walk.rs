@@ -0,0 +1,74 @@
+use crate::path::{Path, PathSegment};
+use crate::Yaml;
+
+/// Callbacks for [`Yaml::walk`], e.g. for linters or analyzers which look
+/// at every node of a tree. Every method has an empty default, so a visitor
+/// only implements those it needs.
+///
+/// Each callback is given the node's path, written as for
+/// [`Document::span`](crate::Document::span) (`""` for the root, then e.g.
+/// `spec.ports[0]`), and the node itself. A tag is not a level of the path:
+/// a tagged node is reported by the kind of its value, but the node passed
+/// is the tagged one, so the tag can be read from it.
+pub trait Visitor<'a> {
+    /// Called on reaching a mapping, before any of its values
+    fn enter_mapping(&mut self, path: &str, node: &Yaml<'a>) {
+        let _ = (path, node);
+    }
+
+    /// Called after every value of a mapping has been visited
+    fn exit_mapping(&mut self, path: &str, node: &Yaml<'a>) {
+        let _ = (path, node);
+    }
+
+    /// Called on reaching a sequence, before any of its items
+    fn enter_sequence(&mut self, path: &str, node: &Yaml<'a>) {
+        let _ = (path, node);
+    }
+
+    /// Called after every item of a sequence has been visited
+    fn exit_sequence(&mut self, path: &str, node: &Yaml<'a>) {
+        let _ = (path, node);
+    }
+
+    /// Called on every node which is neither a mapping nor a sequence,
+    /// including nulls and aliases
+    fn scalar(&mut self, path: &str, node: &Yaml<'a>) {
+        let _ = (path, node);
+    }
+}
+
+impl<'a> Yaml<'a> {
+    /// Visit this node and everything below it depth first, in document
+    /// order, calling `visitor` on entering and leaving every mapping and
+    /// sequence and on every scalar. Mapping keys are not visited
+    /// themselves; they are the last segment of their values' paths.
+    pub fn walk(&self, visitor: &mut impl Visitor<'a>) {
+        self.walk_at(&mut Path::default(), visitor);
+    }
+
+    fn walk_at(&self, path: &mut Path, visitor: &mut impl Visitor<'a>) {
+        let here = path.to_string();
+        match self.untagged() {
+            Yaml::Mapping(entries) => {
+                visitor.enter_mapping(&here, self);
+                for entry in entries {
+                    path.push(PathSegment::Key(entry.key.key_string()));
+                    entry.value.walk_at(path, visitor);
+                    path.pop();
+                }
+                visitor.exit_mapping(&here, self);
+            }
+            Yaml::Sequence(items) => {
+                visitor.enter_sequence(&here, self);
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(PathSegment::Index(idx));
+                    item.walk_at(path, visitor);
+                    path.pop();
+                }
+                visitor.exit_sequence(&here, self);
+            }
+            _ => visitor.scalar(&here, self),
+        }
+    }
+}